@@ -22,6 +22,7 @@ fn bench_execute(b: &mut Bencher) {
         service_name: "asset".to_string(),
         method:       "transfer".to_string(),
         payload:      serde_json::to_string(&payload).unwrap(),
+        encoding:     PayloadEncoding::Json,
     }).collect::<Vec<_>>();
 
     perf_exec!(bench_adapter, req, b);
@@ -47,6 +48,7 @@ fn bench_execute_with_assets(b: &mut Bencher) {
         service_name: "asset".to_string(),
         method:       "transfer".to_string(),
         payload:      serde_json::to_string(&payload).unwrap(),
+        encoding:     PayloadEncoding::Json,
     }).collect::<Vec<_>>();
 
     perf_exec!(bench_adapter, req, b);
@@ -65,6 +67,7 @@ fn create_assets(bench_adapter: &mut BenchmarkAdapter, num: u64) {
                 service_name: "asset".to_string(),
                 method: "create_asset".to_string(),
                 payload: serde_json::to_string(&payload).unwrap(),
+                encoding: PayloadEncoding::Json,
             }
         })
         .collect::<Vec<_>>();
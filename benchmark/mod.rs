@@ -16,8 +16,8 @@ use protocol::traits::{
     ServiceMapping, ServiceSDK, Storage,
 };
 use protocol::types::{
-    Address, Block, BlockHeader, Bytes, Genesis, Hash, Hex, MerkleRoot, Proof,
-    RawTransaction, SignedTransaction, TransactionRequest,
+    Address, Block, BlockHeader, Bytes, Genesis, Hash, Hex, MerkleRoot, PayloadEncoding,
+    Proof, RawTransaction, SignedTransaction, TransactionRequest,
 };
 use protocol::ProtocolResult;
 use test::Bencher;
@@ -70,8 +70,6 @@ macro_rules! perf_exec {
 
 mod bench_executor;
 mod bench_mempool;
-// This is a test service that provides transaction hooks.
-mod governance;
 
 pub struct BenchmarkAdapter {
     trie_db: Arc<RocksTrieDB>,
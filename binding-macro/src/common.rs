@@ -1,4 +1,37 @@
-use syn::{FnArg, Pat, Path, Type};
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{Error, FnArg, Ident, LitStr, Pat, Path, Token, Type};
+
+// The codec of payloads declared by `#[read(codec = "...")]` or
+// `#[write(codec = "...")]`, JSON if not declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadCodec {
+    Json,
+    Fixed,
+}
+
+impl Parse for PayloadCodec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.is_empty() {
+            return Ok(PayloadCodec::Json);
+        }
+
+        let name: Ident = input.parse()?;
+        if name != "codec" {
+            return Err(Error::new(name.span(), "expect `codec = \"...\"`"));
+        }
+        input.parse::<Token![=]>()?;
+
+        let codec: LitStr = input.parse()?;
+        match codec.value().as_str() {
+            "json" => Ok(PayloadCodec::Json),
+            "fixed" => Ok(PayloadCodec::Fixed),
+            _ => Err(Error::new(
+                codec.span(),
+                "The codec should be either \"json\" or \"fixed\"",
+            )),
+        }
+    }
+}
 
 pub fn get_request_context_pat(bound_name: &str, fn_arg: &FnArg) -> Option<Pat> {
     if let FnArg::Typed(pat_type) = &*fn_arg {
@@ -63,6 +63,13 @@ pub fn tx_hook_after(_: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// `#[tx_pledge]` marks a service method returning the fee pledged from the
+/// sender by its tx hooks, of the type `Option<FeePledge>`
+#[proc_macro_attribute]
+pub fn tx_pledge(_: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 #[rustfmt::skip]
 /// `#[read]` marks a service method as readable.
 ///
@@ -91,8 +98,8 @@ pub fn tx_hook_after(_: TokenStream, item: TokenStream) -> TokenStream {
 /// }
 /// ```
 #[proc_macro_attribute]
-pub fn read(_: TokenStream, item: TokenStream) -> TokenStream {
-    verify_read_or_write(item, false)
+pub fn read(attr: TokenStream, item: TokenStream) -> TokenStream {
+    verify_read_or_write(attr, item, false)
 }
 
 #[rustfmt::skip]
@@ -123,9 +130,29 @@ pub fn read(_: TokenStream, item: TokenStream) -> TokenStream {
 ///     }
 /// }
 /// ```
+///
+/// Both `#[read]` and `#[write]` accept `codec = "fixed"`, with which the
+/// method also accepts payloads encoded by `FixedCodec`, sent as hex strings
+/// along with `PayloadEncoding::Fixed`. The payload type must implement both
+/// `Deserialize` and `FixedCodec`, and the response is still encoded as JSON.
+///
+/// ```rust
+/// struct Service;
+/// #[service]
+/// impl Service {
+///     #[write(codec = "fixed")]
+///     fn transfer(
+///         &mut self,
+///         _ctx: ServiceContext,
+///         payload: TransferPayload,
+///     ) -> ServiceResponse<()> {
+///         ServiceResponse::<()>::from_succeed(())
+///     }
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn write(_: TokenStream, item: TokenStream) -> TokenStream {
-    verify_read_or_write(item, true)
+pub fn write(attr: TokenStream, item: TokenStream) -> TokenStream {
+    verify_read_or_write(attr, item, true)
 }
 
 #[rustfmt::skip]
@@ -3,9 +3,16 @@ use quote::quote;
 use syn::punctuated::Punctuated;
 use syn::{parse_macro_input, FnArg, ImplItemMethod, ReturnType, Token, Visibility};
 
-use crate::common::{arg_is_immutable_receiver, arg_is_mutable_receiver, assert_type};
+use crate::common::{
+    arg_is_immutable_receiver, arg_is_mutable_receiver, assert_type, PayloadCodec,
+};
 
-pub fn verify_read_or_write(item: TokenStream, mutable: bool) -> TokenStream {
+pub fn verify_read_or_write(
+    attr: TokenStream,
+    item: TokenStream,
+    mutable: bool,
+) -> TokenStream {
+    let codec = parse_macro_input!(attr as PayloadCodec);
     let method_item = parse_macro_input!(item as ImplItemMethod);
 
     let visibility = &method_item.vis;
@@ -16,6 +23,10 @@ pub fn verify_read_or_write(item: TokenStream, mutable: bool) -> TokenStream {
 
     verify_inputs(inputs, mutable);
 
+    if codec == PayloadCodec::Fixed && inputs.len() != 3 {
+        panic!("The method with a fixed codec should take a payload")
+    }
+
     verify_ret_type(ret_type);
 
     TokenStream::from(quote! {#method_item})
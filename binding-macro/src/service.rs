@@ -1,6 +1,11 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, Type};
+use syn::{
+    parse_macro_input, Attribute, FnArg, GenericArgument, Ident, ImplItem,
+    ImplItemMethod, ItemImpl, LitInt, PathArguments, ReturnType, Type,
+};
+
+use crate::common::PayloadCodec;
 
 const READ_ATTRIBUTE: &str = "read";
 const WRITE_ATTRIBUTE: &str = "write";
@@ -9,10 +14,12 @@ const HOOK_BEFORE_ATTRIBUTE: &str = "hook_before";
 const HOOK_AFTER_ATTRIBUTE: &str = "hook_after";
 const TX_HOOK_BEFORE_ATTRIBUTE: &str = "tx_hook_before";
 const TX_HOOK_AFTER_ATTRIBUTE: &str = "tx_hook_after";
+const TX_PLEDGE_ATTRIBUTE: &str = "tx_pledge";
+const CYCLES_ATTRIBUTE: &str = "cycles";
 
 enum ServiceMethod {
-    Read(ImplItemMethod),
-    Write(ImplItemMethod),
+    Read(ImplItemMethod, PayloadCodec),
+    Write(ImplItemMethod, PayloadCodec),
 }
 
 struct Hooks {
@@ -20,12 +27,17 @@ struct Hooks {
     after: Option<Ident>,
     tx_before: Option<Ident>,
     tx_after: Option<Ident>,
+    tx_pledge: Option<Ident>,
 }
 
 struct MethodMeta {
     method_ident: Ident,
     payload_ident: Option<Ident>,
     readonly: bool,
+    codec: PayloadCodec,
+    cycles: Option<u64>,
+    // The `T` of the returned `ServiceResponse<T>`
+    response_ty: Option<Type>,
 }
 
 pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
@@ -96,13 +108,22 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
         }
         None => quote! {ServiceResponse::<String>::from_succeed("".to_owned())},
     };
+    let tx_pledge = &hooks.tx_pledge;
+    let tx_pledge_fn = match tx_pledge {
+        Some(tx_pledge) => quote! {
+            fn tx_pledge_(&self, _ctx: ServiceContext) -> Option<protocol::types::FeePledge> {
+                self.#tx_pledge(_ctx)
+            }
+        },
+        None => quote! {},
+    };
 
     let list_method_meta: Vec<MethodMeta> =
         methods.into_iter().map(extract_method_meta).collect();
 
-    let (list_read_name, list_read_ident, list_read_payload) =
+    let (list_read_name, list_read_ident, list_read_payload, list_read_decode) =
         split_list_for_metadata(&list_method_meta, true);
-    let (list_write_name, list_write_ident, list_write_payload) =
+    let (list_write_name, list_write_ident, list_write_payload, list_write_decode) =
         split_list_for_metadata(&list_method_meta, false);
 
     let (list_read_name_nonepayload, list_read_ident_nonepayload) =
@@ -110,6 +131,9 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
     let (list_write_name_nonepayload, list_write_ident_nonepayload) =
         split_list_for_metadata_nonepayload(&list_method_meta, false);
 
+    let list_method_schema: Vec<proc_macro2::TokenStream> =
+        list_method_meta.iter().map(gen_method_schema).collect();
+
     TokenStream::from(quote! {
         impl #impl_generics protocol::traits::Service for #service_ident #ty_generics #where_clause {
             fn genesis_(&mut self, _payload: String) {
@@ -132,17 +156,19 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
                  #tx_hook_after_body
             }
 
+            #tx_pledge_fn
+
             fn read_(&self, ctx: protocol::types::ServiceContext) -> ServiceResponse<String> {
                 let service = ctx.get_service_name();
                 let method = ctx.get_service_method();
 
                 match method {
                     #(#list_read_name => {
-                        let payload_res: Result<#list_read_payload, _> = serde_json::from_str(ctx.get_payload());
-                        if payload_res.is_err() {
-                            return ServiceResponse::<String>::from_error(1, "decode service payload failed".to_owned());
+                        let payload_res: Result<#list_read_payload, String> = #list_read_decode;
+                        let payload = match payload_res {
+                            Ok(payload) => payload,
+                            Err(e) => return ServiceResponse::<String>::from_error(1, e),
                         };
-                        let payload = payload_res.unwrap();
                         let res = self.#list_read_ident(ctx, payload);
                         if !res.is_error() {
                             let mut data_json = serde_json::to_string(&res.succeed_data).unwrap_or_else(|e| panic!("encode succeed_data of ServiceResponse failed: {:?}", e));
@@ -176,11 +202,11 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
 
                 match method {
                     #(#list_write_name => {
-                        let payload_res: Result<#list_write_payload, _> = serde_json::from_str(ctx.get_payload());
-                        if payload_res.is_err() {
-                            return ServiceResponse::<String>::from_error(1, "decode service payload failed".to_owned());
+                        let payload_res: Result<#list_write_payload, String> = #list_write_decode;
+                        let payload = match payload_res {
+                            Ok(payload) => payload,
+                            Err(e) => return ServiceResponse::<String>::from_error(1, e),
                         };
-                        let payload = payload_res.unwrap();
                         let res = self.#list_write_ident(ctx, payload);
                         if !res.is_error() {
                             let mut data_json = serde_json::to_string(&res.succeed_data).unwrap_or_else(|e| panic!("encode succeed_data of ServiceResponse failed: {:?}", e));
@@ -207,19 +233,79 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
                     _ => ServiceResponse::<String>::from_error(2, format!("not found method:{:?} of service:{:?}", method, service))
                 }
             }
+
+            fn schema_(&self) -> protocol::types::ServiceSchema {
+                #[allow(unused_imports)]
+                use protocol::types::{AnySchemaProbe as _, JsonSchemaProbe as _};
+
+                protocol::types::ServiceSchema {
+                    methods: vec![#(#list_method_schema,)*],
+                }
+            }
         }
 
         #impl_item
     })
 }
 
+fn gen_method_schema(meta: &MethodMeta) -> proc_macro2::TokenStream {
+    let name = meta.method_ident.to_string();
+    let kind = if meta.readonly {
+        quote! { protocol::types::MethodKind::Read }
+    } else {
+        quote! { protocol::types::MethodKind::Write }
+    };
+    let cycles = match meta.cycles {
+        Some(cycles) => quote! { Some(#cycles) },
+        None => quote! { None },
+    };
+    let (payload, payload_typed) = match &meta.payload_ident {
+        Some(payload_ident) => (
+            quote! {
+                Some((&protocol::types::SchemaProbe::<#payload_ident>::new()).json_schema())
+            },
+            quote! { (&protocol::types::SchemaProbe::<#payload_ident>::new()).is_typed() },
+        ),
+        None => (quote! { None }, quote! { true }),
+    };
+    let (response, response_typed) = match &meta.response_ty {
+        Some(response_ty) => (
+            quote! {
+                (&protocol::types::SchemaProbe::<#response_ty>::new()).json_schema()
+            },
+            quote! { (&protocol::types::SchemaProbe::<#response_ty>::new()).is_typed() },
+        ),
+        None => (
+            quote! { serde_json::Value::Object(Default::default()) },
+            quote! { true },
+        ),
+    };
+
+    quote! {
+        protocol::types::MethodSchema {
+            name: #name.to_owned(),
+            kind: #kind,
+            cycles: #cycles,
+            payload: #payload,
+            response: #response,
+            typed: #payload_typed && #response_typed,
+        }
+    }
+}
+
 fn split_list_for_metadata(
     list: &[MethodMeta],
     readonly: bool,
-) -> (Vec<String>, Vec<Ident>, Vec<Ident>) {
+) -> (
+    Vec<String>,
+    Vec<Ident>,
+    Vec<Ident>,
+    Vec<proc_macro2::TokenStream>,
+) {
     let mut methods = vec![];
     let mut method_idents = vec![];
     let mut payload_idents = vec![];
+    let mut payload_decodes = vec![];
 
     list.iter()
         .filter(|meta| meta.readonly == readonly && meta.payload_ident.is_some())
@@ -232,8 +318,31 @@ fn split_list_for_metadata(
                     .expect("MethodMeta should have payload ident")
                     .clone(),
             );
+            payload_decodes.push(gen_payload_decode(meta));
         });
-    (methods, method_idents, payload_idents)
+    (methods, method_idents, payload_idents, payload_decodes)
+}
+
+// Decode the payload by the encoding of the request, the payloads encoded by
+// `FixedCodec` are only accepted by the methods declared with the fixed codec.
+fn gen_payload_decode(meta: &MethodMeta) -> proc_macro2::TokenStream {
+    let fixed_decode = match meta.codec {
+        PayloadCodec::Fixed => quote! {
+            protocol::types::decode_fixed_payload(ctx.get_payload())
+                .map_err(|_| "decode service payload failed".to_owned())
+        },
+        PayloadCodec::Json => quote! {
+            Err(format!("method:{:?} of service:{:?} only accepts json payload", method, service))
+        },
+    };
+
+    quote! {
+        match ctx.get_payload_encoding() {
+            protocol::types::PayloadEncoding::Json => serde_json::from_str(ctx.get_payload())
+                .map_err(|_| "decode service payload failed".to_owned()),
+            protocol::types::PayloadEncoding::Fixed => #fixed_decode,
+        }
+    }
 }
 
 fn split_list_for_metadata_nonepayload(
@@ -265,9 +374,9 @@ fn find_service_method(method: &ImplItemMethod) -> Option<ServiceMethod> {
     for attr in attrs {
         for segment in &attr.path.segments {
             if segment.ident == READ_ATTRIBUTE {
-                return Some(ServiceMethod::Read(method.clone()));
+                return Some(ServiceMethod::Read(method.clone(), parse_codec(attr)));
             } else if segment.ident == WRITE_ATTRIBUTE {
-                return Some(ServiceMethod::Write(method.clone()));
+                return Some(ServiceMethod::Write(method.clone(), parse_codec(attr)));
             }
         }
     }
@@ -275,6 +384,15 @@ fn find_service_method(method: &ImplItemMethod) -> Option<ServiceMethod> {
     None
 }
 
+fn parse_codec(attr: &Attribute) -> PayloadCodec {
+    if attr.tokens.is_empty() {
+        return PayloadCodec::Json;
+    }
+
+    attr.parse_args()
+        .expect("The codec should be like `#[read(codec = \"fixed\")]`")
+}
+
 fn find_genesis(items: &[ImplItem]) -> Option<ImplItemMethod> {
     let methods: Vec<ImplItemMethod> = find_list_for_item_method(items);
 
@@ -341,12 +459,14 @@ fn extract_hooks(items: &[ImplItem]) -> Hooks {
         after: None,
         tx_before: None,
         tx_after: None,
+        tx_pledge: None,
     };
 
     let mut before_count = 0;
     let mut after_count = 0;
     let mut tx_before_count = 0;
     let mut tx_after_count = 0;
+    let mut tx_pledge_count = 0;
 
     for method in methods {
         for attr in &method.attrs {
@@ -379,6 +499,13 @@ fn extract_hooks(items: &[ImplItem]) -> Hooks {
                     } else {
                         panic!("The tx after hook can only have one")
                     }
+                } else if segment.ident == TX_PLEDGE_ATTRIBUTE {
+                    if tx_pledge_count == 0 {
+                        hooks.tx_pledge = Some(method.sig.ident.clone());
+                        tx_pledge_count = 1;
+                    } else {
+                        panic!("The tx pledge can only have one")
+                    }
                 }
             }
         }
@@ -402,17 +529,23 @@ fn find_list_for_item_method(items: &[ImplItem]) -> Vec<ImplItemMethod> {
 }
 
 fn extract_method_meta(method: ServiceMethod) -> MethodMeta {
-    let (impl_method, readonly) = match method {
-        ServiceMethod::Read(impl_method) => (impl_method, true),
-        ServiceMethod::Write(impl_method) => (impl_method, false),
+    let (impl_method, readonly, codec) = match method {
+        ServiceMethod::Read(impl_method, codec) => (impl_method, true, codec),
+        ServiceMethod::Write(impl_method, codec) => (impl_method, false, codec),
     };
 
+    let cycles = extract_cycles(&impl_method);
+    let response_ty = extract_response_type(&impl_method.sig.output);
+
     match &impl_method.sig.inputs.len() {
         // Method input params: `(&self/&mut self, ctx: ServiceContext)`
         2 => MethodMeta {
             method_ident: impl_method.sig.ident,
             payload_ident: None,
             readonly,
+            codec,
+            cycles,
+            response_ty,
         },
         // Method input params: `(&self/&mut self, ctx: ServiceContext, payload: PayloadType)`
         3 => {
@@ -437,6 +570,9 @@ fn extract_method_meta(method: ServiceMethod) -> MethodMeta {
                 method_ident: impl_method.sig.ident,
                 payload_ident,
                 readonly,
+                codec,
+                cycles,
+                response_ty,
             }
         }
         _ => panic!(
@@ -444,3 +580,40 @@ fn extract_method_meta(method: ServiceMethod) -> MethodMeta {
         ),
     }
 }
+
+fn extract_cycles(method: &ImplItemMethod) -> Option<u64> {
+    let attr = method.attrs.iter().find(|attr| {
+        attr.path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == CYCLES_ATTRIBUTE)
+    })?;
+
+    let lit: LitInt = attr
+        .parse_args()
+        .expect("The cycles attribute should be like `#[cycles(10_000)]`");
+    Some(
+        lit.base10_parse::<u64>()
+            .expect("The cycles attribute should be an u64"),
+    )
+}
+
+fn extract_response_type(output: &ReturnType) -> Option<Type> {
+    let ty = match output {
+        ReturnType::Type(_, ty) => ty,
+        ReturnType::Default => return None,
+    };
+
+    let segment = match &**ty {
+        Type::Path(type_path) => type_path.path.segments.last()?,
+        _ => return None,
+    };
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -6,7 +6,9 @@ use std::cell::RefCell;
 use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
@@ -14,8 +16,8 @@ use protocol::traits::{
     StoreMap, StoreString, StoreUint64,
 };
 use protocol::types::{
-    Address, Block, Hash, Receipt, ServiceContext, ServiceContextParams,
-    SignedTransaction,
+    encode_fixed_payload, Address, Block, FeePledge, Hash, MethodKind, PayloadEncoding,
+    Receipt, ServiceContext, ServiceContextParams, SignedTransaction,
 };
 
 #[test]
@@ -101,6 +103,38 @@ fn test_tx_hooks() {
     assert_eq!(t.height, 10);
 }
 
+#[test]
+fn test_tx_pledge() {
+    struct Tests {
+        pub amount: u64,
+    };
+
+    #[service]
+    impl Tests {
+        #[tx_pledge]
+        fn tx_pledge(&self, ctx: ServiceContext) -> Option<FeePledge> {
+            Some(FeePledge {
+                asset_service: "asset".to_owned(),
+                asset_id: Hash::from_empty(),
+                amount: self.amount * ctx.get_cycles_limit(),
+            })
+        }
+    }
+
+    struct NoPledge;
+
+    #[service]
+    impl NoPledge {
+        #[hook_before]
+        fn hook_before(&mut self, _params: &ExecutorParams) {}
+    }
+
+    let context = get_context(1000, "", "", "");
+    let pledge = Tests { amount: 2 }.tx_pledge_(context.clone()).unwrap();
+    assert_eq!(pledge.amount, 2000);
+    assert_eq!(NoPledge.tx_pledge_(context), None);
+}
+
 #[test]
 fn test_read_and_write_with_noneparams() {
     struct Tests;
@@ -433,11 +467,146 @@ fn test_service_none_response() {
     assert_eq!(test_service.hook_after, true);
 }
 
+#[test]
+fn test_service_schema() {
+    #[derive(Serialize, Deserialize, Debug, JsonSchema)]
+    struct TestServicePayload {
+        name: String,
+        age: u64,
+    }
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestServiceResponse {
+        pub message: String,
+    }
+
+    struct Tests;
+
+    #[service]
+    impl Tests {
+        #[cycles(100)]
+        #[read]
+        fn test_read(
+            &self,
+            ctx: ServiceContext,
+            _payload: TestServicePayload,
+        ) -> ServiceResponse<TestServiceResponse> {
+            ServiceResponse::<TestServiceResponse>::from_succeed(
+                TestServiceResponse::default(),
+            )
+        }
+
+        #[write]
+        fn test_write(&mut self, _ctx: ServiceContext) -> ServiceResponse<u64> {
+            ServiceResponse::<u64>::from_succeed(0)
+        }
+    }
+
+    let schema = Tests.schema_();
+    assert_eq!(schema.methods.len(), 2);
+
+    let read = &schema.methods[0];
+    assert_eq!(read.name, "test_read");
+    assert_eq!(read.kind, MethodKind::Read);
+    assert_eq!(read.cycles, Some(100));
+    let payload = read.payload.as_ref().expect("payload schema");
+    assert_eq!(payload["title"], json!("TestServicePayload"));
+    assert_eq!(payload["properties"]["age"]["type"], json!("integer"));
+    // The response does not implement `JsonSchema`, so it can be anything
+    assert_eq!(read.response, json!({}));
+    assert!(!read.typed);
+
+    let write = &schema.methods[1];
+    assert_eq!(write.name, "test_write");
+    assert_eq!(write.kind, MethodKind::Write);
+    assert_eq!(write.cycles, None);
+    assert_eq!(write.payload, None);
+    assert_eq!(write.response["type"], json!("integer"));
+    assert!(write.typed);
+}
+
+#[test]
+fn test_service_fixed_payload() {
+    struct Tests;
+
+    #[service]
+    impl Tests {
+        #[read]
+        fn test_json(
+            &self,
+            _ctx: ServiceContext,
+            payload: String,
+        ) -> ServiceResponse<String> {
+            ServiceResponse::<String>::from_succeed(payload)
+        }
+
+        #[write(codec = "fixed")]
+        fn test_fixed(
+            &mut self,
+            _ctx: ServiceContext,
+            payload: String,
+        ) -> ServiceResponse<String> {
+            ServiceResponse::<String>::from_succeed(payload)
+        }
+    }
+
+    let mut test_service = Tests;
+    let fixed_payload = encode_fixed_payload(&"fixed".to_owned()).unwrap();
+
+    let context = get_context_with_encoding(
+        1024,
+        "",
+        "test_fixed",
+        &fixed_payload,
+        PayloadEncoding::Fixed,
+    );
+    let write_res = test_service.write_(context);
+    assert_eq!(write_res.succeed_data, r#""fixed""#);
+
+    // A fixed codec method still accepts json payloads
+    let context = get_context(1024, "", "test_fixed", r#""json""#);
+    let write_res = test_service.write_(context);
+    assert_eq!(write_res.succeed_data, r#""json""#);
+
+    let context = get_context_with_encoding(
+        1024,
+        "",
+        "test_fixed",
+        "0xzz",
+        PayloadEncoding::Fixed,
+    );
+    assert_eq!(test_service.write_(context).is_error(), true);
+
+    let context = get_context_with_encoding(
+        1024,
+        "",
+        "test_json",
+        &fixed_payload,
+        PayloadEncoding::Fixed,
+    );
+    assert_eq!(test_service.read_(context).is_error(), true);
+}
+
 fn get_context(
     cycles_limit: u64,
     service: &str,
     method: &str,
     payload: &str,
+) -> ServiceContext {
+    get_context_with_encoding(
+        cycles_limit,
+        service,
+        method,
+        payload,
+        PayloadEncoding::Json,
+    )
+}
+
+fn get_context_with_encoding(
+    cycles_limit: u64,
+    service: &str,
+    method: &str,
+    payload: &str,
+    encoding: PayloadEncoding,
 ) -> ServiceContext {
     let params = ServiceContextParams {
         tx_hash: None,
@@ -451,6 +620,7 @@ fn get_context(
         service_name: service.to_owned(),
         service_method: method.to_owned(),
         service_payload: payload.to_owned(),
+        service_payload_encoding: encoding,
         extra: None,
         events: Rc::new(RefCell::new(vec![])),
     };
@@ -552,4 +722,26 @@ impl ServiceSDK for MockServiceSDK {
     fn get_receipt_by_hash(&self, _tx_hash: &Hash) -> Option<Receipt> {
         unimplemented!()
     }
+
+    // Call a `#[read]` method of another service by name
+    fn read(
+        &self,
+        _ctx: &ServiceContext,
+        _service: &str,
+        _method: &str,
+        _payload: &str,
+    ) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    // Call a `#[write]` method of another service by name
+    fn write(
+        &mut self,
+        _ctx: &ServiceContext,
+        _service: &str,
+        _method: &str,
+        _payload: &str,
+    ) -> ServiceResponse<String> {
+        unimplemented!()
+    }
 }
@@ -270,7 +270,7 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
     }
 
     #[cycles(21_000)]
-    #[write]
+    #[write(codec = "fixed")]
     pub fn transfer(
         &mut self,
         ctx: ServiceContext,
@@ -6,12 +6,14 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use cita_trie::MemoryDB;
 
-use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use framework::binding::meter::CyclesMeter;
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK, NoopDispatcher};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
-use protocol::traits::{CommonStorage, Context, Storage};
+use protocol::traits::{CommonStorage, Context, Service, Storage};
 use protocol::types::{
-    Address, Block, BlockHeader, Hash, Proof, Receipt, ServiceContext,
-    ServiceContextParams, SignedTransaction,
+    encode_fixed_payload, Address, Block, BlockHeader, EventFilter, Hash, IndexedEvent,
+    PayloadEncoding, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
 };
 use protocol::ProtocolResult;
 
@@ -170,6 +172,57 @@ fn test_approve() {
     assert_eq!(allowance_res.value, 1024);
 }
 
+#[test]
+fn test_transfer_with_fixed_payload() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller =
+        Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let supply = 1024 * 1024;
+    let asset = service
+        .create_asset(
+            context.clone(),
+            CreateAssetPayload {
+                name: "test".to_owned(),
+                symbol: "test".to_owned(),
+                supply,
+            },
+        )
+        .succeed_data;
+
+    let to_address =
+        Address::from_str("muta15a8a9ksxe3hhjpw3l7wz7ry778qg8h9wz8y35p").unwrap();
+    let payload = encode_fixed_payload(&TransferPayload {
+        asset_id: asset.id.clone(),
+        to: to_address,
+        value: 1024,
+    })
+    .unwrap();
+    let fixed_context = mock_context_with_payload(
+        cycles_limit,
+        caller.clone(),
+        "transfer",
+        &payload,
+        PayloadEncoding::Fixed,
+    );
+    let res = service.write_(fixed_context);
+    assert!(!res.is_error());
+
+    let balance_res = service
+        .get_balance(
+            context,
+            GetBalancePayload {
+                asset_id: asset.id,
+                user: caller,
+            },
+        )
+        .succeed_data;
+    assert_eq!(balance_res.balance, supply - 1024);
+}
+
 #[test]
 fn test_transfer_from() {
     let cycles_limit = 1024 * 1024 * 1024; // 1073741824
@@ -252,18 +305,43 @@ fn test_transfer_from() {
 }
 
 fn new_asset_service() -> AssetService<
-    DefaultServiceSDK<GeneralServiceState<MemoryDB>, DefaultChainQuerier<MockStorage>>,
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        NoopDispatcher,
+    >,
 > {
     let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
     let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
     let state = GeneralServiceState::new(trie);
 
-    let sdk = DefaultServiceSDK::new(Rc::new(RefCell::new(state)), Rc::new(chain_db));
+    let sdk = DefaultServiceSDK::new(
+        Rc::new(RefCell::new(state)),
+        Rc::new(chain_db),
+        Rc::new(NoopDispatcher),
+        Rc::new(CyclesMeter::default()),
+    );
 
     AssetService::new(sdk)
 }
 
 fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    mock_context_with_payload(
+        cycles_limit,
+        caller,
+        "service_method",
+        "service_payload",
+        PayloadEncoding::Json,
+    )
+}
+
+fn mock_context_with_payload(
+    cycles_limit: u64,
+    caller: Address,
+    method: &str,
+    payload: &str,
+    encoding: PayloadEncoding,
+) -> ServiceContext {
     let params = ServiceContextParams {
         tx_hash: None,
         nonce: None,
@@ -274,8 +352,9 @@ fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
         height: 1,
         timestamp: 0,
         service_name: "service_name".to_owned(),
-        service_method: "service_method".to_owned(),
-        service_payload: "service_payload".to_owned(),
+        service_method: method.to_owned(),
+        service_payload: payload.to_owned(),
+        service_payload_encoding: encoding,
         extra: None,
         events: Rc::new(RefCell::new(vec![])),
     };
@@ -396,4 +475,16 @@ impl Storage for MockStorage {
     async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
         unimplemented!()
     }
+
+    async fn get_events(
+        &self,
+        _ctx: Context,
+        _: EventFilter,
+        _: u64,
+        _: u64,
+        _: u64,
+        _: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        unimplemented!()
+    }
 }
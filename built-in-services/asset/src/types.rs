@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 use muta_codec_derive::RlpFixedCodec;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use protocol::fixed_codec::{FixedCodec, FixedCodecError};
@@ -17,19 +18,19 @@ pub struct InitGenesisPayload {
     pub issuer: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct CreateAssetPayload {
     pub name: String,
     pub symbol: String,
     pub supply: u64,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct GetAssetPayload {
     pub id: Hash,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct TransferPayload {
     pub asset_id: Hash,
     pub to: Address,
@@ -54,7 +55,7 @@ pub struct ApproveEvent {
     pub value: u64,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct TransferFromPayload {
     pub asset_id: Hash,
     pub sender: Address,
@@ -71,27 +72,27 @@ pub struct TransferFromEvent {
     pub value: u64,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct GetBalancePayload {
     pub asset_id: Hash,
     pub user: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, JsonSchema)]
 pub struct GetBalanceResponse {
     pub asset_id: Hash,
     pub user: Address,
     pub balance: u64,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct GetAllowancePayload {
     pub asset_id: Hash,
     pub grantor: Address,
     pub grantee: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, JsonSchema)]
 pub struct GetAllowanceResponse {
     pub asset_id: Hash,
     pub grantor: Address,
@@ -99,7 +100,9 @@ pub struct GetAllowanceResponse {
     pub value: u64,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
+#[derive(
+    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Default, JsonSchema,
+)]
 pub struct Asset {
     pub id: Hash,
     pub name: String,
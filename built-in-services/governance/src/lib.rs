@@ -0,0 +1,466 @@
+#[cfg(test)]
+mod tests;
+pub mod types;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use binding_macro::{
+    cycles, genesis, hook_after, service, tx_hook_after, tx_hook_before, tx_pledge,
+};
+use derive_more::Display;
+
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
+use protocol::types::{
+    fee_policy, Address, FeePledge, FeePolicy, Hash, PayloadEncoding, ServiceContext,
+    ServiceContextParams,
+};
+
+use asset::types::{GetBalancePayload, TransferPayload};
+use asset::{Assets, ASSET_SERVICE_NAME};
+
+use crate::types::{
+    GetMinerChargeAddressPayload, GovernanceInfo, InitGenesisPayload, SetAddressPayload,
+    SetAdminPayload, SetGovernInfoPayload,
+};
+
+pub const GOVERNANCE_SERVICE_NAME: &str = "governance";
+const ADMIN_KEY: &str = "admin";
+const ASSET_ID_KEY: &str = "asset_id";
+const INFO_KEY: &str = "info";
+const TX_FEE_INLET_KEY: &str = "tx_fee_inlet_address";
+const MINER_PROFIT_OUTLET_KEY: &str = "miner_profit_outlet_address";
+
+/// Charges the fees of the txs in a native asset through its tx hooks, and
+/// pays a benefit to the proposer of every block.
+///
+/// The failure fee is pledged to the tx fee inlet address before a tx is
+/// executed. After the tx, the fee of the cycles it used is charged, and the
+/// pledge is refunded if it succeeds. The asset transfers are made with
+/// contexts of their own, so they do not use the cycles of the tx.
+///
+/// It replaces the fee policy of the executor, which must not be set in the
+/// metadata of a chain running it.
+pub struct GovernanceService<A, SDK> {
+    sdk: SDK,
+    miners: Box<dyn StoreMap<Address, Address>>,
+    asset: A,
+}
+
+#[service]
+impl<A: Assets, SDK: ServiceSDK> GovernanceService<A, SDK> {
+    pub fn new(mut sdk: SDK, asset: A) -> Self {
+        let miners: Box<dyn StoreMap<Address, Address>> =
+            sdk.alloc_or_recover_map("miner_charge_address");
+
+        Self { sdk, miners, asset }
+    }
+
+    #[genesis]
+    fn init_genesis(&mut self, payload: InitGenesisPayload) {
+        verify_fee_policy(fee_policy().as_ref()).expect("conflicting fee policy");
+        let info = sort_info(payload.info).expect("invalid governance info");
+
+        self.sdk.set_value(ADMIN_KEY.to_owned(), payload.admin);
+        self.sdk
+            .set_value(ASSET_ID_KEY.to_owned(), payload.asset_id);
+        self.sdk.set_value(INFO_KEY.to_owned(), info);
+        self.sdk
+            .set_value(TX_FEE_INLET_KEY.to_owned(), payload.tx_fee_inlet_address);
+        self.sdk.set_value(
+            MINER_PROFIT_OUTLET_KEY.to_owned(),
+            payload.miner_profit_outlet_address,
+        );
+
+        for miner in payload.miner_charge_map.into_iter() {
+            self.miners
+                .insert(miner.address, miner.miner_charge_address);
+        }
+    }
+
+    #[cycles(10_000)]
+    #[read]
+    fn get_admin_address(&self, ctx: ServiceContext) -> ServiceResponse<Address> {
+        match self.sdk.get_value(&ADMIN_KEY.to_owned()) {
+            Some(admin) => ServiceResponse::from_succeed(admin),
+            None => ServiceError::MissingInfo.into(),
+        }
+    }
+
+    #[cycles(10_000)]
+    #[read]
+    fn get_asset_id(&self, ctx: ServiceContext) -> ServiceResponse<Hash> {
+        match self.sdk.get_value(&ASSET_ID_KEY.to_owned()) {
+            Some(asset_id) => ServiceResponse::from_succeed(asset_id),
+            None => ServiceError::MissingInfo.into(),
+        }
+    }
+
+    #[cycles(10_000)]
+    #[read]
+    fn get_govern_info(&self, ctx: ServiceContext) -> ServiceResponse<GovernanceInfo> {
+        match self.sdk.get_value(&INFO_KEY.to_owned()) {
+            Some(info) => ServiceResponse::from_succeed(info),
+            None => ServiceError::MissingInfo.into(),
+        }
+    }
+
+    #[cycles(10_000)]
+    #[read]
+    fn get_tx_fee_inlet_address(&self, ctx: ServiceContext) -> ServiceResponse<Address> {
+        match self.sdk.get_value(&TX_FEE_INLET_KEY.to_owned()) {
+            Some(address) => ServiceResponse::from_succeed(address),
+            None => ServiceError::MissingInfo.into(),
+        }
+    }
+
+    #[cycles(10_000)]
+    #[read]
+    fn get_miner_profit_outlet_address(
+        &self,
+        ctx: ServiceContext,
+    ) -> ServiceResponse<Address> {
+        match self.sdk.get_value(&MINER_PROFIT_OUTLET_KEY.to_owned()) {
+            Some(address) => ServiceResponse::from_succeed(address),
+            None => ServiceError::MissingInfo.into(),
+        }
+    }
+
+    /// The address the miner benefit of the blocks proposed by the address is
+    /// paid to, the address itself if no other address is set.
+    #[cycles(10_000)]
+    #[read]
+    fn get_miner_charge_address(
+        &self,
+        ctx: ServiceContext,
+        payload: GetMinerChargeAddressPayload,
+    ) -> ServiceResponse<Address> {
+        let address = self.miners.get(&payload.address).unwrap_or(payload.address);
+        ServiceResponse::from_succeed(address)
+    }
+
+    #[cycles(21_000)]
+    #[write]
+    fn set_admin(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetAdminPayload,
+    ) -> ServiceResponse<()> {
+        if let Err(err) = self.verify_admin(&ctx) {
+            return err.into();
+        }
+
+        self.sdk.set_value(ADMIN_KEY.to_owned(), payload.admin);
+        ServiceResponse::from_succeed(())
+    }
+
+    #[cycles(21_000)]
+    #[write]
+    fn set_govern_info(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetGovernInfoPayload,
+    ) -> ServiceResponse<()> {
+        if let Err(err) = self.verify_admin(&ctx) {
+            return err.into();
+        }
+
+        match sort_info(payload.info) {
+            Ok(info) => {
+                self.sdk.set_value(INFO_KEY.to_owned(), info);
+                ServiceResponse::from_succeed(())
+            }
+            Err(err) => err.into(),
+        }
+    }
+
+    #[cycles(21_000)]
+    #[write]
+    fn set_tx_fee_inlet_address(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetAddressPayload,
+    ) -> ServiceResponse<()> {
+        if let Err(err) = self.verify_admin(&ctx) {
+            return err.into();
+        }
+
+        self.sdk
+            .set_value(TX_FEE_INLET_KEY.to_owned(), payload.address);
+        ServiceResponse::from_succeed(())
+    }
+
+    #[cycles(21_000)]
+    #[write]
+    fn set_miner_profit_outlet_address(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetAddressPayload,
+    ) -> ServiceResponse<()> {
+        if let Err(err) = self.verify_admin(&ctx) {
+            return err.into();
+        }
+
+        self.sdk
+            .set_value(MINER_PROFIT_OUTLET_KEY.to_owned(), payload.address);
+        ServiceResponse::from_succeed(())
+    }
+
+    /// Pay the miner benefit of the blocks proposed by the caller to the
+    /// address in the payload.
+    #[cycles(21_000)]
+    #[write]
+    fn set_miner_charge_address(
+        &mut self,
+        ctx: ServiceContext,
+        payload: SetAddressPayload,
+    ) -> ServiceResponse<()> {
+        self.miners.insert(ctx.get_caller(), payload.address);
+        ServiceResponse::from_succeed(())
+    }
+
+    #[tx_hook_before]
+    fn pledge_fee(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
+        let (asset_id, info, inlet) = match self.fee_config() {
+            Ok(config) => config,
+            Err(err) => return err.into(),
+        };
+
+        self.transfer(&ctx, asset_id, ctx.get_caller(), inlet, info.tx_failure_fee)
+    }
+
+    /// The failure fee pledged by `pledge_fee`, so that the mempool keeps
+    /// out the txs whose senders can not pledge it.
+    #[tx_pledge]
+    fn failure_fee_pledge(&self, _ctx: ServiceContext) -> Option<FeePledge> {
+        // Every pledge fails without the config, which is not up to the sender
+        let (asset_id, info, _) = self.fee_config().ok()?;
+        if info.tx_failure_fee == 0 {
+            return None;
+        }
+
+        Some(FeePledge {
+            asset_service: ASSET_SERVICE_NAME.to_owned(),
+            asset_id,
+            amount: info.tx_failure_fee,
+        })
+    }
+
+    #[tx_hook_after]
+    fn deduct_fee(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
+        let (asset_id, info, inlet) = match self.fee_config() {
+            Ok(config) => config,
+            Err(err) => return err.into(),
+        };
+
+        let sender = ctx.get_caller();
+        let fee = match self.calc_fee(&ctx, &asset_id, &info) {
+            Ok(fee) => fee,
+            Err(err) => return err.into(),
+        };
+
+        // The pledge of a failed tx is kept as the failure fee, otherwise it
+        // pays for the fee
+        if ctx.canceled() {
+            self.transfer(&ctx, asset_id, sender, inlet, fee)
+        } else if fee >= info.tx_failure_fee {
+            self.transfer(&ctx, asset_id, sender, inlet, fee - info.tx_failure_fee)
+        } else {
+            self.transfer(&ctx, asset_id, inlet, sender, info.tx_failure_fee - fee)
+        }
+    }
+
+    #[hook_after]
+    fn handle_miner_profit(&mut self, params: &ExecutorParams) {
+        let asset_id: Option<Hash> = self.sdk.get_value(&ASSET_ID_KEY.to_owned());
+        let info: Option<GovernanceInfo> = self.sdk.get_value(&INFO_KEY.to_owned());
+        let outlet: Option<Address> =
+            self.sdk.get_value(&MINER_PROFIT_OUTLET_KEY.to_owned());
+
+        let (asset_id, info, outlet) = match (asset_id, info, outlet) {
+            (Some(asset_id), Some(info), Some(outlet)) => (asset_id, info, outlet),
+            _ => return,
+        };
+
+        let recipient = self
+            .miners
+            .get(&params.proposer)
+            .unwrap_or_else(|| params.proposer.clone());
+
+        let ctx = asset_context(outlet.clone(), params.height, params.timestamp);
+
+        // Not paid if the outlet runs out of the asset
+        let _ = self.transfer(&ctx, asset_id, outlet, recipient, info.miner_benefit);
+    }
+
+    fn verify_admin(&self, ctx: &ServiceContext) -> Result<(), ServiceError> {
+        let admin: Option<Address> = self.sdk.get_value(&ADMIN_KEY.to_owned());
+        if admin != Some(ctx.get_caller()) {
+            return Err(ServiceError::NonAuthorized);
+        }
+
+        Ok(())
+    }
+
+    fn fee_config(&self) -> Result<(Hash, GovernanceInfo, Address), ServiceError> {
+        let asset_id = self.sdk.get_value(&ASSET_ID_KEY.to_owned());
+        let info = self.sdk.get_value(&INFO_KEY.to_owned());
+        let inlet = self.sdk.get_value(&TX_FEE_INLET_KEY.to_owned());
+
+        match (asset_id, info, inlet) {
+            (Some(asset_id), Some(info), Some(inlet)) => Ok((asset_id, info, inlet)),
+            _ => Err(ServiceError::MissingInfo),
+        }
+    }
+
+    // The fee of the cycles used by the tx, discounted by the balance of the
+    // sender and no less than the floor fee
+    fn calc_fee(
+        &self,
+        ctx: &ServiceContext,
+        asset_id: &Hash,
+        info: &GovernanceInfo,
+    ) -> Result<u64, ServiceError> {
+        let fee = ctx
+            .get_cycles_used()
+            .checked_mul(ctx.get_cycles_price())
+            .ok_or(ServiceError::Overflow)?;
+
+        let balance_ctx = asset_context(
+            ctx.get_caller(),
+            ctx.get_current_height(),
+            ctx.get_timestamp(),
+        );
+        let resp = self.asset.balance_(
+            &balance_ctx,
+            GetBalancePayload {
+                asset_id: asset_id.clone(),
+                user: ctx.get_caller(),
+            },
+        );
+        if resp.is_error() {
+            return Err(ServiceError::QueryBalance);
+        }
+
+        let discount_percent = info
+            .tx_fee_discount
+            .iter()
+            .rev()
+            .find(|level| resp.succeed_data.balance >= level.threshold)
+            .map_or(100, |level| level.discount_percent);
+        let fee = fee
+            .checked_mul(discount_percent)
+            .ok_or(ServiceError::Overflow)?
+            / 100;
+
+        Ok(fee.max(info.tx_floor_fee))
+    }
+
+    fn transfer(
+        &mut self,
+        ctx: &ServiceContext,
+        asset_id: Hash,
+        from: Address,
+        to: Address,
+        value: u64,
+    ) -> ServiceResponse<()> {
+        // The asset service rejects transferring to oneself
+        if value == 0 || from == to {
+            return ServiceResponse::from_succeed(());
+        }
+
+        let ctx = asset_context(from, ctx.get_current_height(), ctx.get_timestamp());
+        self.asset.transfer_(
+            &ctx,
+            TransferPayload {
+                asset_id,
+                to,
+                value,
+            },
+        )
+    }
+}
+
+// The context of the calls to the asset service made by the hooks, which do
+// not use the cycles of the tx
+fn asset_context(caller: Address, height: u64, timestamp: u64) -> ServiceContext {
+    ServiceContext::new(ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit: std::u64::MAX,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height,
+        timestamp,
+        service_name: String::new(),
+        service_method: String::new(),
+        service_payload: String::new(),
+        service_payload_encoding: PayloadEncoding::Json,
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    })
+}
+
+// The fees would be charged twice if the executor charged them as well
+fn verify_fee_policy(fee_policy: Option<&FeePolicy>) -> Result<(), ServiceError> {
+    match fee_policy {
+        Some(_) => Err(ServiceError::ConflictingFeePolicy),
+        None => Ok(()),
+    }
+}
+
+// Sort the discount levels by the threshold, so the highest level a balance
+// reaches is the last one no higher than it
+fn sort_info(mut info: GovernanceInfo) -> Result<GovernanceInfo, ServiceError> {
+    if info
+        .tx_fee_discount
+        .iter()
+        .any(|level| level.discount_percent > 100)
+    {
+        return Err(ServiceError::InvalidDiscount);
+    }
+
+    info.tx_fee_discount.sort_by_key(|level| level.threshold);
+    Ok(info)
+}
+
+#[derive(Debug, Display)]
+pub enum ServiceError {
+    #[display(fmt = "caller is not the admin")]
+    NonAuthorized,
+
+    #[display(fmt = "governance info is missing")]
+    MissingInfo,
+
+    #[display(fmt = "discount percent can not be higher than 100")]
+    InvalidDiscount,
+
+    #[display(fmt = "fee overflows")]
+    Overflow,
+
+    #[display(fmt = "query balance failed")]
+    QueryBalance,
+
+    #[display(fmt = "the fee policy of metadata must not be set with governance")]
+    ConflictingFeePolicy,
+}
+
+impl ServiceError {
+    fn code(&self) -> u64 {
+        match self {
+            ServiceError::NonAuthorized => 101,
+            ServiceError::MissingInfo => 102,
+            ServiceError::InvalidDiscount => 103,
+            ServiceError::Overflow => 104,
+            ServiceError::QueryBalance => 105,
+            ServiceError::ConflictingFeePolicy => 106,
+        }
+    }
+}
+
+impl<T: Default> From<ServiceError> for ServiceResponse<T> {
+    fn from(err: ServiceError) -> ServiceResponse<T> {
+        ServiceResponse::from_error(err.code(), err.to_string())
+    }
+}
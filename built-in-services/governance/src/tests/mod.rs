@@ -0,0 +1,527 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::MemoryDB;
+
+use asset::types::GetBalancePayload;
+use asset::{AssetService, Assets};
+use framework::binding::meter::CyclesMeter;
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK, NoopDispatcher};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::traits::{CommonStorage, Context, ExecutorParams, Service, Storage};
+use protocol::types::{
+    Address, Block, BlockHeader, Bytes, EventFilter, FeePolicy, Hash, IndexedEvent,
+    PayloadEncoding, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
+};
+use protocol::ProtocolResult;
+
+use crate::types::{
+    DiscountLevel, GetMinerChargeAddressPayload, GovernanceInfo, InitGenesisPayload,
+    MinerChargeConfig, SetAddressPayload, SetAdminPayload, SetGovernInfoPayload,
+};
+use crate::{verify_fee_policy, GovernanceService};
+
+type TestSDK = DefaultServiceSDK<
+    GeneralServiceState<MemoryDB>,
+    DefaultChainQuerier<MockStorage>,
+    NoopDispatcher,
+>;
+
+const ADMIN: &str = "muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705";
+const INLET: &str = "muta15a8a9ksxe3hhjpw3l7wz7ry778qg8h9wz8y35p";
+const PROPOSER: &str = "muta1h99h6f54vytatam3ckftrmvcdpn4jlmnwm6hl0";
+const SUPPLY: u64 = 1_000_000;
+
+#[test]
+fn test_init_genesis() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_str(ADMIN).unwrap();
+    let context = mock_context(cycles_limit, admin.clone());
+
+    let service = new_governance_service();
+
+    let resp = service.get_admin_address(context.clone());
+    assert_eq!(resp.succeed_data, admin);
+
+    // The discount levels are sorted by the threshold
+    let info = service.get_govern_info(context.clone()).succeed_data;
+    let thresholds = info
+        .tx_fee_discount
+        .iter()
+        .map(|level| level.threshold)
+        .collect::<Vec<_>>();
+    assert_eq!(thresholds, vec![1000, 100_000]);
+
+    let resp = service.get_asset_id(context.clone());
+    assert_eq!(resp.succeed_data, mock_asset_id());
+
+    let resp = service.get_tx_fee_inlet_address(context.clone());
+    assert_eq!(resp.succeed_data, Address::from_str(INLET).unwrap());
+    let resp = service.get_miner_profit_outlet_address(context.clone());
+    assert_eq!(resp.succeed_data, admin);
+
+    let proposer = Address::from_str(PROPOSER).unwrap();
+    let resp = service.get_miner_charge_address(
+        context.clone(),
+        GetMinerChargeAddressPayload {
+            address: proposer.clone(),
+        },
+    );
+    assert_eq!(resp.succeed_data, mock_charge_address());
+
+    let resp = service.get_miner_charge_address(
+        context,
+        GetMinerChargeAddressPayload {
+            address: admin.clone(),
+        },
+    );
+    assert_eq!(resp.succeed_data, admin);
+}
+
+#[test]
+fn test_verify_fee_policy() {
+    let fee = FeePolicy {
+        asset_service: "asset".to_owned(),
+        asset_id: mock_asset_id(),
+        failure_fee: 0,
+    };
+
+    assert!(verify_fee_policy(None).is_ok());
+    assert!(verify_fee_policy(Some(&fee)).is_err());
+}
+
+#[test]
+fn test_set_admin() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_str(ADMIN).unwrap();
+    let other = Address::from_str(INLET).unwrap();
+
+    let mut service = new_governance_service();
+
+    let resp = service.set_admin(
+        mock_context(cycles_limit, other.clone()),
+        SetAdminPayload {
+            admin: other.clone(),
+        },
+    );
+    assert_eq!(resp.code, 101);
+
+    let resp = service.set_admin(
+        mock_context(cycles_limit, admin.clone()),
+        SetAdminPayload {
+            admin: other.clone(),
+        },
+    );
+    assert!(!resp.is_error());
+
+    let resp = service.get_admin_address(mock_context(cycles_limit, admin.clone()));
+    assert_eq!(resp.succeed_data, other);
+
+    // The old admin is no longer allowed
+    let resp = service.set_admin(
+        mock_context(cycles_limit, admin.clone()),
+        SetAdminPayload { admin },
+    );
+    assert_eq!(resp.code, 101);
+}
+
+#[test]
+fn test_set_govern_info() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin = Address::from_str(ADMIN).unwrap();
+    let other = Address::from_str(INLET).unwrap();
+
+    let mut service = new_governance_service();
+
+    let mut info = mock_info();
+    info.tx_floor_fee = 100;
+
+    let resp = service.set_govern_info(
+        mock_context(cycles_limit, other.clone()),
+        SetGovernInfoPayload { info: info.clone() },
+    );
+    assert_eq!(resp.code, 101);
+
+    let mut invalid = info.clone();
+    invalid.tx_fee_discount[0].discount_percent = 101;
+    let resp = service.set_govern_info(
+        mock_context(cycles_limit, admin.clone()),
+        SetGovernInfoPayload { info: invalid },
+    );
+    assert_eq!(resp.code, 103);
+
+    let resp = service.set_govern_info(
+        mock_context(cycles_limit, admin.clone()),
+        SetGovernInfoPayload { info },
+    );
+    assert!(!resp.is_error());
+
+    let info = service
+        .get_govern_info(mock_context(cycles_limit, admin.clone()))
+        .succeed_data;
+    assert_eq!(info.tx_floor_fee, 100);
+    assert_eq!(info.tx_fee_discount[0].threshold, 1000);
+
+    let resp = service.set_tx_fee_inlet_address(
+        mock_context(cycles_limit, other.clone()),
+        SetAddressPayload {
+            address: other.clone(),
+        },
+    );
+    assert_eq!(resp.code, 101);
+
+    let resp = service.set_tx_fee_inlet_address(
+        mock_context(cycles_limit, admin.clone()),
+        SetAddressPayload {
+            address: admin.clone(),
+        },
+    );
+    assert!(!resp.is_error());
+    let resp = service.set_miner_profit_outlet_address(
+        mock_context(cycles_limit, admin.clone()),
+        SetAddressPayload {
+            address: other.clone(),
+        },
+    );
+    assert!(!resp.is_error());
+
+    let context = mock_context(cycles_limit, admin.clone());
+    let resp = service.get_tx_fee_inlet_address(context.clone());
+    assert_eq!(resp.succeed_data, admin);
+    let resp = service.get_miner_profit_outlet_address(context);
+    assert_eq!(resp.succeed_data, other);
+}
+
+#[test]
+fn test_set_miner_charge_address() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let miner = Address::from_str(INLET).unwrap();
+    let charge_address = Address::from_str(ADMIN).unwrap();
+
+    let mut service = new_governance_service();
+
+    let resp = service.set_miner_charge_address(
+        mock_context(cycles_limit, miner.clone()),
+        SetAddressPayload {
+            address: charge_address.clone(),
+        },
+    );
+    assert!(!resp.is_error());
+
+    let resp = service.get_miner_charge_address(
+        mock_context(cycles_limit, miner.clone()),
+        GetMinerChargeAddressPayload { address: miner },
+    );
+    assert_eq!(resp.succeed_data, charge_address);
+}
+
+#[test]
+fn test_tx_fee() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let sender = Address::from_str(ADMIN).unwrap();
+    let inlet = Address::from_str(INLET).unwrap();
+
+    let mut service = new_governance_service();
+
+    // The sender holds more than 100_000, so pays half of the fee
+    let context = mock_context(cycles_limit, sender.clone());
+    let pledge = service.failure_fee_pledge(context.clone()).unwrap();
+    assert_eq!(pledge.asset_service, "asset");
+    assert_eq!(pledge.amount, 30);
+    assert!(!service.pledge_fee(context.clone()).is_error());
+    assert_eq!(balance(&service, &sender), SUPPLY - 30);
+    assert_eq!(balance(&service, &inlet), 30);
+
+    context.sub_cycles(100);
+    assert!(!service.deduct_fee(context).is_error());
+    assert_eq!(balance(&service, &sender), SUPPLY - 50);
+    assert_eq!(balance(&service, &inlet), 50);
+
+    // The fee is less than the pledge, the floor fee is charged and the rest of
+    // the pledge is refunded
+    let context = mock_context(cycles_limit, sender.clone());
+    assert!(!service.pledge_fee(context.clone()).is_error());
+    context.sub_cycles(10);
+    assert!(!service.deduct_fee(context).is_error());
+    assert_eq!(balance(&service, &sender), SUPPLY - 70);
+    assert_eq!(balance(&service, &inlet), 70);
+
+    // A failed tx pays both the failure fee and the fee
+    let context = mock_context(cycles_limit, sender.clone());
+    assert!(!service.pledge_fee(context.clone()).is_error());
+    context.sub_cycles(100);
+    context.cancel("tx_exec_return_code_not_zero".to_owned());
+    assert!(!service.deduct_fee(context).is_error());
+    assert_eq!(balance(&service, &sender), SUPPLY - 150);
+    assert_eq!(balance(&service, &inlet), 150);
+
+    // The pledge fails without the asset
+    let poor = Address::from_hash(Hash::digest(Bytes::from("poor"))).unwrap();
+    let resp = service.pledge_fee(mock_context(cycles_limit, poor.clone()));
+    assert!(resp.is_error());
+    assert_eq!(balance(&service, &poor), 0);
+}
+
+#[test]
+fn test_miner_profit() {
+    let outlet = Address::from_str(ADMIN).unwrap();
+    let proposer = Address::from_str(PROPOSER).unwrap();
+    let other = Address::from_str(INLET).unwrap();
+
+    let mut service = new_governance_service();
+
+    // Paid to the charge address of the proposer
+    service.handle_miner_profit(&mock_executor_params(1, proposer));
+    assert_eq!(balance(&service, &outlet), SUPPLY - 10);
+    assert_eq!(balance(&service, &mock_charge_address()), 10);
+
+    service.handle_miner_profit(&mock_executor_params(2, other.clone()));
+    assert_eq!(balance(&service, &outlet), SUPPLY - 20);
+    assert_eq!(balance(&service, &other), 10);
+}
+
+fn new_governance_service() -> GovernanceService<AssetService<TestSDK>, TestSDK> {
+    let mut asset = AssetService::new(new_sdk());
+    asset.genesis_(
+        serde_json::json!({
+            "id": mock_asset_id(),
+            "name": "MutaToken",
+            "symbol": "MT",
+            "supply": SUPPLY,
+            "issuer": ADMIN,
+        })
+        .to_string(),
+    );
+
+    let mut service = GovernanceService::new(new_sdk(), asset);
+    service.init_genesis(InitGenesisPayload {
+        admin: Address::from_str(ADMIN).unwrap(),
+        asset_id: mock_asset_id(),
+        info: mock_info(),
+        tx_fee_inlet_address: Address::from_str(INLET).unwrap(),
+        miner_profit_outlet_address: Address::from_str(ADMIN).unwrap(),
+        miner_charge_map: vec![MinerChargeConfig {
+            address: Address::from_str(PROPOSER).unwrap(),
+            miner_charge_address: mock_charge_address(),
+        }],
+    });
+
+    service
+}
+
+fn new_sdk() -> TestSDK {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    DefaultServiceSDK::new(
+        Rc::new(RefCell::new(state)),
+        Rc::new(chain_db),
+        Rc::new(NoopDispatcher),
+        Rc::new(CyclesMeter::default()),
+    )
+}
+
+fn balance(
+    service: &GovernanceService<AssetService<TestSDK>, TestSDK>,
+    user: &Address,
+) -> u64 {
+    let context = mock_context(1024 * 1024 * 1024, user.clone());
+    let resp = service.asset.balance_(
+        &context,
+        GetBalancePayload {
+            asset_id: mock_asset_id(),
+            user: user.clone(),
+        },
+    );
+    resp.succeed_data.balance
+}
+
+fn mock_info() -> GovernanceInfo {
+    GovernanceInfo {
+        tx_failure_fee: 30,
+        tx_floor_fee: 20,
+        tx_fee_discount: vec![
+            DiscountLevel {
+                threshold: 100_000,
+                discount_percent: 50,
+            },
+            DiscountLevel {
+                threshold: 1000,
+                discount_percent: 90,
+            },
+        ],
+        miner_benefit: 10,
+    }
+}
+
+fn mock_asset_id() -> Hash {
+    Hash::from_hex("0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c")
+        .unwrap()
+}
+
+fn mock_charge_address() -> Address {
+    Address::from_hash(Hash::digest(Bytes::from("charge"))).unwrap()
+}
+
+fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height: 1,
+        timestamp: 0,
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        service_payload_encoding: PayloadEncoding::Json,
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
+
+fn mock_executor_params(height: u64, proposer: Address) -> ExecutorParams {
+    ExecutorParams {
+        state_root: Hash::from_empty(),
+        height,
+        timestamp: 0,
+        cycles_limit: 1024 * 1024 * 1024,
+        proposer,
+    }
+}
+
+struct MockStorage;
+
+#[async_trait]
+impl CommonStorage for MockStorage {
+    async fn insert_block(&self, _ctx: Context, _block: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_block(
+        &self,
+        _ctx: Context,
+        _height: u64,
+    ) -> ProtocolResult<Option<Block>> {
+        unimplemented!()
+    }
+
+    async fn set_block(&self, _ctx: Context, _block: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn remove_block(&self, _ctx: Context, _height: u64) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self, _ctx: Context) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn set_latest_block(
+        &self,
+        _ctx: Context,
+        _block: Block,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block_header(
+        &self,
+        _ctx: Context,
+    ) -> ProtocolResult<BlockHeader> {
+        unimplemented!()
+    }
+
+    async fn get_block_header(
+        &self,
+        _ctx: Context,
+        _height: u64,
+    ) -> ProtocolResult<Option<BlockHeader>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _ctx: Context, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _ctx: Context,
+        _: &Hash,
+    ) -> ProtocolResult<Option<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: &[Hash],
+    ) -> ProtocolResult<Vec<Option<SignedTransaction>>> {
+        unimplemented!()
+    }
+
+    async fn get_receipt_by_hash(
+        &self,
+        _ctx: Context,
+        _: Hash,
+    ) -> ProtocolResult<Option<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(
+        &self,
+        _ctx: Context,
+        _: u64,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<Option<Receipt>>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn get_events(
+        &self,
+        _ctx: Context,
+        _: EventFilter,
+        _: u64,
+        _: u64,
+        _: u64,
+        _: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        unimplemented!()
+    }
+}
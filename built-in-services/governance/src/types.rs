@@ -0,0 +1,90 @@
+use muta_codec_derive::RlpFixedCodec;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::{Address, Bytes, Hash};
+use protocol::ProtocolResult;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct InitGenesisPayload {
+    /// The account allowed to update the governance parameters and addresses.
+    pub admin: Address,
+    /// The native asset the fees are charged in.
+    pub asset_id: Hash,
+    pub info: GovernanceInfo,
+    pub tx_fee_inlet_address: Address,
+    pub miner_profit_outlet_address: Address,
+    #[serde(default)]
+    pub miner_charge_map: Vec<MinerChargeConfig>,
+}
+
+/// The miner benefit of the blocks proposed by `address` is paid to
+/// `miner_charge_address`.
+#[derive(
+    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, JsonSchema,
+)]
+pub struct MinerChargeConfig {
+    pub address: Address,
+    pub miner_charge_address: Address,
+}
+
+#[derive(
+    RlpFixedCodec,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct GovernanceInfo {
+    /// Pledged before a tx is executed, and kept as a fine if it fails.
+    pub tx_failure_fee: u64,
+    /// The least fee of a tx after the discount.
+    pub tx_floor_fee: u64,
+    /// Sorted by the threshold.
+    pub tx_fee_discount: Vec<DiscountLevel>,
+    /// Paid to the proposer of every block from the miner profit outlet.
+    pub miner_benefit: u64,
+}
+
+/// The senders holding at least `threshold` of the native asset pay
+/// `discount_percent` percent of the fee.
+#[derive(
+    RlpFixedCodec,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    JsonSchema,
+)]
+pub struct DiscountLevel {
+    pub threshold: u64,
+    pub discount_percent: u64,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct SetAdminPayload {
+    pub admin: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct SetGovernInfoPayload {
+    pub info: GovernanceInfo,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct SetAddressPayload {
+    pub address: Address,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct GetMinerChargeAddressPayload {
+    pub address: Address,
+}
@@ -1,11 +1,26 @@
 #[cfg(test)]
 mod tests;
+pub mod types;
 
-use binding_macro::{cycles, genesis, service};
-use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK};
-use protocol::types::{Metadata, ServiceContext, METADATA_KEY};
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+
+use binding_macro::{cycles, genesis, hook_before, service};
+use derive_more::Display;
+
+use common_crypto::BlsPublicKey;
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
+use protocol::types::{
+    Address, Metadata, ServiceContext, ValidatorExtend, METADATA_KEY,
+};
+
+use crate::types::{
+    InitGenesisPayload, UpdateIntervalPayload, UpdateRatioPayload,
+    UpdateValidatorsPayload,
+};
 
 pub const METADATA_SERVICE_NAME: &str = "metadata";
+const ADMIN_KEY: &str = "admin";
 
 pub trait MetaData {
     fn get_(&self, ctx: &ServiceContext) -> ServiceResponse<Metadata>;
@@ -13,6 +28,9 @@ pub trait MetaData {
 
 pub struct MetadataService<SDK> {
     sdk: SDK,
+    pending_validators: Box<dyn StoreMap<u64, UpdateValidatorsPayload>>,
+    pending_intervals: Box<dyn StoreMap<u64, UpdateIntervalPayload>>,
+    pending_ratios: Box<dyn StoreMap<u64, UpdateRatioPayload>>,
 }
 
 impl<SDK: ServiceSDK> MetaData for MetadataService<SDK> {
@@ -23,13 +41,30 @@ impl<SDK: ServiceSDK> MetaData for MetadataService<SDK> {
 
 #[service]
 impl<SDK: ServiceSDK> MetadataService<SDK> {
-    pub fn new(sdk: SDK) -> Self {
-        Self { sdk }
+    pub fn new(mut sdk: SDK) -> Self {
+        let pending_validators: Box<dyn StoreMap<u64, UpdateValidatorsPayload>> =
+            sdk.alloc_or_recover_map("pending_validators");
+        let pending_intervals: Box<dyn StoreMap<u64, UpdateIntervalPayload>> =
+            sdk.alloc_or_recover_map("pending_intervals");
+        let pending_ratios: Box<dyn StoreMap<u64, UpdateRatioPayload>> =
+            sdk.alloc_or_recover_map("pending_ratios");
+
+        Self {
+            sdk,
+            pending_validators,
+            pending_intervals,
+            pending_ratios,
+        }
     }
 
     #[genesis]
-    fn init_genesis(&mut self, metadata: Metadata) {
-        self.sdk.set_value(METADATA_KEY.to_string(), metadata)
+    fn init_genesis(&mut self, payload: InitGenesisPayload) {
+        if let Some(admin) = payload.admin {
+            self.sdk.set_value(ADMIN_KEY.to_owned(), admin);
+        }
+
+        self.sdk
+            .set_value(METADATA_KEY.to_string(), payload.metadata)
     }
 
     #[cycles(21_000)]
@@ -41,4 +76,187 @@ impl<SDK: ServiceSDK> MetadataService<SDK> {
             .expect("metadata should not be none");
         ServiceResponse::<Metadata>::from_succeed(metadata)
     }
+
+    #[cycles(21_000)]
+    #[write]
+    fn update_validators(
+        &mut self,
+        ctx: ServiceContext,
+        payload: UpdateValidatorsPayload,
+    ) -> ServiceResponse<()> {
+        if let Err(err) = self.verify_update(&ctx, payload.effective_height) {
+            return err.into();
+        }
+        if let Err(err) = verify_verifier_list(&payload.verifier_list) {
+            return err.into();
+        }
+
+        self.pending_validators
+            .insert(payload.effective_height, payload);
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(21_000)]
+    #[write]
+    fn update_interval(
+        &mut self,
+        ctx: ServiceContext,
+        payload: UpdateIntervalPayload,
+    ) -> ServiceResponse<()> {
+        if let Err(err) = self.verify_update(&ctx, payload.effective_height) {
+            return err.into();
+        }
+        if payload.interval == 0 {
+            return ServiceError::InvalidInterval.into();
+        }
+
+        self.pending_intervals
+            .insert(payload.effective_height, payload);
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[cycles(21_000)]
+    #[write]
+    fn update_ratio(
+        &mut self,
+        ctx: ServiceContext,
+        payload: UpdateRatioPayload,
+    ) -> ServiceResponse<()> {
+        if let Err(err) = self.verify_update(&ctx, payload.effective_height) {
+            return err.into();
+        }
+        if payload.propose_ratio == 0
+            || payload.prevote_ratio == 0
+            || payload.precommit_ratio == 0
+            || payload.brake_ratio == 0
+        {
+            return ServiceError::InvalidRatio.into();
+        }
+
+        self.pending_ratios
+            .insert(payload.effective_height, payload);
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    /// Applies the updates of the executing block to the state, consensus
+    /// follows them once a committed block carries the resulting state root.
+    #[hook_before]
+    fn apply_pending_updates(&mut self, params: &ExecutorParams) {
+        let validators = self.pending_validators.remove(&params.height);
+        let interval = self.pending_intervals.remove(&params.height);
+        let ratio = self.pending_ratios.remove(&params.height);
+
+        if validators.is_none() && interval.is_none() && ratio.is_none() {
+            return;
+        }
+
+        let mut metadata: Metadata = self
+            .sdk
+            .get_value(&METADATA_KEY.to_owned())
+            .expect("metadata should not be none");
+
+        if let Some(validators) = validators {
+            metadata.verifier_list = validators.verifier_list;
+        }
+        if let Some(interval) = interval {
+            metadata.interval = interval.interval;
+        }
+        if let Some(ratio) = ratio {
+            metadata.propose_ratio = ratio.propose_ratio;
+            metadata.prevote_ratio = ratio.prevote_ratio;
+            metadata.precommit_ratio = ratio.precommit_ratio;
+            metadata.brake_ratio = ratio.brake_ratio;
+        }
+
+        self.sdk.set_value(METADATA_KEY.to_owned(), metadata)
+    }
+
+    fn verify_update(
+        &self,
+        ctx: &ServiceContext,
+        effective_height: u64,
+    ) -> Result<(), ServiceError> {
+        let admin: Option<Address> = self.sdk.get_value(&ADMIN_KEY.to_owned());
+        if admin != Some(ctx.get_caller()) {
+            return Err(ServiceError::NonAuthorized);
+        }
+
+        if effective_height <= ctx.get_current_height() {
+            return Err(ServiceError::InvalidEffectiveHeight);
+        }
+
+        Ok(())
+    }
+}
+
+/// Rejects the verifier list which consensus can not run with.
+fn verify_verifier_list(verifier_list: &[ValidatorExtend]) -> Result<(), ServiceError> {
+    if verifier_list.is_empty() {
+        return Err(ServiceError::EmptyVerifierList);
+    }
+
+    let mut pub_keys = BTreeSet::new();
+    for validator in verifier_list.iter() {
+        let invalid = |reason: &str| ServiceError::InvalidValidator {
+            pub_key: validator.pub_key.as_string(),
+            reason: reason.to_owned(),
+        };
+
+        if BlsPublicKey::try_from(validator.bls_pub_key.decode().as_ref()).is_err() {
+            return Err(invalid("malformed bls public key"));
+        }
+        match Address::from_pubkey_bytes(validator.pub_key.decode()) {
+            Ok(address) if address == validator.address => (),
+            Ok(_) => return Err(invalid("address doesn't match the public key")),
+            Err(_) => return Err(invalid("malformed public key")),
+        }
+        if validator.propose_weight == 0 || validator.vote_weight == 0 {
+            return Err(invalid("weights can not be zero"));
+        }
+        if !pub_keys.insert(validator.pub_key.clone()) {
+            return Err(invalid("duplicate validator"));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Display)]
+pub enum ServiceError {
+    #[display(fmt = "caller is not the admin")]
+    NonAuthorized,
+
+    #[display(fmt = "effective height must be higher than the current height")]
+    InvalidEffectiveHeight,
+
+    #[display(fmt = "verifier list can not be empty")]
+    EmptyVerifierList,
+
+    #[display(fmt = "interval can not be zero")]
+    InvalidInterval,
+
+    #[display(fmt = "ratios can not be zero")]
+    InvalidRatio,
+
+    #[display(fmt = "invalid validator {}, {}", pub_key, reason)]
+    InvalidValidator { pub_key: String, reason: String },
+}
+
+impl ServiceError {
+    fn code(&self) -> u64 {
+        match self {
+            ServiceError::NonAuthorized => 101,
+            ServiceError::InvalidEffectiveHeight => 102,
+            ServiceError::EmptyVerifierList => 103,
+            ServiceError::InvalidInterval => 104,
+            ServiceError::InvalidRatio => 105,
+            ServiceError::InvalidValidator { .. } => 106,
+        }
+    }
+}
+
+impl<T: Default> From<ServiceError> for ServiceResponse<T> {
+    fn from(err: ServiceError) -> ServiceResponse<T> {
+        ServiceResponse::from_error(err.code(), err.to_string())
+    }
 }
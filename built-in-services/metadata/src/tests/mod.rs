@@ -6,15 +6,21 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use cita_trie::MemoryDB;
 
-use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use framework::binding::meter::CyclesMeter;
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK, NoopDispatcher};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
-use protocol::traits::{CommonStorage, Context, ServiceSDK, Storage};
+use protocol::traits::{CommonStorage, Context, ExecutorParams, ServiceSDK, Storage};
 use protocol::types::{
-    Address, Block, BlockHeader, Hash, Hex, Metadata, Proof, Receipt, ServiceContext,
-    ServiceContextParams, SignedTransaction, ValidatorExtend, METADATA_KEY,
+    Address, Block, BlockHeader, EventFilter, Hash, Hex, IndexedEvent, Metadata,
+    PayloadEncoding, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction, ValidatorExtend, METADATA_KEY,
 };
 use protocol::{types::Bytes, ProtocolResult};
 
+use crate::types::{
+    InitGenesisPayload, UpdateIntervalPayload, UpdateRatioPayload,
+    UpdateValidatorsPayload,
+};
 use crate::MetadataService;
 
 #[test]
@@ -32,17 +38,155 @@ fn test_get_metadata() {
     assert_eq!(metadata, init_metadata);
 }
 
+#[test]
+fn test_update_metadata() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin =
+        Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705").unwrap();
+    let init_metadata = mock_metadata();
+
+    let mut service = new_metadata_service_with_metadata(init_metadata.clone());
+    service.init_genesis(InitGenesisPayload {
+        metadata: init_metadata.clone(),
+        admin: Some(admin.clone()),
+    });
+
+    let mut verifier_list = init_metadata.verifier_list.clone();
+    verifier_list[0].vote_weight = 2;
+    let update_validators = UpdateValidatorsPayload {
+        effective_height: 3,
+        verifier_list: verifier_list.clone(),
+    };
+
+    let other =
+        Address::from_str("muta15a8a9ksxe3hhjpw3l7wz7ry778qg8h9wz8y35p").unwrap();
+    let resp = service
+        .update_validators(mock_context(cycles_limit, other), update_validators.clone());
+    assert_eq!(resp.code, 101);
+
+    let resp = service.update_validators(
+        mock_context(cycles_limit, admin.clone()),
+        UpdateValidatorsPayload {
+            effective_height: 1,
+            verifier_list: verifier_list.clone(),
+        },
+    );
+    assert_eq!(resp.code, 102);
+
+    let resp = service
+        .update_validators(mock_context(cycles_limit, admin.clone()), update_validators);
+    assert!(!resp.is_error());
+    let resp = service.update_interval(
+        mock_context(cycles_limit, admin.clone()),
+        UpdateIntervalPayload {
+            effective_height: 3,
+            interval: 1000,
+        },
+    );
+    assert!(!resp.is_error());
+    let resp = service.update_ratio(
+        mock_context(cycles_limit, admin.clone()),
+        UpdateRatioPayload {
+            effective_height: 4,
+            propose_ratio: 15,
+            prevote_ratio: 10,
+            precommit_ratio: 10,
+            brake_ratio: 3,
+        },
+    );
+    assert!(!resp.is_error());
+
+    service.apply_pending_updates(&mock_executor_params(2));
+    let metadata = service
+        .get_metadata(mock_context(cycles_limit, admin.clone()))
+        .succeed_data;
+    assert_eq!(metadata, init_metadata);
+
+    service.apply_pending_updates(&mock_executor_params(3));
+    let metadata = service
+        .get_metadata(mock_context(cycles_limit, admin.clone()))
+        .succeed_data;
+    assert_eq!(metadata.verifier_list, verifier_list);
+    assert_eq!(metadata.interval, 1000);
+    assert_eq!(metadata.propose_ratio, init_metadata.propose_ratio);
+
+    service.apply_pending_updates(&mock_executor_params(4));
+    let metadata = service
+        .get_metadata(mock_context(cycles_limit, admin))
+        .succeed_data;
+    assert_eq!(metadata.propose_ratio, 15);
+    assert_eq!(metadata.brake_ratio, 3);
+}
+
+#[test]
+fn test_update_invalid_validators() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let admin =
+        Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705").unwrap();
+    let init_metadata = mock_metadata();
+
+    let mut service = new_metadata_service_with_metadata(init_metadata.clone());
+    service.init_genesis(InitGenesisPayload {
+        metadata: init_metadata.clone(),
+        admin: Some(admin.clone()),
+    });
+
+    let validator = init_metadata.verifier_list[0].clone();
+    let mut malformed_bls_key = validator.clone();
+    malformed_bls_key.bls_pub_key = Hex::from_string("0x0102".to_owned()).unwrap();
+    let mut malformed_pub_key = validator.clone();
+    malformed_pub_key.pub_key = Hex::from_string("0x0102".to_owned()).unwrap();
+    let mut mismatched_address = validator.clone();
+    mismatched_address.address =
+        Address::from_str("muta15a8a9ksxe3hhjpw3l7wz7ry778qg8h9wz8y35p").unwrap();
+    let mut zero_weight = validator.clone();
+    zero_weight.vote_weight = 0;
+
+    let invalid_lists = vec![
+        vec![],
+        vec![malformed_bls_key],
+        vec![malformed_pub_key],
+        vec![mismatched_address],
+        vec![zero_weight],
+        vec![validator.clone(), validator],
+    ];
+    for verifier_list in invalid_lists.into_iter() {
+        let resp = service.update_validators(
+            mock_context(cycles_limit, admin.clone()),
+            UpdateValidatorsPayload {
+                effective_height: 3,
+                verifier_list,
+            },
+        );
+        assert!(resp.is_error());
+    }
+
+    service.apply_pending_updates(&mock_executor_params(3));
+    let metadata = service
+        .get_metadata(mock_context(cycles_limit, admin))
+        .succeed_data;
+    assert_eq!(metadata, init_metadata);
+}
+
 fn new_metadata_service_with_metadata(
     metadata: Metadata,
 ) -> MetadataService<
-    DefaultServiceSDK<GeneralServiceState<MemoryDB>, DefaultChainQuerier<MockStorage>>,
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        NoopDispatcher,
+    >,
 > {
     let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
     let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
     let state = GeneralServiceState::new(trie);
 
-    let mut sdk =
-        DefaultServiceSDK::new(Rc::new(RefCell::new(state)), Rc::new(chain_db));
+    let mut sdk = DefaultServiceSDK::new(
+        Rc::new(RefCell::new(state)),
+        Rc::new(chain_db),
+        Rc::new(NoopDispatcher),
+        Rc::new(CyclesMeter::default()),
+    );
 
     sdk.set_value(METADATA_KEY.to_string(), metadata);
 
@@ -88,6 +232,7 @@ fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
         service_name: "service_name".to_owned(),
         service_method: "service_method".to_owned(),
         service_payload: "service_payload".to_owned(),
+        service_payload_encoding: PayloadEncoding::Json,
         extra: None,
         events: Rc::new(RefCell::new(vec![])),
     };
@@ -95,6 +240,17 @@ fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
     ServiceContext::new(params)
 }
 
+fn mock_executor_params(height: u64) -> ExecutorParams {
+    ExecutorParams {
+        state_root: Hash::from_empty(),
+        height,
+        timestamp: 0,
+        cycles_limit: 1024 * 1024 * 1024,
+        proposer: Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705")
+            .unwrap(),
+    }
+}
+
 struct MockStorage;
 
 #[async_trait]
@@ -208,4 +364,16 @@ impl Storage for MockStorage {
     async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
         unimplemented!()
     }
+
+    async fn get_events(
+        &self,
+        _ctx: Context,
+        _: EventFilter,
+        _: u64,
+        _: u64,
+        _: u64,
+        _: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        unimplemented!()
+    }
 }
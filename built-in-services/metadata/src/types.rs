@@ -0,0 +1,50 @@
+use muta_codec_derive::RlpFixedCodec;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use protocol::fixed_codec::{FixedCodec, FixedCodecError};
+use protocol::types::{Address, Bytes, Metadata, ValidatorExtend};
+use protocol::ProtocolResult;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct InitGenesisPayload {
+    #[serde(flatten)]
+    pub metadata: Metadata,
+    /// The account allowed to update the validators, interval and ratios. The
+    /// metadata can not be updated without it.
+    #[serde(default)]
+    pub admin: Option<Address>,
+}
+
+/// The updates are written to the state when the block of `effective_height`
+/// is executed, which must be higher than the height of the block containing
+/// the transaction. Consensus reads the metadata from the state root of the
+/// latest committed block, which records the execution up to its
+/// `exec_height`, so the updates take effect from the height next to the first
+/// committed block whose `exec_height` reaches `effective_height`.
+#[derive(
+    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, JsonSchema,
+)]
+pub struct UpdateValidatorsPayload {
+    pub effective_height: u64,
+    pub verifier_list: Vec<ValidatorExtend>,
+}
+
+#[derive(
+    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, JsonSchema,
+)]
+pub struct UpdateIntervalPayload {
+    pub effective_height: u64,
+    pub interval: u64,
+}
+
+#[derive(
+    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, JsonSchema,
+)]
+pub struct UpdateRatioPayload {
+    pub effective_height: u64,
+    pub propose_ratio: u64,
+    pub prevote_ratio: u64,
+    pub precommit_ratio: u64,
+    pub brake_ratio: u64,
+}
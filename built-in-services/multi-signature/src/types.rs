@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use muta_codec_derive::RlpFixedCodec;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use protocol::fixed_codec::{FixedCodec, FixedCodecError};
@@ -30,7 +31,9 @@ pub struct InitGenesisPayload {
     pub memo: String,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[derive(
+    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, JsonSchema,
+)]
 pub struct GenerateMultiSigAccountPayload {
     pub owner: Address,
     pub autonomy: bool,
@@ -39,67 +42,69 @@ pub struct GenerateMultiSigAccountPayload {
     pub memo: String,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, JsonSchema)]
 pub struct GenerateMultiSigAccountResponse {
     pub address: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct VerifySignaturePayload {
     pub tx_hash: Hash,
+    #[schemars(with = "Vec<Vec<u8>>")]
     pub pubkeys: Vec<Bytes>,
+    #[schemars(with = "Vec<Vec<u8>>")]
     pub signatures: Vec<Bytes>,
     pub sender: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct GetMultiSigAccountPayload {
     pub multi_sig_address: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, JsonSchema)]
 pub struct GetMultiSigAccountResponse {
     pub permission: MultiSigPermission,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct ChangeOwnerPayload {
     pub multi_sig_address: Address,
     pub new_owner: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct ChangeMemoPayload {
     pub multi_sig_address: Address,
     pub new_memo: String,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct AddAccountPayload {
     pub multi_sig_address: Address,
     pub new_account: Account,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct RemoveAccountPayload {
     pub multi_sig_address: Address,
     pub account_address: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct SetAccountWeightPayload {
     pub multi_sig_address: Address,
     pub account_address: Address,
     pub new_weight: u8,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct SetThresholdPayload {
     pub multi_sig_address: Address,
     pub new_threshold: u32,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, JsonSchema)]
 pub struct UpdateAccountPayload {
     pub account_address: Address,
     pub owner: Address,
@@ -109,7 +114,15 @@ pub struct UpdateAccountPayload {
 }
 
 #[derive(
-    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq,
+    RlpFixedCodec,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    JsonSchema,
 )]
 pub struct MultiSigPermission {
     pub owner: Address,
@@ -199,7 +212,15 @@ impl MultiSigPermission {
 }
 
 #[derive(
-    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq,
+    RlpFixedCodec,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    JsonSchema,
 )]
 pub struct Account {
     pub address: Address,
@@ -207,7 +228,9 @@ pub struct Account {
     pub is_multiple: bool,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[derive(
+    RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, JsonSchema,
+)]
 pub struct AddressWithWeight {
     pub address: Address,
     pub weight: u8,
@@ -11,12 +11,13 @@ use common_crypto::{
     Crypto, PrivateKey, PublicKey, Secp256k1, Secp256k1PrivateKey, Signature,
     ToPublicKey,
 };
-use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use framework::binding::meter::CyclesMeter;
+use framework::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK, NoopDispatcher};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
 use protocol::traits::{CommonStorage, Context, Storage};
 use protocol::types::{
-    Address, Block, BlockHeader, Hash, Hex, Proof, Receipt, ServiceContext,
-    ServiceContextParams, SignedTransaction,
+    Address, Block, BlockHeader, EventFilter, Hash, Hex, IndexedEvent, PayloadEncoding,
+    Proof, Receipt, ServiceContext, ServiceContextParams, SignedTransaction,
 };
 use protocol::ProtocolResult;
 
@@ -94,13 +95,22 @@ fn test_verify() {
 }
 
 fn new_util_service() -> UtilService<
-    DefaultServiceSDK<GeneralServiceState<MemoryDB>, DefaultChainQuerier<MockStorage>>,
+    DefaultServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        NoopDispatcher,
+    >,
 > {
     let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
     let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
     let state = GeneralServiceState::new(trie);
 
-    let sdk = DefaultServiceSDK::new(Rc::new(RefCell::new(state)), Rc::new(chain_db));
+    let sdk = DefaultServiceSDK::new(
+        Rc::new(RefCell::new(state)),
+        Rc::new(chain_db),
+        Rc::new(NoopDispatcher),
+        Rc::new(CyclesMeter::default()),
+    );
 
     UtilService::new(sdk)
 }
@@ -118,6 +128,7 @@ fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
         service_name: "service_name".to_owned(),
         service_method: "service_method".to_owned(),
         service_payload: "service_payload".to_owned(),
+        service_payload_encoding: PayloadEncoding::Json,
         extra: None,
         events: Rc::new(RefCell::new(vec![])),
     };
@@ -238,4 +249,16 @@ impl Storage for MockStorage {
     async fn get_latest_proof(&self, _: Context) -> ProtocolResult<Proof> {
         unimplemented!()
     }
+
+    async fn get_events(
+        &self,
+        _: Context,
+        _: EventFilter,
+        _: u64,
+        _: u64,
+        _: u64,
+        _: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        unimplemented!()
+    }
 }
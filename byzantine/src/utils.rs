@@ -22,8 +22,8 @@ use core_consensus::fixed_types::FixedPill;
 use core_consensus::util::OverlordCrypto;
 use protocol::fixed_codec::FixedCodec;
 use protocol::types::{
-    Address, Block, BlockHeader, Hash, Metadata, Pill, Proof, RawTransaction,
-    SignedTransaction, TransactionRequest, Validator,
+    Address, Block, BlockHeader, Hash, Metadata, PayloadEncoding, Pill, Proof,
+    RawTransaction, SignedTransaction, TransactionRequest, Validator,
 };
 
 use crate::invalid_types::InvalidStruct;
@@ -89,6 +89,7 @@ pub fn gen_invalid_request() -> TransactionRequest {
         method: gen_random_string(10),
         service_name: gen_random_string(10),
         payload: gen_random_string(100),
+        encoding: PayloadEncoding::Json,
     }
 }
 
@@ -97,6 +98,7 @@ pub fn gen_transfer_tx_request() -> TransactionRequest {
         method: "asset".to_string(),
         service_name: "transfer".to_string(),
         payload: "{ \"asset_id\": \"0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c\", \"to\":\"0x0000000000000000000000000000000000000001\", \"value\": 100 }".to_string(),
+        encoding: PayloadEncoding::Json,
     }
 }
 
@@ -16,6 +16,7 @@ make_auto_flush_static_metric! {
     signed_tx,
     wal,
     hash_height,
+    event,
     state,
   }
 
@@ -118,6 +119,10 @@ pub fn on_storage_get_cf(sc: StorageCategory, duration: Duration, keys: i64) {
             STORAGE_GET_CF_TIME_USAGE.hash_height.inc_by(seconds);
             STORAGE_GET_CF_COUNTER.hash_height.inc_by(keys);
         }
+        StorageCategory::Event => {
+            STORAGE_GET_CF_TIME_USAGE.event.inc_by(seconds);
+            STORAGE_GET_CF_COUNTER.event.inc_by(keys);
+        }
     }
 }
 
@@ -149,5 +154,9 @@ pub fn on_storage_put_cf(sc: StorageCategory, duration: Duration, size: i64) {
             STORAGE_PUT_CF_TIME_USAGE.hash_height.inc_by(seconds);
             STORAGE_PUT_CF_BYTES_COUNTER.hash_height.inc_by(size);
         }
+        StorageCategory::Event => {
+            STORAGE_PUT_CF_TIME_USAGE.event.inc_by(seconds);
+            STORAGE_PUT_CF_BYTES_COUNTER.event.inc_by(size);
+        }
     }
 }
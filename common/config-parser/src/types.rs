@@ -4,7 +4,10 @@ use std::path::PathBuf;
 
 use serde_derive::Deserialize;
 
-use core_consensus::{DEFAULT_OVERLORD_GAP, DEFAULT_SYNC_TXS_CHUNK_SIZE};
+use core_consensus::{
+    DEFAULT_CONSENSUS_WAL_RETAINED_FILES, DEFAULT_OVERLORD_GAP,
+    DEFAULT_SYNC_TXS_CHUNK_SIZE,
+};
 use core_mempool::{DEFAULT_BROADCAST_TXS_INTERVAL, DEFAULT_BROADCAST_TXS_SIZE};
 use protocol::types::Hex;
 
@@ -19,8 +22,12 @@ pub struct ConfigGraphQL {
     pub maxconn: usize,
     #[serde(default)]
     pub max_payload_size: usize,
+    #[serde(default)]
+    pub max_batch_txs: usize,
     pub tls: Option<ConfigGraphQLTLS>,
     pub enable_dump_profile: Option<bool>,
+    pub backup_path: Option<PathBuf>,
+    pub admin_listening_address: Option<SocketAddr>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -66,12 +73,20 @@ fn default_sync_txs_chunk_size() -> usize {
     DEFAULT_SYNC_TXS_CHUNK_SIZE
 }
 
+fn default_wal_retained_files() -> usize {
+    DEFAULT_CONSENSUS_WAL_RETAINED_FILES
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ConfigConsensus {
     #[serde(default = "default_overlord_gap")]
     pub overlord_gap: usize,
     #[serde(default = "default_sync_txs_chunk_size")]
     pub sync_txs_chunk_size: usize,
+    /// How many latest consensus wal files are kept, the older ones are
+    /// removed after writing a new one.
+    #[serde(default = "default_wal_retained_files")]
+    pub wal_retained_files: usize,
 }
 
 fn default_broadcast_txs_size() -> usize {
@@ -90,22 +105,29 @@ pub struct ConfigMempool {
     pub broadcast_txs_size: usize,
     #[serde(default = "default_broadcast_txs_interval")]
     pub broadcast_txs_interval: u64,
+    #[serde(default)]
+    pub enable_priority: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigExecutor {
     pub light: bool,
     pub triedb_cache_size: usize,
+    pub state_history: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigRocksDB {
     pub max_open_files: i32,
+    pub tx_history: Option<u64>,
 }
 
 impl Default for ConfigRocksDB {
     fn default() -> Self {
-        Self { max_open_files: 64 }
+        Self {
+            max_open_files: 64,
+            tx_history: None,
+        }
     }
 }
 
@@ -137,6 +159,15 @@ impl Default for ConfigLogger {
     }
 }
 
+/// A follower node only syncs blocks and serves the API, it never runs the
+/// consensus engine even if it is in the verifier list.
+#[derive(Debug, Deserialize)]
+pub struct ConfigFollower {
+    /// Accept transactions from the API and gossip them to the validators.
+    #[serde(default)]
+    pub forward_txs: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ConfigAPM {
     pub service_name: String,
@@ -161,6 +192,7 @@ pub struct Config {
     #[serde(default)]
     pub rocksdb: ConfigRocksDB,
     pub apm: Option<ConfigAPM>,
+    pub follower: Option<ConfigFollower>,
 }
 
 impl Config {
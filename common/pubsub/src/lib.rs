@@ -1,7 +1,122 @@
+use std::sync::Arc;
+
+use futures::stream::{self, Stream};
+use tokio::sync::broadcast::{self, RecvError};
+
+use protocol::types::{Block, Receipt};
+
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 256;
+
+/// A broadcast topic. Every subscriber receives its own copy of each message
+/// published after it subscribes.
+#[derive(Debug)]
+pub struct PubSub<T> {
+    sender: broadcast::Sender<T>,
+}
+
+impl<T> Clone for PubSub<T> {
+    fn clone(&self) -> Self {
+        PubSub {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T> PubSub<T>
+where
+    T: Clone + Send + 'static,
+{
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        PubSub { sender }
+    }
+
+    /// Publish a message. It is dropped if there are no subscribers.
+    pub fn publish(&self, msg: T) {
+        let _ = self.sender.send(msg);
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// A subscriber falling behind more than the channel capacity skips the
+    /// messages it missed instead of blocking the publisher.
+    pub fn subscribe(&self) -> impl Stream<Item = T> + Send + 'static {
+        stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(msg) => return Some((msg, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!(
+                            "[pubsub]: subscriber lagged, skip {} messages",
+                            skipped
+                        );
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+/// Chain data published by consensus once it reaches storage.
+#[derive(Clone, Debug)]
+pub struct ChainPubSub {
+    /// Committed blocks.
+    pub blocks: PubSub<Block>,
+    /// Receipts of one executed block.
+    pub receipts: PubSub<Arc<Vec<Receipt>>>,
+}
+
+impl ChainPubSub {
+    pub fn new(capacity: usize) -> Self {
+        ChainPubSub {
+            blocks: PubSub::new(capacity),
+            receipts: PubSub::new(capacity),
+        }
+    }
+}
+
+impl Default for ChainPubSub {
+    fn default() -> Self {
+        ChainPubSub::new(DEFAULT_CHANNEL_CAPACITY)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    #[test]
-    fn it_works() {
-        assert_eq!(2 + 2, 4);
+    use futures::stream::StreamExt;
+
+    use super::PubSub;
+
+    #[tokio::test]
+    async fn test_publish_to_all_subscribers() {
+        let topic = PubSub::new(4);
+        topic.publish(0u64);
+
+        let sub_a = topic.subscribe();
+        let sub_b = topic.subscribe();
+        assert_eq!(topic.subscriber_count(), 2);
+
+        topic.publish(1u64);
+        topic.publish(2u64);
+        drop(topic);
+
+        assert_eq!(sub_a.collect::<Vec<_>>().await, vec![1, 2]);
+        assert_eq!(sub_b.collect::<Vec<_>>().await, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_lagged_subscriber_skips_messages() {
+        let topic = PubSub::new(2);
+        let sub = topic.subscribe();
+
+        for i in 0..5u64 {
+            topic.publish(i);
+        }
+        drop(topic);
+
+        assert_eq!(sub.collect::<Vec<_>>().await, vec![3, 4]);
     }
 }
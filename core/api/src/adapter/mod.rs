@@ -5,14 +5,18 @@ use async_trait::async_trait;
 use derive_more::Display;
 
 use protocol::traits::{
-    APIAdapter, Context, ExecutorFactory, ExecutorParams, MemPool, ServiceMapping,
-    ServiceResponse, Storage,
+    APIAdapter, Context, DryRunResp, Executor, ExecutorFactory, ExecutorParams, MemPool,
+    ServiceMapping, ServiceResponse, StateProof, Storage,
 };
 use protocol::types::{
-    Address, Block, BlockHeader, Hash, Receipt, SignedTransaction, TransactionRequest,
+    Address, Block, BlockHeader, EventFilter, Hash, IndexedEvent, PayloadEncoding,
+    Receipt, ServiceSchema, SignedTransaction, TransactionRequest,
 };
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
+/// The max number of heights `get_events` searches at once.
+pub const MAX_EVENTS_HEIGHT_RANGE: u64 = 10_000;
+
 #[derive(Debug, Display)]
 pub enum APIError {
     #[display(
@@ -24,6 +28,42 @@ pub enum APIError {
 
     #[display(fmt = "not found")]
     NotFound,
+
+    #[display(
+        fmt = "State of height {} is pruned, the oldest state kept is of height {}",
+        height,
+        oldest
+    )]
+    StatePruned { height: u64, oldest: u64 },
+
+    #[display(
+        fmt = "Transaction {:?} not found, the transactions below height {} are pruned",
+        tx_hash,
+        pruned_height
+    )]
+    TransactionPruned { tx_hash: Hash, pruned_height: u64 },
+
+    #[display(fmt = "Transactions are not accepted by this node")]
+    TransactionsNotAccepted,
+
+    #[display(
+        fmt = "Height range [{}, {}] is too large, at most {} heights are allowed",
+        from_height,
+        to_height,
+        max
+    )]
+    HeightRangeTooLarge {
+        from_height: u64,
+        to_height: u64,
+        max: u64,
+    },
+
+    #[display(
+        fmt = "Batch of {} transactions is too long, at most {} transactions are allowed",
+        len,
+        max
+    )]
+    BatchTooLong { len: usize, max: usize },
 }
 
 impl std::error::Error for APIError {}
@@ -39,6 +79,12 @@ pub struct DefaultAPIAdapter<EF, M, S, DB, Mapping> {
     storage: Arc<S>,
     trie_db: Arc<DB>,
     service_mapping: Arc<Mapping>,
+    // Number of the latest blocks whose states are kept, `None` if the states
+    // are never pruned.
+    state_history: Option<u64>,
+    // Whether the transactions are inserted into the mempool, a follower node
+    // may not accept transactions.
+    accept_txs: bool,
 
     pin_ef: PhantomData<EF>,
 }
@@ -56,15 +102,60 @@ impl<
         storage: Arc<S>,
         trie_db: Arc<DB>,
         service_mapping: Arc<Mapping>,
+        state_history: Option<u64>,
+        accept_txs: bool,
     ) -> Self {
         Self {
             mempool,
             storage,
             trie_db,
             service_mapping,
+            state_history,
+            accept_txs,
             pin_ef: PhantomData,
         }
     }
+
+    async fn executor_at(
+        &self,
+        ctx: Context,
+        height: u64,
+        cycles_limit: u64,
+    ) -> ProtocolResult<(Box<dyn Executor>, ExecutorParams)> {
+        if let Some(state_history) = self.state_history {
+            let latest_height = self
+                .storage
+                .get_latest_block_header(ctx.clone())
+                .await?
+                .height;
+            let oldest = latest_height.saturating_sub(state_history - 1);
+            if height < oldest {
+                return Err(APIError::StatePruned { height, oldest }.into());
+            }
+        }
+
+        let header = self
+            .get_block_header_by_height(ctx, Some(height))
+            .await?
+            .ok_or(APIError::NotFound)?;
+
+        let executor = EF::from_root(
+            header.state_root.clone(),
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            Arc::clone(&self.service_mapping),
+        )?;
+
+        let params = ExecutorParams {
+            state_root: header.state_root,
+            height,
+            timestamp: header.timestamp,
+            cycles_limit,
+            proposer: header.proposer,
+        };
+
+        Ok((executor, params))
+    }
 }
 
 #[async_trait]
@@ -81,9 +172,25 @@ impl<
         ctx: Context,
         signed_tx: SignedTransaction,
     ) -> ProtocolResult<()> {
+        if !self.accept_txs {
+            return Err(APIError::TransactionsNotAccepted.into());
+        }
+
         self.mempool.insert(ctx, signed_tx).await
     }
 
+    async fn insert_signed_txs_batch(
+        &self,
+        ctx: Context,
+        signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<Vec<ProtocolResult<()>>> {
+        if !self.accept_txs {
+            return Err(APIError::TransactionsNotAccepted.into());
+        }
+
+        self.mempool.insert_batch(ctx, signed_txs).await
+    }
+
     async fn get_block_by_height(
         &self,
         ctx: Context,
@@ -136,7 +243,107 @@ impl<
         ctx: Context,
         tx_hash: Hash,
     ) -> ProtocolResult<Option<SignedTransaction>> {
-        self.storage.get_transaction_by_hash(ctx, &tx_hash).await
+        let opt_stx = self
+            .storage
+            .get_transaction_by_hash(ctx.clone(), &tx_hash)
+            .await?;
+
+        // The hash index of the pruned transactions is kept
+        if opt_stx.is_none()
+            && self
+                .storage
+                .contains_transaction(ctx.clone(), &tx_hash)
+                .await?
+        {
+            let pruned_height = self.storage.get_pruned_height(ctx).await?.unwrap_or(0);
+            return Err(APIError::TransactionPruned {
+                tx_hash,
+                pruned_height,
+            }
+            .into());
+        }
+
+        Ok(opt_stx)
+    }
+
+    async fn get_receipts_by_height(
+        &self,
+        ctx: Context,
+        height: u64,
+    ) -> ProtocolResult<Option<Vec<Receipt>>> {
+        let exec_height = self
+            .storage
+            .get_latest_block_header(ctx.clone())
+            .await?
+            .exec_height;
+        if height > exec_height {
+            return Ok(None);
+        }
+
+        let block = match self.storage.get_block(ctx.clone(), height).await? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+        let receipts = self
+            .storage
+            .get_receipts(ctx, height, block.ordered_tx_hashes)
+            .await?;
+
+        Ok(receipts.into_iter().collect())
+    }
+
+    async fn get_events(
+        &self,
+        ctx: Context,
+        filter: EventFilter,
+        from_height: Option<u64>,
+        to_height: Option<u64>,
+        offset: u64,
+        limit: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        let exec_height = self
+            .storage
+            .get_latest_block_header(ctx.clone())
+            .await?
+            .exec_height;
+        let to_height = std::cmp::min(to_height.unwrap_or(exec_height), exec_height);
+        let from_height = from_height
+            .unwrap_or_else(|| to_height.saturating_sub(MAX_EVENTS_HEIGHT_RANGE - 1));
+
+        if from_height <= to_height && to_height - from_height >= MAX_EVENTS_HEIGHT_RANGE
+        {
+            return Err(APIError::HeightRangeTooLarge {
+                from_height,
+                to_height,
+                max: MAX_EVENTS_HEIGHT_RANGE,
+            }
+            .into());
+        }
+
+        self.storage
+            .get_events(ctx, filter, from_height, to_height, offset, limit)
+            .await
+    }
+
+    async fn get_account_nonce(
+        &self,
+        ctx: Context,
+        height: Option<u64>,
+        address: Address,
+    ) -> ProtocolResult<u64> {
+        let height = match height {
+            Some(height) => height,
+            None => {
+                self.storage
+                    .get_latest_block_header(ctx.clone())
+                    .await?
+                    .height
+            }
+        };
+        let (executor, _) = self.executor_at(ctx, height, std::u64::MAX).await?;
+
+        executor.get_account_nonce(&address)
     }
 
     async fn query_service(
@@ -149,26 +356,10 @@ impl<
         service_name: String,
         method: String,
         payload: String,
+        payload_encoding: PayloadEncoding,
     ) -> ProtocolResult<ServiceResponse<String>> {
-        let header = self
-            .get_block_header_by_height(ctx.clone(), Some(height))
-            .await?
-            .ok_or(APIError::NotFound)?;
-
-        let executor = EF::from_root(
-            header.state_root.clone(),
-            Arc::clone(&self.trie_db),
-            Arc::clone(&self.storage),
-            Arc::clone(&self.service_mapping),
-        )?;
+        let (executor, params) = self.executor_at(ctx, height, cycles_limit).await?;
 
-        let params = ExecutorParams {
-            state_root: header.state_root,
-            height,
-            timestamp: header.timestamp,
-            cycles_limit,
-            proposer: header.proposer,
-        };
         executor.read(
             &params,
             &caller,
@@ -177,7 +368,83 @@ impl<
                 service_name,
                 method,
                 payload,
+                encoding: payload_encoding,
+            },
+        )
+    }
+
+    async fn query_service_with_proof(
+        &self,
+        ctx: Context,
+        height: u64,
+        cycles_limit: u64,
+        cycles_price: u64,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+        payload_encoding: PayloadEncoding,
+    ) -> ProtocolResult<(ServiceResponse<String>, StateProof)> {
+        let (executor, params) = self.executor_at(ctx, height, cycles_limit).await?;
+
+        executor.read_with_proof(
+            &params,
+            &caller,
+            cycles_price,
+            &TransactionRequest {
+                service_name,
+                method,
+                payload,
+                encoding: payload_encoding,
             },
         )
     }
+
+    async fn dry_run_transaction(
+        &self,
+        ctx: Context,
+        height: u64,
+        cycles_limit: u64,
+        cycles_price: u64,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+        payload_encoding: PayloadEncoding,
+    ) -> ProtocolResult<DryRunResp> {
+        let (mut executor, params) =
+            self.executor_at(ctx.clone(), height, cycles_limit).await?;
+
+        executor.dry_run(
+            ctx,
+            &params,
+            &caller,
+            cycles_price,
+            &TransactionRequest {
+                service_name,
+                method,
+                payload,
+                encoding: payload_encoding,
+            },
+        )
+    }
+
+    async fn list_services(&self, _ctx: Context) -> ProtocolResult<Vec<String>> {
+        Ok(self.service_mapping.list_service_name())
+    }
+
+    async fn get_service_schema(
+        &self,
+        ctx: Context,
+        service_name: String,
+    ) -> ProtocolResult<ServiceSchema> {
+        let height = self
+            .storage
+            .get_latest_block_header(ctx.clone())
+            .await?
+            .height;
+        let (executor, _) = self.executor_at(ctx, height, std::u64::MAX).await?;
+
+        executor.get_service_schema(&service_name)
+    }
 }
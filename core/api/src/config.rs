@@ -20,9 +20,22 @@ pub struct GraphQLConfig {
     // The default value is 1024 * 1024, which is 1MB.
     pub max_payload_size: usize,
 
+    // Set the max number of transactions sent by one `sendTransactions`.
+    // The default value is 1000.
+    pub max_batch_txs: usize,
+
     pub tls: Option<GraphQLTLS>,
 
     pub enable_dump_profile: bool,
+
+    // The directory the backups taken by the `/backup` endpoint are saved in,
+    // the endpoint is disabled if it is not set.
+    pub backup_path: Option<PathBuf>,
+
+    // The address of the admin listener serving the `/backup` endpoint, it must
+    // not be reachable from the public network. The endpoint is disabled if it
+    // is not set.
+    pub admin_listening_address: Option<SocketAddr>,
 }
 
 #[derive(Debug, Clone)]
@@ -43,8 +56,11 @@ impl Default for GraphQLConfig {
             workers: num_cpus::get(),
             maxconn: 25000,
             max_payload_size: 1024 * 1024, // 1MB
+            max_batch_txs: 1000,
             tls: None,
             enable_dump_profile: false,
+            backup_path: None,
+            admin_listening_address: None,
         }
     }
 }
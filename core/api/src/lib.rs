@@ -1,14 +1,19 @@
 pub mod adapter;
 pub mod config;
+mod proof;
 mod schema;
+mod subscription;
 
 use std::cmp;
 use std::convert::TryFrom;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use actix_web::{web, App, Error, FromRequest, HttpResponse, HttpServer};
 use futures::executor::block_on;
+use futures::future;
+use futures::lock::Mutex;
 use juniper::http::GraphQLRequest;
 use juniper::FieldResult;
 use lazy_static::lazy_static;
@@ -17,16 +22,26 @@ use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
 use common_crypto::{
     HashValue, PrivateKey, PublicKey, Secp256k1PrivateKey, Signature, ToPublicKey,
 };
+use common_pubsub::ChainPubSub;
 
 use protocol::fixed_codec::FixedCodec;
-use protocol::traits::{APIAdapter, Context};
+use protocol::traits::{APIAdapter, Backup, Context};
+use protocol::ProtocolError;
 
+use crate::adapter::APIError;
 use crate::config::GraphQLConfig;
 use crate::schema::{
-    to_signed_transaction, to_transaction, Address, Block, Bytes, Hash,
-    InputRawTransaction, InputTransactionEncryption, Receipt, ServiceResponse,
-    SignedTransaction, Uint64,
+    to_signed_transaction, to_transaction, Address, Block, Bytes, DryRunResponse, Hash,
+    IndexedEvent, InputRawTransaction, InputSignedTransaction,
+    InputTransactionEncryption, PayloadEncoding, Receipt, ReceiptProof,
+    SendTransactionResult, ServiceResponse, ServiceResponseWithProof, ServiceSchema,
+    SignedTransaction, StateProof, TransactionProof, Uint64,
 };
+use crate::subscription::{subscriptions, Subscription};
+
+const DEFAULT_EVENTS_PAGE_SIZE: u64 = 100;
+const MAX_EVENTS_PAGE_SIZE: u64 = 1000;
+const MIN_BACKUP_INTERVAL: Duration = Duration::from_secs(60);
 
 lazy_static! {
     static ref GRAPHIQL_HTML: &'static str = include_str!("../source/graphiql.html");
@@ -37,6 +52,19 @@ lazy_static! {
 struct State {
     adapter: Arc<Box<dyn APIAdapter>>,
     schema: Arc<Schema>,
+    pubsub: ChainPubSub,
+    max_batch_txs: usize,
+}
+
+impl juniper::Context for State {}
+
+// The state of the admin listener.
+#[derive(Clone)]
+struct AdminState {
+    backup: Arc<dyn Backup>,
+    backup_path: PathBuf,
+    // When the last backup started, it is locked while a backup is running.
+    last_backup: Arc<Mutex<Option<Instant>>>,
 }
 
 // We define `Query` unit struct here. GraphQL queries will refer to this
@@ -137,6 +165,114 @@ impl Query {
         Ok(opt_receipt.map(Receipt::from))
     }
 
+    #[graphql(
+        name = "getEvents",
+        description = "Get events emitted by a service within a height range, \
+                       optionally filtered by event name. The range covers at most \
+                       10000 heights, it ends at the latest executed height by \
+                       default. At most 1000 events are returned per page"
+    )]
+    async fn get_events(
+        state_ctx: &State,
+        service: String,
+        name: Option<String>,
+        from_height: Option<Uint64>,
+        to_height: Option<Uint64>,
+        offset: Option<Uint64>,
+        limit: Option<Uint64>,
+    ) -> FieldResult<Vec<IndexedEvent>> {
+        let ctx = Context::new();
+
+        let from_height = match from_height {
+            Some(height) => Some(height.try_into_u64()?),
+            None => None,
+        };
+        let to_height = match to_height {
+            Some(height) => Some(height.try_into_u64()?),
+            None => None,
+        };
+        let offset = match offset {
+            Some(offset) => offset.try_into_u64()?,
+            None => 0,
+        };
+        let limit = match limit {
+            Some(limit) => cmp::min(limit.try_into_u64()?, MAX_EVENTS_PAGE_SIZE),
+            None => DEFAULT_EVENTS_PAGE_SIZE,
+        };
+
+        let filter = protocol::types::EventFilter { service, name };
+        let events = state_ctx
+            .adapter
+            .get_events(ctx.clone(), filter, from_height, to_height, offset, limit)
+            .await?;
+
+        Ok(events.into_iter().map(IndexedEvent::from).collect())
+    }
+
+    #[graphql(
+        name = "getTransactionProof",
+        description = "Get the merkle proof of an executed transaction against the \
+                       order root of its block"
+    )]
+    async fn get_transaction_proof(
+        state_ctx: &State,
+        tx_hash: Hash,
+    ) -> FieldResult<Option<TransactionProof>> {
+        let ctx = Context::new();
+
+        let hash = protocol::types::Hash::from_hex(&tx_hash.as_hex())?;
+
+        let adapter = &**state_ctx.adapter;
+        let opt_proof = proof::get_transaction_proof(adapter, ctx.clone(), hash).await?;
+
+        Ok(opt_proof)
+    }
+
+    #[graphql(
+        name = "getReceiptProof",
+        description = "Get the merkle proof of a receipt against the receipt root \
+                       carried by a later block header"
+    )]
+    async fn get_receipt_proof(
+        state_ctx: &State,
+        tx_hash: Hash,
+    ) -> FieldResult<Option<ReceiptProof>> {
+        let ctx = Context::new();
+
+        let hash = protocol::types::Hash::from_hex(&tx_hash.as_hex())?;
+
+        let adapter = &**state_ctx.adapter;
+        let opt_proof = proof::get_receipt_proof(adapter, ctx.clone(), hash).await?;
+
+        Ok(opt_proof)
+    }
+
+    #[graphql(
+        name = "getAccountNonce",
+        description = "Get the next sequential nonce of an account, which is tracked \
+                       only in the sequential nonce mode"
+    )]
+    async fn get_account_nonce(
+        state_ctx: &State,
+        address: Address,
+        height: Option<Uint64>,
+    ) -> FieldResult<Uint64> {
+        let ctx = Context::new();
+
+        let height = match height {
+            Some(id) => Some(id.try_into_u64()?),
+            None => None,
+        };
+        let address: protocol::types::Address = address.to_str().parse()?;
+
+        let nonce = state_ctx
+            .adapter
+            .get_account_nonce(ctx.clone(), height, address)
+            .await?;
+
+        Ok(Uint64::from(nonce))
+    }
+
     #[graphql(name = "queryService", description = "query service")]
     async fn query_service(
         state_ctx: &State,
@@ -147,6 +283,7 @@ impl Query {
         service_name: String,
         method: String,
         payload: String,
+        payload_encoding: Option<PayloadEncoding>,
     ) -> FieldResult<ServiceResponse> {
         let ctx = Context::new();
 
@@ -170,6 +307,9 @@ impl Query {
         };
 
         let address: protocol::types::Address = caller.to_str().parse()?;
+        let payload_encoding = payload_encoding
+            .map(protocol::types::PayloadEncoding::from)
+            .unwrap_or_default();
 
         let exec_resp = state_ctx
             .adapter
@@ -182,10 +322,160 @@ impl Query {
                 service_name,
                 method,
                 payload,
+                payload_encoding,
             )
             .await?;
         Ok(ServiceResponse::from(exec_resp))
     }
+
+    #[graphql(
+        name = "queryServiceWithProof",
+        description = "query service, along with merkle patricia proofs of every \
+                       state key the query read"
+    )]
+    async fn query_service_with_proof(
+        state_ctx: &State,
+        height: Option<Uint64>,
+        cycles_limit: Option<Uint64>,
+        cycles_price: Option<Uint64>,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+        payload_encoding: Option<PayloadEncoding>,
+    ) -> FieldResult<ServiceResponseWithProof> {
+        let ctx = Context::new();
+
+        let height = match height {
+            Some(id) => id.try_into_u64()?,
+            None => {
+                block_on(state_ctx.adapter.get_block_by_height(Context::new(), None))?
+                    .expect("Always not none")
+                    .header
+                    .height
+            }
+        };
+        let cycles_limit = match cycles_limit {
+            Some(cycles_limit) => cycles_limit.try_into_u64()?,
+            None => std::u64::MAX,
+        };
+
+        let cycles_price = match cycles_price {
+            Some(cycles_price) => cycles_price.try_into_u64()?,
+            None => 1,
+        };
+
+        let address: protocol::types::Address = caller.to_str().parse()?;
+        let payload_encoding = payload_encoding
+            .map(protocol::types::PayloadEncoding::from)
+            .unwrap_or_default();
+
+        let (exec_resp, proof) = state_ctx
+            .adapter
+            .query_service_with_proof(
+                ctx.clone(),
+                height,
+                cycles_limit,
+                cycles_price,
+                address,
+                service_name,
+                method,
+                payload,
+                payload_encoding,
+            )
+            .await?;
+        Ok(ServiceResponseWithProof {
+            response: ServiceResponse::from(exec_resp),
+            proof: StateProof::from(proof),
+        })
+    }
+
+    #[graphql(
+        name = "dryRunTransaction",
+        description = "Execute a transaction, including tx hooks, against the state \
+                       at a height without committing it, to estimate its cycles"
+    )]
+    async fn dry_run_transaction(
+        state_ctx: &State,
+        height: Option<Uint64>,
+        cycles_limit: Option<Uint64>,
+        cycles_price: Option<Uint64>,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+        payload_encoding: Option<PayloadEncoding>,
+    ) -> FieldResult<DryRunResponse> {
+        let ctx = Context::new();
+
+        let height = match height {
+            Some(id) => id.try_into_u64()?,
+            None => {
+                block_on(state_ctx.adapter.get_block_by_height(Context::new(), None))?
+                    .expect("Always not none")
+                    .header
+                    .height
+            }
+        };
+        let cycles_limit = match cycles_limit {
+            Some(cycles_limit) => cycles_limit.try_into_u64()?,
+            None => std::u64::MAX,
+        };
+
+        let cycles_price = match cycles_price {
+            Some(cycles_price) => cycles_price.try_into_u64()?,
+            None => 1,
+        };
+
+        let address: protocol::types::Address = caller.to_str().parse()?;
+        let payload_encoding = payload_encoding
+            .map(protocol::types::PayloadEncoding::from)
+            .unwrap_or_default();
+
+        let dry_run_resp = state_ctx
+            .adapter
+            .dry_run_transaction(
+                ctx.clone(),
+                height,
+                cycles_limit,
+                cycles_price,
+                address,
+                service_name,
+                method,
+                payload,
+                payload_encoding,
+            )
+            .await?;
+        Ok(DryRunResponse::from(dry_run_resp))
+    }
+
+    #[graphql(name = "listServices", description = "List the names of all services")]
+    async fn list_services(state_ctx: &State) -> FieldResult<Vec<String>> {
+        let ctx = Context::new();
+
+        let services = state_ctx.adapter.list_services(ctx).await?;
+
+        Ok(services)
+    }
+
+    #[graphql(
+        name = "getServiceSchema",
+        description = "Get the methods of a service, along with their cycles and the \
+                       JSON Schemas of their payloads and responses"
+    )]
+    async fn get_service_schema(
+        state_ctx: &State,
+        service_name: String,
+    ) -> FieldResult<ServiceSchema> {
+        let ctx = Context::new();
+
+        let schema = state_ctx
+            .adapter
+            .get_service_schema(ctx, service_name.clone())
+            .await?;
+
+        Ok(ServiceSchema::new(service_name, schema))
+    }
 }
 
 struct Mutation;
@@ -227,6 +517,97 @@ impl Mutation {
         Ok(Hash::from(tx_hash))
     }
 
+    #[graphql(
+        name = "sendTransactions",
+        description = "send a batch of transactions, which are verified in parallel at \
+                       once, the whole batch is bounded by max_payload_size and \
+                       max_batch_txs"
+    )]
+    async fn send_transactions(
+        state_ctx: &State,
+        inputs: Vec<InputSignedTransaction>,
+    ) -> FieldResult<Vec<SendTransactionResult>> {
+        let ctx = Context::new();
+
+        if inputs.len() > state_ctx.max_batch_txs {
+            return Err(ProtocolError::from(APIError::BatchTooLong {
+                len: inputs.len(),
+                max: state_ctx.max_batch_txs,
+            })
+            .into());
+        }
+
+        let inst = Instant::now();
+        common_apm::metrics::api::API_REQUEST_COUNTER_VEC_STATIC
+            .send_transaction
+            .inc_by(inputs.len() as i64);
+
+        let mut stxs = Vec::with_capacity(inputs.len());
+        let tx_hashes = inputs
+            .into_iter()
+            .map(|input| {
+                let stx = to_signed_transaction(input.raw, input.encryption)?;
+                let tx_hash = stx.tx_hash.clone();
+
+                stxs.push(stx);
+                Ok::<_, ProtocolError>(tx_hash)
+            })
+            .collect::<Vec<_>>();
+
+        let inserted = match state_ctx.adapter.insert_signed_txs_batch(ctx, stxs).await {
+            Ok(inserted) => inserted,
+            Err(err) => {
+                common_apm::metrics::api::API_REQUEST_RESULT_COUNTER_VEC_STATIC
+                    .send_transaction
+                    .failure
+                    .inc_by(tx_hashes.len() as i64);
+                return Err(err.into());
+            }
+        };
+
+        // The inserted results are in the order of the well-formed transactions.
+        let mut inserted = inserted.into_iter();
+        let results = tx_hashes
+            .into_iter()
+            .map(|res| {
+                res.and_then(|tx_hash| match inserted.next() {
+                    Some(Err(err)) => Err(err),
+                    _ => Ok(tx_hash),
+                })
+            })
+            .map(|res| match res {
+                Ok(tx_hash) => {
+                    common_apm::metrics::api::API_REQUEST_RESULT_COUNTER_VEC_STATIC
+                        .send_transaction
+                        .success
+                        .inc();
+
+                    SendTransactionResult {
+                        tx_hash: Some(Hash::from(tx_hash)),
+                        error: None,
+                    }
+                }
+                Err(err) => {
+                    common_apm::metrics::api::API_REQUEST_RESULT_COUNTER_VEC_STATIC
+                        .send_transaction
+                        .failure
+                        .inc();
+
+                    SendTransactionResult {
+                        tx_hash: None,
+                        error: Some(err.to_string()),
+                    }
+                }
+            })
+            .collect();
+
+        common_apm::metrics::api::API_REQUEST_TIME_HISTOGRAM_STATIC
+            .send_transaction
+            .observe(common_apm::metrics::duration_to_sec(inst.elapsed()));
+
+        Ok(results)
+    }
+
     #[graphql(
         name = "unsafeSendTransaction",
         deprecated = "DON'T use it in production! This is just for development."
@@ -261,9 +642,9 @@ impl Mutation {
     }
 }
 
-// Adding `Query` and `Mutation` together we get `Schema`, which describes,
-// well, the whole GraphQL schema.
-type Schema = juniper::RootNode<'static, Query, Mutation>;
+// Adding `Query`, `Mutation` and `Subscription` together we get `Schema`,
+// which describes, well, the whole GraphQL schema.
+type Schema = juniper::RootNode<'static, Query, Mutation, Subscription>;
 
 async fn graphiql() -> HttpResponse {
     HttpResponse::Ok()
@@ -275,7 +656,7 @@ async fn graphql(
     st: web::Data<State>,
     data: web::Json<GraphQLRequest>,
 ) -> Result<HttpResponse, Error> {
-    let result = data.execute_async(&st.schema, &st).await;
+    let result = data.execute(&st.schema, &st).await;
     let res = Ok::<_, serde_json::error::Error>(serde_json::to_string(&result)?)?;
 
     Ok(HttpResponse::Ok()
@@ -283,6 +664,34 @@ async fn graphql(
         .body(res))
 }
 
+async fn take_backup(st: web::Data<AdminState>) -> HttpResponse {
+    // A backup copies the whole db, so run one at a time and not too often.
+    let mut last_backup = match st.last_backup.try_lock() {
+        Some(last_backup) => last_backup,
+        None => return HttpResponse::TooManyRequests().body("a backup is in progress"),
+    };
+    if let Some(last) = *last_backup {
+        if last.elapsed() < MIN_BACKUP_INTERVAL {
+            return HttpResponse::TooManyRequests().body(format!(
+                "a backup was taken in the last {} seconds",
+                MIN_BACKUP_INTERVAL.as_secs()
+            ));
+        }
+    }
+    *last_backup = Some(Instant::now());
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = st.backup_path.join(format!("backup-{}", timestamp));
+
+    match st.backup.backup(Context::new(), path).await {
+        Ok(manifest) => HttpResponse::Ok().json(manifest),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
 async fn metrics() -> HttpResponse {
     let metrics_data = match common_apm::metrics::all_metrics() {
         Ok(data) => data,
@@ -421,15 +830,19 @@ mod profile {
     }
 }
 
-pub async fn start_graphql<Adapter: APIAdapter + 'static>(
+pub async fn start_graphql<Adapter: APIAdapter + 'static, B: Backup + 'static>(
     cfg: GraphQLConfig,
     adapter: Adapter,
+    backup: Arc<B>,
+    pubsub: ChainPubSub,
 ) {
-    let schema = Schema::new(Query, Mutation);
+    let schema = Schema::new(Query, Mutation, Subscription);
 
     let state = State {
         adapter: Arc::new(Box::new(adapter)),
         schema: Arc::new(schema),
+        pubsub,
+        max_batch_txs: cfg.max_batch_txs,
     };
 
     let path_graphql_uri = cfg.graphql_uri.to_owned();
@@ -449,7 +862,8 @@ pub async fn start_graphql<Adapter: APIAdapter + 'static>(
                     .app_data(web::Json::<GraphQLRequest>::configure(|cfg| {
                         cfg.limit(max_payload_size)
                     }))
-                    .route(web::post().to(graphql)),
+                    .route(web::post().to(graphql))
+                    .route(web::get().to(subscriptions)),
             )
             .service(web::resource(&path_graphiql_uri).route(web::get().to(graphiql)))
             .service(web::resource("/metrics").route(web::get().to(metrics)));
@@ -466,7 +880,7 @@ pub async fn start_graphql<Adapter: APIAdapter + 'static>(
     .workers(workers)
     .maxconn(cmp::max(maxconn / workers, 1));
 
-    if let Some(tls) = cfg.tls {
+    let server = if let Some(tls) = cfg.tls {
         // load ssl keys
         let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
         builder
@@ -480,14 +894,43 @@ pub async fn start_graphql<Adapter: APIAdapter + 'static>(
             .bind_openssl(add_listening_address, builder)
             .unwrap()
             .run()
-            .await
-            .unwrap()
     } else {
-        server
-            .bind(add_listening_address)
-            .unwrap()
-            .run()
-            .await
+        server.bind(add_listening_address).unwrap().run()
+    };
+
+    // The admin endpoints are served apart from the public ones
+    let admin_server = match (cfg.admin_listening_address, cfg.backup_path) {
+        (Some(admin_listening_address), Some(backup_path)) => {
+            let admin_state = AdminState {
+                backup,
+                backup_path,
+                last_backup: Arc::new(Mutex::new(None)),
+            };
+
+            let admin_server = HttpServer::new(move || {
+                App::new()
+                    .data(admin_state.clone())
+                    .service(web::resource("/backup").route(web::post().to(take_backup)))
+            })
+            .workers(1)
+            .bind(admin_listening_address)
             .unwrap()
+            .run();
+            Some(admin_server)
+        }
+        (None, Some(_)) => {
+            log::warn!("[api]: backup is disabled without an admin listening address");
+            None
+        }
+        _ => None,
+    };
+
+    match admin_server {
+        Some(admin_server) => {
+            let (res, admin_res) = future::join(server, admin_server).await;
+            res.unwrap();
+            admin_res.unwrap();
+        }
+        None => server.await.unwrap(),
     }
 }
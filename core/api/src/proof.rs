@@ -0,0 +1,133 @@
+use common_merkle::Merkle;
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::{APIAdapter, Context};
+use protocol::types::Hash;
+use protocol::ProtocolResult;
+
+use crate::schema::{self, BlockHeader, ProofNode, ReceiptProof, TransactionProof};
+
+// Proofs are only built for executed transactions, the receipt tells us
+// which block the transaction was ordered in.
+pub async fn get_transaction_proof(
+    adapter: &dyn APIAdapter,
+    ctx: Context,
+    tx_hash: Hash,
+) -> ProtocolResult<Option<TransactionProof>> {
+    let receipt = match adapter
+        .get_receipt_by_tx_hash(ctx.clone(), tx_hash.clone())
+        .await?
+    {
+        Some(receipt) => receipt,
+        None => return Ok(None),
+    };
+
+    let block = match adapter
+        .get_block_by_height(ctx, Some(receipt.height))
+        .await?
+    {
+        Some(block) => block,
+        None => return Ok(None),
+    };
+
+    let index = match block.ordered_tx_hashes.iter().position(|h| h == &tx_hash) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+
+    let merkle = Merkle::from_hashes(block.ordered_tx_hashes);
+    let path = match merkle.get_proof_by_input_index(index) {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    Ok(Some(TransactionProof {
+        leaf: schema::Hash::from(tx_hash),
+        index: schema::Uint64::from(index as u64),
+        path: path.into_iter().map(ProofNode::from).collect(),
+        header: BlockHeader::from(block.header),
+    }))
+}
+
+pub async fn get_receipt_proof(
+    adapter: &dyn APIAdapter,
+    ctx: Context,
+    tx_hash: Hash,
+) -> ProtocolResult<Option<ReceiptProof>> {
+    let height = match adapter
+        .get_receipt_by_tx_hash(ctx.clone(), tx_hash.clone())
+        .await?
+    {
+        Some(receipt) => receipt.height,
+        None => return Ok(None),
+    };
+
+    let receipts = match adapter.get_receipts_by_height(ctx.clone(), height).await? {
+        Some(receipts) => receipts,
+        None => return Ok(None),
+    };
+
+    let index = match receipts.iter().position(|r| r.tx_hash == tx_hash) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+
+    let leaves = receipts
+        .into_iter()
+        .map(|receipt| Ok(Hash::digest(receipt.encode_fixed()?)))
+        .collect::<ProtocolResult<Vec<_>>>()?;
+    let leaf = leaves[index].clone();
+
+    let merkle = Merkle::from_hashes(leaves);
+    let (root, path) = match (
+        merkle.get_root_hash(),
+        merkle.get_proof_by_input_index(index),
+    ) {
+        (Some(root), Some(path)) => (root, path),
+        _ => return Ok(None),
+    };
+
+    // Receipt roots are carried by later blocks, each header lists the roots
+    // of the heights executed since its previous block.
+    let latest_height = match adapter
+        .get_block_header_by_height(ctx.clone(), None)
+        .await?
+    {
+        Some(header) => header.height,
+        None => return Ok(None),
+    };
+
+    for anchor_height in height + 1..=latest_height {
+        let header = match adapter
+            .get_block_header_by_height(ctx.clone(), Some(anchor_height))
+            .await?
+        {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        if header.exec_height < height {
+            continue;
+        }
+
+        let covered_from =
+            (header.exec_height + 1).saturating_sub(header.receipt_root.len() as u64);
+        if height < covered_from {
+            return Ok(None);
+        }
+
+        let root_index = (height - covered_from) as usize;
+        if header.receipt_root.get(root_index) != Some(&root) {
+            return Ok(None);
+        }
+
+        return Ok(Some(ReceiptProof {
+            leaf: schema::Hash::from(leaf),
+            index: schema::Uint64::from(index as u64),
+            path: path.into_iter().map(ProofNode::from).collect(),
+            root: schema::Hash::from(root),
+            header: BlockHeader::from(header),
+        }));
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,126 @@
+use crate::schema::{BlockHeader, Bytes, Hash, MerkleRoot, ServiceResponse, Uint64};
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(description = "A sibling hash on the path from a merkle leaf to the root")]
+pub struct ProofNode {
+    #[graphql(description = "Whether the sibling is the right child")]
+    pub is_right: bool,
+    pub hash: Hash,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(description = "Merkle proof that a transaction is ordered in a block")]
+pub struct TransactionProof {
+    #[graphql(description = "The merkle leaf, which is the transaction hash")]
+    pub leaf: Hash,
+    #[graphql(description = "The index of the transaction in the block")]
+    pub index: Uint64,
+    #[graphql(description = "The path from the leaf up to the order root")]
+    pub path: Vec<ProofNode>,
+    #[graphql(description = "The header of the block, carrying the order root")]
+    pub header: BlockHeader,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(description = "Merkle proof that a receipt is in the receipts of a block")]
+pub struct ReceiptProof {
+    #[graphql(
+        description = "The merkle leaf, which is the hash of the encoded receipt"
+    )]
+    pub leaf: Hash,
+    #[graphql(description = "The index of the receipt in the executed block")]
+    pub index: Uint64,
+    #[graphql(description = "The path from the leaf up to the receipt root")]
+    pub path: Vec<ProofNode>,
+    #[graphql(description = "The receipt root of the executed block")]
+    pub root: MerkleRoot,
+    #[graphql(description = "The header whose receipt roots contain the root")]
+    pub header: BlockHeader,
+}
+
+impl From<common_merkle::ProofNode> for ProofNode {
+    fn from(node: common_merkle::ProofNode) -> Self {
+        ProofNode {
+            is_right: node.is_right,
+            hash: Hash::from(node.hash),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(
+    description = "Merkle patricia proofs of the state a service read touched, \
+                   anchored to the state root of a block"
+)]
+pub struct StateProof {
+    pub state_root: MerkleRoot,
+    pub services: Vec<ServiceStateProof>,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(
+    description = "The root of a service state proven in the root state trie, and the \
+                   keys read from the service state proven against it"
+)]
+pub struct ServiceStateProof {
+    pub service_name: String,
+    pub service_root: MerkleRoot,
+    pub service_root_proof: Vec<Bytes>,
+    pub entries: Vec<StateProofEntry>,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+pub struct StateProofEntry {
+    pub key: Bytes,
+    #[graphql(description = "The value, or null if the proof shows the key is absent")]
+    pub value: Option<Bytes>,
+    pub proof: Vec<Bytes>,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+pub struct ServiceResponseWithProof {
+    pub response: ServiceResponse,
+    pub proof: StateProof,
+}
+
+impl From<protocol::traits::StateProof> for StateProof {
+    fn from(proof: protocol::traits::StateProof) -> Self {
+        StateProof {
+            state_root: MerkleRoot::from(proof.state_root),
+            services: proof
+                .services
+                .into_iter()
+                .map(ServiceStateProof::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<protocol::traits::ServiceStateProof> for ServiceStateProof {
+    fn from(proof: protocol::traits::ServiceStateProof) -> Self {
+        ServiceStateProof {
+            service_name: proof.service_name,
+            service_root: MerkleRoot::from(proof.service_root),
+            service_root_proof: proof
+                .service_root_proof
+                .into_iter()
+                .map(Bytes::from)
+                .collect(),
+            entries: proof
+                .entries
+                .into_iter()
+                .map(StateProofEntry::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<protocol::traits::StateProofEntry> for StateProofEntry {
+    fn from(entry: protocol::traits::StateProofEntry) -> Self {
+        StateProofEntry {
+            key: Bytes::from(entry.key),
+            value: entry.value.map(Bytes::from),
+            proof: entry.proof.into_iter().map(Bytes::from).collect(),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 mod block;
+mod merkle;
 mod receipt;
+mod service;
 mod transaction;
 
 use std::convert::From;
@@ -10,10 +12,18 @@ use std::num::ParseIntError;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 pub use block::{Block, BlockHeader};
-pub use receipt::{Event, Receipt, ReceiptResponse};
+pub use merkle::{
+    ProofNode, ReceiptProof, ServiceResponseWithProof, ServiceStateProof, StateProof,
+    StateProofEntry, TransactionProof,
+};
+pub use receipt::{
+    DryRunResponse, Event, IndexedEvent, Receipt, ReceiptFee, ReceiptResponse,
+};
+pub use service::{MethodSchema, ServiceSchema};
 pub use transaction::{
-    to_signed_transaction, to_transaction, InputRawTransaction,
-    InputTransactionEncryption, SignedTransaction,
+    to_signed_transaction, to_transaction, InputRawTransaction, InputSignedTransaction,
+    InputTransactionEncryption, PayloadEncoding, SendTransactionResult,
+    SignedTransaction,
 };
 
 #[derive(juniper::GraphQLObject, Clone)]
@@ -1,4 +1,4 @@
-use crate::schema::{Hash, MerkleRoot, ServiceResponse, Uint64};
+use crate::schema::{Address, Hash, MerkleRoot, ServiceResponse, Uint64};
 
 #[derive(juniper::GraphQLObject, Clone)]
 pub struct Receipt {
@@ -8,6 +8,18 @@ pub struct Receipt {
     pub cycles_used: Uint64,
     pub events: Vec<Event>,
     pub response: ReceiptResponse,
+    #[graphql(
+        description = "The fee charged from the sender, null if no fee policy is set"
+    )]
+    pub fee: Option<ReceiptFee>,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+pub struct ReceiptFee {
+    pub asset_id: Hash,
+    pub payer: Address,
+    pub payee: Address,
+    pub amount: Uint64,
 }
 
 #[derive(juniper::GraphQLObject, Clone)]
@@ -17,6 +29,14 @@ pub struct Event {
     pub data: String,
 }
 
+#[derive(juniper::GraphQLObject, Clone)]
+pub struct IndexedEvent {
+    pub height: Uint64,
+    pub tx_hash: Hash,
+    pub index: Uint64,
+    pub event: Event,
+}
+
 #[derive(juniper::GraphQLObject, Clone)]
 pub struct ReceiptResponse {
     pub service_name: String,
@@ -24,6 +44,18 @@ pub struct ReceiptResponse {
     pub response: ServiceResponse,
 }
 
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(
+    description = "The outcome of a transaction executed against the state of a \
+                   block, with all of its state changes discarded"
+)]
+pub struct DryRunResponse {
+    pub response: ServiceResponse,
+    #[graphql(description = "The cycles the transaction would use")]
+    pub cycles_used: Uint64,
+    pub events: Vec<Event>,
+}
+
 impl From<protocol::types::Receipt> for Receipt {
     fn from(receipt: protocol::types::Receipt) -> Self {
         Self {
@@ -33,6 +65,18 @@ impl From<protocol::types::Receipt> for Receipt {
             cycles_used: Uint64::from(receipt.cycles_used),
             events: receipt.events.into_iter().map(Event::from).collect(),
             response: ReceiptResponse::from(receipt.response),
+            fee: receipt.fee.map(ReceiptFee::from),
+        }
+    }
+}
+
+impl From<protocol::types::ReceiptFee> for ReceiptFee {
+    fn from(fee: protocol::types::ReceiptFee) -> Self {
+        Self {
+            asset_id: Hash::from(fee.asset_id),
+            payer: Address::from(fee.payer),
+            payee: Address::from(fee.payee),
+            amount: Uint64::from(fee.amount),
         }
     }
 }
@@ -47,6 +91,17 @@ impl From<protocol::types::Event> for Event {
     }
 }
 
+impl From<protocol::types::IndexedEvent> for IndexedEvent {
+    fn from(indexed: protocol::types::IndexedEvent) -> Self {
+        Self {
+            height: Uint64::from(indexed.height),
+            tx_hash: Hash::from(indexed.tx_hash),
+            index: Uint64::from(indexed.index),
+            event: Event::from(indexed.event),
+        }
+    }
+}
+
 impl From<protocol::types::ReceiptResponse> for ReceiptResponse {
     fn from(response: protocol::types::ReceiptResponse) -> Self {
         Self {
@@ -56,3 +111,13 @@ impl From<protocol::types::ReceiptResponse> for ReceiptResponse {
         }
     }
 }
+
+impl From<protocol::traits::DryRunResp> for DryRunResponse {
+    fn from(resp: protocol::traits::DryRunResp) -> Self {
+        Self {
+            response: ServiceResponse::from(resp.response),
+            cycles_used: Uint64::from(resp.cycles_used),
+            events: resp.events.into_iter().map(Event::from).collect(),
+        }
+    }
+}
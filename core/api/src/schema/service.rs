@@ -0,0 +1,56 @@
+use crate::schema::Uint64;
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(description = "The read and write methods of a service")]
+pub struct ServiceSchema {
+    pub service_name: String,
+    pub methods: Vec<MethodSchema>,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+pub struct MethodSchema {
+    pub name: String,
+    #[graphql(description = "Either read or write")]
+    pub kind: String,
+    #[graphql(description = "The cycles charged, or null if not declared")]
+    pub cycles: Option<Uint64>,
+    #[graphql(
+        description = "The JSON Schema of the payload, or null if the method takes no \
+                       payload"
+    )]
+    pub payload: Option<String>,
+    #[graphql(description = "The JSON Schema of the succeed data of the response")]
+    pub response: String,
+    #[graphql(
+        description = "False if the payload or the response has no JSON Schema, so its \
+                       schema is empty"
+    )]
+    pub typed: bool,
+}
+
+impl ServiceSchema {
+    pub fn new(service_name: String, schema: protocol::types::ServiceSchema) -> Self {
+        ServiceSchema {
+            service_name,
+            methods: schema.methods.into_iter().map(MethodSchema::from).collect(),
+        }
+    }
+}
+
+impl From<protocol::types::MethodSchema> for MethodSchema {
+    fn from(method: protocol::types::MethodSchema) -> Self {
+        let kind = match method.kind {
+            protocol::types::MethodKind::Read => "read",
+            protocol::types::MethodKind::Write => "write",
+        };
+
+        MethodSchema {
+            name: method.name,
+            kind: kind.to_owned(),
+            cycles: method.cycles.map(Uint64::from),
+            payload: method.payload.map(|payload| payload.to_string()),
+            response: method.response.to_string(),
+            typed: method.typed,
+        }
+    }
+}
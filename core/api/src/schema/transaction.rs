@@ -13,6 +13,7 @@ pub struct SignedTransaction {
     pub service_name: String,
     pub method: String,
     pub payload: String,
+    pub payload_encoding: PayloadEncoding,
     pub tx_hash: Hash,
     pub pubkey: Bytes,
     pub signature: Bytes,
@@ -30,6 +31,7 @@ impl From<protocol::types::SignedTransaction> for SignedTransaction {
             service_name: stx.raw.request.service_name,
             method: stx.raw.request.method,
             payload: stx.raw.request.payload,
+            payload_encoding: PayloadEncoding::from(stx.raw.request.encoding),
             tx_hash: Hash::from(stx.tx_hash),
             pubkey: Bytes::from(stx.pubkey),
             signature: Bytes::from(stx.signature),
@@ -37,6 +39,35 @@ impl From<protocol::types::SignedTransaction> for SignedTransaction {
     }
 }
 
+#[derive(juniper::GraphQLEnum, Clone, Copy)]
+#[graphql(
+    description = "The encoding of a payload, either a JSON string, or the hex \
+                   string of the FixedCodec bytes accepted by the methods declared \
+                   with the fixed codec"
+)]
+pub enum PayloadEncoding {
+    Json,
+    Fixed,
+}
+
+impl From<protocol::types::PayloadEncoding> for PayloadEncoding {
+    fn from(encoding: protocol::types::PayloadEncoding) -> Self {
+        match encoding {
+            protocol::types::PayloadEncoding::Json => PayloadEncoding::Json,
+            protocol::types::PayloadEncoding::Fixed => PayloadEncoding::Fixed,
+        }
+    }
+}
+
+impl From<PayloadEncoding> for protocol::types::PayloadEncoding {
+    fn from(encoding: PayloadEncoding) -> Self {
+        match encoding {
+            PayloadEncoding::Json => protocol::types::PayloadEncoding::Json,
+            PayloadEncoding::Fixed => protocol::types::PayloadEncoding::Fixed,
+        }
+    }
+}
+
 // #####################
 // GraphQLInputObject
 // #####################
@@ -68,6 +99,8 @@ pub struct InputRawTransaction {
     pub service_name: String,
     pub method: String,
     pub payload: String,
+    #[graphql(description = "The encoding of the payload, JSON by default")]
+    pub payload_encoding: Option<PayloadEncoding>,
     pub sender: Address,
 }
 
@@ -82,6 +115,23 @@ pub struct InputTransactionEncryption {
     pub signature: Bytes,
 }
 
+#[derive(juniper::GraphQLInputObject, Clone)]
+#[graphql(description = "A raw transaction along with its signature")]
+pub struct InputSignedTransaction {
+    pub raw: InputRawTransaction,
+    pub encryption: InputTransactionEncryption,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+#[graphql(
+    description = "The result of a transaction sent in a batch, either the \
+                   transaction hash or the reason it was rejected"
+)]
+pub struct SendTransactionResult {
+    pub tx_hash: Option<Hash>,
+    pub error: Option<String>,
+}
+
 pub fn to_signed_transaction(
     raw: InputRawTransaction,
     encryption: InputTransactionEncryption,
@@ -102,6 +152,14 @@ pub fn to_signed_transaction(
 pub fn to_transaction(
     raw: InputRawTransaction,
 ) -> ProtocolResult<protocol::types::RawTransaction> {
+    let encoding = raw
+        .payload_encoding
+        .map(protocol::types::PayloadEncoding::from)
+        .unwrap_or_default();
+    if encoding == protocol::types::PayloadEncoding::Fixed {
+        protocol::types::Hex::from_string(raw.payload.to_owned())?;
+    }
+
     Ok(protocol::types::RawTransaction {
         chain_id: protocol::types::Hash::from_hex(&raw.chain_id.as_hex())?,
         nonce: protocol::types::Hash::from_hex(&raw.nonce.as_hex())?,
@@ -112,6 +170,7 @@ pub fn to_transaction(
             service_name: raw.service_name.to_owned(),
             method: raw.method.to_owned(),
             payload: raw.payload.to_owned(),
+            encoding,
         },
         sender: raw.sender.to_str().parse()?,
     })
@@ -0,0 +1,179 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use futures::future::FutureExt;
+use futures::stream::{self, Stream, StreamExt};
+use juniper::{DefaultScalarValue, FieldError};
+use juniper_graphql_ws::{
+    ArcSchema, ClientMessage, Connection, ConnectionConfig, ServerMessage,
+};
+
+use crate::schema::{Block, Event, Receipt};
+use crate::State;
+
+const SUBSCRIPTION_PROTOCOL: &str = "graphql-ws";
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+type SubscriptionStream<T> = Pin<Box<dyn Stream<Item = Result<T, FieldError>> + Send>>;
+
+// Subscription streams are fed by consensus right after blocks and receipts
+// are saved.
+pub struct Subscription;
+
+#[juniper::graphql_subscription(Context = State)]
+impl Subscription {
+    #[graphql(name = "newBlock", description = "Subscribe committed blocks")]
+    async fn new_block(state_ctx: &State) -> SubscriptionStream<Block> {
+        let blocks = state_ctx
+            .pubsub
+            .blocks
+            .subscribe()
+            .map(|block| Ok(Block::from(block)));
+
+        Box::pin(blocks)
+    }
+
+    #[graphql(
+        name = "newReceipt",
+        description = "Subscribe receipts once their block is executed"
+    )]
+    async fn new_receipt(state_ctx: &State) -> SubscriptionStream<Receipt> {
+        let receipts = state_ctx.pubsub.receipts.subscribe().flat_map(|receipts| {
+            let receipts = receipts
+                .iter()
+                .cloned()
+                .map(|receipt| Ok(Receipt::from(receipt)))
+                .collect::<Vec<_>>();
+            stream::iter(receipts)
+        });
+
+        Box::pin(receipts)
+    }
+
+    #[graphql(
+        name = "newEvent",
+        description = "Subscribe events emitted by services, optionally filtered by \
+                       service name and event name"
+    )]
+    async fn new_event(
+        state_ctx: &State,
+        service: Option<String>,
+        name: Option<String>,
+    ) -> SubscriptionStream<Event> {
+        let events = state_ctx
+            .pubsub
+            .receipts
+            .subscribe()
+            .flat_map(move |receipts| {
+                let events = receipts
+                    .iter()
+                    .flat_map(|receipt| receipt.events.iter())
+                    .filter(|event| {
+                        service.as_ref().map_or(true, |s| s == &event.service)
+                            && name.as_ref().map_or(true, |n| n == &event.name)
+                    })
+                    .cloned()
+                    .map(|event| Ok(Event::from(event)))
+                    .collect::<Vec<_>>();
+                stream::iter(events)
+            });
+
+        Box::pin(events)
+    }
+}
+
+/// A websocket session speaking the graphql-ws protocol. Client messages are
+/// forwarded to a `juniper_graphql_ws::Connection`, and its server messages
+/// are written back to the socket.
+struct SubscriptionSession {
+    state: State,
+    client_msgs: Option<UnboundedSender<ClientMessage<DefaultScalarValue>>>,
+}
+
+impl Actor for SubscriptionSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let config = ConnectionConfig::new(self.state.clone())
+            .with_keep_alive_interval(KEEP_ALIVE_INTERVAL);
+        let conn = Connection::new(ArcSchema(Arc::clone(&self.state.schema)), config);
+        let (conn_sink, conn_stream) = conn.split();
+
+        let (client_msgs, client_rx) = unbounded();
+        self.client_msgs = Some(client_msgs);
+
+        ctx.spawn(actix::fut::wrap_future(
+            client_rx.map(Ok).forward(conn_sink).map(|_| ()),
+        ));
+        ctx.add_stream(conn_stream);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for SubscriptionSession {
+    fn handle(
+        &mut self,
+        msg: Result<ws::Message, ws::ProtocolError>,
+        ctx: &mut Self::Context,
+    ) {
+        match msg {
+            Ok(ws::Message::Text(text)) => {
+                match serde_json::from_str::<ClientMessage<DefaultScalarValue>>(&text) {
+                    Ok(client_msg) => {
+                        if let Some(client_msgs) = self.client_msgs.as_ref() {
+                            let _ = client_msgs.unbounded_send(client_msg);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("[api]: invalid subscription message {:?}", e);
+                        ctx.close(Some(ws::CloseReason {
+                            code: ws::CloseCode::Invalid,
+                            description: Some(e.to_string()),
+                        }));
+                        ctx.stop();
+                    }
+                }
+            }
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("[api]: subscription websocket error {:?}", e);
+                ctx.stop();
+            }
+        }
+    }
+}
+
+impl StreamHandler<ServerMessage<DefaultScalarValue>> for SubscriptionSession {
+    fn handle(
+        &mut self,
+        msg: ServerMessage<DefaultScalarValue>,
+        ctx: &mut Self::Context,
+    ) {
+        match serde_json::to_string(&msg) {
+            Ok(text) => ctx.text(text),
+            Err(e) => log::error!("[api]: encode subscription message {:?}", e),
+        }
+    }
+}
+
+pub async fn subscriptions(
+    st: web::Data<State>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> Result<HttpResponse, Error> {
+    let session = SubscriptionSession {
+        state: st.get_ref().clone(),
+        client_msgs: None,
+    };
+
+    ws::start_with_protocols(session, &[SUBSCRIPTION_PROTOCOL], &req, payload)
+}
@@ -2,8 +2,12 @@ use std::error::Error;
 
 use derive_more::{Display, From};
 
+use protocol::types::Hash;
 use protocol::{ProtocolError, ProtocolErrorKind};
 
+use crate::replay::Divergence;
+use crate::verify::Inconsistency;
+
 #[derive(Debug, Display, From)]
 pub enum CliError {
     #[display(fmt = "input is not a valid JSON format for target, {:?}", _0)]
@@ -32,6 +36,69 @@ pub enum CliError {
 
     #[display(fmt = "genesis.toml is missing")]
     MissingGenesis,
+
+    #[display(fmt = "proof for height {} not found", _0)]
+    ProofNotFound(u64),
+
+    #[display(fmt = "transaction {:?} of height {} not found", tx_hash, height)]
+    TransactionNotFound { height: u64, tx_hash: Hash },
+
+    #[display(fmt = "invalid snapshot format")]
+    SnapshotFormat,
+
+    #[display(fmt = "snapshot is truncated")]
+    SnapshotTruncated,
+
+    #[display(fmt = "snapshot checksum mismatch")]
+    SnapshotChecksum,
+
+    #[display(fmt = "snapshot contains no block")]
+    SnapshotEmpty,
+
+    #[display(fmt = "trie node {} of snapshot mismatches its hash", _0)]
+    TrieNodeHash(String),
+
+    #[display(fmt = "block of height {} does not follow the previous block", _0)]
+    PrevHashMismatch(u64),
+
+    #[display(fmt = "proof of height {} mismatches the block", _0)]
+    ProofMismatch(u64),
+
+    #[display(
+        fmt = "state root {:?} of height {} misses {} trie nodes",
+        state_root,
+        height,
+        missing
+    )]
+    StateIncomplete {
+        height: u64,
+        state_root: Hash,
+        missing: usize,
+    },
+
+    #[display(fmt = "data already exists, latest height {}", _0)]
+    DataExists(u64),
+
+    #[display(fmt = "crypto error: {:?}", _0)]
+    Crypto(common_crypto::Error),
+
+    #[display(fmt = "{}", _0)]
+    Inconsistent(Inconsistency),
+
+    #[display(fmt = "invalid height range from {} to {}", from, to)]
+    HeightRange { from: u64, to: u64 },
+
+    #[display(fmt = "path already exists: {}", _0)]
+    PathExists(String),
+
+    #[display(fmt = "state root after executing height {} not recorded", _0)]
+    StateRootNotFound(u64),
+
+    #[display(fmt = "execution of height {} not recorded", _0)]
+    NotExecuted(u64),
+
+    #[display(fmt = "{}", _0)]
+    Diverged(Divergence),
 }
 
 impl Error for CliError {}
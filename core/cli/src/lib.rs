@@ -1,29 +1,62 @@
 mod error;
+mod replay;
+mod snapshot;
+mod verify;
+
+pub use replay::{Divergence, DivergenceKind, ReceiptDivergence, ReplayReport};
+pub use verify::{Inconsistency, InconsistencyKind, VerifyReport};
 
 #[cfg(test)]
 mod tests;
 
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fs;
+use std::io::{BufReader, BufWriter};
+use std::mem;
 use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+use cita_trie::DB;
 use clap::ArgMatches;
 use common_config_parser::types::Config;
+use common_crypto::{BlsCommonReference, BlsPrivateKey};
+use common_merkle::Merkle;
+use core_consensus::util::{
+    convert_hex_to_bls_pubkeys, digest_signed_transactions, OverlordCrypto,
+};
 use core_consensus::wal::ConsensusWal;
-use core_consensus::SignedTxsWAL;
+use core_consensus::{Node, SignedTxsWAL};
 use core_storage::adapter::rocks::RocksAdapter;
-use core_storage::ImplStorage;
-use protocol::traits::{Context, MaintenanceStorage, ServiceMapping};
-use protocol::types::{Block, Genesis, SignedTransaction};
-use protocol::ProtocolResult;
+use core_storage::{ImplStorage, StorageError};
+use framework::binding::state::RocksTrieDB;
+use framework::executor::ServiceExecutorFactory;
+use overlord::extract_voters;
+use overlord::types::{Vote, VoteType};
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::{
+    Backup, BackupManifest, Context, ExecutorFactory, ExecutorParams, ExecutorResp,
+    MaintenanceStorage, ServiceMapping, Storage,
+};
+use protocol::types::{
+    Address, Block, BlockHeader, Genesis, Hash, Hex, MerkleRoot, Metadata,
+    PayloadEncoding, Proof, Receipt, SignedTransaction, TransactionRequest,
+};
+use protocol::{Bytes, ProtocolResult};
+use run::HotBackup;
 
 use crate::error::CliError;
+use crate::snapshot::{SnapshotEntry, SnapshotReader, SnapshotWriter};
 
 const PLEASE_CONFIRM: &str =
     "Please use -y to confirm modification and DO BACK UP YOUR DB DATA AND WAL";
 
+// The number of trie nodes written to the trie db at once on snapshot import
+// and on copying the state to replay from.
+const TRIE_NODE_BATCH: usize = 10_000;
+
 pub struct CliConfig {
     pub app_name: &'static str,
     pub version: &'static str,
@@ -140,6 +173,31 @@ where
                 let maintenance_cli = self.generate_maintenance_cli();
                 maintenance_cli.start()
             }
+
+            ("snapshot", Some(_sub_cmd)) => {
+                log::info!("run subcommand snapshot");
+                let maintenance_cli = self.generate_maintenance_cli();
+                maintenance_cli.start()
+            }
+
+            ("verify", Some(_sub_cmd)) => {
+                log::info!("run subcommand verify");
+                let maintenance_cli = self.generate_maintenance_cli();
+                maintenance_cli.start()
+            }
+
+            ("replay", Some(_sub_cmd)) => {
+                log::info!("run subcommand replay");
+                // the services are executed with the chain wide settings in genesis
+                if let Some(genesis) = &self.genesis {
+                    Self::init_chain_settings(genesis);
+                } else {
+                    log::error!("genesis.toml is missing");
+                    return Err(CliError::MissingGenesis.into());
+                }
+                let maintenance_cli = self.generate_maintenance_cli();
+                maintenance_cli.start()
+            }
             _ => {
                 log::info!("run without any subcommand, default to run");
                 if let Some(genesis) = self.genesis {
@@ -154,6 +212,27 @@ where
         }
     }
 
+    fn init_chain_settings(genesis: &Genesis) {
+        let metadata_payload = genesis.get_payload("metadata");
+
+        if !protocol::address_hrp_inited() {
+            let hrp = Metadata::get_hrp_from_json(metadata_payload.to_string());
+            protocol::init_address_hrp(hrp.into());
+        }
+
+        let sequential_nonce =
+            Metadata::get_sequential_nonce_from_json(metadata_payload.to_string());
+        protocol::types::init_sequential_nonce(sequential_nonce);
+
+        let storage_cycles =
+            Metadata::get_storage_cycles_from_json(metadata_payload.to_string());
+        protocol::types::init_storage_cycles(storage_cycles);
+
+        let fee_policy =
+            Metadata::get_fee_policy_from_json(metadata_payload.to_string());
+        protocol::types::init_fee_policy(fee_policy);
+    }
+
     pub fn generate_matches(
         cli_config: CliConfig,
         cmds: Option<Vec<&str>>,
@@ -257,6 +336,73 @@ where
                         clap::SubCommand::with_name("restore")
                             .about("restore db from [FROM] place")
                             .arg(clap::Arg::with_name("FROM").required(true).help("path")),
+                    )
+                    .subcommand(
+                        clap::SubCommand::with_name("checkpoint")
+                            .about("save a consistent checkpoint of db and its manifest to [TO] place")
+                            .arg(clap::Arg::with_name("TO").required(true).help("path")),
+                    ),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("snapshot")
+                    .about("APIs for state snapshot operation")
+                    .subcommand(
+                        clap::SubCommand::with_name("export")
+                            .about("export the state and the block of [HEIGHT] to [TO] file")
+                            .arg(
+                                clap::Arg::with_name("height")
+                                    .long("height")
+                                    .value_name("HEIGHT")
+                                    .required(true)
+                                    .help("block height"),
+                            )
+                            .arg(clap::Arg::with_name("TO").required(true).help("path")),
+                    )
+                    .subcommand(
+                        clap::SubCommand::with_name("import")
+                            .about("import a snapshot from [FROM] file into an empty db")
+                            .arg(clap::Arg::with_name("FROM").required(true).help("path"))
+                            .arg(clap::Arg::with_name("confirm").short("y").help("confirm to take effect")),
+                    ),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("verify")
+                    .about("verify the stored blocks, transactions, receipts and proofs, and print a JSON report")
+                    .arg(
+                        clap::Arg::with_name("from")
+                            .long("from")
+                            .value_name("HEIGHT")
+                            .help("the height to start from, default to 0"),
+                    )
+                    .arg(
+                        clap::Arg::with_name("to")
+                            .long("to")
+                            .value_name("HEIGHT")
+                            .help("the height to end at, default to the latest height"),
+                    ),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("replay")
+                    .about("re-execute the stored blocks and compare the results with the recorded ones")
+                    .arg(
+                        clap::Arg::with_name("from")
+                            .long("from")
+                            .value_name("HEIGHT")
+                            .required(true)
+                            .help("the height to start from"),
+                    )
+                    .arg(
+                        clap::Arg::with_name("to")
+                            .long("to")
+                            .value_name("HEIGHT")
+                            .required(true)
+                            .help("the height to end at"),
+                    )
+                    .arg(
+                        clap::Arg::with_name("scratch")
+                            .long("scratch")
+                            .value_name("PATH")
+                            .help("the path of the scratch state db, default to [data_path]/replay"),
                     ),
             );
         match cmds {
@@ -277,7 +423,7 @@ where
                     panic!("rocks_adapter init fails")
                 }
             };
-        let storage = ImplStorage::new(rocks_adapter);
+        let storage = ImplStorage::new(Arc::clone(&rocks_adapter));
 
         // Init full transactions wal
         let txs_wal_path = self
@@ -295,13 +441,17 @@ where
             .to_str()
             .unwrap()
             .to_string();
-        let consensus_wal = ConsensusWal::new(consensus_wal_path);
+        let consensus_wal = ConsensusWal::new(
+            consensus_wal_path,
+            self.config.consensus.wal_retained_files,
+        );
 
         MaintenanceCli::new(
             self.matches,
             self.config,
             self.service_mapping,
             storage,
+            rocks_adapter,
             txs_wal,
             consensus_wal,
         )
@@ -311,26 +461,30 @@ where
 pub struct MaintenanceCli<'a, Mapping, S>
 where
     Mapping: 'static + ServiceMapping,
-    S: 'static + MaintenanceStorage,
+    S: 'static + MaintenanceStorage + Storage,
 {
     pub matches: ArgMatches<'a>,
     pub config: Config,
     pub service_mapping: Arc<Mapping>,
     pub storage: Arc<S>,
+    pub block_db: Arc<RocksAdapter>,
     pub txs_wal: Arc<SignedTxsWAL>,
     pub consensus_wal: Arc<ConsensusWal>,
+    // Opened by the first subcommand using it, see `trie_db`
+    trie_db: Mutex<Option<Arc<RocksTrieDB>>>,
 }
 
 impl<'a, Mapping, S> MaintenanceCli<'a, Mapping, S>
 where
     Mapping: 'static + ServiceMapping,
-    S: 'static + MaintenanceStorage,
+    S: 'static + MaintenanceStorage + Storage,
 {
     pub fn new(
         matches: ArgMatches<'a>,
         config: Config,
         service_mapping: Arc<Mapping>,
         storage: S,
+        block_db: Arc<RocksAdapter>,
         txs_wal: SignedTxsWAL,
         consensus_wal: ConsensusWal,
     ) -> Self {
@@ -339,9 +493,29 @@ where
             config,
             service_mapping,
             storage: Arc::new(storage),
+            block_db,
             txs_wal: Arc::new(txs_wal),
             consensus_wal: Arc::new(consensus_wal),
+            trie_db: Mutex::new(None),
+        }
+    }
+
+    /// Open the trie db on first use, so that the subcommands not reading the
+    /// state don't lock it.
+    pub fn trie_db(&self) -> ProtocolResult<Arc<RocksTrieDB>> {
+        let mut trie_db = self.trie_db.lock().expect("trie db lock poisoned");
+        if let Some(trie_db) = trie_db.as_ref() {
+            return Ok(Arc::clone(trie_db));
         }
+
+        let opened = Arc::new(RocksTrieDB::new(
+            self.config.data_path_for_state(),
+            self.config.executor.light,
+            self.config.rocksdb.max_open_files,
+            self.config.executor.triedb_cache_size,
+        )?);
+        *trie_db = Some(Arc::clone(&opened));
+        Ok(opened)
     }
 
     pub fn start(&self) -> ProtocolResult<()> {
@@ -350,6 +524,9 @@ where
             ("block", Some(sub_cmd)) => self.block(sub_cmd),
             ("wal", Some(sub_cmd)) => self.wal(sub_cmd),
             ("backup", Some(sub_cmd)) => self.backup(sub_cmd),
+            ("snapshot", Some(sub_cmd)) => self.snapshot(sub_cmd),
+            ("verify", Some(sub_cmd)) => self.verify(sub_cmd),
+            ("replay", Some(sub_cmd)) => self.replay(sub_cmd),
             _ => Err(CliError::UnsupportedCommand.into()),
         }
     }
@@ -588,6 +765,16 @@ where
                 )
             }
 
+            ("checkpoint", Some(cmd)) => {
+                let to = cmd.value_of("TO").expect("missing [TO]");
+                let to =
+                    PathBuf::from_str(to).map_err(|e| CliError::Path(e.to_string()))?;
+
+                let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+                rt.block_on(async move { self.backup_checkpoint(to).await })?;
+                Ok(())
+            }
+
             _ => Err(CliError::Grammar.into()),
         }
     }
@@ -634,4 +821,899 @@ where
         log::info!("backup_restore successfully to: {:?}", from.to_str());
         Ok(())
     }
+
+    pub async fn backup_checkpoint(
+        &self,
+        to: PathBuf,
+    ) -> ProtocolResult<BackupManifest> {
+        let hot_backup = HotBackup::new(
+            Arc::clone(&self.block_db),
+            self.trie_db()?,
+            self.config.rocksdb.max_open_files,
+        );
+        let manifest = hot_backup.backup(Context::new(), to.clone()).await?;
+        log::info!(
+            "backup_checkpoint successfully to: {:?}, manifest: {}",
+            to.to_str(),
+            serde_json::to_string(&manifest).unwrap()
+        );
+        Ok(manifest)
+    }
+
+    pub fn snapshot(&self, sub_cmd: &ArgMatches) -> ProtocolResult<()> {
+        let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+
+        match sub_cmd.subcommand() {
+            ("export", Some(cmd)) => {
+                let height = cmd
+                    .value_of("height")
+                    .expect("missing [HEIGHT]")
+                    .parse()
+                    .map_err(|_| CliError::Parse)?;
+                let to = cmd.value_of("TO").expect("missing [TO]");
+                let to =
+                    PathBuf::from_str(to).map_err(|e| CliError::Path(e.to_string()))?;
+
+                rt.block_on(async move { self.snapshot_export(height, to).await })?;
+                Ok(())
+            }
+
+            ("import", Some(cmd)) => {
+                let confirm = cmd.is_present("confirm");
+                if !confirm {
+                    log::info!("{}", PLEASE_CONFIRM);
+                    return Ok(());
+                }
+
+                let from = cmd.value_of("FROM").expect("missing [FROM]");
+                let from = PathBuf::from_str(from)
+                    .map_err(|e| CliError::Path(e.to_string()))?;
+
+                rt.block_on(async move { self.snapshot_import(from).await })?;
+                Ok(())
+            }
+
+            _ => Err(CliError::Grammar.into()),
+        }
+    }
+
+    /// Export the state at the state root of the block of `height`, along with
+    /// the proof of the block and the blocks to be re-executed on startup,
+    /// namely the blocks after the exec height of the block. Return the
+    /// checksum of the snapshot.
+    pub async fn snapshot_export<P: AsRef<Path>>(
+        &self,
+        height: u64,
+        to: P,
+    ) -> ProtocolResult<Hash> {
+        let block = self
+            .block_get(height)
+            .await?
+            .ok_or(CliError::BlockNotFound(height))?;
+        let proof = match self.block_get(height + 1).await? {
+            Some(next_block) => next_block.header.proof,
+            None => self.storage.get_latest_proof(Context::new()).await?,
+        };
+        if proof.height != height {
+            return Err(CliError::ProofNotFound(height).into());
+        }
+
+        let file = fs::File::create(to.as_ref()).map_err(CliError::IO)?;
+        let mut writer = SnapshotWriter::new(BufWriter::new(file))?;
+        writer.write(&SnapshotEntry::Proof(proof))?;
+
+        let start = (block.header.exec_height + 1).min(height);
+        for idx in start..height {
+            let exec_block = self
+                .block_get(idx)
+                .await?
+                .ok_or(CliError::BlockNotFound(idx))?;
+            self.snapshot_export_block(&mut writer, exec_block).await?;
+        }
+        let state_root = block.header.state_root.clone();
+        self.snapshot_export_block(&mut writer, block).await?;
+
+        let trie_db = self.trie_db()?;
+        let mut count = 0;
+        trie_db.visit_state(&state_root, |key, value| {
+            count += 1;
+            writer.write(&SnapshotEntry::TrieNode {
+                key: key.to_vec(),
+                value: value.to_vec(),
+            })
+        })?;
+
+        let checksum = writer.finish()?;
+        log::info!(
+            "snapshot export of height {} with {} trie nodes successfully to: {:?}, checksum: {:?}",
+            height,
+            count,
+            to.as_ref().to_str(),
+            checksum
+        );
+        Ok(checksum)
+    }
+
+    async fn snapshot_export_block<W: std::io::Write>(
+        &self,
+        writer: &mut SnapshotWriter<W>,
+        block: Block,
+    ) -> ProtocolResult<()> {
+        let height = block.header.height;
+        let txs = self
+            .storage
+            .get_transactions(Context::new(), height, &block.ordered_tx_hashes)
+            .await?;
+        let tx_hashes = block.ordered_tx_hashes.clone();
+        writer.write(&SnapshotEntry::Block(block))?;
+
+        for (opt_stx, tx_hash) in txs.into_iter().zip(tx_hashes.into_iter()) {
+            let stx =
+                opt_stx.ok_or(CliError::TransactionNotFound { height, tx_hash })?;
+            writer.write(&SnapshotEntry::Transaction(stx))?;
+        }
+        Ok(())
+    }
+
+    /// Import a snapshot into an empty db, the node then starts syncing from the
+    /// height of the snapshot. The checksum is verified before anything is
+    /// written, and the latest block is set at last so that an interrupted
+    /// import can be retried. The trie nodes must match their hashes, the blocks
+    /// must link up to the block of the proof, and the state of that block must
+    /// be complete.
+    pub async fn snapshot_import<P: AsRef<Path>>(
+        &self,
+        from: P,
+    ) -> ProtocolResult<Hash> {
+        match self.storage.get_latest_block(Context::new()).await {
+            Ok(block) => return Err(CliError::DataExists(block.header.height).into()),
+            Err(e) => match e.downcast_ref::<StorageError>() {
+                Some(StorageError::GetNone) => {}
+                _ => return Err(e),
+            },
+        }
+
+        let open = || -> ProtocolResult<_> {
+            let file = fs::File::open(from.as_ref()).map_err(CliError::IO)?;
+            SnapshotReader::new(BufReader::new(file))
+        };
+        let checksum = open()?.verify()?;
+
+        let trie_db = self.trie_db()?;
+        let mut reader = open()?;
+        let mut proof = None;
+        let mut latest_block = None;
+        let mut txs = Vec::new();
+        let (mut keys, mut values) = (Vec::new(), Vec::new());
+        while let Some(entry) = reader.next_entry()? {
+            match entry {
+                SnapshotEntry::Proof(p) => proof = Some(p),
+                SnapshotEntry::Block(block) => {
+                    if let Some(prev_block) = latest_block.replace(block) {
+                        self.snapshot_import_block(&prev_block, mem::take(&mut txs))
+                            .await?;
+
+                        let prev_hash = Hash::digest(prev_block.header.encode_fixed()?);
+                        let height = prev_block.header.height + 1;
+                        match latest_block.as_ref() {
+                            Some(block)
+                                if block.header.height == height
+                                    && block.header.prev_hash == prev_hash => {}
+                            _ => return Err(CliError::PrevHashMismatch(height).into()),
+                        }
+                    }
+                }
+                SnapshotEntry::Transaction(stx) => txs.push(stx),
+                SnapshotEntry::TrieNode { key, value } => {
+                    // The trie nodes are keyed by their hash
+                    let hash = Hash::digest(Bytes::copy_from_slice(&value));
+                    if hash.as_bytes().as_ref() != key.as_slice() {
+                        return Err(CliError::TrieNodeHash(hex::encode(key)).into());
+                    }
+                    keys.push(key);
+                    values.push(value);
+                    if keys.len() >= TRIE_NODE_BATCH {
+                        trie_db.insert_batch(
+                            mem::take(&mut keys),
+                            mem::take(&mut values),
+                        )?;
+                        trie_db.flush()?;
+                    }
+                }
+            }
+        }
+        trie_db.insert_batch(keys, values)?;
+        trie_db.flush()?;
+
+        let block = latest_block.ok_or(CliError::SnapshotEmpty)?;
+        let height = block.header.height;
+        let proof = proof.ok_or(CliError::ProofNotFound(height))?;
+        if proof.height != height
+            || proof.block_hash != Hash::digest(block.header.encode_fixed()?)
+        {
+            return Err(CliError::ProofMismatch(height).into());
+        }
+
+        // The state of the block must be complete, or the node can not go on
+        // executing from it
+        let state_root = block.header.state_root.clone();
+        let missing = trie_db.count_missing(&state_root)?;
+        if missing > 0 {
+            return Err(CliError::StateIncomplete {
+                height,
+                state_root,
+                missing,
+            }
+            .into());
+        }
+        self.snapshot_import_block(&block, txs).await?;
+        self.storage
+            .update_latest_proof(Context::new(), proof)
+            .await?;
+        self.storage.set_latest_block(Context::new(), block).await?;
+
+        log::info!(
+            "snapshot import of height {} successfully from: {:?}, checksum: {:?}",
+            height,
+            from.as_ref().to_str(),
+            checksum
+        );
+        Ok(checksum)
+    }
+
+    async fn snapshot_import_block(
+        &self,
+        block: &Block,
+        txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        let tx_hashes = txs
+            .iter()
+            .map(|stx| stx.tx_hash.clone())
+            .collect::<Vec<_>>();
+        if tx_hashes != block.ordered_tx_hashes {
+            return Err(CliError::SnapshotFormat.into());
+        }
+        for stx in txs.iter() {
+            if Hash::digest(stx.raw.encode_fixed()?) != stx.tx_hash {
+                return Err(CliError::SnapshotFormat.into());
+            }
+        }
+
+        self.storage
+            .set_block(Context::new(), block.clone())
+            .await?;
+        self.storage
+            .insert_transactions(Context::new(), block.header.height, txs)
+            .await?;
+        Ok(())
+    }
+
+    pub fn verify(&self, sub_cmd: &ArgMatches) -> ProtocolResult<()> {
+        let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+
+        let from = match sub_cmd.value_of("from") {
+            Some(from) => from.parse().map_err(|_| CliError::Parse)?,
+            None => 0,
+        };
+        let to = match sub_cmd.value_of("to") {
+            Some(to) => Some(to.parse().map_err(|_| CliError::Parse)?),
+            None => None,
+        };
+
+        let report = rt.block_on(async move { self.verify_chain(from, to).await })?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(CliError::JSONFormat)?
+        );
+        match report.inconsistency {
+            Some(inconsistency) => Err(CliError::Inconsistent(inconsistency).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Walk the stored blocks from `from` to `to`, which defaults to the latest
+    /// height, and stop at the first inconsistent height.
+    pub async fn verify_chain(
+        &self,
+        from: u64,
+        to: Option<u64>,
+    ) -> ProtocolResult<VerifyReport> {
+        let latest_header = self.storage.get_latest_block_header(Context::new()).await?;
+        let to = to.map_or(latest_header.height, |to| to.min(latest_header.height));
+        let pruned_height = self.storage.get_pruned_height(Context::new()).await?;
+        let state_height = if self.config.executor.light {
+            latest_header.height
+        } else {
+            self.config
+                .executor
+                .state_history
+                .map_or(0, |state_history| {
+                    latest_header.height.saturating_sub(state_history - 1)
+                })
+        };
+
+        let mut report = VerifyReport {
+            from,
+            to,
+            verified_height: None,
+            pruned_height,
+            state_height,
+            inconsistency: None,
+        };
+        let mut prev_header = match from {
+            0 => None,
+            _ => {
+                self.storage
+                    .get_block_header(Context::new(), from - 1)
+                    .await?
+            }
+        };
+        for height in from..=to {
+            let block = match self.storage.get_block(Context::new(), height).await? {
+                Some(block) => block,
+                None => {
+                    report.inconsistency = Some(Inconsistency::new(
+                        height,
+                        InconsistencyKind::Block,
+                        "block not found".to_owned(),
+                    ));
+                    break;
+                }
+            };
+
+            let inconsistency = self
+                .verify_block(&block, prev_header.as_ref(), &report)
+                .await?;
+            if inconsistency.is_some() {
+                report.inconsistency = inconsistency;
+                break;
+            }
+            report.verified_height = Some(height);
+            prev_header = Some(block.header);
+        }
+
+        log::info!("verify from {} to {}: {:?}", from, to, report);
+        Ok(report)
+    }
+
+    async fn verify_block(
+        &self,
+        block: &Block,
+        prev_header: Option<&BlockHeader>,
+        report: &VerifyReport,
+    ) -> ProtocolResult<Option<Inconsistency>> {
+        let header = &block.header;
+        let height = header.height;
+        let inconsistent = |kind, detail| -> ProtocolResult<Option<Inconsistency>> {
+            Ok(Some(Inconsistency::new(height, kind, detail)))
+        };
+
+        if let Some(prev_header) = prev_header {
+            let prev_hash = Hash::digest(prev_header.encode_fixed()?);
+            if header.prev_hash != prev_hash {
+                return inconsistent(
+                    InconsistencyKind::PrevHash,
+                    format!("expect {:?}, actual {:?}", prev_hash, header.prev_hash),
+                );
+            }
+        }
+
+        let order_root = Merkle::from_hashes(block.ordered_tx_hashes.clone())
+            .get_root_hash()
+            .unwrap_or_else(Hash::from_empty);
+        if header.order_root != order_root {
+            return inconsistent(
+                InconsistencyKind::OrderRoot,
+                format!("expect {:?}, actual {:?}", order_root, header.order_root),
+            );
+        }
+
+        let pruned_height = report.pruned_height.unwrap_or(0);
+        if height >= pruned_height {
+            let txs = self
+                .storage
+                .get_transactions(Context::new(), height, &block.ordered_tx_hashes)
+                .await?;
+            let mut signed_txs = Vec::with_capacity(txs.len());
+            for (tx_hash, stx) in block.ordered_tx_hashes.iter().zip(txs) {
+                let stx = match stx {
+                    Some(stx) => stx,
+                    None => {
+                        return inconsistent(
+                            InconsistencyKind::Transaction,
+                            format!("transaction {:?} not found", tx_hash),
+                        )
+                    }
+                };
+                if &stx.tx_hash != tx_hash
+                    || &Hash::digest(stx.raw.encode_fixed()?) != tx_hash
+                {
+                    return inconsistent(
+                        InconsistencyKind::Transaction,
+                        format!("transaction {:?} mismatches its hash", tx_hash),
+                    );
+                }
+                signed_txs.push(stx);
+            }
+
+            let signed_txs_hash = digest_signed_transactions(&signed_txs)?;
+            if header.order_signed_transactions_hash != signed_txs_hash {
+                return inconsistent(
+                    InconsistencyKind::SignedTransactionsHash,
+                    format!(
+                        "expect {:?}, actual {:?}",
+                        signed_txs_hash, header.order_signed_transactions_hash
+                    ),
+                );
+            }
+        }
+
+        // The receipt roots are of the heights executed since the previous block,
+        // the last of which is the exec height.
+        let exec_len = header.receipt_root.len() as u64;
+        if header.exec_height + 1 < exec_len
+            || prev_header.map_or(false, |prev_header| {
+                prev_header.exec_height + exec_len != header.exec_height
+            })
+        {
+            return inconsistent(
+                InconsistencyKind::ReceiptRoot,
+                format!(
+                    "{} receipt roots mismatch exec height {}",
+                    exec_len, header.exec_height
+                ),
+            );
+        }
+        let exec_heights = (header.exec_height + 1 - exec_len)..=header.exec_height;
+        for (exec_height, receipt_root) in exec_heights.zip(&header.receipt_root) {
+            if exec_height < pruned_height {
+                continue;
+            }
+
+            let exec_block =
+                match self.storage.get_block(Context::new(), exec_height).await? {
+                    Some(exec_block) => exec_block,
+                    None => {
+                        return inconsistent(
+                            InconsistencyKind::Receipt,
+                            format!("executed block {} not found", exec_height),
+                        )
+                    }
+                };
+            let receipts = self
+                .storage
+                .get_receipts(
+                    Context::new(),
+                    exec_height,
+                    exec_block.ordered_tx_hashes.clone(),
+                )
+                .await?;
+            let mut stored_receipts = Vec::with_capacity(receipts.len());
+            for (tx_hash, receipt) in exec_block.ordered_tx_hashes.iter().zip(receipts) {
+                match receipt {
+                    Some(receipt) => stored_receipts.push(receipt),
+                    None => {
+                        return inconsistent(
+                            InconsistencyKind::Receipt,
+                            format!("receipt of transaction {:?} not found", tx_hash),
+                        )
+                    }
+                }
+            }
+
+            let expect = receipt_root(&stored_receipts)?;
+            if receipt_root != &expect {
+                return inconsistent(
+                    InconsistencyKind::ReceiptRoot,
+                    format!(
+                        "expect {:?} of exec height {}, actual {:?}",
+                        expect, exec_height, receipt_root
+                    ),
+                );
+            }
+        }
+
+        // The block 0 has no proof, and the validators of a proof are read from
+        // the state of the previous block.
+        let prev_header = match prev_header {
+            Some(prev_header) if prev_header.height >= report.state_height => {
+                prev_header
+            }
+            _ => return Ok(None),
+        };
+        let proof = match self
+            .storage
+            .get_block_header(Context::new(), height + 1)
+            .await?
+        {
+            Some(next_header) => next_header.proof,
+            None => self.storage.get_latest_proof(Context::new()).await?,
+        };
+        let block_hash = Hash::digest(header.encode_fixed()?);
+        if proof.height != height || proof.block_hash != block_hash {
+            return inconsistent(
+                InconsistencyKind::Proof,
+                format!(
+                    "proof of height {} with block hash {:?}, expect block hash {:?}",
+                    proof.height, proof.block_hash, block_hash
+                ),
+            );
+        }
+
+        let params = ExecutorParams {
+            state_root: prev_header.state_root.clone(),
+            height: prev_header.height,
+            timestamp: prev_header.timestamp,
+            cycles_limit: u64::max_value(),
+            proposer: prev_header.proposer.clone(),
+        };
+        let metadata = self.get_metadata(self.trie_db()?, &params)?;
+        if let Some(detail) = self.verify_proof_signature(&proof, &metadata)? {
+            return inconsistent(InconsistencyKind::ProofSignature, detail);
+        }
+        Ok(None)
+    }
+
+    fn verify_proof_signature(
+        &self,
+        proof: &Proof,
+        metadata: &Metadata,
+    ) -> ProtocolResult<Option<String>> {
+        let mut authority_list = metadata
+            .verifier_list
+            .iter()
+            .map(|v| Node {
+                address: v.pub_key.decode(),
+                propose_weight: v.propose_weight,
+                vote_weight: v.vote_weight,
+            })
+            .collect::<Vec<_>>();
+        let signed_voters = match extract_voters(&mut authority_list, &proof.bitmap) {
+            Ok(signed_voters) => signed_voters,
+            Err(_) => return Ok(Some("invalid bitmap".to_owned())),
+        };
+
+        let mut total_weight = 0u64;
+        let mut signed_weight = 0u64;
+        let mut pub_keys = Vec::new();
+        for v in metadata.verifier_list.iter() {
+            total_weight += u64::from(v.vote_weight);
+            if signed_voters.contains(&v.pub_key.decode()) {
+                signed_weight += u64::from(v.vote_weight);
+                pub_keys.push(convert_hex_to_bls_pubkeys(v.bls_pub_key.clone())?);
+            }
+        }
+        if 3 * signed_weight <= 2 * total_weight {
+            return Ok(Some(format!(
+                "signed weight {} of total weight {} is not enough",
+                signed_weight, total_weight
+            )));
+        }
+
+        let vote = Vote {
+            height: proof.height,
+            round: proof.round,
+            vote_type: VoteType::Precommit,
+            block_hash: proof.block_hash.as_bytes(),
+        };
+        let vote_hash = Hash::digest(Bytes::from(rlp::encode(&vote))).as_bytes();
+        let crypto = self.overlord_crypto(&metadata.common_ref)?;
+        match crypto.inner_verify_aggregated_signature(
+            vote_hash,
+            pub_keys,
+            proof.signature.clone(),
+        ) {
+            Ok(()) => Ok(None),
+            Err(e) => Ok(Some(e.to_string())),
+        }
+    }
+
+    fn overlord_crypto(&self, common_ref: &Hex) -> ProtocolResult<OverlordCrypto> {
+        let mut priv_key = Vec::new();
+        priv_key.extend_from_slice(&[0u8; 16]);
+        let mut tmp = hex::decode(self.config.privkey.as_string_trim0x())
+            .map_err(|_| CliError::Parse)?;
+        priv_key.append(&mut tmp);
+        let bls_priv_key =
+            BlsPrivateKey::try_from(priv_key.as_ref()).map_err(CliError::Crypto)?;
+
+        let hex_common_ref =
+            hex::decode(common_ref.as_string_trim0x()).map_err(|_| CliError::Parse)?;
+        let common_ref: BlsCommonReference =
+            std::str::from_utf8(hex_common_ref.as_ref())
+                .map_err(|_| CliError::Parse)?
+                .into();
+
+        Ok(OverlordCrypto::new(
+            bls_priv_key,
+            HashMap::new(),
+            common_ref,
+        ))
+    }
+
+    pub fn replay(&self, sub_cmd: &ArgMatches) -> ProtocolResult<()> {
+        let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+
+        let from = sub_cmd
+            .value_of("from")
+            .expect("missing [FROM]")
+            .parse()
+            .map_err(|_| CliError::Parse)?;
+        let to = sub_cmd
+            .value_of("to")
+            .expect("missing [TO]")
+            .parse()
+            .map_err(|_| CliError::Parse)?;
+        let scratch = match sub_cmd.value_of("scratch") {
+            Some(scratch) => {
+                PathBuf::from_str(scratch).map_err(|e| CliError::Path(e.to_string()))?
+            }
+            None => self.config.data_path.join("replay"),
+        };
+
+        let report =
+            rt.block_on(async move { self.replay_chain(from, to, scratch).await })?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(CliError::JSONFormat)?
+        );
+        match report.divergence {
+            Some(divergence) => Err(CliError::Diverged(divergence).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Re-execute the blocks from `from` to `to` on a scratch trie db at
+    /// `scratch`, starting from the recorded state after executing `from - 1`,
+    /// and stop at the first height whose receipt root, cycles used or state
+    /// root diverges from the recorded one. The scratch trie db is removed
+    /// afterwards.
+    pub async fn replay_chain<P: AsRef<Path>>(
+        &self,
+        from: u64,
+        to: u64,
+        scratch: P,
+    ) -> ProtocolResult<ReplayReport> {
+        if from == 0 || from > to {
+            return Err(CliError::HeightRange { from, to }.into());
+        }
+        let scratch = scratch.as_ref();
+        if scratch.exists() {
+            return Err(CliError::PathExists(format!("{:?}", scratch)).into());
+        }
+
+        let mut cursor = from - 1;
+        let base_header = self
+            .get_executed_header(from - 1, &mut cursor)
+            .await?
+            .filter(|header| header.exec_height == from - 1)
+            .ok_or(CliError::StateRootNotFound(from - 1))?;
+
+        let scratch_db = Arc::new(RocksTrieDB::new(
+            scratch,
+            false,
+            self.config.rocksdb.max_open_files,
+            self.config.executor.triedb_cache_size,
+        )?);
+        let report = self
+            .replay_blocks(&scratch_db, from, to, base_header.state_root, cursor)
+            .await;
+        drop(scratch_db);
+        fs::remove_dir_all(scratch).map_err(CliError::IO)?;
+        let report = report?;
+
+        log::info!("replay from {} to {}: {:?}", from, to, report);
+        Ok(report)
+    }
+
+    async fn replay_blocks(
+        &self,
+        scratch_db: &Arc<RocksTrieDB>,
+        from: u64,
+        to: u64,
+        mut state_root: MerkleRoot,
+        mut cursor: u64,
+    ) -> ProtocolResult<ReplayReport> {
+        let trie_db = self.trie_db()?;
+        let (mut keys, mut values) = (Vec::new(), Vec::new());
+        trie_db.visit_state(&state_root, |key, value| {
+            keys.push(key.to_vec());
+            values.push(value.to_vec());
+            if keys.len() >= TRIE_NODE_BATCH {
+                scratch_db.insert_batch(mem::take(&mut keys), mem::take(&mut values))?;
+            }
+            Ok(())
+        })?;
+        scratch_db.insert_batch(keys, values)?;
+        scratch_db.flush()?;
+
+        let mut report = ReplayReport {
+            from,
+            to,
+            replayed_height: None,
+            divergence: None,
+        };
+        for height in from..=to {
+            let block = self
+                .block_get(height)
+                .await?
+                .ok_or(CliError::BlockNotFound(height))?;
+            let txs = self
+                .storage
+                .get_transactions(Context::new(), height, &block.ordered_tx_hashes)
+                .await?
+                .into_iter()
+                .zip(block.ordered_tx_hashes.iter())
+                .map(|(opt_stx, tx_hash)| {
+                    opt_stx.ok_or_else(|| CliError::TransactionNotFound {
+                        height,
+                        tx_hash: tx_hash.clone(),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // The cycles limit of the status is of the latest state when the
+            // block is executed, which is the state it is executed on unless
+            // the cycles limit changes meanwhile.
+            let mut params = ExecutorParams {
+                state_root: state_root.clone(),
+                height,
+                timestamp: block.header.timestamp,
+                cycles_limit: u64::max_value(),
+                proposer: block.header.proposer.clone(),
+            };
+            params.cycles_limit = self
+                .get_metadata(Arc::clone(scratch_db), &params)?
+                .cycles_limit;
+
+            let mut executor = ServiceExecutorFactory::from_root(
+                state_root.clone(),
+                Arc::clone(scratch_db),
+                Arc::clone(&self.storage),
+                Arc::clone(&self.service_mapping),
+            )?;
+            let resp = executor.exec(Context::new(), &params, &txs)?;
+
+            let header = self
+                .get_executed_header(height, &mut cursor)
+                .await?
+                .ok_or(CliError::NotExecuted(height))?;
+            let offset = (header.exec_height - height) as usize;
+            if offset >= header.receipt_root.len() {
+                return Err(CliError::NotExecuted(height).into());
+            }
+            let idx = header.receipt_root.len() - 1 - offset;
+
+            let divergence = self.replay_divergence(&block, &header, idx, &resp).await?;
+            if divergence.is_some() {
+                report.divergence = divergence;
+                break;
+            }
+            state_root = resp.state_root;
+            report.replayed_height = Some(height);
+        }
+
+        Ok(report)
+    }
+
+    // The execution of a height is recorded in the first block whose exec
+    // height reaches it, which is searched from `cursor` on.
+    async fn get_executed_header(
+        &self,
+        exec_height: u64,
+        cursor: &mut u64,
+    ) -> ProtocolResult<Option<BlockHeader>> {
+        loop {
+            match self
+                .storage
+                .get_block_header(Context::new(), *cursor)
+                .await?
+            {
+                Some(header) if header.exec_height >= exec_height => {
+                    return Ok(Some(header))
+                }
+                Some(_) => *cursor += 1,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    async fn replay_divergence(
+        &self,
+        block: &Block,
+        header: &BlockHeader,
+        idx: usize,
+        resp: &ExecutorResp,
+    ) -> ProtocolResult<Option<Divergence>> {
+        let height = block.header.height;
+
+        let receipt_root = receipt_root(&resp.receipts)?;
+        if header.receipt_root[idx] != receipt_root {
+            let stored_receipts = self
+                .storage
+                .get_receipts(Context::new(), height, block.ordered_tx_hashes.clone())
+                .await?;
+            let receipt = stored_receipts
+                .into_iter()
+                .zip(resp.receipts.iter())
+                .find(|(stored, receipt)| stored.as_ref() != Some(*receipt))
+                .map(|(stored, receipt)| ReceiptDivergence {
+                    tx_hash: receipt.tx_hash.clone(),
+                    expect: stored.map(|stored| format!("{:?}", stored)),
+                    actual: format!("{:?}", receipt),
+                });
+
+            return Ok(Some(Divergence {
+                height,
+                kind: DivergenceKind::ReceiptRoot,
+                expect: format!("{:?}", header.receipt_root[idx]),
+                actual: format!("{:?}", receipt_root),
+                receipt,
+            }));
+        }
+
+        let cycles_used = header.cycles_used.get(idx);
+        if cycles_used != Some(&resp.all_cycles_used) {
+            return Ok(Some(Divergence {
+                height,
+                kind: DivergenceKind::CyclesUsed,
+                expect: format!("{:?}", cycles_used),
+                actual: resp.all_cycles_used.to_string(),
+                receipt: None,
+            }));
+        }
+
+        // Only the state root of the exec height is recorded in a block.
+        if header.exec_height == height && header.state_root != resp.state_root {
+            return Ok(Some(Divergence {
+                height,
+                kind: DivergenceKind::StateRoot,
+                expect: format!("{:?}", header.state_root),
+                actual: format!("{:?}", resp.state_root),
+                receipt: None,
+            }));
+        }
+        Ok(None)
+    }
+
+    fn get_metadata<DB: 'static + cita_trie::DB>(
+        &self,
+        trie_db: Arc<DB>,
+        params: &ExecutorParams,
+    ) -> ProtocolResult<Metadata> {
+        let executor = ServiceExecutorFactory::from_root(
+            params.state_root.clone(),
+            trie_db,
+            Arc::clone(&self.storage),
+            Arc::clone(&self.service_mapping),
+        )?;
+
+        let caller = Address::from_hash(Hash::digest(protocol::address_hrp().as_str()))?;
+        let exec_resp = executor.read(
+            params,
+            &caller,
+            1,
+            &TransactionRequest {
+                service_name: "metadata".to_string(),
+                method: "get_metadata".to_string(),
+                payload: "".to_string(),
+                encoding: PayloadEncoding::Json,
+            },
+        )?;
+
+        let metadata = serde_json::from_str(&exec_resp.succeed_data)
+            .map_err(CliError::JSONFormat)?;
+        Ok(metadata)
+    }
+}
+
+fn receipt_root(receipts: &[Receipt]) -> ProtocolResult<MerkleRoot> {
+    let receipt_hashes = receipts
+        .iter()
+        .map(|receipt| Ok(Hash::digest(receipt.encode_fixed()?)))
+        .collect::<ProtocolResult<Vec<_>>>()?;
+
+    Ok(Merkle::from_hashes(receipt_hashes)
+        .get_root_hash()
+        .unwrap_or_else(Hash::from_empty))
 }
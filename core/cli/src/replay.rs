@@ -0,0 +1,55 @@
+use derive_more::Display;
+use serde_derive::Serialize;
+
+use protocol::types::Hash;
+
+/// The report of re-executing the stored blocks, printed as JSON so that it
+/// can be consumed by scripts.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub from: u64,
+    pub to: u64,
+    /// The highest height all the heights from `from` up to which are
+    /// re-executed to the recorded results, `None` if the height `from` is not.
+    pub replayed_height: Option<u64>,
+    pub divergence: Option<Divergence>,
+}
+
+#[derive(Debug, Clone, Display, Serialize)]
+#[display(
+    fmt = "height {} diverges on {}, expect {}, actual {}",
+    height,
+    kind,
+    expect,
+    actual
+)]
+pub struct Divergence {
+    pub height: u64,
+    pub kind: DivergenceKind,
+    pub expect: String,
+    pub actual: String,
+    /// The first receipt differing from the stored one if the receipt root
+    /// diverges.
+    pub receipt: Option<ReceiptDivergence>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiptDivergence {
+    pub tx_hash: Hash,
+    /// `None` if the stored receipt is not found, e.g. it has been pruned.
+    pub expect: Option<String>,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DivergenceKind {
+    #[display(fmt = "receipt_root")]
+    ReceiptRoot,
+
+    #[display(fmt = "cycles_used")]
+    CyclesUsed,
+
+    #[display(fmt = "state_root")]
+    StateRoot,
+}
@@ -0,0 +1,176 @@
+use std::io::{ErrorKind, Read, Write};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use rlp::{Rlp, RlpStream};
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::{Block, Hash, Proof, SignedTransaction};
+use protocol::ProtocolResult;
+
+use crate::error::CliError;
+
+const SNAPSHOT_MAGIC: &[u8] = b"MUTASNAP";
+const SNAPSHOT_VERSION: u8 = 1;
+
+const TAG_END: u8 = 0;
+const TAG_PROOF: u8 = 1;
+const TAG_BLOCK: u8 = 2;
+const TAG_TRANSACTION: u8 = 3;
+const TAG_TRIE_NODE: u8 = 4;
+
+// A snapshot is the magic and version followed by records, each of which is
+// a tag, the big endian u32 length of the payload, and the payload. The last
+// record holds the checksum chained over all the previous records, so that
+// a truncated or corrupted snapshot is detected.
+pub enum SnapshotEntry {
+    Proof(Proof),
+    Block(Block),
+    Transaction(SignedTransaction),
+    TrieNode { key: Vec<u8>, value: Vec<u8> },
+}
+
+pub struct SnapshotWriter<W: Write> {
+    inner: W,
+    checksum: Hash,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    pub fn new(mut inner: W) -> ProtocolResult<Self> {
+        let header = snapshot_header();
+        inner.write_all(&header).map_err(CliError::IO)?;
+
+        Ok(Self {
+            inner,
+            checksum: Hash::digest(header),
+        })
+    }
+
+    pub fn write(&mut self, entry: &SnapshotEntry) -> ProtocolResult<()> {
+        let (tag, payload) = match entry {
+            SnapshotEntry::Proof(proof) => (TAG_PROOF, proof.encode_fixed()?),
+            SnapshotEntry::Block(block) => (TAG_BLOCK, block.encode_fixed()?),
+            SnapshotEntry::Transaction(stx) => (TAG_TRANSACTION, stx.encode_fixed()?),
+            SnapshotEntry::TrieNode { key, value } => {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(key).append(value);
+                (TAG_TRIE_NODE, Bytes::from(stream.out()))
+            }
+        };
+
+        let record = encode_record(tag, &payload);
+        self.inner.write_all(&record).map_err(CliError::IO)?;
+        self.checksum = chain_checksum(&self.checksum, &record);
+        Ok(())
+    }
+
+    /// Write the checksum record and return the checksum.
+    pub fn finish(mut self) -> ProtocolResult<Hash> {
+        let record = encode_record(TAG_END, self.checksum.as_bytes().as_ref());
+        self.inner.write_all(&record).map_err(CliError::IO)?;
+        self.inner.flush().map_err(CliError::IO)?;
+        Ok(self.checksum)
+    }
+}
+
+pub struct SnapshotReader<R: Read> {
+    inner: R,
+    checksum: Hash,
+    finished: bool,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    pub fn new(mut inner: R) -> ProtocolResult<Self> {
+        let expected = snapshot_header();
+        let mut header = vec![0u8; expected.len()];
+        read_exact(&mut inner, &mut header)?;
+        if header != expected {
+            return Err(CliError::SnapshotFormat.into());
+        }
+
+        Ok(Self {
+            inner,
+            checksum: Hash::digest(Bytes::from(header)),
+            finished: false,
+        })
+    }
+
+    /// Read the next entry, `None` is returned once the checksum record is
+    /// read and matches.
+    pub fn next_entry(&mut self) -> ProtocolResult<Option<SnapshotEntry>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let mut prefix = [0u8; 5];
+        read_exact(&mut self.inner, &mut prefix)?;
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&prefix[1..]);
+        let mut payload = vec![0u8; u32::from_be_bytes(len) as usize];
+        read_exact(&mut self.inner, &mut payload)?;
+
+        let tag = prefix[0];
+        if tag == TAG_END {
+            if payload != self.checksum.as_bytes().as_ref() {
+                return Err(CliError::SnapshotChecksum.into());
+            }
+            self.finished = true;
+            return Ok(None);
+        }
+
+        let mut record = prefix.to_vec();
+        record.extend_from_slice(&payload);
+        self.checksum = chain_checksum(&self.checksum, &record);
+
+        let payload = Bytes::from(payload);
+        let entry = match tag {
+            TAG_PROOF => SnapshotEntry::Proof(Proof::decode_fixed(payload)?),
+            TAG_BLOCK => SnapshotEntry::Block(Block::decode_fixed(payload)?),
+            TAG_TRANSACTION => {
+                SnapshotEntry::Transaction(SignedTransaction::decode_fixed(payload)?)
+            }
+            TAG_TRIE_NODE => {
+                let rlp = Rlp::new(&payload);
+                SnapshotEntry::TrieNode {
+                    key: rlp.val_at(0).map_err(|_| CliError::SnapshotFormat)?,
+                    value: rlp.val_at(1).map_err(|_| CliError::SnapshotFormat)?,
+                }
+            }
+            _ => return Err(CliError::SnapshotFormat.into()),
+        };
+        Ok(Some(entry))
+    }
+
+    /// Read through all the remaining entries to verify the checksum.
+    pub fn verify(mut self) -> ProtocolResult<Hash> {
+        while self.next_entry()?.is_some() {}
+        Ok(self.checksum)
+    }
+}
+
+fn snapshot_header() -> Bytes {
+    let mut header = BytesMut::from(SNAPSHOT_MAGIC);
+    header.put_u8(SNAPSHOT_VERSION);
+    header.freeze()
+}
+
+fn encode_record(tag: u8, payload: &[u8]) -> Bytes {
+    let mut record = BytesMut::with_capacity(payload.len() + 5);
+    record.put_u8(tag);
+    record.put_u32(payload.len() as u32);
+    record.put_slice(payload);
+    record.freeze()
+}
+
+fn chain_checksum(checksum: &Hash, record: &[u8]) -> Hash {
+    let mut buf = BytesMut::from(checksum.as_bytes().as_ref());
+    buf.put_slice(record);
+    Hash::digest(buf.freeze())
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> ProtocolResult<()> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        ErrorKind::UnexpectedEof => CliError::SnapshotTruncated,
+        _ => CliError::IO(e),
+    })?;
+    Ok(())
+}
@@ -1,18 +1,41 @@
 mod service_mapping;
 
-use std::path::PathBuf;
+use std::fs;
+use std::io::{BufReader, BufWriter};
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 
-use protocol::traits::{CommonStorage, Context};
-use protocol::types::{Block, BlockHeader, Bytes, Hash, Proof};
+use common_merkle::Merkle;
+use core_consensus::util::{
+    convert_hex_to_bls_pubkeys, digest_signed_transactions, OverlordCrypto,
+};
+use core_storage::adapter::rocks::RocksAdapter;
+use core_storage::ImplStorage;
+use framework::executor::{ServiceExecutor, ServiceExecutorFactory};
+use overlord::types::{Vote, VoteType};
+use overlord::Crypto;
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::{
+    BackupManifest, CommonStorage, Context, ExecutorFactory, ExecutorParams, Storage,
+};
+use protocol::types::{
+    Block, BlockHeader, Bytes, Genesis, Hash, PayloadEncoding, Proof, RawTransaction,
+    SignedTransaction, TransactionRequest,
+};
 use protocol::ProtocolResult;
 
-use crate::{Cli, CliConfig};
+use crate::snapshot::{SnapshotEntry, SnapshotReader, SnapshotWriter};
+use crate::{Cli, CliConfig, DivergenceKind, InconsistencyKind, MaintenanceCli};
 
 use service_mapping::DefaultServiceMapping;
 
 const SAVE_DIR: &str = "./free-space/save";
+const SNAPSHOT_PATH: &str = "./free-space/snapshot";
+const CHECKPOINT_DIR: &str = "./free-space/checkpoint";
 const DATA_DIR: &str = "./free-space/data";
+const REPLAY_DIR: &str = "./free-space/data/replay";
 const CONFIG_PATH: &str = "./src/tests/config.toml";
 const GENESIS_PATH: &str = "./src/tests/genesis.toml";
 
@@ -40,6 +63,26 @@ fn test_lineally() {
     prepare();
     block_set();
     clean();
+
+    prepare();
+    snapshot_export_import();
+    clean();
+
+    prepare();
+    backup_checkpoint();
+    clean();
+
+    prepare();
+    verify();
+    clean();
+
+    prepare_verified();
+    verify_linked();
+    clean();
+
+    prepare_executed();
+    replay();
+    clean();
 }
 
 fn save_restore() {
@@ -83,6 +126,425 @@ fn save_restore() {
     println!("tested save_restore");
 }
 
+fn backup_checkpoint() {
+    println!("test backup_checkpoint");
+    let checkpoint =
+        PathBuf::from_str(CHECKPOINT_DIR).expect("backup_checkpoint, path fails");
+    fs_extra::dir::remove(checkpoint.clone())
+        .expect("backup_checkpoint, remove checkpoint fails");
+
+    run(vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "backup",
+        "checkpoint",
+        CHECKPOINT_DIR,
+    ])
+    .expect("backup_checkpoint, run checkpoint fails");
+
+    let manifest = fs::read_to_string(checkpoint.join(run::BACKUP_MANIFEST))
+        .expect("backup_checkpoint, read manifest fails");
+    let manifest: BackupManifest =
+        serde_json::from_str(&manifest).expect("backup_checkpoint, decode fails");
+    assert_eq!(manifest.height, 23);
+    assert!(!manifest.column_families.is_empty());
+
+    // the checkpoint is restored the same as a backup
+    clean();
+    run(vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "backup",
+        "restore",
+        CHECKPOINT_DIR,
+    ])
+    .expect("backup_checkpoint, run restore fails");
+    latest_get(23);
+
+    fs_extra::dir::remove(checkpoint)
+        .expect("backup_checkpoint, remove checkpoint fails");
+    println!("tested backup_checkpoint");
+}
+
+fn snapshot_export_import() {
+    println!("test snapshot_export_import");
+    let snapshot = PathBuf::from_str(SNAPSHOT_PATH).expect("snapshot, path fails");
+
+    run(vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "snapshot",
+        "export",
+        "--height",
+        "20",
+        SNAPSHOT_PATH,
+    ])
+    .expect("snapshot, run export fails");
+    assert!(snapshot.exists());
+    clean();
+
+    let import = vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "snapshot",
+        "import",
+        "-y",
+        SNAPSHOT_PATH,
+    ];
+
+    // a corrupted snapshot is rejected
+    let content = fs::read(&snapshot).expect("snapshot, read fails");
+    let mut corrupted = content.clone();
+    *corrupted.last_mut().unwrap() ^= 1;
+    fs::write(&snapshot, corrupted).expect("snapshot, write fails");
+    assert!(run(import.clone()).is_err());
+
+    // a snapshot with a valid checksum is still checked against its hashes
+    let rejected =
+        |kind: &str, tamper: &dyn Fn(usize, SnapshotEntry) -> Option<SnapshotEntry>| {
+            fs::write(&snapshot, &content).expect("snapshot, write fails");
+            rewrite_snapshot(&snapshot, tamper);
+            let err = run(import.clone()).expect_err("snapshot, tampered import passes");
+            assert!(err.to_string().contains(kind), "{}", err);
+        };
+    rejected("TrieNodeHash", &|_, entry| match entry {
+        SnapshotEntry::TrieNode { key, mut value } => {
+            *value.last_mut().unwrap() ^= 1;
+            Some(SnapshotEntry::TrieNode { key, value })
+        }
+        entry => Some(entry),
+    });
+    // the root node is the first one exported. The state is checked before the
+    // proof, as a proof rejected after the trie nodes are written leaves them.
+    rejected("StateIncomplete", &|index, entry| match entry {
+        SnapshotEntry::TrieNode { .. } if index == 0 => None,
+        entry => Some(entry),
+    });
+    rejected("ProofMismatch", &|_, entry| match entry {
+        SnapshotEntry::Proof(mut proof) => {
+            proof.block_hash = Hash::from_empty();
+            Some(SnapshotEntry::Proof(proof))
+        }
+        entry => Some(entry),
+    });
+
+    fs::write(&snapshot, content).expect("snapshot, write fails");
+    run(import.clone()).expect("snapshot, run import fails");
+    let latest = latest_get(20);
+    assert_eq!(latest.header.exec_height, 19);
+
+    // importing into a db with data is rejected
+    assert!(run(import).is_err());
+
+    fs::remove_file(snapshot).expect("snapshot, remove snapshot fails");
+    println!("tested snapshot_export_import");
+}
+
+// Rewrite each entry of a snapshot with `tamper`, which is also passed the
+// index of a trie node among the trie nodes, and keep the checksum valid.
+fn rewrite_snapshot(
+    path: &Path,
+    tamper: &dyn Fn(usize, SnapshotEntry) -> Option<SnapshotEntry>,
+) {
+    let file = fs::File::open(path).expect("snapshot, open fails");
+    let mut reader =
+        SnapshotReader::new(BufReader::new(file)).expect("snapshot, read fails");
+    let mut entries = Vec::new();
+    let mut node_index = 0;
+    while let Some(entry) = reader.next_entry().expect("snapshot, read entry fails") {
+        let index = match entry {
+            SnapshotEntry::TrieNode { .. } => {
+                node_index += 1;
+                node_index - 1
+            }
+            _ => 0,
+        };
+        entries.extend(tamper(index, entry));
+    }
+
+    let file = fs::File::create(path).expect("snapshot, create fails");
+    let mut writer =
+        SnapshotWriter::new(BufWriter::new(file)).expect("snapshot, write fails");
+    for entry in entries.iter() {
+        writer.write(entry).expect("snapshot, write entry fails");
+    }
+    writer.finish().expect("snapshot, finish fails");
+}
+
+fn verify() {
+    println!("test verify");
+    let cmd = vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "verify",
+        "--to",
+        "10",
+    ];
+    assert!(run(cmd.clone()).is_err());
+
+    let maintenance_cli = Cli::new(
+        DefaultServiceMapping {},
+        CliConfig {
+            app_name: "Rodents",
+            version: "Big Cheek",
+            author: "Hamsters",
+            config_path: "./cofnig.toml",
+            genesis_patch: "./genesis.toml",
+        },
+        Some(cmd),
+    )
+    .generate_maintenance_cli();
+    let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+    let report = rt
+        .block_on(maintenance_cli.verify_chain(0, Some(10)))
+        .expect("verify, verify chain fails");
+
+    // the fake blocks are not linked by prev_hash
+    assert_eq!(report.to, 10);
+    assert_eq!(report.verified_height, Some(0));
+    let inconsistency = report.inconsistency.expect("verify, no inconsistency");
+    assert_eq!(inconsistency.height, 1);
+    assert_eq!(inconsistency.kind, InconsistencyKind::PrevHash);
+    println!("tested verify");
+}
+
+// The tampered blocks of `verify_linked` are all at height 3, or the proof of it
+// in the block 4.
+fn verify_linked() {
+    println!("test verify_linked");
+    let cmd = vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "verify",
+    ];
+    run(cmd.clone()).expect("verify_linked, run verify fails");
+
+    let maintenance_cli = Cli::new(
+        DefaultServiceMapping {},
+        CliConfig {
+            app_name: "Rodents",
+            version: "Big Cheek",
+            author: "Hamsters",
+            config_path: "./cofnig.toml",
+            genesis_patch: "./genesis.toml",
+        },
+        Some(cmd),
+    )
+    .generate_maintenance_cli();
+    let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+
+    rt.block_on(async {
+        let report = maintenance_cli
+            .verify_chain(0, None)
+            .await
+            .expect("verify_linked, verify chain fails");
+        assert_eq!(report.verified_height, Some(5));
+        assert!(report.inconsistency.is_none());
+
+        let storage = &maintenance_cli.storage;
+        let get_block = |height| async move {
+            storage
+                .get_block(Context::new(), height)
+                .await
+                .expect("verify_linked, get block fails")
+                .expect("verify_linked, block not found")
+        };
+        let (block_3, block_4) = (get_block(3).await, get_block(4).await);
+        let tampered_hash = Hash::digest(Bytes::from_static(b"tampered"));
+
+        storage
+            .remove_block(Context::new(), 3)
+            .await
+            .expect("verify_linked, remove block fails");
+        assert_inconsistent(&maintenance_cli, vec![], InconsistencyKind::Block).await;
+        storage
+            .set_block(Context::new(), block_3.clone())
+            .await
+            .expect("verify_linked, set block fails");
+
+        let mut block = block_3.clone();
+        block.header.prev_hash = Hash::from_empty();
+        assert_inconsistent(&maintenance_cli, vec![block], InconsistencyKind::PrevHash)
+            .await;
+
+        let mut block = block_3.clone();
+        block.header.order_root = tampered_hash.clone();
+        assert_inconsistent(&maintenance_cli, vec![block], InconsistencyKind::OrderRoot)
+            .await;
+
+        // the transaction is not stored
+        let stx = mock_signed_tx();
+        let mut block = block_3.clone();
+        block.ordered_tx_hashes = vec![stx.tx_hash.clone()];
+        block.header.order_root = Merkle::from_hashes(block.ordered_tx_hashes.clone())
+            .get_root_hash()
+            .unwrap();
+        assert_inconsistent(
+            &maintenance_cli,
+            vec![block.clone()],
+            InconsistencyKind::Transaction,
+        )
+        .await;
+
+        storage
+            .insert_transactions(Context::new(), 3, vec![stx.clone()])
+            .await
+            .expect("verify_linked, insert transactions fails");
+        block.header.order_signed_transactions_hash = tampered_hash.clone();
+        assert_inconsistent(
+            &maintenance_cli,
+            vec![block.clone()],
+            InconsistencyKind::SignedTransactionsHash,
+        )
+        .await;
+
+        // the block 3 records its own execution, the receipt of which is not stored
+        block.header.order_signed_transactions_hash =
+            digest_signed_transactions(&[stx]).unwrap();
+        block.header.exec_height = 3;
+        block.header.receipt_root = vec![crate::receipt_root(&[]).unwrap(); 2];
+        assert_inconsistent(&maintenance_cli, vec![block], InconsistencyKind::Receipt)
+            .await;
+
+        let mut block = block_3;
+        block.header.receipt_root = vec![tampered_hash];
+        assert_inconsistent(
+            &maintenance_cli,
+            vec![block],
+            InconsistencyKind::ReceiptRoot,
+        )
+        .await;
+
+        let mut block = block_4.clone();
+        block.header.proof.block_hash = Hash::from_empty();
+        assert_inconsistent(&maintenance_cli, vec![block], InconsistencyKind::Proof)
+            .await;
+
+        // the signature is of the precommit of round 0
+        let mut block = block_4;
+        block.header.proof.round = 1;
+        assert_inconsistent(
+            &maintenance_cli,
+            vec![block],
+            InconsistencyKind::ProofSignature,
+        )
+        .await;
+    });
+    println!("tested verify_linked");
+}
+
+// Verify the chain with the `tampered` blocks set, and restore the blocks after.
+async fn assert_inconsistent(
+    maintenance_cli: &MaintenanceCli<
+        '_,
+        DefaultServiceMapping,
+        ImplStorage<RocksAdapter>,
+    >,
+    tampered: Vec<Block>,
+    kind: InconsistencyKind,
+) {
+    let storage = &maintenance_cli.storage;
+    let mut origins = Vec::with_capacity(tampered.len());
+    for block in tampered {
+        let origin = storage
+            .get_block(Context::new(), block.header.height)
+            .await
+            .expect("verify_linked, get block fails")
+            .expect("verify_linked, block not found");
+        origins.push(origin);
+        storage
+            .set_block(Context::new(), block)
+            .await
+            .expect("verify_linked, set block fails");
+    }
+
+    let report = maintenance_cli
+        .verify_chain(0, None)
+        .await
+        .expect("verify_linked, verify chain fails");
+    assert_eq!(report.verified_height, Some(2));
+    let inconsistency = report
+        .inconsistency
+        .expect("verify_linked, no inconsistency");
+    assert_eq!(inconsistency.height, 3);
+    assert_eq!(inconsistency.kind, kind, "{}", inconsistency);
+
+    for block in origins {
+        storage
+            .set_block(Context::new(), block)
+            .await
+            .expect("verify_linked, set block fails");
+    }
+}
+
+fn replay() {
+    println!("test replay");
+    let cmd = vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "replay",
+        "--from",
+        "1",
+        "--to",
+        "3",
+    ];
+    run(cmd.clone()).expect("replay, run replay fails");
+
+    let maintenance_cli = Cli::new(
+        DefaultServiceMapping {},
+        CliConfig {
+            app_name: "Rodents",
+            version: "Big Cheek",
+            author: "Hamsters",
+            config_path: "./cofnig.toml",
+            genesis_patch: "./genesis.toml",
+        },
+        Some(cmd),
+    )
+    .generate_maintenance_cli();
+    let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+
+    // the execution of height 2 is recorded in block 3
+    let mut block = rt
+        .block_on(maintenance_cli.block_get(3))
+        .expect("replay, get block fails")
+        .expect("replay, block not found");
+    block.header.cycles_used = vec![u64::max_value()];
+    rt.block_on(maintenance_cli.storage.set_block(Context::new(), block))
+        .expect("replay, set block fails");
+
+    let report = rt
+        .block_on(maintenance_cli.replay_chain(1, 3, REPLAY_DIR))
+        .expect("replay, replay chain fails");
+    assert_eq!(report.replayed_height, Some(1));
+    let divergence = report.divergence.expect("replay, no divergence");
+    assert_eq!(divergence.height, 2);
+    assert_eq!(divergence.kind, DivergenceKind::CyclesUsed);
+    assert!(!PathBuf::from_str(REPLAY_DIR).unwrap().exists());
+    println!("tested replay");
+}
+
 fn block_get() -> Block {
     println!("test block_get");
     let cmd = vec![
@@ -332,7 +794,10 @@ fn prepare() {
                     cycles_used: vec![],
                     proposer: Default::default(),
                     proof: Proof {
-                        height: 0,
+                        height: match idx {
+                            i if i > 0 => i - 1,
+                            _ => 0,
+                        },
                         round: 0,
                         block_hash: Default::default(),
                         signature: Default::default(),
@@ -377,6 +842,273 @@ fn prepare() {
     };
 }
 
+// Prepare the blocks executed from the genesis state, the block of each height
+// records the execution of the previous height.
+fn prepare_executed() {
+    clean();
+
+    let cmd = vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "latest_block",
+        "get",
+    ];
+
+    let maintenance_cli = Cli::new(
+        DefaultServiceMapping {},
+        CliConfig {
+            app_name: "Rodents",
+            version: "Big Cheek",
+            author: "Hamsters",
+            config_path: "./cofnig.toml",
+            genesis_patch: "./genesis.toml",
+        },
+        Some(cmd),
+    )
+    .generate_maintenance_cli();
+    let trie_db = maintenance_cli.trie_db().unwrap();
+    let storage = maintenance_cli.storage;
+    let service_mapping = maintenance_cli.service_mapping;
+
+    let genesis: Genesis = toml::from_str(
+        &fs::read_to_string(GENESIS_PATH).expect("prepare_executed, read genesis fails"),
+    )
+    .expect("prepare_executed, decode genesis fails");
+    let mut state_root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&trie_db),
+        Arc::clone(&storage),
+        Arc::clone(&service_mapping),
+    )
+    .expect("prepare_executed, create genesis fails");
+
+    let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+    let (mut receipt_root, mut cycles_used) = (vec![], vec![]);
+    for height in 0..=4 {
+        let block = Block {
+            header: BlockHeader {
+                chain_id: Default::default(),
+                height,
+                exec_height: height.saturating_sub(1),
+                prev_hash: Default::default(),
+                timestamp: 0,
+                order_root: Default::default(),
+                order_signed_transactions_hash: Default::default(),
+                confirm_root: vec![],
+                state_root: state_root.clone(),
+                receipt_root: mem::take(&mut receipt_root),
+                cycles_used: mem::take(&mut cycles_used),
+                proposer: Default::default(),
+                proof: Proof {
+                    height: height.saturating_sub(1),
+                    round: 0,
+                    block_hash: Default::default(),
+                    signature: Default::default(),
+                    bitmap: Default::default(),
+                },
+                validator_version: 0,
+                validators: vec![],
+            },
+            ordered_tx_hashes: vec![],
+        };
+        rt.block_on(storage.insert_block(Context::new(), block.clone()))
+            .expect("prepare_executed, insert block fails");
+        if height == 0 {
+            continue;
+        }
+
+        let mut executor = ServiceExecutorFactory::from_root(
+            state_root.clone(),
+            Arc::clone(&trie_db),
+            Arc::clone(&storage),
+            Arc::clone(&service_mapping),
+        )
+        .expect("prepare_executed, create executor fails");
+        let params = ExecutorParams {
+            state_root: state_root.clone(),
+            height,
+            timestamp: block.header.timestamp,
+            cycles_limit: 4_294_967_295,
+            proposer: block.header.proposer,
+        };
+        let resp = executor
+            .exec(Context::new(), &params, &[])
+            .expect("prepare_executed, exec fails");
+        receipt_root = vec![crate::receipt_root(&resp.receipts).unwrap()];
+        cycles_used = vec![resp.all_cycles_used];
+        state_root = resp.state_root;
+    }
+}
+
+// Prepare the blocks linked by prev_hash on the genesis state, each of which is
+// committed by a proof signed by the only verifier of the genesis.
+fn prepare_verified() {
+    clean();
+
+    let cmd = vec![
+        "muta-chain",
+        "--config",
+        CONFIG_PATH,
+        "--genesis",
+        GENESIS_PATH,
+        "latest_block",
+        "get",
+    ];
+
+    let maintenance_cli = Cli::new(
+        DefaultServiceMapping {},
+        CliConfig {
+            app_name: "Rodents",
+            version: "Big Cheek",
+            author: "Hamsters",
+            config_path: "./cofnig.toml",
+            genesis_patch: "./genesis.toml",
+        },
+        Some(cmd),
+    )
+    .generate_maintenance_cli();
+    let trie_db = maintenance_cli.trie_db().unwrap();
+    let storage = Arc::clone(&maintenance_cli.storage);
+
+    let genesis: Genesis = toml::from_str(
+        &fs::read_to_string(GENESIS_PATH).expect("prepare_verified, read genesis fails"),
+    )
+    .expect("prepare_verified, decode genesis fails");
+    let state_root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&trie_db),
+        Arc::clone(&storage),
+        Arc::clone(&maintenance_cli.service_mapping),
+    )
+    .expect("prepare_verified, create genesis fails");
+
+    let params = ExecutorParams {
+        state_root: state_root.clone(),
+        height: 0,
+        timestamp: 0,
+        cycles_limit: u64::max_value(),
+        proposer: Default::default(),
+    };
+    let metadata = maintenance_cli
+        .get_metadata(trie_db, &params)
+        .expect("prepare_verified, get metadata fails");
+    let verifier = &metadata.verifier_list[0];
+    let crypto = maintenance_cli
+        .overlord_crypto(&metadata.common_ref)
+        .expect("prepare_verified, create crypto fails");
+    let bls_pub_key = convert_hex_to_bls_pubkeys(verifier.bls_pub_key.clone()).unwrap();
+    crypto.update(
+        vec![(verifier.pub_key.decode(), bls_pub_key)]
+            .into_iter()
+            .collect(),
+    );
+
+    let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+    let mut prev_hash = Hash::default();
+    let mut proof = Proof {
+        height: 0,
+        round: 0,
+        block_hash: Default::default(),
+        signature: Default::default(),
+        bitmap: Default::default(),
+    };
+    for height in 0..=5 {
+        let (receipt_root, cycles_used) = match height {
+            0 | 1 => (vec![], vec![]),
+            _ => (vec![crate::receipt_root(&[]).unwrap()], vec![0]),
+        };
+        let header = BlockHeader {
+            chain_id: Default::default(),
+            height,
+            exec_height: height.saturating_sub(1),
+            prev_hash,
+            timestamp: 0,
+            order_root: Hash::from_empty(),
+            order_signed_transactions_hash: Hash::from_empty(),
+            confirm_root: vec![],
+            state_root: state_root.clone(),
+            receipt_root,
+            cycles_used,
+            proposer: Default::default(),
+            proof,
+            validator_version: 0,
+            validators: vec![],
+        };
+        prev_hash = Hash::digest(header.encode_fixed().unwrap());
+        proof = sign_proof(
+            &crypto,
+            verifier.pub_key.decode(),
+            height,
+            prev_hash.clone(),
+        );
+
+        let block = Block {
+            header,
+            ordered_tx_hashes: vec![],
+        };
+        rt.block_on(storage.insert_block(Context::new(), block))
+            .expect("prepare_verified, insert block fails");
+    }
+    rt.block_on(storage.update_latest_proof(Context::new(), proof))
+        .expect("prepare_verified, update latest proof fails");
+}
+
+// Sign the precommit of the block by the only verifier, the first bit of the
+// bitmap.
+fn sign_proof(
+    crypto: &OverlordCrypto,
+    voter: Bytes,
+    height: u64,
+    block_hash: Hash,
+) -> Proof {
+    let vote = Vote {
+        height,
+        round: 0,
+        vote_type: VoteType::Precommit,
+        block_hash: block_hash.as_bytes(),
+    };
+    let vote_hash = crypto.hash(Bytes::from(rlp::encode(&vote)));
+    let signature = crypto.sign(vote_hash).expect("sign_proof, sign fails");
+    let signature = crypto
+        .aggregate_signatures(vec![signature], vec![voter])
+        .expect("sign_proof, aggregate fails");
+
+    Proof {
+        height,
+        round: 0,
+        block_hash,
+        signature,
+        bitmap: Bytes::from_static(&[0b1000_0000]),
+    }
+}
+
+fn mock_signed_tx() -> SignedTransaction {
+    let raw = RawTransaction {
+        chain_id: Default::default(),
+        cycles_price: 1,
+        cycles_limit: 21_000,
+        nonce: Hash::from_empty(),
+        request: TransactionRequest {
+            service_name: "asset".to_owned(),
+            method: "transfer".to_owned(),
+            payload: "".to_owned(),
+            encoding: PayloadEncoding::Json,
+        },
+        timeout: 0,
+        sender: Default::default(),
+    };
+
+    SignedTransaction {
+        tx_hash: Hash::digest(raw.encode_fixed().unwrap()),
+        raw,
+        pubkey: Bytes::new(),
+        signature: Bytes::new(),
+    }
+}
+
 fn clean() {
     let to = PathBuf::from_str(DATA_DIR).expect("clean, data dir fails");
     if to.exists() {
@@ -0,0 +1,70 @@
+use derive_more::Display;
+use serde_derive::Serialize;
+
+/// The report of walking the stored chain, printed as JSON so that it can be
+/// consumed by scripts.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub from: u64,
+    pub to: u64,
+    /// The highest height all the heights from `from` up to which are
+    /// consistent, `None` if the height `from` is not.
+    pub verified_height: Option<u64>,
+    /// The transactions and receipts below the height are pruned, so they are
+    /// not checked.
+    pub pruned_height: Option<u64>,
+    /// The states below the height are pruned, the proofs of the heights up to
+    /// it are not checked since their validators are read from the state of
+    /// the previous height.
+    pub state_height: u64,
+    pub inconsistency: Option<Inconsistency>,
+}
+
+#[derive(Debug, Clone, Display, Serialize)]
+#[display(fmt = "height {} is inconsistent on {}, {}", height, kind, detail)]
+pub struct Inconsistency {
+    pub height: u64,
+    pub kind: InconsistencyKind,
+    pub detail: String,
+}
+
+impl Inconsistency {
+    pub fn new(height: u64, kind: InconsistencyKind, detail: String) -> Self {
+        Inconsistency {
+            height,
+            kind,
+            detail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InconsistencyKind {
+    #[display(fmt = "block")]
+    Block,
+
+    #[display(fmt = "prev_hash")]
+    PrevHash,
+
+    #[display(fmt = "order_root")]
+    OrderRoot,
+
+    #[display(fmt = "transaction")]
+    Transaction,
+
+    #[display(fmt = "order_signed_transactions_hash")]
+    SignedTransactionsHash,
+
+    #[display(fmt = "receipt_root")]
+    ReceiptRoot,
+
+    #[display(fmt = "receipt")]
+    Receipt,
+
+    #[display(fmt = "proof")]
+    Proof,
+
+    #[display(fmt = "proof_signature")]
+    ProofSignature,
+}
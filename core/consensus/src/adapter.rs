@@ -13,6 +13,7 @@ use tokio::sync::mpsc::{channel, Receiver, Sender};
 
 use common_apm::muta_apm;
 use common_merkle::Merkle;
+use common_pubsub::ChainPubSub;
 
 use core_network::{PeerId, PeerIdExt};
 
@@ -22,8 +23,8 @@ use protocol::traits::{
     Priority, Rpc, ServiceMapping, Storage, SynchronizationAdapter, TrustFeedback,
 };
 use protocol::types::{
-    Address, Block, BlockHeader, Bytes, Hash, Hex, MerkleRoot, Metadata, Proof, Receipt,
-    SignedTransaction, TransactionRequest, Validator,
+    Address, Block, BlockHeader, Bytes, Hash, Hex, MerkleRoot, Metadata,
+    PayloadEncoding, Proof, Receipt, SignedTransaction, TransactionRequest, Validator,
 };
 use protocol::{fixed_codec::FixedCodec, ProtocolResult};
 
@@ -60,6 +61,7 @@ pub struct OverlordConsensusAdapter<
     exec_queue: Sender<ExecuteInfo>,
     exec_demons: Option<ExecDemons<S, DB, EF, Mapping>>,
     crypto: Arc<OverlordCrypto>,
+    pubsub: ChainPubSub,
 }
 
 #[async_trait]
@@ -248,10 +250,15 @@ where
         brake_ratio: u64,
         validators: Vec<Validator>,
     ) -> ProtocolResult<()> {
-        self.overlord_handler
-            .read()
-            .as_ref()
-            .expect("Please set the overlord handle first")
+        // The overlord engine does not run on a follower node, so no handler is
+        // set.
+        let overlord_handler = self.overlord_handler.read();
+        let overlord_handler = match overlord_handler.as_ref() {
+            Some(overlord_handler) => overlord_handler,
+            None => return Ok(()),
+        };
+
+        overlord_handler
             .send_msg(
                 ctx,
                 OverlordMsg::RichStatus(gen_overlord_status(
@@ -387,7 +394,9 @@ where
         logs = "{'txs_len': 'block.ordered_tx_hashes.len()'}"
     )]
     async fn save_block(&self, ctx: Context, block: Block) -> ProtocolResult<()> {
-        self.storage.insert_block(ctx, block).await
+        self.storage.insert_block(ctx, block.clone()).await?;
+        self.pubsub.blocks.publish(block);
+        Ok(())
     }
 
     #[muta_apm::derive::tracing_span(kind = "consensus.adapter")]
@@ -421,7 +430,11 @@ where
         height: u64,
         receipts: Vec<Receipt>,
     ) -> ProtocolResult<()> {
-        self.storage.insert_receipts(ctx, height, receipts).await
+        self.storage
+            .insert_receipts(ctx, height, receipts.clone())
+            .await?;
+        self.pubsub.receipts.publish(Arc::new(receipts));
+        Ok(())
     }
 
     /// Flush the given transactions in the mempool.
@@ -529,6 +542,7 @@ where
                 service_name: "metadata".to_string(),
                 method: "get_metadata".to_string(),
                 payload: "".to_string(),
+                encoding: PayloadEncoding::Json,
             },
         )?;
 
@@ -914,6 +928,7 @@ where
         status_agent: StatusAgent,
         crypto: Arc<OverlordCrypto>,
         gap: usize,
+        pubsub: ChainPubSub,
     ) -> ProtocolResult<Self> {
         let (exec_queue, rx) = channel(gap);
         let exec_demons = Some(ExecDemons::new(
@@ -922,6 +937,7 @@ where
             Arc::clone(&service_mapping),
             rx,
             status_agent,
+            pubsub.clone(),
         ));
 
         let adapter = OverlordConsensusAdapter {
@@ -934,6 +950,7 @@ where
             exec_queue,
             exec_demons,
             crypto,
+            pubsub,
         };
 
         Ok(adapter)
@@ -958,6 +975,7 @@ pub struct ExecDemons<S, DB, EF, Mapping> {
     pin_ef: PhantomData<EF>,
     queue: Receiver<ExecuteInfo>,
     status: StatusAgent,
+    pubsub: ChainPubSub,
 }
 
 impl<S, DB, EF, Mapping> ExecDemons<S, DB, EF, Mapping>
@@ -973,6 +991,7 @@ where
         service_mapping: Arc<Mapping>,
         rx: Receiver<ExecuteInfo>,
         status_agent: StatusAgent,
+        pubsub: ChainPubSub,
     ) -> Self {
         ExecDemons {
             storage,
@@ -981,6 +1000,7 @@ where
             queue: rx,
             pin_ef: PhantomData,
             status: status_agent,
+            pubsub,
         }
     }
 
@@ -1066,7 +1086,11 @@ where
         height: u64,
         receipts: Vec<Receipt>,
     ) -> ProtocolResult<()> {
-        self.storage.insert_receipts(ctx, height, receipts).await
+        self.storage
+            .insert_receipts(ctx, height, receipts.clone())
+            .await?;
+        self.pubsub.receipts.publish(Arc::new(receipts));
+        Ok(())
     }
 }
 
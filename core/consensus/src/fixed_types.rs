@@ -181,8 +181,8 @@ mod test {
     use rand::random;
 
     use protocol::types::{
-        Address, Block, BlockHeader, Hash, Proof, RawTransaction, SignedTransaction,
-        TransactionRequest,
+        Address, Block, BlockHeader, Hash, PayloadEncoding, Proof, RawTransaction,
+        SignedTransaction, TransactionRequest,
     };
     use protocol::Bytes;
 
@@ -241,6 +241,7 @@ mod test {
             service_name: "test".to_owned(),
             method: "test".to_owned(),
             payload: "test".to_owned(),
+            encoding: PayloadEncoding::Json,
         };
         let mut raw = RawTransaction {
             chain_id: nonce.clone(),
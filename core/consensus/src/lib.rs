@@ -34,6 +34,7 @@ pub use overlord::{types::Node, DurationConfig};
 
 pub const DEFAULT_OVERLORD_GAP: usize = 5;
 pub const DEFAULT_SYNC_TXS_CHUNK_SIZE: usize = 5000;
+pub const DEFAULT_CONSENSUS_WAL_RETAINED_FILES: usize = 3;
 
 #[derive(Clone, Debug, Display, PartialEq, Eq)]
 pub enum ConsensusType {
@@ -164,6 +165,9 @@ pub enum ConsensusError {
 
     #[display(fmt = "no consensus wal file available")]
     ConsensusWalNoWalFile,
+
+    #[display(fmt = "corrupted wal file {:?}", _0)]
+    CorruptedWal(std::path::PathBuf),
 }
 
 #[derive(Debug, Display)]
@@ -58,6 +58,10 @@ impl StatusAgent {
         status.current_proof = new_status.current_proof;
         status.validators = new_status.validators;
         status.consensus_interval = new_status.consensus_interval;
+        status.propose_ratio = new_status.propose_ratio;
+        status.prevote_ratio = new_status.prevote_ratio;
+        status.precommit_ratio = new_status.precommit_ratio;
+        status.brake_ratio = new_status.brake_ratio;
     }
 
     pub fn to_inner(&self) -> CurrentConsensusStatus {
@@ -1,27 +1,50 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::future::{self, Either};
 use futures::lock::Mutex;
+use futures::stream::{self, StreamExt};
+use futures::{pin_mut, SinkExt};
 use futures_timer::Delay;
+use overlord::types::{Node, Vote, VoteType};
+use overlord::{extract_voters, Crypto};
+use parking_lot::RwLock;
 
 use common_apm::muta_apm;
+use core_network::{NetworkContext, PeerId};
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
     Context, ExecutorParams, ExecutorResp, Synchronization, SynchronizationAdapter,
+    TrustFeedback,
+};
+use protocol::types::{
+    Block, BlockHeader, Bytes, Hash, Metadata, Proof, Receipt, SignedTransaction,
 };
-use protocol::types::{Block, Hash, Proof, Receipt, SignedTransaction};
 use protocol::ProtocolResult;
 
 use crate::engine::generate_new_crypto_map;
 use crate::status::{ExecutedInfo, StatusAgent};
 use crate::util::{digest_signed_transactions, OverlordCrypto};
+use crate::BlockProofField::{BitMap, HashMismatch, HeightMismatch};
 use crate::ConsensusError;
 
 const POLLING_BROADCAST: u64 = 2000;
 const WAIT_EXECUTION: u64 = 1000;
-const ONCE_SYNC_BLOCK_LIMIT: u64 = 50;
+const ONCE_SYNC_BLOCK_LIMIT: u64 = 200;
+/// How many heights are downloaded and verified concurrently ahead of the
+/// height being committed.
+const SYNC_WINDOW: usize = 16;
+/// A peer failing to serve so many times is not requested in the rest of the
+/// sync.
+const MAX_PEER_FAILURES: usize = 3;
+/// A peer which hasn't broadcast its height for so long is not synced from.
+const PEER_EXPIRATION: Duration = Duration::from_secs(30);
 
 #[derive(Clone, Debug)]
 pub struct RichBlock {
@@ -29,12 +52,31 @@ pub struct RichBlock {
     pub txs: Vec<SignedTransaction>,
 }
 
+/// A block downloaded with the proof of it, both of them are verified without
+/// the local chain as far as possible before the block is committed.
+struct SyncedBlock {
+    rich_block: RichBlock,
+    proof: Proof,
+    /// The index of the peer serving the block.
+    peer: usize,
+    /// Whether the proof is verified with the validators before syncing.
+    proof_verified: bool,
+}
+
+#[derive(Clone)]
+struct SyncPeer {
+    ctx: Context,
+    height: u64,
+    updated_at: Instant,
+}
+
 pub struct OverlordSynchronization<Adapter: SynchronizationAdapter> {
     adapter: Arc<Adapter>,
     status: StatusAgent,
     crypto: Arc<OverlordCrypto>,
     lock: Arc<Mutex<()>>,
     syncing: Mutex<()>,
+    peers: RwLock<HashMap<PeerId, SyncPeer>>,
 
     sync_txs_chunk_size: usize,
 }
@@ -52,6 +94,8 @@ impl<Adapter: SynchronizationAdapter> Synchronization
         ctx: Context,
         remote_height: u64,
     ) -> ProtocolResult<()> {
+        self.record_peer(&ctx, remote_height);
+
         let syncing_lock = self.syncing.try_lock();
         if syncing_lock.is_none() {
             return Ok(());
@@ -126,6 +170,7 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
             crypto,
             lock,
             syncing,
+            peers: RwLock::new(HashMap::new()),
 
             sync_txs_chunk_size,
         }
@@ -160,138 +205,145 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
             current_height + ONCE_SYNC_BLOCK_LIMIT
         };
 
-        let mut current_consented_height = current_height;
-
-        while current_consented_height < remote_height {
-            let inst = Instant::now();
+        let peers = self.sync_peers(&ctx, remote_height);
+        let failures = peers
+            .iter()
+            .map(|_| AtomicUsize::new(0))
+            .collect::<Vec<_>>();
 
-            let consenting_height = current_consented_height + 1;
-            log::info!(
-                "[synchronization]: try syncing block, current_consented_height:{},syncing_height:{}",
-                current_consented_height,
-                consenting_height
-            );
+        // The proofs are verified out of order with the validators before
+        // syncing, and verified again in order if they fail or the validators
+        // are updated by the synced blocks.
+        let current_block_header = self
+            .adapter
+            .get_block_header_by_height(ctx.clone(), current_height)
+            .await?;
+        let metadata = self.adapter.get_metadata(
+            ctx.clone(),
+            current_block_header.state_root.clone(),
+            current_block_header.height,
+            current_block_header.timestamp,
+            current_block_header.proposer,
+        )?;
 
-            let consenting_rich_block: RichBlock = self
-                .get_rich_block_from_remote(ctx.clone(), consenting_height)
-                .await
-                .map_err(|e| {
-                    log::error!(
-                        "[synchronization]: get_rich_block_from_remote error, height: {:?}",
-                        consenting_height
-                    );
-                    e
-                })?;
+        // The downloading goes on while the downloaded blocks are committed.
+        let (block_tx, block_rx) = mpsc::channel(0);
+        let download = self.download_blocks(
+            ctx.clone(),
+            &peers,
+            &failures,
+            &metadata,
+            current_height + 1..=remote_height,
+            block_tx,
+        );
+        let commit = self.commit_synced_blocks(
+            ctx,
+            sync_status_agent,
+            &peers,
+            &failures,
+            &metadata,
+            block_rx,
+        );
+        pin_mut!(download, commit);
 
-            let consenting_proof: Proof = self
-                .adapter
-                .get_proof_from_remote(ctx.clone(), consenting_height)
-                .await
-                .map_err(|e| {
-                    log::error!(
-                        "[synchronization]: get_proof_from_remote error, height: {:?}",
-                        consenting_height
-                    );
-                    e
-                })?;
+        match future::select(download, commit).await {
+            // All the blocks are downloaded, wait for the rest to be committed.
+            Either::Left(((), commit)) => commit.await,
+            Either::Right((res, _)) => res,
+        }
+    }
 
-            self.adapter
-                .verify_block_header(ctx.clone(), &consenting_rich_block.block)
-                .await
-                .map_err(|e| {
-                    log::error!(
-                        "[synchronization]: verify_block_header error, block header: {:?}",
-                        consenting_rich_block.block.header
-                    );
-                    e
-                })?;
+    /// Downloads the blocks of the heights, at most `SYNC_WINDOW` of them
+    /// concurrently, and sends them in order.
+    async fn download_blocks(
+        &self,
+        ctx: Context,
+        peers: &[SyncPeer],
+        failures: &[AtomicUsize],
+        metadata: &Metadata,
+        heights: RangeInclusive<u64>,
+        mut block_tx: mpsc::Sender<ProtocolResult<SyncedBlock>>,
+    ) {
+        let mut synced_blocks = stream::iter(heights)
+            .map(|height| {
+                self.download_block(ctx.clone(), peers, failures, metadata, height)
+            })
+            .buffered(SYNC_WINDOW);
+
+        while let Some(synced_block) = synced_blocks.next().await {
+            // The receiver is dropped once the committing stops.
+            if block_tx.send(synced_block).await.is_err() {
+                return;
+            }
+        }
+    }
 
-            // verify syncing proof
-            self.adapter
-                .verify_proof(
-                    ctx.clone(),
-                    &consenting_rich_block.block.header,
-                    &consenting_proof,
-                )
-                .await
-                .map_err(|e| {
-                    log::error!(
-                        "[synchronization]: verify_proof error, syncing block header: {:?}, proof: {:?}",
-                        consenting_rich_block.block.header,
-                        consenting_proof,
-                    );
-                    e
-                })?;
+    async fn commit_synced_blocks(
+        &self,
+        ctx: Context,
+        sync_status_agent: StatusAgent,
+        peers: &[SyncPeer],
+        failures: &[AtomicUsize],
+        metadata: &Metadata,
+        mut block_rx: mpsc::Receiver<ProtocolResult<SyncedBlock>>,
+    ) -> ProtocolResult<()> {
+        let mut previous_proof = None;
+        while let Some(synced_block) = block_rx.next().await {
+            let mut synced_block = synced_block?;
+            let height = synced_block.rich_block.block.header.height;
+            log::info!(
+                "[synchronization]: try syncing block, syncing_height:{}",
+                height
+            );
 
-            // verify previous proof
-            let previous_block_header = self
-                .adapter
-                .get_block_header_by_height(
+            loop {
+                self.verify_synced_block(
                     ctx.clone(),
-                    consenting_rich_block.block.header.height - 1,
+                    &synced_block.rich_block.block,
+                    previous_proof.as_ref(),
                 )
-                .await
-                .map_err(|e| {
-                    log::error!(
-                        "[synchronization] get previous block {} error",
-                        consenting_rich_block.block.header.height - 1
-                    );
-                    e
-                })?;
+                .await?;
 
-            self.adapter
-                .verify_proof(
-                    ctx.clone(),
-                    &previous_block_header,
-                    &consenting_rich_block.block.header.proof,
-                )
-                .await
-                .map_err(|e| {
-                    log::error!(
-                        "[synchronization]: verify_proof error, previous block header: {:?}, proof: {:?}",
-                        previous_block_header,
-                        consenting_rich_block.block.header.proof
-                    );
-                    e
-                })?;
-
-            let order_signed_transactions_hash =
-                digest_signed_transactions(&consenting_rich_block.txs)?;
-            if order_signed_transactions_hash
-                != consenting_rich_block
-                    .block
-                    .header
-                    .order_signed_transactions_hash
-            {
-                return Err(ConsensusError::InvalidOrderSignedTransactionsHash {
-                    expect: order_signed_transactions_hash,
-                    actual: consenting_rich_block
-                        .block
-                        .header
-                        .order_signed_transactions_hash
-                        .clone(),
-                }
-                .into());
+                let e = match self
+                    .verify_synced_proof(ctx.clone(), &synced_block, metadata)
+                    .await
+                {
+                    Ok(()) => break,
+                    Err(e) => e,
+                };
+
+                // The proof failing in order is invalid for sure, so the block is
+                // downloaded again from the other peers.
+                let peer = synced_block.peer;
+                failures[peer].fetch_add(MAX_PEER_FAILURES, Ordering::SeqCst);
+                self.adapter.report_bad(
+                    peers[peer].ctx.clone(),
+                    TrustFeedback::Worse(e.to_string()),
+                );
+                synced_block = self
+                    .download_block(ctx.clone(), peers, failures, metadata, height)
+                    .await?;
             }
 
+            let SyncedBlock {
+                rich_block, proof, ..
+            } = synced_block;
+
             let inst = Instant::now();
             self.commit_block(
                 ctx.clone(),
-                consenting_rich_block.clone(),
-                consenting_proof,
+                rich_block,
+                proof.clone(),
                 sync_status_agent.clone(),
             )
             .await
             .map_err(|e| {
-                log::error!(
-                    "[synchronization]: commit block {} error",
-                    consenting_rich_block.block.header.height
-                );
+                log::error!("[synchronization]: commit block {} error", height);
                 e
             })?;
 
             self.update_status(ctx.clone(), sync_status_agent.clone())?;
-            current_consented_height += 1;
+            previous_proof = Some(proof);
 
             common_apm::metrics::consensus::ENGINE_SYNC_BLOCK_COUNTER.inc_by(1 as i64);
             common_apm::metrics::consensus::ENGINE_SYNC_BLOCK_HISTOGRAM
@@ -300,6 +352,308 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
         Ok(())
     }
 
+    /// Downloads the block and the proof of the height from the peers having
+    /// it in turn, the peers serving invalid data are reported.
+    async fn download_block(
+        &self,
+        ctx: Context,
+        peers: &[SyncPeer],
+        failures: &[AtomicUsize],
+        metadata: &Metadata,
+        height: u64,
+    ) -> ProtocolResult<SyncedBlock> {
+        let mut last_err = None;
+
+        for attempt in 0..peers.len() {
+            let index = (height as usize + attempt) % peers.len();
+            let peer = &peers[index];
+            if peer.height < height
+                || failures[index].load(Ordering::SeqCst) >= MAX_PEER_FAILURES
+            {
+                continue;
+            }
+
+            let (rich_block, proof) = match self
+                .fetch_block(peer.ctx.clone(), height)
+                .await
+            {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    log::warn!("[synchronization]: fetch block {} error {}", height, e);
+                    failures[index].fetch_add(1, Ordering::SeqCst);
+                    self.adapter
+                        .report_bad(peer.ctx.clone(), TrustFeedback::Bad(e.to_string()));
+                    last_err = Some(e);
+                    continue;
+                }
+            };
+
+            if let Err(e) = self.verify_downloaded_block(&rich_block, &proof) {
+                log::error!(
+                    "[synchronization]: verify downloaded block {} error {}",
+                    height,
+                    e
+                );
+                failures[index].fetch_add(MAX_PEER_FAILURES, Ordering::SeqCst);
+                self.adapter
+                    .report_bad(peer.ctx.clone(), TrustFeedback::Worse(e.to_string()));
+                last_err = Some(e);
+                continue;
+            }
+
+            // The validators may be updated by the blocks being synced, so the
+            // proof failing with the validators before syncing is not blamed on
+            // the peer, but verified again in order.
+            let proof_verified = match self.verify_proof_with_metadata(
+                ctx.clone(),
+                &rich_block.block.header,
+                &proof,
+                metadata,
+            ) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!(
+                        "[synchronization]: proof of block {} is left to be verified in order, {}",
+                        height,
+                        e
+                    );
+                    false
+                }
+            };
+
+            return Ok(SyncedBlock {
+                rich_block,
+                proof,
+                peer: index,
+                proof_verified,
+            });
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            ConsensusError::Other(format!("no peer to sync block {} from", height))
+                .into()
+        }))
+    }
+
+    async fn fetch_block(
+        &self,
+        ctx: Context,
+        height: u64,
+    ) -> ProtocolResult<(RichBlock, Proof)> {
+        let rich_block = self
+            .get_rich_block_from_remote(ctx.clone(), height)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "[synchronization]: get_rich_block_from_remote error, height: {:?}",
+                    height
+                );
+                e
+            })?;
+
+        let proof = self
+            .adapter
+            .get_proof_from_remote(ctx.clone(), height)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "[synchronization]: get_proof_from_remote error, height: {:?}",
+                    height
+                );
+                e
+            })?;
+
+        if rich_block.block.header.height != height {
+            return Err(ConsensusError::Other(format!(
+                "remote block of height {} is returned for height {}",
+                rich_block.block.header.height, height
+            ))
+            .into());
+        }
+
+        Ok((rich_block, proof))
+    }
+
+    /// Verifies everything of the downloaded block not depending on the local
+    /// chain or the validators, so that it can be done out of order.
+    fn verify_downloaded_block(
+        &self,
+        rich_block: &RichBlock,
+        proof: &Proof,
+    ) -> ProtocolResult<()> {
+        let block_header = &rich_block.block.header;
+
+        let order_signed_transactions_hash =
+            digest_signed_transactions(&rich_block.txs)?;
+        if order_signed_transactions_hash != block_header.order_signed_transactions_hash
+        {
+            return Err(ConsensusError::InvalidOrderSignedTransactionsHash {
+                expect: order_signed_transactions_hash,
+                actual: block_header.order_signed_transactions_hash.clone(),
+            }
+            .into());
+        }
+
+        if block_header.height != proof.height {
+            return Err(ConsensusError::VerifyProof(
+                block_header.height,
+                HeightMismatch(block_header.height, proof.height),
+            )
+            .into());
+        }
+
+        if Hash::digest(block_header.encode_fixed()?) != proof.block_hash {
+            return Err(
+                ConsensusError::VerifyProof(block_header.height, HashMismatch).into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the block against the committed previous block, the proof of
+    /// the previous block is skipped if it is the one downloaded and verified
+    /// with the previous block.
+    async fn verify_synced_block(
+        &self,
+        ctx: Context,
+        block: &Block,
+        previous_proof: Option<&Proof>,
+    ) -> ProtocolResult<()> {
+        self.adapter
+            .verify_block_header(ctx.clone(), block)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "[synchronization]: verify_block_header error, block header: {:?}",
+                    block.header
+                );
+                e
+            })?;
+
+        if previous_proof == Some(&block.header.proof) {
+            return Ok(());
+        }
+
+        let previous_block_header = self
+            .adapter
+            .get_block_header_by_height(ctx.clone(), block.header.height - 1)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "[synchronization] get previous block {} error",
+                    block.header.height - 1
+                );
+                e
+            })?;
+
+        self.adapter
+            .verify_proof(ctx, &previous_block_header, &block.header.proof)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "[synchronization]: verify_proof error, previous block header: {:?}, proof: {:?}",
+                    previous_block_header,
+                    block.header.proof
+                );
+                e
+            })
+    }
+
+    /// Verifies the proof of the synced block in order if it failed with the
+    /// validators before syncing, or the validators are updated since then.
+    async fn verify_synced_proof(
+        &self,
+        ctx: Context,
+        synced_block: &SyncedBlock,
+        metadata: &Metadata,
+    ) -> ProtocolResult<()> {
+        let block_header = &synced_block.rich_block.block.header;
+
+        if synced_block.proof_verified {
+            let previous_block_header = self
+                .adapter
+                .get_block_header_by_height(ctx.clone(), block_header.height - 1)
+                .await?;
+            let previous_metadata = self.adapter.get_metadata(
+                ctx.clone(),
+                previous_block_header.state_root.clone(),
+                previous_block_header.height,
+                previous_block_header.timestamp,
+                previous_block_header.proposer,
+            )?;
+            if previous_metadata.verifier_list == metadata.verifier_list {
+                return Ok(());
+            }
+        }
+
+        self.adapter
+            .verify_proof(ctx, block_header, &synced_block.proof)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "[synchronization]: verify_proof error, syncing block header: {:?}, proof: {:?}",
+                    block_header,
+                    synced_block.proof,
+                );
+                e
+            })
+    }
+
+    fn verify_proof_with_metadata(
+        &self,
+        ctx: Context,
+        block_header: &BlockHeader,
+        proof: &Proof,
+        metadata: &Metadata,
+    ) -> ProtocolResult<()> {
+        let mut authority_list = metadata
+            .verifier_list
+            .iter()
+            .map(|v| Node {
+                address: v.pub_key.decode(),
+                propose_weight: v.propose_weight,
+                vote_weight: v.vote_weight,
+            })
+            .collect::<Vec<Node>>();
+
+        let signed_voters = extract_voters(&mut authority_list, &proof.bitmap)
+            .map_err(|_| ConsensusError::VerifyProof(block_header.height, BitMap))?;
+
+        let weight_map = authority_list
+            .iter()
+            .map(|node| (node.address.clone(), node.vote_weight))
+            .collect::<HashMap<_, _>>();
+        self.adapter.verify_proof_weight(
+            ctx.clone(),
+            block_header.height,
+            weight_map,
+            signed_voters.clone(),
+        )?;
+
+        let vote = Vote {
+            height: proof.height,
+            round: proof.round,
+            vote_type: VoteType::Precommit,
+            block_hash: proof.block_hash.as_bytes(),
+        };
+        let vote_hash = self.crypto.hash(Bytes::from(rlp::encode(&vote)));
+        let hex_pubkeys = metadata
+            .verifier_list
+            .iter()
+            .filter(|v| signed_voters.contains(&v.pub_key.decode()))
+            .map(|v| v.bls_pub_key.clone())
+            .collect::<Vec<_>>();
+
+        self.adapter.verify_proof_signature(
+            ctx,
+            block_header.height,
+            vote_hash,
+            proof.signature.clone(),
+            hex_pubkeys,
+        )
+    }
+
     #[muta_apm::derive::tracing_span(kind = "consensus.sync")]
     async fn commit_block(
         &self,
@@ -513,6 +867,40 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
         Ok(true)
     }
 
+    fn record_peer(&self, ctx: &Context, height: u64) {
+        if let Ok(peer_id) = ctx.remote_peer_id() {
+            self.peers.write().insert(
+                peer_id,
+                SyncPeer {
+                    ctx: ctx.clone(),
+                    height,
+                    updated_at: Instant::now(),
+                },
+            );
+        }
+    }
+
+    /// Returns the peer broadcasting the remote height and the other peers
+    /// which have broadcast their heights recently.
+    fn sync_peers(&self, ctx: &Context, remote_height: u64) -> Vec<SyncPeer> {
+        let remote_peer_id = ctx.remote_peer_id().ok();
+        let mut peers = vec![SyncPeer {
+            ctx: ctx.clone(),
+            height: remote_height,
+            updated_at: Instant::now(),
+        }];
+
+        let mut recorded_peers = self.peers.write();
+        recorded_peers.retain(|_, peer| peer.updated_at.elapsed() < PEER_EXPIRATION);
+        peers.extend(
+            recorded_peers
+                .iter()
+                .filter(|(peer_id, _)| Some(*peer_id) != remote_peer_id.as_ref())
+                .map(|(_, peer)| peer.clone()),
+        );
+        peers
+    }
+
     fn update_status(
         &self,
         ctx: Context,
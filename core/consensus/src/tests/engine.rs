@@ -24,6 +24,7 @@ use crate::fixed_types::FixedPill;
 use crate::status::StatusAgent;
 use crate::util::OverlordCrypto;
 use crate::wal::{ConsensusWal, SignedTxsWAL};
+use crate::DEFAULT_CONSENSUS_WAL_RETAINED_FILES;
 
 use super::*;
 
@@ -82,7 +83,10 @@ fn init_engine(
         Arc::new(MockConsensusAdapter {}),
         Arc::new(init_crypto()),
         Arc::new(Mutex::new(())),
-        Arc::new(ConsensusWal::new(FULL_CONSENSUS_PATH)),
+        Arc::new(ConsensusWal::new(
+            FULL_CONSENSUS_PATH,
+            DEFAULT_CONSENSUS_WAL_RETAINED_FILES,
+        )),
     )
 }
 
@@ -18,6 +18,7 @@ use common_crypto::{
     Secp256k1PrivateKey, Secp256k1PublicKey, Signature, ToPublicKey,
 };
 use common_merkle::Merkle;
+use core_network::{NetworkContext, PeerId};
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
@@ -27,9 +28,9 @@ use protocol::traits::{
     Context, ExecutorParams, ExecutorResp, ServiceResponse, TrustFeedback,
 };
 use protocol::types::{
-    Address, Block, BlockHeader, Bytes, Hash, Hex, MerkleRoot, Metadata, Proof,
-    RawTransaction, Receipt, ReceiptResponse, SignedTransaction, TransactionRequest,
-    Validator, ValidatorExtend,
+    Address, Block, BlockHeader, Bytes, Hash, Hex, MerkleRoot, Metadata,
+    PayloadEncoding, Proof, RawTransaction, Receipt, ReceiptResponse, SignedTransaction,
+    TransactionRequest, Validator, ValidatorExtend,
 };
 use protocol::ProtocolResult;
 
@@ -133,6 +134,163 @@ fn sync_gap_test() {
     }
 }
 
+// The proofs failing with the validators before syncing are verified in order.
+#[test]
+fn sync_validator_update_test() {
+    let key_tool = get_mock_key_tool();
+    let updated_key_tool = KeyTool::new(
+        key_tool.signer_node.clone(),
+        Arc::clone(&key_tool.overlord_crypto),
+        mock_updated_verifier_list(),
+    );
+
+    let max_height = 20;
+    let update_height = 5;
+
+    let list_rich_block = mock_validator_updated_rich_block(
+        max_height,
+        1,
+        &key_tool,
+        Some((update_height, &updated_key_tool)),
+    );
+    let genesis_block = list_rich_block.0[0].block.clone();
+    let adapter = Arc::new(mock_sync_adapter(list_rich_block, &key_tool));
+    *adapter.updated_verifier_list.write() =
+        Some((update_height, mock_updated_verifier_list()));
+    let (_, common_ref) = get_mock_public_keys_and_common_ref();
+    let crypto = Arc::new(mock_crypto_with_common_ref(common_ref));
+    let (sync, status_agent, _) =
+        mock_sync(Arc::clone(&adapter), Arc::clone(&crypto), genesis_block);
+
+    // The votes are verified with the keys of the committed validators.
+    let hash = key_tool.overlord_crypto.hash(Bytes::from("vote"));
+    let signature = key_tool.overlord_crypto.sign(hash.clone()).unwrap();
+    let voter = key_tool.signer_node.secp_public_key.to_bytes();
+    assert!(crypto
+        .verify_signature(signature.clone(), hash.clone(), voter.clone())
+        .is_err());
+
+    block_on(sync.receive_remote_block(Context::new(), max_height)).unwrap();
+
+    let status = status_agent.to_inner();
+    assert_eq!(status.latest_committed_height, max_height);
+    assert!(adapter.worse_peers.read().is_empty());
+
+    let updated_pub_keys = mock_updated_verifier_list()
+        .iter()
+        .map(|v| v.pub_key.decode())
+        .collect::<Vec<_>>();
+    let pub_keys = status
+        .validators
+        .iter()
+        .map(|v| v.pub_key.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(pub_keys.len(), updated_pub_keys.len());
+    assert!(pub_keys.iter().all(|key| updated_pub_keys.contains(key)));
+    assert!(crypto.verify_signature(signature, hash, voter).is_ok());
+}
+
+// The blocks with invalid proofs are downloaded again from the other peers.
+#[test]
+fn sync_bad_peer_test() {
+    let key_tool = get_mock_key_tool();
+    let max_height = 20;
+
+    let list_rich_block = mock_chained_rich_block(max_height, 1, &key_tool);
+    let genesis_block = list_rich_block.0[0].block.clone();
+    let adapter = Arc::new(mock_sync_adapter(list_rich_block, &key_tool));
+    let (sync, status_agent, lock) =
+        mock_sync(Arc::clone(&adapter), Arc::new(mock_crypto()), genesis_block);
+
+    let bad_peer = PeerId::random();
+    let good_peer = PeerId::random();
+    adapter.bad_peers.write().insert(bad_peer.clone());
+
+    // The good peer is recorded without syncing while the commit is locked.
+    let commit_lock = lock.try_lock().unwrap();
+    let good_ctx = Context::new().set_remote_peer_id(good_peer.clone());
+    block_on(sync.receive_remote_block(good_ctx, max_height)).unwrap();
+    drop(commit_lock);
+    assert_eq!(status_agent.to_inner().latest_committed_height, 0);
+
+    let bad_ctx = Context::new().set_remote_peer_id(bad_peer.clone());
+    block_on(sync.receive_remote_block(bad_ctx, max_height)).unwrap();
+
+    let status = status_agent.to_inner();
+    assert_eq!(status.latest_committed_height, max_height);
+    let worse_peers = adapter.worse_peers.read();
+    assert!(worse_peers.contains(&Some(bad_peer)));
+    assert!(!worse_peers.contains(&Some(good_peer)));
+}
+
+fn mock_sync_adapter(
+    list_rich_block: (Vec<RichBlock>, Vec<Proof>),
+    key_tool: &KeyTool,
+) -> MockCommonConsensusAdapter {
+    let remote_blocks = gen_remote_block_hashmap(list_rich_block.0.clone());
+    let remote_proofs = gen_remote_proof_hashmap(list_rich_block.1);
+    let genesis_block = remote_blocks.read().get(&0).unwrap().clone();
+
+    let local_blocks = Arc::new(RwLock::new(HashMap::new()));
+    local_blocks
+        .write()
+        .insert(genesis_block.header.height, genesis_block);
+
+    MockCommonConsensusAdapter::new(
+        0,
+        local_blocks,
+        remote_blocks,
+        remote_proofs,
+        Arc::new(RwLock::new(HashMap::new())),
+        gen_remote_tx_hashmap(list_rich_block.0),
+        Arc::clone(&key_tool.overlord_crypto),
+    )
+}
+
+fn mock_sync(
+    adapter: Arc<MockCommonConsensusAdapter>,
+    crypto: Arc<OverlordCrypto>,
+    genesis_block: Block,
+) -> (
+    OverlordSynchronization<MockCommonConsensusAdapter>,
+    StatusAgent,
+    Arc<Mutex<()>>,
+) {
+    let block_hash = Hash::digest(genesis_block.header.encode_fixed().unwrap());
+    let status = CurrentConsensusStatus {
+        cycles_price: 1,
+        cycles_limit: 300_000_000,
+        latest_committed_height: genesis_block.header.height,
+        exec_height: genesis_block.header.exec_height,
+        current_hash: block_hash,
+        list_confirm_root: vec![],
+        latest_committed_state_root: genesis_block.header.state_root.clone(),
+        list_state_root: vec![],
+        list_receipt_root: vec![],
+        list_cycles_used: vec![],
+        current_proof: genesis_block.header.proof,
+        validators: genesis_block.header.validators,
+        consensus_interval: 3000,
+        propose_ratio: 15,
+        prevote_ratio: 10,
+        precommit_ratio: 10,
+        brake_ratio: 3,
+        tx_num_limit: 20000,
+        max_tx_size: 1_073_741_824,
+    };
+    let status_agent = StatusAgent::new(status);
+    let lock = Arc::new(Mutex::new(()));
+    let sync = OverlordSynchronization::<_>::new(
+        5000,
+        adapter,
+        status_agent.clone(),
+        crypto,
+        Arc::clone(&lock),
+    );
+
+    (sync, status_agent, lock)
+}
+
 pub type SafeHashMap<K, V> = Arc<RwLock<HashMap<K, V>>>;
 
 pub struct MockCommonConsensusAdapter {
@@ -143,6 +301,12 @@ pub struct MockCommonConsensusAdapter {
     local_transactions: SafeHashMap<Hash, SignedTransaction>,
     remote_transactions: SafeHashMap<Hash, SignedTransaction>,
     crypto: Arc<OverlordCrypto>,
+    /// The proofs served by these peers are tampered.
+    bad_peers: RwLock<HashSet<PeerId>>,
+    /// The verifier list is updated to the new one from the height on.
+    updated_verifier_list: RwLock<Option<(u64, Vec<ValidatorExtend>)>>,
+    /// The peers reported with `TrustFeedback::Worse`.
+    worse_peers: RwLock<Vec<Option<PeerId>>>,
 }
 
 impl MockCommonConsensusAdapter {
@@ -163,6 +327,9 @@ impl MockCommonConsensusAdapter {
             local_transactions,
             remote_transactions,
             crypto,
+            bad_peers: RwLock::new(HashSet::new()),
+            updated_verifier_list: RwLock::new(None),
+            worse_peers: RwLock::new(vec![]),
         }
     }
 }
@@ -222,10 +389,17 @@ impl SynchronizationAdapter for MockCommonConsensusAdapter {
 
     async fn get_proof_from_remote(
         &self,
-        _: Context,
+        ctx: Context,
         height: u64,
     ) -> ProtocolResult<Proof> {
-        Ok(self.remote_proofs.read().get(&height).unwrap().clone())
+        let mut proof = self.remote_proofs.read().get(&height).unwrap().clone();
+        if let Ok(peer_id) = ctx.remote_peer_id() {
+            if self.bad_peers.read().contains(&peer_id) {
+                // The signature is of the round 0.
+                proof.round += 1;
+            }
+        }
+        Ok(proof)
     }
 }
 
@@ -323,10 +497,15 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
         &self,
         _context: Context,
         _state_root: MerkleRoot,
-        _height: u64,
+        height: u64,
         _timestamp: u64,
         _proposer: Address,
     ) -> ProtocolResult<Metadata> {
+        let verifier_list = match &*self.updated_verifier_list.read() {
+            Some((update_height, list)) if height >= *update_height => list.clone(),
+            _ => mock_verifier_list(),
+        };
+
         Ok(Metadata {
             chain_id: Hash::from_empty(),
             bech32_address_hrp: "muta".to_owned(),
@@ -335,7 +514,7 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
             cycles_limit: 9999,
             cycles_price: 1,
             interval: 3000,
-            verifier_list: mock_verifier_list(),
+            verifier_list,
             propose_ratio: 10,
             prevote_ratio: 10,
             precommit_ratio: 10,
@@ -349,7 +528,11 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
         Ok(())
     }
 
-    fn report_bad(&self, _ctx: Context, _feedback: TrustFeedback) {}
+    fn report_bad(&self, ctx: Context, feedback: TrustFeedback) {
+        if let TrustFeedback::Worse(_) = feedback {
+            self.worse_peers.write().push(ctx.remote_peer_id().ok());
+        }
+    }
 
     fn set_args(
         &self,
@@ -678,8 +861,12 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
 }
 
 fn mock_crypto() -> OverlordCrypto {
+    mock_crypto_with_common_ref("muta".into())
+}
+
+fn mock_crypto_with_common_ref(common_ref: BlsCommonReference) -> OverlordCrypto {
     let priv_key = BlsPrivateKey::try_from(hex::decode("00000000000000000000000000000000d654c7a6747fc2e34808c1ebb1510bfb19b443d639f2fab6dc41fce9f634de37").unwrap().as_ref()).unwrap();
-    OverlordCrypto::new(priv_key, HashMap::new(), "muta".into())
+    OverlordCrypto::new(priv_key, HashMap::new(), common_ref)
 }
 
 fn gen_remote_tx_hashmap(list: Vec<RichBlock>) -> SafeHashMap<Hash, SignedTransaction> {
@@ -716,6 +903,17 @@ fn mock_chained_rich_block(
     len: u64,
     gap: u64,
     key_tool: &KeyTool,
+) -> (Vec<RichBlock>, Vec<Proof>) {
+    mock_validator_updated_rich_block(len, gap, key_tool, None)
+}
+
+// The proofs of the blocks above the update height are signed with the
+// updated key tool.
+fn mock_validator_updated_rich_block(
+    len: u64,
+    gap: u64,
+    key_tool: &KeyTool,
+    updated: Option<(u64, &KeyTool)>,
 ) -> (Vec<RichBlock>, Vec<Proof>) {
     let mut list_rich_block = vec![];
     let mut list_proof = vec![];
@@ -815,8 +1013,15 @@ fn mock_chained_rich_block(
             Hash::digest(rich_block.block.header.encode_fixed().unwrap());
 
         // generate proof for current height and for next block use
-        last_proof =
-            mock_proof(current_block_hash.clone(), current_height, 0, &key_tool);
+        let signer = match updated {
+            Some((update_height, updated_key_tool))
+                if current_height > update_height =>
+            {
+                updated_key_tool
+            }
+            _ => key_tool,
+        };
+        last_proof = mock_proof(current_block_hash.clone(), current_height, 0, signer);
 
         list_proof.push(last_proof.clone());
 
@@ -892,6 +1097,7 @@ fn get_receipt(tx: &SignedTransaction, height: u64) -> Receipt {
                 error_message: "".to_owned(),
             },
         },
+        fee: None,
     }
 }
 
@@ -910,6 +1116,7 @@ fn mock_tx_list(num: usize, height: u64) -> Vec<SignedTransaction> {
                 service_name: "test_service".to_owned(),
                 method: "test_method".to_owned(),
                 payload: "test_payload".to_owned(),
+                encoding: PayloadEncoding::Json,
             },
             sender: Address::from_pubkey_bytes(Bytes::from(
                 hex::decode(PUB_KEY_STR).unwrap(),
@@ -1187,6 +1394,26 @@ fn mock_verifier_list() -> Vec<ValidatorExtend> {
     ]
 }
 
+// The validator 1 is kept and the validator 4 is added.
+fn mock_updated_verifier_list() -> Vec<ValidatorExtend> {
+    vec![
+        ValidatorExtend {
+            bls_pub_key: Hex::from_string("0x04102947214862a503c73904deb5818298a186d68c7907bb609583192a7de6331493835e5b8281f4d9ee705537c0e765580e06f86ddce5867812fceb42eecefd209f0eddd0389d6b7b0100f00fb119ef9ab23826c6ea09aadcc76fa6cea6a32724".to_owned()).unwrap(),
+            pub_key: Hex::from_string("0x02ef0cb0d7bc6c18b4bea1f5908d9106522b35ab3c399369605d4242525bda7e60".to_owned()).unwrap(),
+            address: Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705").unwrap(),
+            propose_weight: 5,
+            vote_weight:    5,
+        },
+        ValidatorExtend {
+            bls_pub_key: Hex::from_string("0x041342e9a35278b298a67006cd98d663053e3f7eb72a08ffe9835074e430b2112a866c1c8d981edcd793cb16d459fc952b0464007d876355eea671e74727588bae69740c6a0b49d8142b7b0821a78acd34b4d8012b9ef69444a476e03d5fea5330".to_owned()).unwrap(),
+            pub_key: Hex::from_string("0x0245a0c291f56c2c5751db1c0bf1ed986e703d29a0fe023df770fe92c7c2347316".to_owned()).unwrap(),
+            address: Address::from_str("muta16xukzz73l5r6vulk9q697tave8c5mfu33mwud6").unwrap(),
+            propose_weight: 1,
+            vote_weight:    1,
+        },
+    ]
+}
+
 #[rustfmt::skip]
 // {
 //   "common_ref": "0x6c747758636859487038",
@@ -1,6 +1,12 @@
+use std::convert::TryFrom;
 use std::fs;
-use std::io::{ErrorKind, Read, Write};
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use bytes::{Buf, BufMut, BytesMut};
+use creep::Context;
 
 use common_apm::muta_apm;
 use protocol::codec::ProtocolCodecSync;
@@ -9,10 +15,12 @@ use protocol::ProtocolResult;
 
 use crate::fixed_types::FixedSignedTxs;
 use crate::ConsensusError;
-use bytes::{BufMut, BytesMut};
-use creep::Context;
-use std::str::FromStr;
-use std::time::SystemTime;
+
+/// The length of the checksum, which is the hash of the record data.
+const CHECK_SUM_LEN: usize = 32;
+/// A record starts with the big endian `u32` length of its data and the
+/// checksum, followed by the data.
+const RECORD_HEADER_LEN: usize = 4 + CHECK_SUM_LEN;
 
 #[derive(Debug)]
 pub struct SignedTxsWAL {
@@ -25,9 +33,13 @@ impl SignedTxsWAL {
             fs::create_dir_all(&path).expect("Failed to create wal directory");
         }
 
-        SignedTxsWAL {
+        let wal = SignedTxsWAL {
             path: path.as_ref().to_path_buf(),
+        };
+        if let Err(e) = wal.recover() {
+            log::error!("[consensus]: recover signed txs wal error {}", e);
         }
+        wal
     }
 
     pub fn save(
@@ -45,27 +57,14 @@ impl SignedTxsWAL {
         wal_path.push(ordered_signed_transactions_hash.as_hex());
         wal_path.set_extension("txt");
 
-        let mut wal_file = match fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(wal_path)
-        {
-            Ok(file) => file,
-            Err(err) => {
-                if err.kind() == ErrorKind::AlreadyExists {
-                    return Ok(());
-                } else {
-                    return Err(ConsensusError::WALErr(err).into());
-                }
-            }
-        };
+        // The file is named by the hash of its transactions, so a valid one
+        // doesn't need to be written again.
+        if read_txs_record(&wal_path).is_ok() {
+            return Ok(());
+        }
 
         let data = FixedSignedTxs::new(txs).encode_sync()?;
-        wal_file
-            .write_all(data.as_ref())
-            .map_err(ConsensusError::WALErr)?;
-        Ok(())
+        write_record(&wal_path, data)
     }
 
     pub fn available_height(&self) -> ProtocolResult<Vec<u64>> {
@@ -74,8 +73,8 @@ impl SignedTxsWAL {
         for item in fs::read_dir(dir_path).map_err(ConsensusError::WALErr)? {
             let item = item.map_err(ConsensusError::WALErr)?;
 
-            if item.path().is_dir() {
-                availables.push(item.file_name().to_str().unwrap().parse().unwrap())
+            if let Some(height) = parse_height(&item.path()) {
+                availables.push(height)
             }
         }
         Ok(availables)
@@ -124,26 +123,36 @@ impl SignedTxsWAL {
     pub fn remove(&self, committed_height: u64) -> ProtocolResult<()> {
         for entry in fs::read_dir(&self.path).map_err(ConsensusError::WALErr)? {
             let folder = entry.map_err(ConsensusError::WALErr)?.path();
-            let folder_name = folder
-                .file_stem()
-                .ok_or_else(|| ConsensusError::Other("file stem error".to_string()))?
-                .to_os_string()
-                .clone();
-            let folder_name = folder_name.into_string().map_err(|err| {
-                ConsensusError::Other(format!(
-                    "transfer os string to string error {:?}",
-                    err
-                ))
-            })?;
-            let height = folder_name.parse::<u64>().map_err(|err| {
-                ConsensusError::Other(format!(
-                    "parse folder name {:?} error {:?}",
-                    folder, err
-                ))
-            })?;
-
-            if height <= committed_height {
-                fs::remove_dir_all(folder).map_err(ConsensusError::WALErr)?;
+
+            match parse_height(&folder) {
+                Some(height) if height <= committed_height => {
+                    fs::remove_dir_all(folder).map_err(ConsensusError::WALErr)?
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the files torn by a crash in the middle of writing, and skips
+    /// the entries not written by the wal.
+    fn recover(&self) -> ProtocolResult<()> {
+        for entry in fs::read_dir(&self.path).map_err(ConsensusError::WALErr)? {
+            let folder = entry.map_err(ConsensusError::WALErr)?.path();
+            if parse_height(&folder).is_none() {
+                log::warn!("[consensus]: skip unknown signed txs wal {:?}", folder);
+                continue;
+            }
+
+            for file in fs::read_dir(&folder).map_err(ConsensusError::WALErr)? {
+                let file_path = file.map_err(ConsensusError::WALErr)?.path();
+                if file_path.is_file() && read_txs_record(&file_path).is_err() {
+                    log::warn!(
+                        "[consensus]: remove corrupted signed txs wal {:?}",
+                        file_path
+                    );
+                    fs::remove_file(file_path).map_err(ConsensusError::WALErr)?;
+                }
             }
         }
         Ok(())
@@ -153,12 +162,8 @@ impl SignedTxsWAL {
         &self,
         file_path: PathBuf,
     ) -> ProtocolResult<Vec<SignedTransaction>> {
-        let mut read_buf = Vec::new();
-        let mut file = fs::File::open(&file_path).map_err(ConsensusError::WALErr)?;
-        let _ = file
-            .read_to_end(&mut read_buf)
-            .map_err(ConsensusError::WALErr)?;
-        let txs = FixedSignedTxs::decode_sync(Bytes::from(read_buf))?;
+        let data = read_txs_record(&file_path)?;
+        let txs = FixedSignedTxs::decode_sync(data)?;
         Ok(txs.inner)
     }
 }
@@ -166,17 +171,25 @@ impl SignedTxsWAL {
 #[derive(Debug)]
 pub struct ConsensusWal {
     path: PathBuf,
+    /// How many latest files are kept after writing a new one, so that an
+    /// older one can be loaded if the latest is corrupted.
+    retained_files: usize,
 }
 
 impl ConsensusWal {
-    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+    pub fn new<P: AsRef<Path>>(path: P, retained_files: usize) -> Self {
         if !path.as_ref().exists() {
             fs::create_dir_all(&path).expect("Failed to create wal directory");
         }
 
-        ConsensusWal {
+        let wal = ConsensusWal {
             path: path.as_ref().to_path_buf(),
+            retained_files: retained_files.max(1),
+        };
+        if let Err(e) = wal.recover() {
+            log::error!("[consensus]: recover consensus wal error {}", e);
         }
+        wal
     }
 
     #[muta_apm::derive::tracing_span(kind = "consensus_wal")]
@@ -188,13 +201,7 @@ impl ConsensusWal {
         }
 
         // 2nd, write info into file
-        let check_sum = Hash::digest(info.clone());
-
-        let mut content = BytesMut::new();
-        content.put(check_sum.as_bytes());
-        content.put(info);
-
-        let (data_path, timestamp) = {
+        let data_path = {
             loop {
                 let timestamp = SystemTime::now()
                     .duration_since(SystemTime::UNIX_EPOCH)
@@ -207,62 +214,85 @@ impl ConsensusWal {
                 data_path.push(timestamp.to_string());
 
                 if !data_path.exists() {
-                    break (data_path, timestamp);
+                    break data_path;
                 }
             }
         };
 
-        let mut data_file = match fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(data_path)
-        {
-            Ok(file) => file,
-            Err(err) => {
-                if err.kind() == ErrorKind::AlreadyExists {
-                    return Ok(());
-                } else {
-                    return Err(ConsensusError::WALErr(err).into());
-                }
+        write_record(&data_path, info)?;
+
+        // 3rd, we can safely clean the wal files out of retention
+        self.compact()
+    }
+
+    #[muta_apm::derive::tracing_span(kind = "consensus_wal")]
+    pub fn load_overlord_wal(&self, ctx: Context) -> ProtocolResult<Bytes> {
+        // 1st,
+        let dir_path = self.path.clone();
+        if !dir_path.exists() {
+            return Err(ConsensusError::ConsensusWalDirNotExist.into());
+        }
+
+        // 2nd, get a latest and valid wal if possible
+        for file_name_timestamp in self.sorted_timestamps()? {
+            let mut log_path = dir_path.clone();
+            log_path.push(file_name_timestamp.to_string());
+
+            if let Ok(content) = read_consensus_record(&log_path) {
+                return Ok(content);
             }
-        };
+        }
+
+        Err(ConsensusError::ConsensusWalNoWalFile.into())
+    }
 
-        data_file
-            .write_all(content.as_ref())
-            .map_err(ConsensusError::WALErr)?;
+    pub fn clear(&self) -> ProtocolResult<()> {
+        let dir_path = self.path.clone();
+        if !dir_path.exists() {
+            return Ok(());
+        }
 
-        // 3rd, we can safely clean other old wal files
         for item in fs::read_dir(dir_path).map_err(ConsensusError::WALErr)? {
             let item = item.map_err(ConsensusError::WALErr)?;
 
-            let file_name = item
-                .file_name()
-                .to_str()
-                .ok_or(ConsensusError::FileNameTimestamp)?
-                .to_owned();
+            fs::remove_file(item.path()).map_err(ConsensusError::WALErr)?;
+        }
+        Ok(())
+    }
 
-            let file_name_timestamp = u128::from_str(file_name.as_str())
-                .map_err(|e| ConsensusError::FileNameTimestamp)?;
+    /// Keeps the latest `retained_files` files and removes the older ones.
+    fn compact(&self) -> ProtocolResult<()> {
+        for timestamp in self
+            .sorted_timestamps()?
+            .into_iter()
+            .skip(self.retained_files)
+        {
+            let mut log_path = self.path.clone();
+            log_path.push(timestamp.to_string());
 
-            if file_name_timestamp < timestamp {
-                fs::remove_file(item.path()).map_err(ConsensusError::WALErr)?;
-            }
+            fs::remove_file(log_path).map_err(ConsensusError::WALErr)?;
         }
-
         Ok(())
     }
 
-    #[muta_apm::derive::tracing_span(kind = "consensus_wal")]
-    pub fn load_overlord_wal(&self, ctx: Context) -> ProtocolResult<Bytes> {
-        // 1st,
-        let dir_path = self.path.clone();
-        if !dir_path.exists() {
-            return Err(ConsensusError::ConsensusWalDirNotExist.into());
+    /// Removes the files torn by a crash in the middle of writing, an older
+    /// file will be loaded instead.
+    fn recover(&self) -> ProtocolResult<()> {
+        for timestamp in self.sorted_timestamps()? {
+            let mut log_path = self.path.clone();
+            log_path.push(timestamp.to_string());
+
+            if read_consensus_record(&log_path).is_err() {
+                log::warn!("[consensus]: remove corrupted consensus wal {:?}", log_path);
+                fs::remove_file(log_path).map_err(ConsensusError::WALErr)?;
+            }
         }
+        Ok(())
+    }
 
-        // 2 read all log files and sort by timestamp in their names
-        let files = fs::read_dir(dir_path.clone()).map_err(ConsensusError::WALErr)?;
+    /// Returns the timestamps in the wal file names, the latest first.
+    fn sorted_timestamps(&self) -> ProtocolResult<Vec<u128>> {
+        let files = fs::read_dir(&self.path).map_err(ConsensusError::WALErr)?;
 
         let mut file_names_timestamps = files
             .filter_map(|item| {
@@ -270,64 +300,93 @@ impl ConsensusWal {
                 let file_name = item.file_name();
                 let file_name = file_name.to_str()?;
 
-                let file_name_timestamp = u128::from_str(file_name).ok()?;
-
-                Some(file_name_timestamp)
+                u128::from_str(file_name).ok()
             })
             .collect::<Vec<_>>();
 
         file_names_timestamps.sort_by_key(|&b| std::cmp::Reverse(b));
+        Ok(file_names_timestamps)
+    }
+}
 
-        // 3rd, get a latest and valid wal if possible
-        let mut index = 0;
-        let content = loop {
-            if index >= file_names_timestamps.len() {
-                break None;
-            }
-
-            let file_name_timestamp = file_names_timestamps[index];
-
-            let mut log_path = dir_path.clone();
-            log_path.push(file_name_timestamp.to_string());
-
-            let mut read_buf = Vec::new();
-            let mut file = fs::File::open(&log_path).map_err(ConsensusError::WALErr)?;
-            let res = file.read_to_end(&mut read_buf);
-            if res.is_err() {
-                continue;
-            }
+fn parse_height(path: &Path) -> Option<u64> {
+    if !path.is_dir() {
+        return None;
+    }
 
-            let mut info = Bytes::from(read_buf);
+    path.file_name()?.to_str()?.parse().ok()
+}
 
-            if info.len() < Hash::default().as_bytes().len() {
-                continue;
-            }
+fn write_record(path: &Path, data: Bytes) -> ProtocolResult<()> {
+    let len = u32::try_from(data.len()).map_err(|_| {
+        ConsensusError::Other(format!("wal record of {} bytes is too large", data.len()))
+    })?;
+    let check_sum = Hash::digest(data.clone());
+
+    let mut record = BytesMut::with_capacity(RECORD_HEADER_LEN + data.len());
+    record.put_u32(len);
+    record.put(check_sum.as_bytes());
+    record.put(data);
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .map_err(ConsensusError::WALErr)?;
+
+    file.write_all(record.as_ref())
+        .map_err(ConsensusError::WALErr)?;
+    file.sync_all().map_err(ConsensusError::WALErr)?;
+    Ok(())
+}
 
-            let content = info.split_off(Hash::default().as_bytes().len());
+/// Reads the data of the record in the file, returns an error if the record
+/// is torn or corrupted.
+fn read_record(path: &Path) -> ProtocolResult<Bytes> {
+    let mut record = Bytes::from(fs::read(path).map_err(ConsensusError::WALErr)?);
+    let corrupted = || ConsensusError::CorruptedWal(path.to_path_buf());
 
-            if info == Hash::digest(content.clone()).as_bytes() {
-                break Some(content);
-            } else {
-                index += 1;
-            }
-        };
+    if record.len() < RECORD_HEADER_LEN {
+        return Err(corrupted().into());
+    }
 
-        content.ok_or_else(|| ConsensusError::ConsensusWalNoWalFile.into())
+    let len = record.get_u32() as usize;
+    let check_sum = record.split_to(CHECK_SUM_LEN);
+    if record.len() != len || Hash::digest(record.clone()).as_bytes() != check_sum {
+        return Err(corrupted().into());
     }
 
-    pub fn clear(&self) -> ProtocolResult<()> {
-        let dir_path = self.path.clone();
-        if !dir_path.exists() {
-            return Ok(());
+    Ok(record)
+}
+
+/// Reads the record of the signed txs wal, the files written before the records
+/// are introduced hold the encoded txs only.
+fn read_txs_record(path: &Path) -> ProtocolResult<Bytes> {
+    read_record(path).or_else(|e| {
+        let data = Bytes::from(fs::read(path).map_err(ConsensusError::WALErr)?);
+        match FixedSignedTxs::decode_sync(data.clone()) {
+            Ok(_) => Ok(data),
+            Err(_) => Err(e),
         }
+    })
+}
 
-        for item in fs::read_dir(dir_path).map_err(ConsensusError::WALErr)? {
-            let item = item.map_err(ConsensusError::WALErr)?;
+/// Reads the record of the consensus wal, the files written before the records
+/// are introduced hold the checksum and the data only.
+fn read_consensus_record(path: &Path) -> ProtocolResult<Bytes> {
+    read_record(path).or_else(|e| {
+        let mut data = Bytes::from(fs::read(path).map_err(ConsensusError::WALErr)?);
+        if data.len() < CHECK_SUM_LEN {
+            return Err(e);
+        }
 
-            fs::remove_file(item.path()).map_err(ConsensusError::WALErr)?;
+        let check_sum = data.split_to(CHECK_SUM_LEN);
+        if Hash::digest(data.clone()).as_bytes() != check_sum {
+            return Err(e);
         }
-        Ok(())
-    }
+        Ok(data)
+    })
 }
 
 #[rustfmt::skip]
@@ -348,7 +407,9 @@ mod tests {
     use rand::random;
     use test::Bencher;
 
-    use protocol::types::{Address, Hash, RawTransaction, TransactionRequest};
+    use protocol::types::{
+        Address, Hash, PayloadEncoding, RawTransaction, TransactionRequest,
+    };
     use protocol::Bytes;
 
     use super::*;
@@ -383,6 +444,7 @@ mod tests {
             service_name: "mock-service".to_owned(),
             method:       "mock-method".to_owned(),
             payload:      "mock-payload".to_owned(),
+            encoding:     PayloadEncoding::Json,
         }
     }
 
@@ -441,7 +503,7 @@ mod tests {
     #[test]
     fn test_consensus_wal() {
         // write one, read one
-        let wal = ConsensusWal::new(FULL_CONSENSUS_PATH.to_string());
+        let wal = ConsensusWal::new(FULL_CONSENSUS_PATH.to_string(), 1);
         let info = get_random_bytes(1000);
         wal.update_overlord_wal(Context::new(),info.clone()).unwrap();
 
@@ -498,6 +560,95 @@ mod tests {
         fs::remove_dir_all(PathBuf::from_str(FULL_CONSENSUS_PATH).unwrap()).unwrap();
     }
 
+    #[test]
+    fn test_consensus_wal_compaction() {
+        let path = "./free-space/wal/consensus_compaction";
+        let wal = ConsensusWal::new(path.to_string(), 2);
+        for _ in 0..4 {
+            wal.update_overlord_wal(Context::new(), get_random_bytes(1000)).unwrap();
+        }
+        assert_eq!(fs::read_dir(path).unwrap().count(), 2);
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_wal_recovery() {
+        let txs_path = "./free-space/wal/torn_txs";
+        let wal = SignedTxsWAL::new(txs_path.to_string());
+        let txs = mock_wal_txs(10);
+        let hash = Hash::digest(Bytes::from(rlp::encode_list(&txs)));
+        wal.save(1u64, hash.clone(), txs.clone()).unwrap();
+
+        // Tear the record as if the node crashed in the middle of writing.
+        let file_path = PathBuf::from(txs_path)
+            .join("1")
+            .join(hash.as_hex())
+            .with_extension("txt");
+        let len = fs::metadata(&file_path).unwrap().len();
+        let file = fs::OpenOptions::new().write(true).open(&file_path).unwrap();
+        file.set_len(len - 1).unwrap();
+        assert!(wal.load(1u64, hash.clone()).is_err());
+
+        let wal = SignedTxsWAL::new(txs_path.to_string());
+        assert!(!file_path.exists());
+        assert_eq!(wal.available_height().unwrap(), vec![1]);
+
+        wal.save(1u64, hash.clone(), txs.clone()).unwrap();
+        assert_eq!(wal.load(1u64, hash).unwrap(), txs);
+        fs::remove_dir_all(txs_path).unwrap();
+
+        let consensus_path = "./free-space/wal/torn_consensus";
+        let wal = ConsensusWal::new(consensus_path.to_string(), 2);
+        let info = get_random_bytes(1000);
+        wal.update_overlord_wal(Context::new(), info.clone()).unwrap();
+        wal.update_overlord_wal(Context::new(), get_random_bytes(1000)).unwrap();
+
+        let latest = wal.sorted_timestamps().unwrap()[0];
+        let file_path = PathBuf::from(consensus_path).join(latest.to_string());
+        let file = fs::OpenOptions::new().write(true).open(&file_path).unwrap();
+        file.set_len(RECORD_HEADER_LEN as u64).unwrap();
+
+        let wal = ConsensusWal::new(consensus_path.to_string(), 2);
+        assert!(!file_path.exists());
+        assert_eq!(wal.load_overlord_wal(Context::new()).unwrap(), info);
+        fs::remove_dir_all(consensus_path).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_wal() {
+        let txs_path = "./free-space/wal/legacy_txs";
+        let txs = mock_wal_txs(10);
+        let hash = Hash::digest(Bytes::from(rlp::encode_list(&txs)));
+        let file_path = PathBuf::from(txs_path)
+            .join("1")
+            .join(hash.as_hex())
+            .with_extension("txt");
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        let data = FixedSignedTxs::new(txs.clone()).encode_sync().unwrap();
+        fs::write(&file_path, data).unwrap();
+
+        let wal = SignedTxsWAL::new(txs_path.to_string());
+        assert!(file_path.exists());
+        assert_eq!(wal.load(1u64, hash).unwrap(), txs);
+        assert_eq!(wal.load_by_height(1), txs);
+        fs::remove_dir_all(txs_path).unwrap();
+
+        let consensus_path = "./free-space/wal/legacy_consensus";
+        let info = get_random_bytes(1000);
+        let mut content = BytesMut::new();
+        content.put(Hash::digest(info.clone()).as_bytes());
+        content.put(info.clone());
+        let file_path = PathBuf::from(consensus_path).join("1");
+        fs::create_dir_all(consensus_path).unwrap();
+        fs::write(&file_path, content).unwrap();
+
+        let wal = ConsensusWal::new(consensus_path.to_string(), 2);
+        assert!(file_path.exists());
+        assert_eq!(wal.load_overlord_wal(Context::new()).unwrap(), info);
+        fs::remove_dir_all(consensus_path).unwrap();
+    }
+
     #[test]
     fn test_wal_txs_codec() {
         for _ in 0..10 {
@@ -3,6 +3,7 @@ use super::TxContext;
 pub mod message;
 
 use std::{
+    collections::HashMap,
     error::Error,
     marker::PhantomData,
     sync::atomic::{AtomicU64, Ordering},
@@ -28,10 +29,14 @@ use common_crypto::Crypto;
 use protocol::{
     fixed_codec::FixedCodec,
     traits::{
-        Context, ExecutorFactory, ExecutorParams, Gossip, MemPoolAdapter, PeerTrust,
-        Priority, Rpc, ServiceMapping, ServiceResponse, Storage, TrustFeedback,
+        Context, Executor, ExecutorFactory, ExecutorParams, Gossip, MemPoolAdapter,
+        PeerTrust, Priority, Rpc, ServiceMapping, ServiceResponse, Storage,
+        TrustFeedback,
+    },
+    types::{
+        sequential_nonce_enabled, Address, BlockHeader, FeePledge, Hash,
+        PayloadEncoding, SignedTransaction, TransactionRequest,
     },
-    types::{Address, Hash, SignedTransaction, TransactionRequest},
     ProtocolError, ProtocolErrorKind, ProtocolResult,
 };
 
@@ -204,6 +209,129 @@ where
             pin_ef: PhantomData,
         }
     }
+
+    fn check_sequential_nonce(
+        &self,
+        ctx: Context,
+        executor: &dyn Executor,
+        stx: &SignedTransaction,
+    ) -> ProtocolResult<()> {
+        let expect = executor.get_account_nonce(&stx.raw.sender)?;
+
+        // Nonces ahead of the account are queued, until the ones before them
+        // are committed
+        match stx.raw.sequential_nonce() {
+            Some(nonce) if nonce >= expect => Ok(()),
+            _ => {
+                if ctx.is_network_origin_txs() {
+                    self.network.report(
+                        ctx,
+                        TrustFeedback::Bad(format!(
+                            "Mempool invalid nonce of tx {:?}",
+                            stx.tx_hash
+                        )),
+                    );
+                }
+
+                Err(MemPoolError::InvalidNonce {
+                    tx_hash: stx.tx_hash.clone(),
+                    expect,
+                }
+                .into())
+            }
+        }
+    }
+
+    // The executor puts the txs whose senders can not pledge the fees in the
+    // block without executing or charging them, so they must be kept out of
+    // the pool. The pending txs of the sender pledge their fees before it, so
+    // the balance must cover theirs too.
+    fn check_pledges(
+        &self,
+        executor: &dyn Executor,
+        header: &BlockHeader,
+        stx: &SignedTransaction,
+        pending_txs: &[SignedTransaction],
+    ) -> ProtocolResult<()> {
+        let params = exec_params(header);
+
+        let mut pending_pledges = HashMap::new();
+        for pending_tx in pending_txs {
+            for pledge in executor.tx_pledges(&params, pending_tx)? {
+                let amount = pending_pledges
+                    .entry((pledge.asset_service, pledge.asset_id))
+                    .or_insert(0u64);
+                *amount = amount.saturating_add(pledge.amount);
+            }
+        }
+
+        for pledge in executor.tx_pledges(&params, stx)? {
+            let balance = read_balance(executor, &params, &stx.raw.sender, &pledge)?;
+            let key = (pledge.asset_service.clone(), pledge.asset_id.clone());
+            let pending = pending_pledges.get(&key).copied().unwrap_or(0);
+            let amount = pending.saturating_add(pledge.amount);
+
+            if balance < amount {
+                return Err(MemPoolError::InsufficientFee {
+                    tx_hash: stx.tx_hash.clone(),
+                    pledge: amount,
+                    balance,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn executor_of(&self, header: &BlockHeader) -> ProtocolResult<Box<dyn Executor>> {
+        EF::from_root(
+            header.state_root.clone(),
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            Arc::clone(&self.service_mapping),
+        )
+    }
+}
+
+fn exec_params(header: &BlockHeader) -> ExecutorParams {
+    ExecutorParams {
+        state_root: header.state_root.clone(),
+        height: header.height,
+        timestamp: header.timestamp,
+        cycles_limit: std::u64::MAX,
+        proposer: header.proposer.clone(),
+    }
+}
+
+// The balance of a user in the asset of a pledge, 0 if it can not be read
+fn read_balance(
+    executor: &dyn Executor,
+    params: &ExecutorParams,
+    user: &Address,
+    pledge: &FeePledge,
+) -> ProtocolResult<u64> {
+    let payload = serde_json::json!({
+        "asset_id": pledge.asset_id,
+        "user": user,
+    });
+
+    let resp = executor.read(
+        params,
+        user,
+        1,
+        &TransactionRequest {
+            service_name: pledge.asset_service.to_owned(),
+            method: "get_balance".to_owned(),
+            payload: payload.to_string(),
+            encoding: PayloadEncoding::Json,
+        },
+    )?;
+    let balance = serde_json::from_str::<serde_json::Value>(&resp.succeed_data)
+        .ok()
+        .and_then(|data| data["balance"].as_u64())
+        .unwrap_or(0);
+    Ok(balance)
 }
 
 #[async_trait]
@@ -324,6 +452,7 @@ where
                         service_name: "authorization".to_string(),
                         method: "check_authorization_by_ptr".to_string(),
                         payload: stx_ptr_json,
+                        encoding: PayloadEncoding::Json,
                     },
                 )?;
 
@@ -357,6 +486,7 @@ where
         &self,
         ctx: Context,
         stx: &SignedTransaction,
+        pending_txs: &[SignedTransaction],
     ) -> ProtocolResult<()> {
         let fixed_bytes = stx.raw.encode_fixed()?;
         let size = fixed_bytes.len() as u64;
@@ -443,6 +573,17 @@ where
             return Err(timeout.into());
         }
 
+        // The checks below are made on the state of the latest block
+        let executor = self.executor_of(&latest_header)?;
+
+        // Verify sequential nonce
+        if sequential_nonce_enabled() {
+            self.check_sequential_nonce(ctx, &*executor, stx)?;
+        }
+
+        // Verify the sender can pledge the fees
+        self.check_pledges(&*executor, &latest_header, stx, pending_txs)?;
+
         Ok(())
     }
 
@@ -451,12 +592,12 @@ where
         ctx: Context,
         tx_hash: &Hash,
     ) -> ProtocolResult<()> {
-        match self.storage.get_transaction_by_hash(ctx, tx_hash).await {
-            Ok(Some(_)) => Err(MemPoolError::CommittedTx {
+        match self.storage.contains_transaction(ctx, tx_hash).await {
+            Ok(true) => Err(MemPoolError::CommittedTx {
                 tx_hash: tx_hash.clone(),
             }
             .into()),
-            Ok(None) => Ok(()),
+            Ok(false) => Ok(()),
             Err(err) => Err(err),
         }
     }
@@ -23,8 +23,10 @@ use std::time::Instant;
 use async_trait::async_trait;
 use derive_more::Display;
 use futures::future::try_join_all;
+use futures::stream::Stream;
 use tokio::sync::RwLock;
 
+use common_pubsub::{PubSub, DEFAULT_CHANNEL_CAPACITY};
 use protocol::traits::{Context, MemPool, MemPoolAdapter, MixedTxHashes};
 use protocol::types::{Hash, SignedTransaction};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
@@ -48,6 +50,8 @@ pub struct HashMemPool<Adapter: MemPoolAdapter> {
     adapter: Arc<Adapter>,
     /// exclusive flush_memory and insert_tx to avoid repeat txs insertion.
     flush_lock: RwLock<()>,
+    /// Report what happens to pending transactions.
+    events: PubSub<MemPoolEvent>,
 }
 
 impl<Adapter: 'static> HashMemPool<Adapter>
@@ -56,16 +60,24 @@ where
 {
     pub async fn new(
         pool_size: usize,
+        enable_priority: bool,
         adapter: Adapter,
         initial_txs: Vec<SignedTransaction>,
     ) -> Self {
+        let tx_cache = if enable_priority {
+            TxCache::with_priority(pool_size * 2)
+        } else {
+            TxCache::new(pool_size * 2)
+        };
+
         let mempool = HashMemPool {
             pool_size,
             timeout_gap: AtomicU64::new(0),
-            tx_cache: TxCache::new(pool_size * 2),
+            tx_cache,
             callback_cache: Arc::new(Map::new(pool_size)),
             adapter: Arc::new(adapter),
             flush_lock: RwLock::new(()),
+            events: PubSub::new(DEFAULT_CHANNEL_CAPACITY),
         };
 
         for tx in initial_txs.into_iter() {
@@ -89,6 +101,10 @@ where
         &self.adapter
     }
 
+    pub fn subscribe_events(&self) -> impl Stream<Item = MemPoolEvent> + Send + 'static {
+        self.events.subscribe()
+    }
+
     async fn show_unknown_txs(&self, tx_hashes: &[Hash]) -> Vec<Hash> {
         let tx_hashes = self.tx_cache.show_unknown(tx_hashes).await;
         let mut unknown_hashes = vec![];
@@ -121,27 +137,94 @@ where
         ctx: Context,
         tx: SignedTransaction,
         tx_type: TxType,
+        verified: bool,
     ) -> ProtocolResult<()> {
         let _lock = self.flush_lock.read().await;
 
         let tx = Box::new(tx);
         let tx_hash = &tx.tx_hash;
-        self.tx_cache.check_reach_limit(self.pool_size).await?;
         self.tx_cache.check_exist(tx_hash).await?;
+        // Transactions from propose-sync are already packaged by the leader.
+        // Without sequential nonces, a nonce only makes the hash unique and
+        // both transactions of the same nonce can be committed, so neither
+        // replaces the other.
+        let replaced_tx = match tx_type {
+            TxType::NewTx if self.tx_cache.is_sequential_nonce() => {
+                self.tx_cache.check_replacement(&tx).await?
+            }
+            _ => None,
+        };
+        // A replacement takes the place of the replaced one in the pool. In
+        // priority mode, the pool limit is checked again on insertion, which
+        // evicts the cheapest transaction if the pool is full.
+        let evict = replaced_tx.is_none() && self.tx_cache.is_priority();
+        if evict {
+            self.tx_cache
+                .check_reach_limit_by_price(self.pool_size, tx.raw.cycles_price)
+                .await?;
+        } else if replaced_tx.is_none() {
+            self.tx_cache.check_reach_limit(self.pool_size).await?;
+        }
+        // The txs of a batch are authorized by `verify_tx_in_parallel` already.
+        if !verified {
+            self.adapter
+                .check_authorization(ctx.clone(), tx.clone())
+                .await?;
+        }
+        // The fee pledged by the replaced one is taken over by the replacement.
+        // Concurrent insertions of a sender may miss each other's pledges, so
+        // the executor still rejects the txs which can not pledge the fee.
+        let pending_txs = self
+            .tx_cache
+            .pending_txs_of(&tx.raw.sender)
+            .await
+            .into_iter()
+            .filter(|pending_tx| {
+                replaced_tx.as_ref().map_or(true, |replaced_tx| {
+                    replaced_tx.tx().tx_hash != pending_tx.tx_hash
+                })
+            })
+            .collect::<Vec<_>>();
         self.adapter
-            .check_authorization(ctx.clone(), tx.clone())
+            .check_transaction(ctx.clone(), &tx, &pending_txs)
             .await?;
-        self.adapter.check_transaction(ctx.clone(), &tx).await?;
         self.adapter
             .check_storage_exist(ctx.clone(), tx_hash)
             .await?;
 
+        if let Some(replaced_tx) = &replaced_tx {
+            self.tx_cache.replace(replaced_tx).await;
+        }
+
+        // Evict only after the new transaction passes all checks.
         match tx_type {
+            TxType::NewTx if evict => {
+                self.tx_cache
+                    .insert_new_tx_or_evict(*tx.clone(), self.pool_size)
+                    .await?
+            }
+            TxType::ProposeTx if evict => {
+                self.tx_cache
+                    .insert_propose_tx_or_evict(*tx.clone(), self.pool_size)
+                    .await?
+            }
             TxType::NewTx => self.tx_cache.insert_new_tx(*tx.clone()).await?,
             TxType::ProposeTx => self.tx_cache.insert_propose_tx(*tx.clone()).await?,
         }
 
-        if !ctx.is_network_origin_txs() {
+        if let Some(replaced_tx) = replaced_tx {
+            self.events.publish(MemPoolEvent::Replaced {
+                old_tx_hash: replaced_tx.tx().tx_hash.clone(),
+                new_tx_hash: tx_hash.clone(),
+            });
+
+            // Peers may only know the replaced one, so rebroadcast the
+            // replacement wherever it comes from.
+            if ctx.is_network_origin_txs() {
+                self.adapter.report_good(ctx.clone());
+            }
+            self.adapter.broadcast_tx(ctx, *tx).await?;
+        } else if !ctx.is_network_origin_txs() {
             self.adapter.broadcast_tx(ctx, *tx).await?;
         } else {
             self.adapter.report_good(ctx);
@@ -170,7 +253,9 @@ where
                     let signed_tx = *(boxed_stx.clone());
 
                     adapter.check_authorization(ctx.clone(), boxed_stx).await?;
-                    adapter.check_transaction(ctx.clone(), &signed_tx).await?;
+                    adapter
+                        .check_transaction(ctx.clone(), &signed_tx, &[])
+                        .await?;
                     adapter
                         .check_storage_exist(ctx.clone(), &signed_tx.tx_hash)
                         .await
@@ -198,7 +283,31 @@ where
     Adapter: MemPoolAdapter,
 {
     async fn insert(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
-        self.insert_tx(ctx, tx, TxType::NewTx).await
+        self.insert_tx(ctx, tx, TxType::NewTx, false).await
+    }
+
+    async fn insert_batch(
+        &self,
+        ctx: Context,
+        txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<Vec<ProtocolResult<()>>> {
+        let (tx_ptrs, txs): (Vec<_>, Vec<_>) = txs
+            .into_iter()
+            .map(|tx| {
+                let boxed = Box::new(tx);
+                (Box::into_raw(boxed.clone()) as usize, boxed)
+            })
+            .unzip();
+
+        self.verify_tx_in_parallel(ctx.clone(), tx_ptrs).await?;
+
+        // Insert in order, so that the sequential nonces of a sender are kept.
+        let mut results = Vec::with_capacity(txs.len());
+        for tx in txs.into_iter() {
+            results.push(self.insert_tx(ctx.clone(), *tx, TxType::NewTx, true).await);
+        }
+
+        Ok(results)
     }
 
     async fn package(
@@ -371,7 +480,9 @@ where
             for tx in txs.into_iter() {
                 // Should not handle error here, it is normal that transactions
                 // response here are exist in pool.
-                let _ = self.insert_tx(ctx.clone(), tx, TxType::ProposeTx).await;
+                let _ = self
+                    .insert_tx(ctx.clone(), tx, TxType::ProposeTx, false)
+                    .await;
             }
         }
         Ok(())
@@ -403,6 +514,17 @@ pub enum TxType {
     ProposeTx,
 }
 
+/// Events of pending transactions, see `HashMemPool::subscribe_events`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemPoolEvent {
+    /// A pending transaction is replaced by a new one of the same sender and
+    /// nonce paying a higher `cycles_price`.
+    Replaced {
+        old_tx_hash: Hash,
+        new_tx_hash: Hash,
+    },
+}
+
 #[derive(Debug, Display)]
 pub enum MemPoolError {
     #[display(
@@ -471,6 +593,37 @@ pub enum MemPoolError {
     #[display(fmt = "Tx: {:?} invalid timeout", tx_hash)]
     InvalidTimeout { tx_hash: Hash },
 
+    #[display(
+        fmt = "Tx: {:?} nonce is not a sequential nonce of at least {}",
+        tx_hash,
+        expect
+    )]
+    InvalidNonce { tx_hash: Hash, expect: u64 },
+
+    #[display(
+        fmt = "Tx: {:?} pledge {} with the pending txs is more than the balance {} of the sender",
+        tx_hash,
+        pledge,
+        balance
+    )]
+    InsufficientFee {
+        tx_hash: Hash,
+        pledge: u64,
+        balance: u64,
+    },
+
+    #[display(
+        fmt = "Tx: {:?} should pay a cycles price higher than {} to replace {:?}",
+        tx_hash,
+        cycles_price,
+        pending_tx_hash
+    )]
+    ReplaceUnderpriced {
+        tx_hash: Hash,
+        pending_tx_hash: Hash,
+        cycles_price: u64,
+    },
+
     #[display(fmt = "Batch transaction validation failed")]
     VerifyBatchTransactions,
 
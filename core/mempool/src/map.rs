@@ -34,6 +34,12 @@ where
         bucket.insert(hash, value).await
     }
 
+    /// Insert a value, replacing the present one of the same key.
+    pub async fn replace(&self, hash: Hash, value: V) -> Option<V> {
+        let bucket = self.get_bucket(&hash);
+        bucket.replace(hash, value).await
+    }
+
     pub async fn contains_key(&self, hash: &Hash) -> bool {
         let bucket = self.get_bucket(hash);
         bucket.contains_key(hash).await
@@ -77,6 +83,17 @@ where
         len
     }
 
+    /// Keep only the values `f` returns true for. This walks every entry, so
+    /// it should only be used on slow paths.
+    pub async fn retain<F>(&self, f: F)
+    where
+        F: Fn(&V) -> bool,
+    {
+        for bucket in self.buckets.iter() {
+            bucket.retain(&f).await;
+        }
+    }
+
     pub async fn clear(&self) {
         let futs = self
             .buckets
@@ -121,6 +138,10 @@ where
         }
     }
 
+    async fn replace(&self, hash: Hash, value: V) -> Option<V> {
+        self.store.write().await.insert(hash, value)
+    }
+
     async fn contains_key(&self, hash: &Hash) -> bool {
         self.store.read().await.contains_key(hash)
     }
@@ -145,6 +166,10 @@ where
         self.store.read().await.len()
     }
 
+    async fn retain<F: Fn(&V) -> bool>(&self, f: &F) {
+        self.store.write().await.retain(|_, v| f(v));
+    }
+
     async fn clear(&self) {
         self.store.write().await.clear();
     }
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use futures::StreamExt;
 use test::Bencher;
 
 use protocol::types::Hash;
@@ -57,6 +58,31 @@ async fn test_insert() {
     insert!(invalid(80, 10, 80));
 }
 
+#[tokio::test]
+async fn test_insert_batch() {
+    let mempool = default_mempool().await;
+
+    // The whole batch is rejected if any tx of it is invalid.
+    let txs = mock_txs(10, 1, TIMEOUT);
+    assert!(mempool.insert_batch(Context::new(), txs).await.is_err());
+    assert_eq!(mempool.get_tx_cache().len().await, 0);
+
+    // Otherwise each tx is inserted on its own.
+    let txs = default_mock_txs(10);
+    mempool
+        .insert(Context::new(), txs[0].clone())
+        .await
+        .unwrap();
+    let results = mempool
+        .insert_batch(Context::new(), txs.clone())
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 10);
+    assert!(results[0].is_err());
+    assert!(results[1..].iter().all(|res| res.is_ok()));
+    assert_eq!(mempool.get_tx_cache().len().await, 10);
+}
+
 macro_rules! package {
     (normal($tx_num_limit: expr, $insert: expr, $expect_order: expr, $expect_propose: expr)) => {
         package!(inner(
@@ -210,6 +236,300 @@ async fn test_sync_propose_txs() {
     assert_eq!(mempool.get_tx_cache().len().await, 50);
 }
 
+#[tokio::test]
+async fn test_priority_package() {
+    let mempool = &Arc::new(new_priority_mempool(POOL_SIZE, CYCLE_LIMIT).await);
+
+    let sender_a = mock_sender_txs(&[1, 5], TX_CYCLE);
+    let sender_b = mock_sender_txs(&[3], TX_CYCLE);
+    let sender_c = mock_sender_txs(&[4], TX_CYCLE);
+    for tx in sender_a
+        .iter()
+        .chain(sender_b.iter())
+        .chain(sender_c.iter())
+    {
+        exec_insert(tx.clone(), Arc::clone(mempool)).await;
+    }
+
+    // The second transaction of sender a pays the most, but it must not
+    // overtake the first one of the same sender.
+    let mixed_tx_hashes =
+        exec_package(Arc::clone(mempool), CYCLE_LIMIT, TX_NUM_LIMIT).await;
+    let expect = vec![
+        sender_c[0].tx_hash.clone(),
+        sender_b[0].tx_hash.clone(),
+        sender_a[0].tx_hash.clone(),
+        sender_a[1].tx_hash.clone(),
+    ];
+    assert_eq!(mixed_tx_hashes.order_tx_hashes, expect);
+    assert!(mixed_tx_hashes.propose_tx_hashes.is_empty());
+}
+
+#[tokio::test]
+async fn test_priority_package_cycles_limit() {
+    let mempool = &Arc::new(new_priority_mempool(POOL_SIZE, CYCLE_LIMIT).await);
+
+    let txs = (1..=10)
+        .flat_map(|price| mock_sender_txs(&[price], 10))
+        .collect::<Vec<_>>();
+    concurrent_insert(txs.clone(), Arc::clone(mempool)).await;
+
+    let mixed_tx_hashes = exec_package(Arc::clone(mempool), 35, TX_NUM_LIMIT).await;
+    let expect_order = txs[7..]
+        .iter()
+        .rev()
+        .map(|tx| tx.tx_hash.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(mixed_tx_hashes.order_tx_hashes, expect_order);
+    assert_eq!(mixed_tx_hashes.propose_tx_hashes.len(), 3);
+}
+
+#[tokio::test]
+async fn test_priority_evict() {
+    let mempool = &Arc::new(new_priority_mempool(10, CYCLE_LIMIT).await);
+
+    let txs = mock_sender_txs(&[2; 10], TX_CYCLE);
+    concurrent_insert(txs, Arc::clone(mempool)).await;
+    assert_eq!(mempool.get_tx_cache().len().await, 10);
+
+    // A transaction paying no more than the cheapest one is rejected.
+    let cheap_tx = mock_sender_txs(&[2], TX_CYCLE).remove(0);
+    assert!(mempool
+        .insert(Context::new(), cheap_tx.clone())
+        .await
+        .is_err());
+    assert!(!mempool.get_tx_cache().contain(&cheap_tx.tx_hash).await);
+
+    // A better paying transaction takes the place of the cheapest one.
+    let rich_tx = mock_sender_txs(&[3], TX_CYCLE).remove(0);
+    mempool
+        .insert(Context::new(), rich_tx.clone())
+        .await
+        .unwrap();
+    assert!(mempool.get_tx_cache().contain(&rich_tx.tx_hash).await);
+    assert_eq!(mempool.get_tx_cache().len().await, 10);
+
+    let mixed_tx_hashes =
+        exec_package(Arc::clone(mempool), CYCLE_LIMIT, TX_NUM_LIMIT).await;
+    assert_eq!(mixed_tx_hashes.order_tx_hashes.len(), 10);
+    assert_eq!(mixed_tx_hashes.order_tx_hashes[0], rich_tx.tx_hash);
+}
+
+#[tokio::test]
+async fn test_priority_evict_concurrently() {
+    let mempool = &Arc::new(new_priority_mempool(10, CYCLE_LIMIT).await);
+
+    let txs = mock_sender_txs(&[2; 10], TX_CYCLE);
+    concurrent_insert(txs, Arc::clone(mempool)).await;
+    assert_eq!(mempool.get_tx_cache().len().await, 10);
+
+    // Each better paying transaction evicts a different cheap one.
+    let rich_txs = mock_sender_txs(&[3; 5], TX_CYCLE);
+    concurrent_insert(rich_txs.clone(), Arc::clone(mempool)).await;
+    assert_eq!(mempool.get_tx_cache().len().await, 10);
+    for tx in rich_txs.iter() {
+        assert!(mempool.get_tx_cache().contain(&tx.tx_hash).await);
+    }
+}
+
+#[tokio::test]
+async fn test_priority_evict_skips_proposed() {
+    let mempool = &Arc::new(new_priority_mempool(10, CYCLE_LIMIT).await);
+
+    let proposed_tx = mock_sender_txs(&[1], TX_CYCLE).remove(0);
+    mempool
+        .get_tx_cache()
+        .insert_propose_tx(proposed_tx.clone())
+        .await
+        .unwrap();
+    let txs = mock_sender_txs(&[2; 9], TX_CYCLE);
+    concurrent_insert(txs, Arc::clone(mempool)).await;
+    assert_eq!(mempool.get_tx_cache().len().await, 10);
+
+    // The cheapest transaction is in a proposal, so a new one is evicted.
+    let rich_tx = mock_sender_txs(&[3], TX_CYCLE).remove(0);
+    mempool
+        .insert(Context::new(), rich_tx.clone())
+        .await
+        .unwrap();
+    assert!(mempool.get_tx_cache().contain(&proposed_tx.tx_hash).await);
+    assert!(mempool.get_tx_cache().contain(&rich_tx.tx_hash).await);
+    assert_eq!(mempool.get_tx_cache().len().await, 10);
+}
+
+#[tokio::test]
+async fn test_priority_evict_by_nonce() {
+    let mut mempool = new_priority_mempool(3, CYCLE_LIMIT).await;
+    mempool.tx_cache.set_sequential_nonce(true);
+    let mempool = &Arc::new(mempool);
+
+    let sender_tx = |cycles_price| {
+        let priv_key = Secp256k1PrivateKey::generate(&mut OsRng);
+        let pub_key = priv_key.pub_key();
+        let tx =
+            mock_priced_tx(&priv_key, &pub_key, TIMEOUT, true, cycles_price, TX_CYCLE);
+        (mock_sequential_tx(&priv_key, &tx, 0), priv_key)
+    };
+
+    let (tx_0, priv_key) = sender_tx(1);
+    let tx_1 =
+        mock_replacement_tx(&priv_key, &mock_sequential_tx(&priv_key, &tx_0, 1), 2);
+    let (other_tx, _) = sender_tx(5);
+    for tx in [&tx_0, &tx_1, &other_tx].iter() {
+        mempool.insert(Context::new(), (*tx).clone()).await.unwrap();
+    }
+
+    // Nonce 1 would wait for the cheaper nonce 0 forever, so nonce 1 is
+    // evicted instead.
+    let (rich_tx, _) = sender_tx(3);
+    mempool
+        .insert(Context::new(), rich_tx.clone())
+        .await
+        .unwrap();
+    assert!(mempool.get_tx_cache().contain(&tx_0.tx_hash).await);
+    assert!(!mempool.get_tx_cache().contain(&tx_1.tx_hash).await);
+    assert_eq!(mempool.get_tx_cache().len().await, 3);
+
+    // Then nonce 0 is the highest one of its sender.
+    let (rich_tx, _) = sender_tx(3);
+    mempool
+        .insert(Context::new(), rich_tx.clone())
+        .await
+        .unwrap();
+    assert!(!mempool.get_tx_cache().contain(&tx_0.tx_hash).await);
+    assert!(mempool.get_tx_cache().contain(&other_tx.tx_hash).await);
+    assert_eq!(mempool.get_tx_cache().len().await, 3);
+}
+
+#[tokio::test]
+async fn test_replace_by_fee() {
+    let mempool = &Arc::new(new_sequential_nonce_mempool(false).await);
+    let mut events = Box::pin(mempool.subscribe_events());
+
+    let priv_key = Secp256k1PrivateKey::generate(&mut OsRng);
+    let pub_key = priv_key.pub_key();
+    let tx = mock_priced_tx(&priv_key, &pub_key, TIMEOUT, true, 2, TX_CYCLE);
+    mempool.insert(Context::new(), tx.clone()).await.unwrap();
+
+    // A replacement paying no more than the pending one is rejected.
+    let cheap_tx = mock_replacement_tx(&priv_key, &tx, 2);
+    assert!(mempool
+        .insert(Context::new(), cheap_tx.clone())
+        .await
+        .is_err());
+    assert!(!mempool.get_tx_cache().contain(&cheap_tx.tx_hash).await);
+
+    let rich_tx = mock_replacement_tx(&priv_key, &tx, 3);
+    mempool
+        .insert(Context::new(), rich_tx.clone())
+        .await
+        .unwrap();
+    assert!(mempool.get_tx_cache().contain(&rich_tx.tx_hash).await);
+    assert!(!mempool.get_tx_cache().contain(&tx.tx_hash).await);
+    assert_eq!(mempool.get_tx_cache().len().await, 1);
+    assert_eq!(
+        events.next().await,
+        Some(MemPoolEvent::Replaced {
+            old_tx_hash: tx.tx_hash.clone(),
+            new_tx_hash: rich_tx.tx_hash.clone(),
+        })
+    );
+
+    let mixed_tx_hashes =
+        exec_package(Arc::clone(mempool), CYCLE_LIMIT, TX_NUM_LIMIT).await;
+    assert_eq!(mixed_tx_hashes.order_tx_hashes, vec![rich_tx.tx_hash]);
+}
+
+#[tokio::test]
+async fn test_flush_replaced_tx() {
+    let mempool = &Arc::new(new_sequential_nonce_mempool(false).await);
+
+    let priv_key = Secp256k1PrivateKey::generate(&mut OsRng);
+    let pub_key = priv_key.pub_key();
+    let tx = mock_priced_tx(&priv_key, &pub_key, TIMEOUT, true, 2, TX_CYCLE);
+    mempool.insert(Context::new(), tx.clone()).await.unwrap();
+    let rich_tx = mock_replacement_tx(&priv_key, &tx, 3);
+    mempool
+        .insert(Context::new(), rich_tx.clone())
+        .await
+        .unwrap();
+
+    // The replaced one is committed by a proposal built before the replacement.
+    exec_flush(vec![tx.tx_hash.clone()], Arc::clone(mempool)).await;
+    assert!(!mempool.get_tx_cache().contain(&rich_tx.tx_hash).await);
+    assert_eq!(mempool.get_tx_cache().len().await, 0);
+
+    let mixed_tx_hashes =
+        exec_package(Arc::clone(mempool), CYCLE_LIMIT, TX_NUM_LIMIT).await;
+    assert!(mixed_tx_hashes.order_tx_hashes.is_empty());
+}
+
+#[tokio::test]
+async fn test_pending_pledges() {
+    let adapter = HashMemPoolAdapter {
+        balance: Some(3 * TX_CYCLE),
+        ..HashMemPoolAdapter::new()
+    };
+    let mempool = &Arc::new(HashMemPool::new(POOL_SIZE, false, adapter, vec![]).await);
+    mempool.set_args(TIMEOUT_GAP, CYCLE_LIMIT, MAX_TX_SIZE);
+
+    // The balance covers the pledges of three pending txs of the sender.
+    let txs = mock_sender_txs(&[1; 4], TX_CYCLE);
+    for tx in txs[..3].iter() {
+        mempool.insert(Context::new(), tx.clone()).await.unwrap();
+    }
+    assert!(mempool
+        .insert(Context::new(), txs[3].clone())
+        .await
+        .is_err());
+
+    // The pledges of other senders are not counted.
+    let other_tx = mock_sender_txs(&[1], TX_CYCLE).remove(0);
+    mempool.insert(Context::new(), other_tx).await.unwrap();
+
+    // A committed tx no longer pledges.
+    exec_flush(vec![txs[0].tx_hash.clone()], Arc::clone(mempool)).await;
+    mempool
+        .insert(Context::new(), txs[3].clone())
+        .await
+        .unwrap();
+    let pending_txs = mempool
+        .get_tx_cache()
+        .pending_txs_of(&txs[0].raw.sender)
+        .await;
+    assert_eq!(pending_txs, txs[1..].to_vec());
+}
+
+#[tokio::test]
+async fn test_package_by_nonce() {
+    for enable_priority in [false, true].iter() {
+        let mempool = &Arc::new(new_sequential_nonce_mempool(*enable_priority).await);
+
+        let priv_key = Secp256k1PrivateKey::generate(&mut OsRng);
+        let pub_key = priv_key.pub_key();
+        let tx = mock_signed_tx(&priv_key, &pub_key, TIMEOUT, true);
+        let txs = [2, 0, 4, 1]
+            .iter()
+            .map(|nonce| mock_sequential_tx(&priv_key, &tx, *nonce))
+            .collect::<Vec<_>>();
+        concurrent_insert(txs.clone(), Arc::clone(mempool)).await;
+        assert_eq!(mempool.get_tx_cache().len().await, 4);
+
+        // Nonce 4 waits for nonce 3.
+        let mixed_tx_hashes =
+            exec_package(Arc::clone(mempool), CYCLE_LIMIT, TX_NUM_LIMIT).await;
+        assert_eq!(
+            mixed_tx_hashes.order_tx_hashes,
+            vec![
+                txs[1].tx_hash.clone(),
+                txs[3].tx_hash.clone(),
+                txs[0].tx_hash.clone(),
+            ]
+        );
+        assert!(mempool.get_tx_cache().contain(&txs[2].tx_hash).await);
+    }
+}
+
 #[rustfmt::skip]
 /// Bench in Intel(R) Core(TM) i7-4770HQ CPU @ 2.20GHz (8 x 2200):
 /// test tests::mempool::bench_check_sig             ... bench:   2,881,140 ns/iter (+/- 907,215)
@@ -18,11 +18,12 @@ use common_crypto::{
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{Context, MemPool, MemPoolAdapter, MixedTxHashes};
 use protocol::types::{
-    Address, Hash, RawTransaction, SignedTransaction, TransactionRequest,
+    sequential_nonce_to_hash, Address, Hash, PayloadEncoding, RawTransaction,
+    SignedTransaction, TransactionRequest,
 };
 use protocol::{Bytes, ProtocolResult};
 
-use crate::{check_dup_order_hashes, HashMemPool, MemPoolError};
+use crate::{check_dup_order_hashes, HashMemPool, MemPoolError, MemPoolEvent};
 
 const CYCLE_LIMIT: u64 = 1_000_000;
 const TX_NUM_LIMIT: u64 = 10_000;
@@ -35,12 +36,15 @@ const TX_CYCLE: u64 = 1;
 
 pub struct HashMemPoolAdapter {
     network_txs: CHashMap<Hash, SignedTransaction>,
+    // The balance of every sender, unlimited if none
+    balance: Option<u64>,
 }
 
 impl HashMemPoolAdapter {
     fn new() -> HashMemPoolAdapter {
         HashMemPoolAdapter {
             network_txs: CHashMap::new(),
+            balance: None,
         }
     }
 }
@@ -83,8 +87,28 @@ impl MemPoolAdapter for HashMemPoolAdapter {
     async fn check_transaction(
         &self,
         _ctx: Context,
-        _tx: &SignedTransaction,
+        tx: &SignedTransaction,
+        pending_txs: &[SignedTransaction],
     ) -> ProtocolResult<()> {
+        let balance = match self.balance {
+            Some(balance) => balance,
+            None => return Ok(()),
+        };
+
+        // Each tx pledges its cycles limit
+        let pledge = pending_txs
+            .iter()
+            .chain(std::iter::once(tx))
+            .map(|tx| tx.raw.cycles_limit)
+            .sum::<u64>();
+        if pledge > balance {
+            return Err(MemPoolError::InsufficientFee {
+                tx_hash: tx.tx_hash.clone(),
+                pledge,
+                balance,
+            }
+            .into());
+        }
         Ok(())
     }
 
@@ -132,6 +156,18 @@ fn mock_txs(
     vec
 }
 
+/// Mock transactions of one sender, one per given cycles price.
+fn mock_sender_txs(cycles_prices: &[u64], cycles_limit: u64) -> Vec<SignedTransaction> {
+    let priv_key = Secp256k1PrivateKey::generate(&mut OsRng);
+    let pub_key = priv_key.pub_key();
+    cycles_prices
+        .iter()
+        .map(|price| {
+            mock_priced_tx(&priv_key, &pub_key, TIMEOUT, true, *price, cycles_limit)
+        })
+        .collect()
+}
+
 fn default_mempool_sync() -> HashMemPool<HashMemPoolAdapter> {
     let mut rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(default_mempool())
@@ -148,11 +184,32 @@ async fn new_mempool(
     max_tx_size: u64,
 ) -> HashMemPool<HashMemPoolAdapter> {
     let adapter = HashMemPoolAdapter::new();
-    let mempool = HashMemPool::new(pool_size, adapter, vec![]).await;
+    let mempool = HashMemPool::new(pool_size, false, adapter, vec![]).await;
     mempool.set_args(timeout_gap, cycles_limit, max_tx_size);
     mempool
 }
 
+async fn new_priority_mempool(
+    pool_size: usize,
+    cycles_limit: u64,
+) -> HashMemPool<HashMemPoolAdapter> {
+    let adapter = HashMemPoolAdapter::new();
+    let mempool = HashMemPool::new(pool_size, true, adapter, vec![]).await;
+    mempool.set_args(TIMEOUT_GAP, cycles_limit, MAX_TX_SIZE);
+    mempool
+}
+
+async fn new_sequential_nonce_mempool(
+    enable_priority: bool,
+) -> HashMemPool<HashMemPoolAdapter> {
+    let adapter = HashMemPoolAdapter::new();
+    let mut mempool =
+        HashMemPool::new(POOL_SIZE, enable_priority, adapter, vec![]).await;
+    mempool.tx_cache.set_sequential_nonce(true);
+    mempool.set_args(TIMEOUT_GAP, CYCLE_LIMIT, MAX_TX_SIZE);
+    mempool
+}
+
 async fn check_hash(tx: &SignedTransaction) -> ProtocolResult<()> {
     let mut raw = tx.raw.clone();
     let raw_bytes = raw.encode().await?;
@@ -285,6 +342,17 @@ fn mock_signed_tx(
     pub_key: &Secp256k1PublicKey,
     timeout: u64,
     valid: bool,
+) -> SignedTransaction {
+    mock_priced_tx(priv_key, pub_key, timeout, valid, 1, TX_CYCLE)
+}
+
+fn mock_priced_tx(
+    priv_key: &Secp256k1PrivateKey,
+    pub_key: &Secp256k1PublicKey,
+    timeout: u64,
+    valid: bool,
+    cycles_price: u64,
+    cycles_limit: u64,
 ) -> SignedTransaction {
     let nonce = Hash::digest(Bytes::from(get_random_bytes(10)));
 
@@ -292,13 +360,14 @@ fn mock_signed_tx(
         service_name: "test".to_owned(),
         method: "test".to_owned(),
         payload: "test".to_owned(),
+        encoding: PayloadEncoding::Json,
     };
     let mut raw = RawTransaction {
         chain_id: nonce.clone(),
         nonce,
         timeout,
-        cycles_limit: TX_CYCLE,
-        cycles_price: 1,
+        cycles_limit,
+        cycles_price,
         request,
         sender: Address::from_pubkey_bytes(pub_key.to_bytes()).unwrap(),
     };
@@ -320,6 +389,51 @@ fn mock_signed_tx(
     }
 }
 
+/// Mock a transaction of the same sender and nonce as `tx`, paying another
+/// cycles price.
+fn mock_replacement_tx(
+    priv_key: &Secp256k1PrivateKey,
+    tx: &SignedTransaction,
+    cycles_price: u64,
+) -> SignedTransaction {
+    let mut raw = tx.raw.clone();
+    raw.cycles_price = cycles_price;
+
+    let raw_bytes = executor::block_on(async { raw.encode().await.unwrap() });
+    let tx_hash = Hash::digest(raw_bytes);
+    let signature =
+        Secp256k1::sign_message(&tx_hash.as_bytes(), &priv_key.to_bytes()).unwrap();
+
+    SignedTransaction {
+        raw,
+        tx_hash,
+        pubkey: tx.pubkey.clone(),
+        signature: signature.to_bytes(),
+    }
+}
+
+/// Mock a transaction of the same sender as `tx`, with the sequential `nonce`.
+fn mock_sequential_tx(
+    priv_key: &Secp256k1PrivateKey,
+    tx: &SignedTransaction,
+    nonce: u64,
+) -> SignedTransaction {
+    let mut raw = tx.raw.clone();
+    raw.nonce = sequential_nonce_to_hash(nonce);
+
+    let raw_bytes = executor::block_on(async { raw.encode().await.unwrap() });
+    let tx_hash = Hash::digest(raw_bytes);
+    let signature =
+        Secp256k1::sign_message(&tx_hash.as_bytes(), &priv_key.to_bytes()).unwrap();
+
+    SignedTransaction {
+        raw,
+        tx_hash,
+        pubkey: tx.pubkey.clone(),
+        signature: signature.to_bytes(),
+    }
+}
+
 fn get_random_bytes(len: usize) -> Vec<u8> {
     (0..len).map(|_| random::<u8>()).collect()
 }
@@ -1,11 +1,14 @@
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use crossbeam_queue::ArrayQueue;
+use tokio::sync::Mutex;
 
 use protocol::traits::MixedTxHashes;
-use protocol::types::{Hash, SignedTransaction};
-use protocol::ProtocolResult;
+use protocol::types::{sequential_nonce_enabled, Address, Hash, SignedTransaction};
+use protocol::{Bytes, ProtocolResult};
 
 use crate::map::Map;
 use crate::MemPoolError;
@@ -59,6 +62,11 @@ impl TxWrapper {
         self.removed.load(Ordering::SeqCst)
     }
 
+    #[inline]
+    pub(crate) fn tx(&self) -> &SignedTransaction {
+        &self.tx
+    }
+
     #[inline]
     fn is_proposed(&self) -> bool {
         self.proposed.load(Ordering::SeqCst)
@@ -95,6 +103,45 @@ impl Stage {
     }
 }
 
+/// A transaction competing for a slot while packaging in priority mode.
+///
+/// Transactions with a higher `cycles_price` come first. Among equal prices
+/// the one inserted earlier wins, so that packaging stays deterministic.
+struct PriorityTx {
+    tx: SharedTx,
+    /// Position in the incumbent queue, namely the insertion order.
+    seq: usize,
+}
+
+impl PriorityTx {
+    #[inline]
+    fn cycles_price(&self) -> u64 {
+        self.tx.tx.raw.cycles_price
+    }
+}
+
+impl PartialEq for PriorityTx {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for PriorityTx {}
+
+impl PartialOrd for PriorityTx {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PriorityTx {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.cycles_price()
+            .cmp(&other.cycles_price())
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
 /// Queue role. Incumbent is for insertion and package.
 struct QueueRole {
     incumbent: Arc<ArrayQueue<SharedTx>>,
@@ -112,6 +159,26 @@ struct QueueRole {
 /// transactions pop from `queue_0` and push into `queue_1` while new
 /// transactions still insert into `queue_0` concurrently. while `queue_0` pop
 /// out, `queue_1` switch to insertion queue.
+///
+/// In priority mode, package orders transactions by `cycles_price` instead of
+/// insertion order and stops filling a stage once either `tx_num_limit` or
+/// the block `cycles_limit` is reached. A full pool evicts its cheapest
+/// transaction in favour of a better paying one rather than rejecting it. The
+/// `price_index` is served for finding the cheapest one, and its lock is held
+/// through the insertion, so that concurrent insertions never evict the same
+/// transaction or overshoot the pool size. Transactions from propose-sync are
+/// in a proposal, so they are never evicted. With sequential nonces, only the
+/// transaction of the highest nonce of a sender is evicted, as the ones after
+/// an evicted nonce could never be packaged.
+///
+/// With sequential nonces, transactions of the same sender are packaged in
+/// nonce order, and the ones after a gap of nonces are left in the pool.
+///
+/// A pending transaction can be replaced by a new one of the same sender and
+/// nonce paying a strictly higher `cycles_price`. The `nonce_index` is served
+/// for finding the transaction to be replaced. The replaced one may still be
+/// committed by a proposal built before the replacement, so it is kept in
+/// `replaced` until it times out, to drop its replacement in that case.
 pub struct TxCache {
     /// One queue.
     queue_0: Arc<ArrayQueue<SharedTx>>,
@@ -119,6 +186,21 @@ pub struct TxCache {
     queue_1: Arc<ArrayQueue<SharedTx>>,
     /// A map for randomly search and removal.
     map: Map<SharedTx>,
+    /// A map from the sender and nonce of a transaction, see `nonce_key`, to
+    /// the transaction. Entries of removed transactions may be left behind,
+    /// so check the transaction is still pending before using it.
+    nonce_index: Map<SharedTx>,
+    /// A map from the hash of a replaced transaction to the transaction.
+    replaced: Map<SharedTx>,
+    /// The new transactions ordered by `price_key`, only kept in priority
+    /// mode. Entries of transactions dropped on a full queue may be left
+    /// behind, so check the transaction is still pending before evicting it.
+    price_index: Mutex<BTreeMap<(u64, Hash), SharedTx>>,
+    /// A map from the sender to its transactions in insertion order, see
+    /// `pending_txs_of`. Entries of transactions dropped on a full queue may
+    /// be left behind, so check the transaction is still pending before using
+    /// it.
+    sender_index: Mutex<HashMap<Address, Vec<SharedTx>>>,
     /// This is used to pick a queue for insertion,
     /// If true selects `queue_0`, else `queue_1`.
     is_zero: AtomicBool,
@@ -127,6 +209,11 @@ pub struct TxCache {
     /// still insert into the old queue. We use this state to make sure
     /// switch insertions *happen-before* old queue re-pop.
     concurrent_count: AtomicUsize,
+    /// Package by `cycles_price` rather than in insertion order.
+    priority: bool,
+    /// Each sender must use nonces 0, 1, 2... in order, see
+    /// `sequential_nonce_enabled`.
+    sequential_nonce: bool,
 }
 
 impl TxCache {
@@ -135,11 +222,36 @@ impl TxCache {
             queue_0: Arc::new(ArrayQueue::new(pool_size * 2)),
             queue_1: Arc::new(ArrayQueue::new(pool_size * 2)),
             map: Map::new(pool_size * 2),
+            nonce_index: Map::new(pool_size * 2),
+            replaced: Map::new(pool_size),
+            price_index: Mutex::new(BTreeMap::new()),
+            sender_index: Mutex::new(HashMap::new()),
             is_zero: AtomicBool::new(true),
             concurrent_count: AtomicUsize::new(0),
+            priority: false,
+            sequential_nonce: sequential_nonce_enabled(),
         }
     }
 
+    pub fn with_priority(pool_size: usize) -> Self {
+        TxCache {
+            priority: true,
+            ..TxCache::new(pool_size)
+        }
+    }
+
+    pub fn is_priority(&self) -> bool {
+        self.priority
+    }
+
+    pub fn set_sequential_nonce(&mut self, enabled: bool) {
+        self.sequential_nonce = enabled;
+    }
+
+    pub fn is_sequential_nonce(&self) -> bool {
+        self.sequential_nonce
+    }
+
     pub async fn len(&self) -> usize {
         self.map.len().await
     }
@@ -151,7 +263,7 @@ impl TxCache {
         let tx_hash = signed_tx.tx_hash.clone();
         let tx_wrapper = TxWrapper::new(signed_tx);
         let shared_tx = Arc::new(tx_wrapper);
-        self.insert(tx_hash, shared_tx).await
+        self.insert_or_evict(tx_hash, shared_tx, None).await
     }
 
     pub async fn insert_propose_tx(
@@ -161,7 +273,35 @@ impl TxCache {
         let tx_hash = signed_tx.tx_hash.clone();
         let tx_wrapper = TxWrapper::propose(signed_tx);
         let shared_tx = Arc::new(tx_wrapper);
-        self.insert(tx_hash, shared_tx).await
+        self.insert_or_evict(tx_hash, shared_tx, None).await
+    }
+
+    /// Insert a new transaction in priority mode, evicting the cheapest one if
+    /// the pool is full, see `insert_or_evict`.
+    pub async fn insert_new_tx_or_evict(
+        &self,
+        signed_tx: SignedTransaction,
+        pool_size: usize,
+    ) -> ProtocolResult<()> {
+        let tx_hash = signed_tx.tx_hash.clone();
+        let tx_wrapper = TxWrapper::new(signed_tx);
+        let shared_tx = Arc::new(tx_wrapper);
+        self.insert_or_evict(tx_hash, shared_tx, Some(pool_size))
+            .await
+    }
+
+    /// Insert a transaction of propose-sync in priority mode, evicting the
+    /// cheapest one if the pool is full, see `insert_or_evict`.
+    pub async fn insert_propose_tx_or_evict(
+        &self,
+        signed_tx: SignedTransaction,
+        pool_size: usize,
+    ) -> ProtocolResult<()> {
+        let tx_hash = signed_tx.tx_hash.clone();
+        let tx_wrapper = TxWrapper::propose(signed_tx);
+        let shared_tx = Arc::new(tx_wrapper);
+        self.insert_or_evict(tx_hash, shared_tx, Some(pool_size))
+            .await
     }
 
     pub async fn show_unknown(&self, tx_hashes: &[Hash]) -> Vec<Hash> {
@@ -177,42 +317,66 @@ impl TxCache {
     }
 
     pub async fn flush(&self, tx_hashes: &[Hash], current_height: u64, timeout: u64) {
+        let mut nonce_keys = Vec::with_capacity(tx_hashes.len());
+        let mut removed_txs = Vec::with_capacity(tx_hashes.len());
         for tx_hash in tx_hashes {
             let opt = self.map.get(tx_hash).await;
             if let Some(shared_tx) = opt {
                 shared_tx.set_removed();
+                nonce_keys.push(nonce_key(&shared_tx.tx));
+                removed_txs.push(shared_tx);
+            } else if let Some(replaced_tx) = self.replaced.get(tx_hash).await {
+                // The nonce is used by the replaced one, so the replacement
+                // can never be committed.
+                let key = nonce_key(&replaced_tx.tx);
+                if let Some(pending_tx) = self.nonce_index.get(&key).await {
+                    pending_tx.set_removed();
+                    self.map.remove(&pending_tx.tx.tx_hash).await;
+                    removed_txs.push(pending_tx);
+                }
+                nonce_keys.push(key);
             }
         }
         // Dividing set removed and remove into two loops is to avoid lock competition.
         self.map.remove_batch(tx_hashes).await;
+        self.nonce_index.remove_batch(&nonce_keys).await;
+        self.unindex_price(&removed_txs).await;
+        self.unindex_sender(&removed_txs).await;
+        self.replaced.remove_batch(tx_hashes).await;
+        // A timeout transaction can no longer be committed.
+        self.replaced
+            .retain(|shared_tx| !shared_tx.is_timeout(current_height, timeout))
+            .await;
         self.flush_incumbent_queue(current_height, timeout).await;
     }
 
     pub async fn package(
         &self,
-        _cycles_limit: u64,
+        cycles_limit: u64,
         tx_num_limit: u64,
         current_height: u64,
         timeout: u64,
     ) -> ProtocolResult<MixedTxHashes> {
+        if self.priority {
+            return self
+                .package_by_priority(cycles_limit, tx_num_limit, current_height, timeout)
+                .await;
+        }
+
         let queue_role = self.get_queue_role();
 
         let mut order_tx_hashes = Vec::new();
         let mut propose_tx_hashes = Vec::new();
-        let mut timeout_tx_hashes = Vec::new();
-
-        let mut tx_count: u64 = 0;
-        let mut stage = Stage::OrderTxs;
+        let mut timeout_txs = Vec::new();
+        let mut valid_txs = Vec::new();
 
         loop {
             if let Ok(shared_tx) = queue_role.incumbent.pop() {
-                let tx_hash = &shared_tx.tx.tx_hash;
-
                 if shared_tx.is_removed() {
                     continue;
                 }
                 if shared_tx.is_timeout(current_height, timeout) {
-                    timeout_tx_hashes.push(tx_hash.clone());
+                    timeout_txs.push(Arc::clone(&shared_tx));
                     continue;
                 }
                 // After previous filter, tx are valid and should cache in temp_queue.
@@ -227,23 +391,104 @@ impl TxCache {
                     );
                     self.map.remove(&shared_tx.tx.tx_hash).await;
                 }
+                valid_txs.push(shared_tx);
+            } else {
+                // Switch queue_roles
+                let new_role = self.switch_queue_role();
+                // Transactions may insert into previous incumbent queue during role switch.
+                self.process_omission_txs(new_role).await;
+                break;
+            }
+        }
+        // Remove timeout tx in map
+        self.remove_timeout_txs(&timeout_txs).await;
 
-                if stage == Stage::Finished
-                    || (stage == Stage::ProposeTxs && shared_tx.is_proposed())
-                {
+        if self.sequential_nonce {
+            valid_txs = order_by_nonce(valid_txs);
+        }
+
+        let mut tx_count: u64 = 0;
+        let mut stage = Stage::OrderTxs;
+
+        for shared_tx in valid_txs {
+            let tx_hash = &shared_tx.tx.tx_hash;
+
+            if stage == Stage::Finished
+                || (stage == Stage::ProposeTxs && shared_tx.is_proposed())
+            {
+                continue;
+            }
+            tx_count += 1;
+            if tx_count > tx_num_limit {
+                stage = stage.next();
+                tx_count = 1;
+            }
+
+            match stage {
+                Stage::OrderTxs => order_tx_hashes.push(tx_hash.clone()),
+                Stage::ProposeTxs => propose_tx_hashes.push(tx_hash.clone()),
+                Stage::Finished => {}
+            }
+        }
+
+        Ok(MixedTxHashes {
+            order_tx_hashes,
+            propose_tx_hashes,
+        })
+    }
+
+    /// Package transactions by `cycles_price`.
+    ///
+    /// Transactions of the same sender keep their insertion order, so senders
+    /// compete with their oldest pending transaction only. This prevents one
+    /// sender from crowding out others by reordering its own transactions,
+    /// while a better paying sender still goes ahead of the cheaper ones.
+    /// With sequential nonces, they are in nonce order instead, see
+    /// `sort_by_nonce`.
+    async fn package_by_priority(
+        &self,
+        cycles_limit: u64,
+        tx_num_limit: u64,
+        current_height: u64,
+        timeout: u64,
+    ) -> ProtocolResult<MixedTxHashes> {
+        let queue_role = self.get_queue_role();
+
+        let mut order_tx_hashes = Vec::new();
+        let mut propose_tx_hashes = Vec::new();
+        let mut timeout_txs = Vec::new();
+
+        let mut sender_queues: HashMap<Address, VecDeque<PriorityTx>> = HashMap::new();
+        let mut seq = 0;
+
+        loop {
+            if let Ok(shared_tx) = queue_role.incumbent.pop() {
+                if shared_tx.is_removed() {
                     continue;
                 }
-                tx_count += 1;
-                if tx_count > tx_num_limit {
-                    stage = stage.next();
-                    tx_count = 1;
+                if shared_tx.is_timeout(current_height, timeout) {
+                    timeout_txs.push(Arc::clone(&shared_tx));
+                    continue;
                 }
-
-                match stage {
-                    Stage::OrderTxs => order_tx_hashes.push(tx_hash.clone()),
-                    Stage::ProposeTxs => propose_tx_hashes.push(tx_hash.clone()),
-                    Stage::Finished => {}
+                // After previous filter, tx are valid and should cache in temp_queue.
+                if queue_role
+                    .candidate
+                    .push(Arc::<TxWrapper>::clone(&shared_tx))
+                    .is_err()
+                {
+                    log::error!(
+                        "[core_mempool]: candidate queue is full while package, delete {:?}",
+                        &shared_tx.tx.tx_hash
+                    );
+                    self.map.remove(&shared_tx.tx.tx_hash).await;
+                    continue;
                 }
+
+                sender_queues
+                    .entry(shared_tx.tx.raw.sender.clone())
+                    .or_insert_with(VecDeque::new)
+                    .push_back(PriorityTx { tx: shared_tx, seq });
+                seq += 1;
             } else {
                 // Switch queue_roles
                 let new_role = self.switch_queue_role();
@@ -253,7 +498,64 @@ impl TxCache {
             }
         }
         // Remove timeout tx in map
-        self.map.remove_batch(&timeout_tx_hashes).await;
+        self.remove_timeout_txs(&timeout_txs).await;
+
+        if self.sequential_nonce {
+            for sender_txs in sender_queues.values_mut() {
+                // Keep the slots of the sender in the insertion order.
+                let mut seqs = sender_txs.iter().map(|tx| tx.seq).collect::<Vec<_>>();
+                seqs.sort_unstable();
+                let mut txs = sender_txs.drain(..).map(|tx| tx.tx).collect::<Vec<_>>();
+                let len = sort_by_nonce(&mut txs);
+                sender_txs.extend(
+                    txs.into_iter()
+                        .take(len)
+                        .zip(seqs)
+                        .map(|(tx, seq)| PriorityTx { tx, seq }),
+                );
+            }
+        }
+
+        let mut heap = sender_queues
+            .values_mut()
+            .filter_map(VecDeque::pop_front)
+            .collect::<BinaryHeap<_>>();
+
+        let mut tx_count: u64 = 0;
+        let mut cycles_count: u64 = 0;
+        let mut stage = Stage::OrderTxs;
+
+        while let Some(PriorityTx { tx: shared_tx, .. }) = heap.pop() {
+            if let Some(next) = sender_queues
+                .get_mut(&shared_tx.tx.raw.sender)
+                .and_then(VecDeque::pop_front)
+            {
+                heap.push(next);
+            }
+
+            if stage == Stage::ProposeTxs && shared_tx.is_proposed() {
+                continue;
+            }
+            let tx_cycles = shared_tx.tx.raw.cycles_limit;
+            tx_count += 1;
+            cycles_count = cycles_count.saturating_add(tx_cycles);
+            if tx_count > tx_num_limit || cycles_count > cycles_limit {
+                stage = stage.next();
+                if stage == Stage::Finished {
+                    break;
+                }
+                tx_count = 1;
+                cycles_count = tx_cycles;
+            }
+
+            match stage {
+                Stage::OrderTxs => order_tx_hashes.push(shared_tx.tx.tx_hash.clone()),
+                Stage::ProposeTxs => {
+                    propose_tx_hashes.push(shared_tx.tx.tx_hash.clone())
+                }
+                Stage::Finished => {}
+            }
+        }
 
         Ok(MixedTxHashes {
             order_tx_hashes,
@@ -278,10 +580,99 @@ impl TxCache {
         Ok(())
     }
 
+    /// Check pool limit in priority mode. Once the pool is full, reject the
+    /// incoming transaction as `check_reach_limit` does unless it pays a
+    /// strictly higher `cycles_price` than an evictable one, see
+    /// `cheapest_evictable`. This rejects an underpriced transaction early,
+    /// while the eviction is taken on insertion by `insert_or_evict`.
+    pub async fn check_reach_limit_by_price(
+        &self,
+        pool_size: usize,
+        cycles_price: u64,
+    ) -> ProtocolResult<()> {
+        let mut price_index = self.price_index.lock().await;
+        if self.len().await < pool_size {
+            return Ok(());
+        }
+
+        match self
+            .cheapest_evictable(&mut price_index, cycles_price)
+            .await
+        {
+            Some(_) => Ok(()),
+            None => Err(MemPoolError::ReachLimit { pool_size }.into()),
+        }
+    }
+
+    /// Find the pending transaction of the same sender and nonce that `tx`
+    /// replaces. The replacement must pay a strictly higher `cycles_price`.
+    pub async fn check_replacement(
+        &self,
+        tx: &SignedTransaction,
+    ) -> ProtocolResult<Option<SharedTx>> {
+        let pending_tx = match self.nonce_index.get(&nonce_key(tx)).await {
+            Some(shared_tx) => shared_tx,
+            None => return Ok(None),
+        };
+        // The index may be left pointing to a flushed or timeout transaction.
+        if pending_tx.is_removed() || !self.contain(&pending_tx.tx.tx_hash).await {
+            return Ok(None);
+        }
+
+        if pending_tx.tx.raw.cycles_price < tx.raw.cycles_price {
+            Ok(Some(pending_tx))
+        } else {
+            Err(MemPoolError::ReplaceUnderpriced {
+                tx_hash: tx.tx_hash.clone(),
+                pending_tx_hash: pending_tx.tx.tx_hash.clone(),
+                cycles_price: pending_tx.tx.raw.cycles_price,
+            }
+            .into())
+        }
+    }
+
+    /// Drop a transaction picked by `check_replacement`. The queue entry is
+    /// skipped by the next package or flush.
+    pub async fn replace(&self, shared_tx: &SharedTx) {
+        shared_tx.set_removed();
+        self.map.remove(&shared_tx.tx.tx_hash).await;
+        self.unindex_price(&[Arc::clone(shared_tx)]).await;
+        self.unindex_sender(&[Arc::clone(shared_tx)]).await;
+        self.replaced
+            .replace(shared_tx.tx.tx_hash.clone(), Arc::clone(shared_tx))
+            .await;
+        log::info!(
+            "[core_mempool]: replace tx {:?} with cycles_price {}",
+            shared_tx.tx.tx_hash,
+            shared_tx.tx.raw.cycles_price
+        );
+    }
+
     pub async fn contain(&self, tx_hash: &Hash) -> bool {
         self.map.contains_key(tx_hash).await
     }
 
+    /// The pending transactions of a sender, in insertion order.
+    pub async fn pending_txs_of(&self, sender: &Address) -> Vec<SignedTransaction> {
+        let indexed = match self.sender_index.lock().await.get(sender) {
+            Some(shared_txs) => shared_txs.clone(),
+            None => return vec![],
+        };
+
+        let mut pending_txs = Vec::with_capacity(indexed.len());
+        let mut stale_txs = vec![];
+        for shared_tx in indexed {
+            if !shared_tx.is_removed() && self.contain(&shared_tx.tx.tx_hash).await {
+                pending_txs.push(shared_tx.tx.clone());
+            } else {
+                stale_txs.push(shared_tx);
+            }
+        }
+
+        self.unindex_sender(&stale_txs).await;
+        pending_txs
+    }
+
     pub async fn get(&self, tx_hash: &Hash) -> Option<SignedTransaction> {
         self.map
             .get(tx_hash)
@@ -297,6 +688,132 @@ impl TxCache {
         }
     }
 
+    /// Insert a transaction, and in priority mode index it by `price_key`
+    /// unless it is from propose-sync. With a `pool_size` in priority mode, a
+    /// full pool evicts its cheapest evictable transaction if the new one pays
+    /// a strictly higher `cycles_price`, otherwise the new one is rejected. The
+    /// lock of the `price_index` is held from the check of the pool size
+    /// through the insertion.
+    async fn insert_or_evict(
+        &self,
+        tx_hash: Hash,
+        shared_tx: SharedTx,
+        pool_size: Option<usize>,
+    ) -> ProtocolResult<()> {
+        if !self.priority {
+            return self.insert(tx_hash, shared_tx).await;
+        }
+
+        let mut price_index = self.price_index.lock().await;
+        if let Some(pool_size) = pool_size {
+            // Check before evicting, as a failed insertion can't bring the
+            // evicted transaction back.
+            self.check_exist(&tx_hash).await?;
+
+            if self.len().await >= pool_size {
+                let cycles_price = shared_tx.tx.raw.cycles_price;
+                let cheapest = match self
+                    .cheapest_evictable(&mut price_index, cycles_price)
+                    .await
+                {
+                    Some(cheapest) => cheapest,
+                    None => return Err(MemPoolError::ReachLimit { pool_size }.into()),
+                };
+
+                // The queue entry is skipped by the next package or flush.
+                cheapest.set_removed();
+                self.map.remove(&cheapest.tx.tx_hash).await;
+                price_index.remove(&price_key(&cheapest.tx));
+                self.unindex_sender(&[Arc::clone(&cheapest)]).await;
+                log::info!(
+                    "[core_mempool]: evict tx {:?} with cycles_price {}",
+                    cheapest.tx.tx_hash,
+                    cheapest.tx.raw.cycles_price
+                );
+            }
+        }
+
+        self.insert(tx_hash, Arc::clone(&shared_tx)).await?;
+        if !shared_tx.is_proposed() {
+            price_index.insert(price_key(&shared_tx.tx), shared_tx);
+        }
+        Ok(())
+    }
+
+    /// Return the cheapest pending transaction in the `price_index` paying a
+    /// lower `cycles_price` than the given one, dropping the entries of the
+    /// removed ones before it. With sequential nonces, only the transaction of
+    /// the highest nonce of a sender is evictable.
+    async fn cheapest_evictable(
+        &self,
+        price_index: &mut BTreeMap<(u64, Hash), SharedTx>,
+        cycles_price: u64,
+    ) -> Option<SharedTx> {
+        let mut stale_keys = vec![];
+        let mut evictable = None;
+        for (key, shared_tx) in price_index.iter() {
+            if key.0 >= cycles_price {
+                break;
+            }
+
+            if shared_tx.is_removed() || !self.contain(&shared_tx.tx.tx_hash).await {
+                stale_keys.push(key.clone());
+            } else if !self.sequential_nonce || !self.has_higher_nonce(shared_tx).await {
+                evictable = Some(Arc::clone(shared_tx));
+                break;
+            }
+        }
+
+        for key in stale_keys.iter() {
+            price_index.remove(key);
+        }
+        evictable
+    }
+
+    /// Whether the sender of a transaction has a pending one of a higher
+    /// sequential nonce.
+    async fn has_higher_nonce(&self, shared_tx: &SharedTx) -> bool {
+        let nonce = shared_tx.tx.raw.sequential_nonce();
+        let sender_index = self.sender_index.lock().await;
+
+        sender_index
+            .get(&shared_tx.tx.raw.sender)
+            .map_or(false, |shared_txs| {
+                shared_txs.iter().any(|pending_tx| {
+                    !pending_tx.is_removed()
+                        && pending_tx.tx.raw.sequential_nonce() > nonce
+                })
+            })
+    }
+
+    async fn unindex_price(&self, removed_txs: &[SharedTx]) {
+        if !self.priority || removed_txs.is_empty() {
+            return;
+        }
+
+        let mut price_index = self.price_index.lock().await;
+        for shared_tx in removed_txs {
+            price_index.remove(&price_key(&shared_tx.tx));
+        }
+    }
+
+    async fn unindex_sender(&self, removed_txs: &[SharedTx]) {
+        if removed_txs.is_empty() {
+            return;
+        }
+
+        let mut sender_index = self.sender_index.lock().await;
+        for shared_tx in removed_txs {
+            let sender = &shared_tx.tx.raw.sender;
+            if let Some(shared_txs) = sender_index.get_mut(sender) {
+                shared_txs.retain(|indexed| !Arc::ptr_eq(indexed, shared_tx));
+                if shared_txs.is_empty() {
+                    sender_index.remove(sender);
+                }
+            }
+        }
+    }
+
     async fn insert(&self, tx_hash: Hash, shared_tx: SharedTx) -> ProtocolResult<()> {
         // If multiple transactions exactly the same insert concurrently,
         // this will prevent them to be both insert successfully into queue.
@@ -322,10 +839,31 @@ impl TxCache {
             self.map.remove(&tx_hash).await;
             Err(MemPoolError::Insert { tx_hash }.into())
         } else {
+            self.nonce_index
+                .replace(nonce_key(&shared_tx.tx), Arc::clone(&shared_tx))
+                .await;
+            self.sender_index
+                .lock()
+                .await
+                .entry(shared_tx.tx.raw.sender.clone())
+                .or_insert_with(Vec::new)
+                .push(shared_tx);
             Ok(())
         }
     }
 
+    async fn remove_timeout_txs(&self, timeout_txs: &[SharedTx]) {
+        let (tx_hashes, nonce_keys): (Vec<_>, Vec<_>) = timeout_txs
+            .iter()
+            .map(|shared_tx| (shared_tx.tx.tx_hash.clone(), nonce_key(&shared_tx.tx)))
+            .unzip();
+
+        self.map.remove_batch(&tx_hashes).await;
+        self.nonce_index.remove_batch(&nonce_keys).await;
+        self.unindex_price(timeout_txs).await;
+        self.unindex_sender(timeout_txs).await;
+    }
+
     // Process transactions insert into previous incumbent queue during role switch.
     async fn process_omission_txs(&self, queue_role: QueueRole) {
         'outer: loop {
@@ -352,17 +890,15 @@ impl TxCache {
 
     async fn flush_incumbent_queue(&self, current_height: u64, timeout: u64) {
         let queue_role = self.get_queue_role();
-        let mut timeout_tx_hashes = Vec::new();
+        let mut timeout_txs = Vec::new();
 
         loop {
             if let Ok(shared_tx) = queue_role.incumbent.pop() {
-                let tx_hash = &shared_tx.tx.tx_hash;
-
                 if shared_tx.is_removed() {
                     continue;
                 }
                 if shared_tx.is_timeout(current_height, timeout) {
-                    timeout_tx_hashes.push(tx_hash.clone());
+                    timeout_txs.push(Arc::clone(&shared_tx));
                     continue;
                 }
                 // After previous filter, tx are valid and should cache in temp_queue.
@@ -386,7 +922,7 @@ impl TxCache {
             }
         }
         // Remove timeout tx in map
-        self.map.remove_batch(&timeout_tx_hashes).await;
+        self.remove_timeout_txs(&timeout_txs).await;
     }
 
     fn switch_queue_role(&self) -> QueueRole {
@@ -407,6 +943,70 @@ impl TxCache {
     }
 }
 
+/// Reorder the transactions of each sender by `sort_by_nonce`, in the slots
+/// they take in `txs`, and drop the ones after a gap of nonces.
+fn order_by_nonce(txs: Vec<SharedTx>) -> Vec<SharedTx> {
+    let mut sender_slots: HashMap<Address, Vec<usize>> = HashMap::new();
+    for (slot, shared_tx) in txs.iter().enumerate() {
+        sender_slots
+            .entry(shared_tx.tx.raw.sender.clone())
+            .or_insert_with(Vec::new)
+            .push(slot);
+    }
+
+    let mut ordered_txs = vec![None; txs.len()];
+    for slots in sender_slots.values() {
+        let mut sender_txs = slots
+            .iter()
+            .map(|slot| Arc::clone(&txs[*slot]))
+            .collect::<Vec<_>>();
+        let len = sort_by_nonce(&mut sender_txs);
+
+        for (slot, shared_tx) in slots.iter().zip(sender_txs.into_iter().take(len)) {
+            ordered_txs[*slot] = Some(shared_tx);
+        }
+    }
+    ordered_txs.into_iter().flatten().collect()
+}
+
+/// Sort the transactions of one sender by sequential nonce, and return how
+/// many of them have nonces in a row. The ones after a gap can't be executed
+/// until the missing nonces arrive, so they are left in the pool.
+fn sort_by_nonce(txs: &mut [SharedTx]) -> usize {
+    txs.sort_by_key(|shared_tx| shared_tx.tx.raw.sequential_nonce());
+
+    let mut expect = match txs.first() {
+        Some(shared_tx) => shared_tx.tx.raw.sequential_nonce(),
+        None => return 0,
+    };
+    if expect.is_none() {
+        return 0;
+    }
+
+    let mut len = 0;
+    for shared_tx in txs.iter() {
+        if shared_tx.tx.raw.sequential_nonce() != expect {
+            break;
+        }
+        len += 1;
+        expect = expect.and_then(|nonce| nonce.checked_add(1));
+    }
+    len
+}
+
+/// Transactions of the same sender and nonce share the same key.
+fn nonce_key(tx: &SignedTransaction) -> Hash {
+    let mut bytes = tx.raw.sender.as_bytes().to_vec();
+    bytes.extend_from_slice(&tx.raw.nonce.as_bytes());
+    Hash::digest(Bytes::from(bytes))
+}
+
+/// The key of a transaction in the `price_index`. The cheapest transaction
+/// comes first, and ties are broken by the hash.
+fn price_key(tx: &SignedTransaction) -> (u64, Hash) {
+    (tx.raw.cycles_price, tx.tx_hash.clone())
+}
+
 #[cfg(test)]
 mod tests {
     extern crate test;
@@ -417,7 +1017,8 @@ mod tests {
     use test::Bencher;
 
     use protocol::types::{
-        Address, Bytes, Hash, RawTransaction, SignedTransaction, TransactionRequest,
+        Address, Bytes, Hash, PayloadEncoding, RawTransaction, SignedTransaction,
+        TransactionRequest,
     };
 
     use crate::map::Map;
@@ -460,6 +1061,7 @@ mod tests {
             service_name: "test".to_owned(),
             method: "test".to_owned(),
             payload: "test".to_owned(),
+            encoding: PayloadEncoding::Json,
         };
 
         let raw = RawTransaction {
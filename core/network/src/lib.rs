@@ -22,6 +22,7 @@ pub use config::NetworkConfig;
 pub use error::NetworkError;
 pub use message::{serde, serde_multi};
 pub use service::{NetworkService, NetworkServiceHandle};
+pub use traits::NetworkContext;
 
 #[cfg(feature = "diagnostic")]
 pub use peer_manager::diagnostic::{DiagnosticEvent, TrustReport};
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use core_storage::{adapter::rocks::RocksAdapter, ImplStorage};
+use framework::binding::state::RocksTrieDB;
+use protocol::traits::{Backup, BackupManifest, CommonStorage, Context};
+use protocol::ProtocolResult;
+
+use crate::MainError;
+
+pub const BACKUP_MANIFEST: &str = "manifest.json";
+
+/// Back up the block db and the trie db with RocksDB checkpoints. A backup is
+/// laid out the same as the data path, so it is restored by copying it to the
+/// data path.
+pub struct HotBackup {
+    block_db: Arc<RocksAdapter>,
+    trie_db: Arc<RocksTrieDB>,
+    max_open_files: i32,
+}
+
+impl HotBackup {
+    pub fn new(
+        block_db: Arc<RocksAdapter>,
+        trie_db: Arc<RocksTrieDB>,
+        max_open_files: i32,
+    ) -> Self {
+        Self {
+            block_db,
+            trie_db,
+            max_open_files,
+        }
+    }
+}
+
+#[async_trait]
+impl Backup for HotBackup {
+    async fn backup(
+        &self,
+        _ctx: Context,
+        path: PathBuf,
+    ) -> ProtocolResult<BackupManifest> {
+        if path.exists() {
+            return Err(MainError::Other(format!(
+                "backup path {:?} already exists",
+                path
+            ))
+            .into());
+        }
+        let rocksdb_path = path.join("rocksdb");
+        fs::create_dir_all(&rocksdb_path).map_err(MainError::Io)?;
+
+        // Back up the block db first, the state of its latest block is then in
+        // the trie db backed up later, since the trie nodes are written before
+        // the block is committed.
+        let block_path = rocksdb_path.join("block_data");
+        let column_families = self.block_db.create_checkpoint(&block_path)?;
+        let latest_block = {
+            let block_db = RocksAdapter::new(&block_path, self.max_open_files)?;
+            ImplStorage::new(Arc::new(block_db))
+                .get_latest_block(Context::new())
+                .await?
+        };
+        self.trie_db
+            .create_checkpoint(rocksdb_path.join("state_data"))?;
+
+        let manifest = BackupManifest {
+            height: latest_block.header.height,
+            state_root: latest_block.header.state_root,
+            column_families,
+        };
+        let manifest_json =
+            serde_json::to_string_pretty(&manifest).map_err(MainError::JSONParse)?;
+        fs::write(path.join(BACKUP_MANIFEST), manifest_json).map_err(MainError::Io)?;
+
+        log::info!(
+            "backup of height {} successfully to: {:?}",
+            manifest.height,
+            path
+        );
+        Ok(manifest)
+    }
+}
@@ -1,5 +1,9 @@
 #![allow(clippy::mutable_key_type)]
 
+mod backup;
+
+pub use backup::{HotBackup, BACKUP_MANIFEST};
+
 use derive_more::{Display, From};
 
 use protocol::{ProtocolError, ProtocolErrorKind};
@@ -24,6 +28,7 @@ use common_crypto::{
     BlsCommonReference, BlsPrivateKey, BlsPublicKey, PublicKey, Secp256k1,
     Secp256k1PrivateKey, ToPublicKey, UncompressedPublicKey,
 };
+use common_pubsub::ChainPubSub;
 use core_api::adapter::DefaultAPIAdapter;
 use core_api::config::{GraphQLConfig, GraphQLTLS};
 use core_consensus::fixed_types::{FixedBlock, FixedProof, FixedSignedTxs};
@@ -51,10 +56,11 @@ use framework::binding::state::RocksTrieDB;
 use framework::executor::{ServiceExecutor, ServiceExecutorFactory};
 use protocol::traits::{
     APIAdapter, CommonStorage, Context, MemPool, Network, NodeInfo, ServiceMapping,
-    Storage,
+    Storage, StorageAdapter,
 };
 use protocol::types::{
-    Address, Block, BlockHeader, Genesis, Hash, Metadata, Proof, Validator,
+    Address, Block, BlockHeader, Genesis, Hash, Metadata, PayloadEncoding, Proof,
+    Validator,
 };
 use protocol::{fixed_codec::FixedCodec, ProtocolResult};
 
@@ -112,6 +118,21 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
             protocol::init_address_hrp(hrp.into());
         }
 
+        // Set sequential nonce mode, it's chain wide and never changes
+        let sequential_nonce =
+            Metadata::get_sequential_nonce_from_json(metadata_payload.to_string());
+        protocol::types::init_sequential_nonce(sequential_nonce);
+
+        // Set the storage cycles, they are chain wide and never change
+        let storage_cycles =
+            Metadata::get_storage_cycles_from_json(metadata_payload.to_string());
+        protocol::types::init_storage_cycles(storage_cycles);
+
+        // Set the fee policy, it changes how every tx is executed
+        let fee_policy =
+            Metadata::get_fee_policy_from_json(metadata_payload.to_string());
+        protocol::types::init_fee_policy(fee_policy);
+
         // Init Block db
         let path_block = self.config.data_path_for_block();
         let rocks_adapter = Arc::new(RocksAdapter::new(
@@ -210,6 +231,12 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         let config = self.config;
         let service_mapping = self.service_mapping;
         // Init Block db
+        if config.rocksdb.tx_history == Some(0) {
+            return Err(MainError::Other(
+                "rocksdb.tx_history should be greater than 0".to_owned(),
+            )
+            .into());
+        }
         let path_block = config.data_path_for_block();
         log::info!("Data path for block: {:?}", path_block);
 
@@ -263,12 +290,32 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
 
         // Init trie db
         let path_state = config.data_path_for_state();
-        let trie_db = Arc::new(RocksTrieDB::new(
-            path_state,
-            config.executor.light,
-            config.rocksdb.max_open_files,
-            config.executor.triedb_cache_size,
-        )?);
+        let trie_db = match config.executor.state_history {
+            Some(0) => {
+                return Err(MainError::Other(
+                    "executor.state_history should be greater than 0".to_owned(),
+                )
+                .into())
+            }
+            Some(_) if config.executor.light => {
+                return Err(MainError::Other(
+                    "executor.state_history can not be set in light mode".to_owned(),
+                )
+                .into())
+            }
+            Some(_) => RocksTrieDB::new_with_pruning(
+                path_state,
+                config.rocksdb.max_open_files,
+                config.executor.triedb_cache_size,
+            )?,
+            None => RocksTrieDB::new(
+                path_state,
+                config.executor.light,
+                config.rocksdb.max_open_files,
+                config.executor.triedb_cache_size,
+            )?,
+        };
+        let trie_db = Arc::new(trie_db);
 
         // Init full transactions wal
         let txs_wal_path = config.data_path_for_txs_wal().to_str().unwrap().to_string();
@@ -280,10 +327,15 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
             .to_str()
             .unwrap()
             .to_string();
-        let consensus_wal = Arc::new(ConsensusWal::new(consensus_wal_path));
+        let consensus_wal = Arc::new(ConsensusWal::new(
+            consensus_wal_path,
+            config.consensus.wal_retained_files,
+        ));
 
-        // Recover signed transactions of current height
+        // Recover signed transactions of current height, the wal of executed
+        // heights may be left if the node crashed before removing it
         let current_block = storage.get_latest_block(Context::new()).await?;
+        txs_wal.remove(current_block.header.exec_height)?;
         let current_stxs = txs_wal.load_by_height(current_block.header.height + 1);
         log::info!(
             "Recover {} tx of height {} from wal",
@@ -304,6 +356,7 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         let mempool = Arc::new(
             HashMemPool::new(
                 config.mempool.pool_size as usize,
+                config.mempool.enable_priority,
                 mempool_adapter,
                 current_stxs,
             )
@@ -328,12 +381,22 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         let my_pubkey = my_privkey.pub_key();
         let my_address = Address::from_pubkey_bytes(my_pubkey.to_uncompressed_bytes())?;
 
+        // A follower node only syncs blocks, the overlord engine is not run, and
+        // it takes transactions only if it forwards them to the validators.
+        let follower = config.follower.is_some();
+        let accept_txs = config
+            .follower
+            .as_ref()
+            .map_or(true, |follower| follower.forward_txs);
+
         // Get metadata
         let api_adapter = DefaultAPIAdapter::<ServiceExecutorFactory, _, _, _, _>::new(
             Arc::clone(&mempool),
             Arc::clone(&storage),
             Arc::clone(&trie_db),
             Arc::clone(&service_mapping),
+            config.executor.state_history,
+            accept_txs,
         );
 
         let exec_resp = api_adapter
@@ -346,6 +409,7 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
                 "metadata".to_string(),
                 "get_metadata".to_string(),
                 "".to_string(),
+                PayloadEncoding::Json,
             )
             .await?;
 
@@ -368,10 +432,12 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         );
 
         // register broadcast new transaction
-        network_service.register_endpoint_handler(
-            END_GOSSIP_NEW_TXS,
-            NewTxsHandler::new(Arc::clone(&mempool)),
-        )?;
+        if accept_txs {
+            network_service.register_endpoint_handler(
+                END_GOSSIP_NEW_TXS,
+                NewTxsHandler::new(Arc::clone(&mempool)),
+            )?;
+        }
 
         // register pull txs from other node
         network_service.register_endpoint_handler(
@@ -464,6 +530,9 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         let crypto =
             Arc::new(OverlordCrypto::new(bls_priv_key, bls_pub_keys, common_ref));
 
+        // Init chain data publisher for API subscriptions
+        let chain_pubsub = ChainPubSub::default();
+
         let mut consensus_adapter =
             OverlordConsensusAdapter::<ServiceExecutorFactory, _, _, _, _, _>::new(
                 Arc::new(network_service.handle()),
@@ -474,6 +543,7 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
                 status_agent.clone(),
                 Arc::clone(&crypto),
                 config.consensus.overlord_gap,
+                chain_pubsub.clone(),
             )?;
 
         let exec_demon = consensus_adapter.take_exec_demon();
@@ -491,8 +561,12 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
             Arc::clone(&consensus_wal),
         ));
 
-        consensus_adapter
-            .set_overlord_handler(overlord_consensus.get_overlord_handler());
+        if follower {
+            log::info!("run as a follower");
+        } else {
+            consensus_adapter
+                .set_overlord_handler(overlord_consensus.get_overlord_handler());
+        }
 
         let synchronization = Arc::new(OverlordSynchronization::<_>::new(
             config.consensus.sync_txs_chunk_size,
@@ -543,22 +617,24 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         }
 
         // register consensus
-        network_service.register_endpoint_handler(
-            END_GOSSIP_SIGNED_PROPOSAL,
-            ProposalMessageHandler::new(Arc::clone(&overlord_consensus)),
-        )?;
-        network_service.register_endpoint_handler(
-            END_GOSSIP_AGGREGATED_VOTE,
-            QCMessageHandler::new(Arc::clone(&overlord_consensus)),
-        )?;
-        network_service.register_endpoint_handler(
-            END_GOSSIP_SIGNED_VOTE,
-            VoteMessageHandler::new(Arc::clone(&overlord_consensus)),
-        )?;
-        network_service.register_endpoint_handler(
-            END_GOSSIP_SIGNED_CHOKE,
-            ChokeMessageHandler::new(Arc::clone(&overlord_consensus)),
-        )?;
+        if !follower {
+            network_service.register_endpoint_handler(
+                END_GOSSIP_SIGNED_PROPOSAL,
+                ProposalMessageHandler::new(Arc::clone(&overlord_consensus)),
+            )?;
+            network_service.register_endpoint_handler(
+                END_GOSSIP_AGGREGATED_VOTE,
+                QCMessageHandler::new(Arc::clone(&overlord_consensus)),
+            )?;
+            network_service.register_endpoint_handler(
+                END_GOSSIP_SIGNED_VOTE,
+                VoteMessageHandler::new(Arc::clone(&overlord_consensus)),
+            )?;
+            network_service.register_endpoint_handler(
+                END_GOSSIP_SIGNED_CHOKE,
+                ChokeMessageHandler::new(Arc::clone(&overlord_consensus)),
+            )?;
+        }
         network_service.register_endpoint_handler(
             BROADCAST_HEIGHT,
             RemoteHeightMessageHandler::new(Arc::clone(&synchronization)),
@@ -602,35 +678,57 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         });
 
         // Run consensus
-        let authority_list = validators
-            .iter()
-            .map(|v| Node {
-                address: v.pub_key.clone(),
-                propose_weight: v.propose_weight,
-                vote_weight: v.vote_weight,
-            })
-            .collect::<Vec<_>>();
+        if !follower {
+            let authority_list = validators
+                .iter()
+                .map(|v| Node {
+                    address: v.pub_key.clone(),
+                    propose_weight: v.propose_weight,
+                    vote_weight: v.vote_weight,
+                })
+                .collect::<Vec<_>>();
 
-        let timer_config = DurationConfig {
-            propose_ratio: metadata.propose_ratio,
-            prevote_ratio: metadata.prevote_ratio,
-            precommit_ratio: metadata.precommit_ratio,
-            brake_ratio: metadata.brake_ratio,
-        };
+            let timer_config = DurationConfig {
+                propose_ratio: metadata.propose_ratio,
+                prevote_ratio: metadata.prevote_ratio,
+                precommit_ratio: metadata.precommit_ratio,
+                brake_ratio: metadata.brake_ratio,
+            };
+
+            tokio::spawn(async move {
+                if let Err(e) = overlord_consensus
+                    .run(
+                        current_height,
+                        consensus_interval,
+                        authority_list,
+                        Some(timer_config),
+                    )
+                    .await
+                {
+                    log::error!("muta-consensus: {:?} error", e);
+                }
+            });
+        }
 
-        tokio::spawn(async move {
-            if let Err(e) = overlord_consensus
-                .run(
-                    current_height,
-                    consensus_interval,
-                    authority_list,
-                    Some(timer_config),
-                )
-                .await
-            {
-                log::error!("muta-consensus: {:?} error", e);
-            }
-        });
+        // Run transaction and receipt pruning
+        if let Some(tx_history) = config.rocksdb.tx_history {
+            tokio::spawn(prune_history(
+                Arc::clone(&storage),
+                chain_pubsub.clone(),
+                tx_history,
+            ));
+        }
+
+        // Run state pruning
+        if let Some(state_history) = config.executor.state_history {
+            tokio::spawn(prune_state(
+                Arc::clone(&trie_db),
+                Arc::clone(&storage),
+                status_agent.clone(),
+                chain_pubsub.clone(),
+                state_history,
+            ));
+        }
 
         let (abortable_demon, abort_handle) = future::abortable(exec_demon.run());
         let exec_handler = tokio::task::spawn_local(abortable_demon);
@@ -649,6 +747,9 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         if config.graphql.max_payload_size != 0 {
             graphql_config.max_payload_size = config.graphql.max_payload_size;
         }
+        if config.graphql.max_batch_txs != 0 {
+            graphql_config.max_batch_txs = config.graphql.max_batch_txs;
+        }
         if let Some(tls) = config.graphql.tls {
             graphql_config.tls = Some(GraphQLTLS {
                 private_key_file_path: tls.private_key_file_path,
@@ -657,13 +758,27 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         }
         graphql_config.enable_dump_profile =
             config.graphql.enable_dump_profile.unwrap_or(false);
+        graphql_config.backup_path = config.graphql.backup_path.clone();
+        graphql_config.admin_listening_address = config.graphql.admin_listening_address;
+
+        let hot_backup = Arc::new(HotBackup::new(
+            Arc::clone(&rocks_adapter),
+            Arc::clone(&trie_db),
+            config.rocksdb.max_open_files,
+        ));
 
         tokio::task::spawn_local(async move {
             let local = tokio::task::LocalSet::new();
             let actix_rt = actix_rt::System::run_in_tokio("muta-graphql", &local);
             tokio::task::spawn_local(actix_rt);
 
-            core_api::start_graphql(graphql_config, api_adapter).await;
+            core_api::start_graphql(
+                graphql_config,
+                api_adapter,
+                hot_backup,
+                chain_pubsub,
+            )
+            .await;
         });
 
         let ctrl_c_handler = tokio::task::spawn_local(async {
@@ -723,6 +838,73 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
     }
 }
 
+// Prune the states older than the latest `state_history` blocks, once every
+// `state_history` blocks.
+async fn prune_state<S: Storage>(
+    trie_db: Arc<RocksTrieDB>,
+    storage: Arc<S>,
+    status_agent: StatusAgent,
+    chain_pubsub: ChainPubSub,
+    state_history: u64,
+) {
+    let mut blocks = Box::pin(chain_pubsub.blocks.subscribe());
+    let mut pruned_height = 0;
+
+    while let Some(block) = blocks.next().await {
+        let height = block.header.height;
+        if height < pruned_height + state_history {
+            continue;
+        }
+        pruned_height = height;
+
+        // The executed states not committed in any block yet are kept too.
+        let status = status_agent.to_inner();
+        let mut roots = status.list_state_root;
+        roots.push(status.latest_committed_state_root);
+        for h in height.saturating_sub(state_history - 1)..=height {
+            match storage.get_block_header(Context::new(), h).await {
+                Ok(Some(header)) => roots.push(header.state_root),
+                Ok(None) => {}
+                Err(e) => {
+                    log::error!("[prune]: get block header {} failed {:?}", h, e);
+                    return;
+                }
+            }
+        }
+
+        let trie_db = Arc::clone(&trie_db);
+        match tokio::task::spawn_blocking(move || trie_db.prune(&roots)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => log::error!("[prune]: prune state failed {:?}", e),
+            Err(e) => log::error!("[prune]: prune state panics {:?}", e),
+        }
+    }
+}
+
+// Prune the transactions and receipts of the blocks except the latest
+// `tx_history` blocks on every new block.
+async fn prune_history<Adapter: StorageAdapter>(
+    storage: Arc<ImplStorage<Adapter>>,
+    chain_pubsub: ChainPubSub,
+    tx_history: u64,
+) {
+    let mut blocks = Box::pin(chain_pubsub.blocks.subscribe());
+
+    while let Some(block) = blocks.next().await {
+        let height = block.header.height;
+        if height < tx_history {
+            continue;
+        }
+
+        if let Err(e) = storage
+            .prune_history(Context::new(), height + 1 - tx_history)
+            .await
+        {
+            log::error!("[prune]: prune history failed {:?}", e);
+        }
+    }
+}
+
 #[derive(Debug, Display, From)]
 pub enum MainError {
     #[display(fmt = "The muta configuration read failed {:?}", _0)]
@@ -1,7 +1,10 @@
 use core_storage::{adapter::rocks::RocksAdapter, CommonHashKey, ImplStorage};
 use protocol::{
     traits::{Context, Storage},
-    types::{Bytes, Hash, RawTransaction, SignedTransaction, TransactionRequest},
+    types::{
+        Bytes, Hash, PayloadEncoding, RawTransaction, SignedTransaction,
+        TransactionRequest,
+    },
 };
 
 use std::{
@@ -157,6 +160,7 @@ fn mock_signed_tx(tx_hash: Hash) -> SignedTransaction {
         service_name: "test".to_owned(),
         method: "test".to_owned(),
         payload: "test".to_owned(),
+        encoding: PayloadEncoding::Json,
     };
 
     let raw = RawTransaction {
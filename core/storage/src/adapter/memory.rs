@@ -1,4 +1,4 @@
-use std::collections::{hash_map, HashMap};
+use std::collections::{btree_map, BTreeMap, HashMap};
 use std::error::Error;
 use std::marker::PhantomData;
 use std::sync::Arc;
@@ -15,7 +15,7 @@ use protocol::traits::{
 use protocol::Bytes;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
-type Category = HashMap<Vec<u8>, Vec<u8>>;
+type Category = BTreeMap<Vec<u8>, Vec<u8>>;
 
 #[derive(Debug)]
 pub struct MemoryAdapter {
@@ -39,7 +39,7 @@ impl Default for MemoryAdapter {
 }
 
 pub struct MemoryIterator<'a, S: StorageSchema> {
-    inner: hash_map::Iter<'a, Vec<u8>, Vec<u8>>,
+    inner: btree_map::Range<'a, Vec<u8>, Vec<u8>>,
     pin_s: PhantomData<S>,
 }
 
@@ -63,6 +63,7 @@ impl<'a, S: StorageSchema> Iterator for MemoryIterator<'a, S> {
 
 pub struct MemoryIntoIterator<'a, S: StorageSchema> {
     inner: parking_lot::RwLockReadGuard<'a, HashMap<String, Category>>,
+    prefix: Vec<u8>,
     pin_s: PhantomData<S>,
 }
 
@@ -76,7 +77,7 @@ impl<'a, 'b: 'a, S: StorageSchema> IntoIterator for &'b MemoryIntoIterator<'a, S
                 .inner
                 .get(&S::category().to_string())
                 .expect("impossible, already ensure we have category in prepare_iter")
-                .iter(),
+                .range(self.prefix.clone()..),
             pin_s: PhantomData::<S>,
         })
     }
@@ -101,7 +102,7 @@ impl StorageAdapter for MemoryAdapter {
         let mut db = self.db.write();
         let db = db
             .entry(S::category().to_string())
-            .or_insert_with(HashMap::new);
+            .or_insert_with(BTreeMap::new);
 
         db.insert(key, val);
 
@@ -117,7 +118,7 @@ impl StorageAdapter for MemoryAdapter {
         let mut db = self.db.write();
         let db = db
             .entry(S::category().to_string())
-            .or_insert_with(HashMap::new);
+            .or_insert_with(BTreeMap::new);
 
         let opt_bytes = db.get(&key.to_vec()).cloned();
 
@@ -139,7 +140,7 @@ impl StorageAdapter for MemoryAdapter {
         let mut db = self.db.write();
         let db = db
             .entry(S::category().to_string())
-            .or_insert_with(HashMap::new);
+            .or_insert_with(BTreeMap::new);
 
         db.remove(&key);
 
@@ -155,7 +156,7 @@ impl StorageAdapter for MemoryAdapter {
         let mut db = self.db.write();
         let db = db
             .entry(S::category().to_string())
-            .or_insert_with(HashMap::new);
+            .or_insert_with(BTreeMap::new);
 
         Ok(db.get(&key).is_some())
     }
@@ -185,7 +186,7 @@ impl StorageAdapter for MemoryAdapter {
         let mut db = self.db.write();
         let db = db
             .entry(S::category().to_string())
-            .or_insert_with(HashMap::new);
+            .or_insert_with(BTreeMap::new);
 
         for (key, value) in pairs.into_iter() {
             match value {
@@ -199,17 +200,20 @@ impl StorageAdapter for MemoryAdapter {
 
     fn prepare_iter<'a, 'b: 'a, S: StorageSchema + 'static, P: AsRef<[u8]> + 'a>(
         &'b self,
-        _prefix: &P,
+        prefix: &'a P,
     ) -> ProtocolResult<Box<dyn IntoIteratorByRef<S> + 'a>> {
         {
             self.db
                 .write()
                 .entry(S::category().to_string())
-                .or_insert_with(HashMap::new);
+                .or_insert_with(BTreeMap::new);
         }
 
+        // Like rocksdb's prefix iterator, seek to the prefix and iterate keys
+        // in order from there.
         Ok(Box::new(MemoryIntoIterator {
             inner: self.db.read(),
+            prefix: prefix.as_ref().to_vec(),
             pin_s: PhantomData::<S>,
         }))
     }
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use derive_more::{Display, From};
+use rocksdb::checkpoint::Checkpoint;
 use rocksdb::{ColumnFamily, DBIterator, Options, WriteBatch, DB};
 
 use async_trait::async_trait;
@@ -37,6 +38,7 @@ impl RocksAdapter {
             map_category(StorageCategory::SignedTransaction),
             map_category(StorageCategory::Wal),
             map_category(StorageCategory::HashHeight),
+            map_category(StorageCategory::Event),
         ];
 
         let db = DB::open_cf(&opts, path, categories.iter())
@@ -44,6 +46,22 @@ impl RocksAdapter {
 
         Ok(RocksAdapter { db: Arc::new(db) })
     }
+
+    /// Create a consistent copy of the db at `path`, which must not exist,
+    /// without blocking writes. Return the column families of the copy.
+    pub fn create_checkpoint<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> ProtocolResult<Vec<String>> {
+        let checkpoint = Checkpoint::new(&self.db).map_err(RocksAdapterError::from)?;
+        checkpoint
+            .create_checkpoint(&path)
+            .map_err(RocksAdapterError::from)?;
+
+        let column_families =
+            DB::list_cf(&Options::default(), path).map_err(RocksAdapterError::from)?;
+        Ok(column_families)
+    }
 }
 
 macro_rules! db {
@@ -262,6 +280,7 @@ const C_RECEIPTS: &str = "c3";
 const C_WALS: &str = "c4";
 const C_HASH_HEIGHT_MAP: &str = "c5";
 const C_BLOCK_HEADERS: &str = "c6";
+const C_EVENTS: &str = "c7";
 
 fn map_category(c: StorageCategory) -> &'static str {
     match c {
@@ -271,6 +290,7 @@ fn map_category(c: StorageCategory) -> &'static str {
         StorageCategory::SignedTransaction => C_SIGNED_TRANSACTIONS,
         StorageCategory::Wal => C_WALS,
         StorageCategory::HashHeight => C_HASH_HEIGHT_MAP,
+        StorageCategory::Event => C_EVENTS,
     }
 }
 
@@ -24,7 +24,10 @@ use protocol::traits::{
     CommonStorage, Context, MaintenanceStorage, Storage, StorageAdapter,
     StorageBatchModify, StorageCategory, StorageSchema,
 };
-use protocol::types::{Block, BlockHeader, Hash, Proof, Receipt, SignedTransaction};
+use protocol::types::{
+    Block, BlockHeader, EventFilter, Hash, IndexedEvent, Proof, Receipt,
+    SignedTransaction,
+};
 use protocol::Bytes;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
@@ -33,6 +36,7 @@ const BATCH_VALUE_DECODE_NUMBER: usize = 1000;
 lazy_static! {
     pub static ref LATEST_BLOCK_KEY: Hash = Hash::digest(Bytes::from("latest_hash"));
     pub static ref LATEST_PROOF_KEY: Hash = Hash::digest(Bytes::from("latest_proof"));
+    pub static ref PRUNED_HEIGHT_KEY: Hash = Hash::digest(Bytes::from("pruned_height"));
 }
 
 // FIXME: https://github.com/facebook/rocksdb/wiki/Transactions
@@ -110,6 +114,92 @@ impl<Adapter: StorageAdapter> ImplStorage<Adapter> {
             latest_block: ArcSwap::from(Arc::new(None)),
         }
     }
+
+    /// Delete the transactions and the receipts of the blocks below `height`.
+    /// The blocks, headers, proofs, events and the hash index of the
+    /// transactions are kept, the later one protects the pruned transactions
+    /// from being replayed.
+    pub async fn prune_history(&self, ctx: Context, height: u64) -> ProtocolResult<()> {
+        let pruned_height = self.get_pruned_height(ctx.clone()).await?.unwrap_or(0);
+
+        for block_height in pruned_height..height {
+            if let Some(block) = self.get_block(ctx.clone(), block_height).await? {
+                let hashes = block.ordered_tx_hashes;
+                let len = hashes.len();
+                let keys = hashes
+                    .iter()
+                    .map(|hash| CommonHashKey::new(block_height, hash.clone()))
+                    .collect::<Vec<_>>();
+
+                self.adapter
+                    .batch_modify::<TransactionSchema>(keys.clone(), removes(len))
+                    .await?;
+                self.adapter
+                    .batch_modify::<ReceiptSchema>(keys, removes(len))
+                    .await?;
+            }
+
+            self.adapter
+                .insert::<PrunedHeightSchema>(
+                    PRUNED_HEIGHT_KEY.clone(),
+                    block_height + 1,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Get at most `count` events of the service and the event name within
+    /// `[from_height, to_height]`, in order.
+    fn get_named_events(
+        &self,
+        service: &Hash,
+        name: &Hash,
+        from_height: u64,
+        to_height: u64,
+        count: usize,
+    ) -> ProtocolResult<Vec<(EventIndexKey, IndexedEvent)>> {
+        let key_prefix = EventIndexKey::make_prefix(service, Some(name), from_height);
+        let prepare_iter = self.adapter.prepare_iter::<EventSchema, _>(&key_prefix)?;
+        let mut found = Vec::new();
+
+        for key_to_event in prepare_iter.ref_to_iter() {
+            if found.len() >= count {
+                break;
+            }
+            let (key, event) = key_to_event?;
+            if &key.service != service || &key.name != name || key.height > to_height {
+                break;
+            }
+            found.push((key, event));
+        }
+        Ok(found)
+    }
+
+    /// Get the name of the first event of the service from the `seek` key.
+    fn get_next_event_name(
+        &self,
+        service: &Hash,
+        seek: &[u8],
+    ) -> ProtocolResult<Option<Hash>> {
+        let prepare_iter = self.adapter.prepare_iter::<EventSchema, _>(&seek)?;
+
+        match prepare_iter.ref_to_iter().next() {
+            Some(key_to_event) => {
+                let (key, _) = key_to_event?;
+                if &key.service == service {
+                    Ok(Some(key.name))
+                } else {
+                    Ok(None)
+                }
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+fn removes<S: StorageSchema>(len: usize) -> Vec<StorageBatchModify<S>> {
+    (0..len).map(|_| StorageBatchModify::Remove).collect()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -232,6 +322,111 @@ impl FromStr for CommonHashKey {
     }
 }
 
+/// Key of the event index. Events are grouped by service and event name, then
+/// ordered by height and their position in the block, so that a scan seeking
+/// from a prefix yields them in chain order.
+#[derive(Debug, Clone)]
+pub struct EventIndexKey {
+    service: Hash,
+    name: Hash,
+    height: u64,
+    receipt_index: u32,
+    event_index: u32,
+}
+
+impl EventIndexKey {
+    pub fn new(
+        service: &str,
+        name: &str,
+        height: u64,
+        receipt_index: u32,
+        event_index: u32,
+    ) -> Self {
+        EventIndexKey {
+            service: Hash::digest(Bytes::from(service.to_owned())),
+            name: Hash::digest(Bytes::from(name.to_owned())),
+            height,
+            receipt_index,
+            event_index,
+        }
+    }
+
+    pub fn len() -> usize {
+        80
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Seek key for a filter: the service, and if an event name is given, the
+    /// event name and the starting height as well.
+    pub fn make_prefix(
+        service: &Hash,
+        name: Option<&Hash>,
+        from_height: u64,
+    ) -> Vec<u8> {
+        let mut prefix = service.as_bytes().to_vec();
+
+        if let Some(name) = name {
+            prefix.extend_from_slice(&name.as_bytes());
+            prefix.extend_from_slice(&from_height.to_be_bytes());
+        }
+
+        prefix
+    }
+
+    /// Seek key for the first event of the service whose name comes after
+    /// `name`, `None` if there can't be one.
+    pub fn make_next_name_prefix(service: &Hash, name: &Hash) -> Option<Vec<u8>> {
+        let mut next_name = name.as_bytes().to_vec();
+        for byte in next_name.iter_mut().rev() {
+            if *byte == u8::max_value() {
+                *byte = 0;
+            } else {
+                *byte += 1;
+
+                let mut prefix = service.as_bytes().to_vec();
+                prefix.extend_from_slice(&next_name);
+                return Some(prefix);
+            }
+        }
+        None
+    }
+}
+
+impl ProtocolCodecSync for EventIndexKey {
+    fn encode_sync(&self) -> ProtocolResult<Bytes> {
+        let mut key = Vec::with_capacity(EventIndexKey::len());
+        key.extend_from_slice(&self.service.as_bytes());
+        key.extend_from_slice(&self.name.as_bytes());
+        key.extend_from_slice(&self.height.to_be_bytes());
+        key.extend_from_slice(&self.receipt_index.to_be_bytes());
+        key.extend_from_slice(&self.event_index.to_be_bytes());
+
+        Ok(Bytes::from(key))
+    }
+
+    fn decode_sync(bytes: Bytes) -> ProtocolResult<Self> {
+        debug_assert!(bytes.len() >= EventIndexKey::len());
+
+        let mut height = [0u8; 8];
+        height.copy_from_slice(&bytes[64..72]);
+        let mut receipt_index = [0u8; 4];
+        receipt_index.copy_from_slice(&bytes[72..76]);
+        let mut event_index = [0u8; 4];
+        event_index.copy_from_slice(&bytes[76..80]);
+
+        Ok(EventIndexKey {
+            service: Hash::from_bytes(bytes.slice(0..32))?,
+            name: Hash::from_bytes(bytes.slice(32..64))?,
+            height: u64::from_be_bytes(height),
+            receipt_index: u32::from_be_bytes(receipt_index),
+            event_index: u32::from_be_bytes(event_index),
+        })
+    }
+}
+
 pub type BlockKey = CommonPrefix;
 
 impl_storage_schema_for!(
@@ -251,8 +446,10 @@ impl_storage_schema_for!(BlockHeaderSchema, BlockKey, BlockHeader, BlockHeader);
 impl_storage_schema_for!(ReceiptSchema, CommonHashKey, Receipt, Receipt);
 impl_storage_schema_for!(ReceiptBytesSchema, CommonHashKey, Bytes, Receipt);
 impl_storage_schema_for!(HashHeightSchema, Hash, u64, HashHeight);
+impl_storage_schema_for!(EventSchema, EventIndexKey, IndexedEvent, Event);
 impl_storage_schema_for!(LatestBlockSchema, Hash, Block, Block);
 impl_storage_schema_for!(LatestProofSchema, Hash, Proof, Block);
+impl_storage_schema_for!(PrunedHeightSchema, Hash, u64, Block);
 
 #[async_trait]
 impl<Adapter: StorageAdapter> MaintenanceStorage for ImplStorage<Adapter> {}
@@ -377,8 +574,40 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         block_height: u64,
         receipts: Vec<Receipt>,
     ) -> ProtocolResult<()> {
+        let (event_keys, events): (Vec<_>, Vec<_>) = receipts
+            .iter()
+            .enumerate()
+            .flat_map(|(receipt_index, receipt)| {
+                receipt
+                    .events
+                    .iter()
+                    .enumerate()
+                    .map(move |(event_index, event)| {
+                        let key = EventIndexKey::new(
+                            &event.service,
+                            &event.name,
+                            block_height,
+                            receipt_index as u32,
+                            event_index as u32,
+                        );
+                        let indexed = IndexedEvent {
+                            height: block_height,
+                            tx_hash: receipt.tx_hash.clone(),
+                            index: event_index as u64,
+                            event: event.clone(),
+                        };
+
+                        (key, StorageBatchModify::Insert(indexed))
+                    })
+            })
+            .unzip();
+
         batch_insert!(self, block_height, receipts, ReceiptSchema);
 
+        self.adapter
+            .batch_modify::<EventSchema>(event_keys, events)
+            .await?;
+
         Ok(())
     }
 
@@ -474,6 +703,64 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         }
     }
 
+    #[muta_apm::derive::tracing_span(kind = "storage")]
+    async fn get_events(
+        &self,
+        ctx: Context,
+        filter: EventFilter,
+        from_height: u64,
+        to_height: u64,
+        offset: u64,
+        limit: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        if from_height > to_height || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let service = Hash::digest(Bytes::from(filter.service));
+        let name = filter.name.map(|name| Hash::digest(Bytes::from(name)));
+        // No more events than the ones up to the end of the page are read
+        let count = offset.saturating_add(limit) as usize;
+        let inst = Instant::now();
+        let found = match name {
+            Some(name) => {
+                self.get_named_events(&service, &name, from_height, to_height, count)?
+            }
+            None => {
+                // Events of different names are interleaved by height, so the
+                // first events of each name are read and merged
+                let mut found = Vec::new();
+                let mut seek = EventIndexKey::make_prefix(&service, None, 0);
+                while let Some(name) = self.get_next_event_name(&service, &seek)? {
+                    found.extend(self.get_named_events(
+                        &service,
+                        &name,
+                        from_height,
+                        to_height,
+                        count,
+                    )?);
+                    seek = match EventIndexKey::make_next_name_prefix(&service, &name) {
+                        Some(seek) => seek,
+                        None => break,
+                    };
+                }
+
+                found.sort_by_key(|(key, _)| {
+                    (key.height, key.receipt_index, key.event_index)
+                });
+                found
+            }
+        };
+        on_storage_get_cf(StorageCategory::Event, inst.elapsed(), found.len() as i64);
+
+        Ok(found
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(_, event)| event)
+            .collect())
+    }
+
     async fn update_latest_proof(
         &self,
         _ctx: Context,
@@ -489,6 +776,20 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         let proof = ensure_get!(self, LATEST_PROOF_KEY.clone(), LatestProofSchema);
         Ok(proof)
     }
+
+    async fn get_pruned_height(&self, _ctx: Context) -> ProtocolResult<Option<u64>> {
+        get!(self, PRUNED_HEIGHT_KEY.clone(), PrunedHeightSchema)
+    }
+
+    async fn contains_transaction(
+        &self,
+        _ctx: Context,
+        hash: &Hash,
+    ) -> ProtocolResult<bool> {
+        self.adapter
+            .contains::<HashHeightSchema>(hash.clone())
+            .await
+    }
 }
 
 #[async_trait]
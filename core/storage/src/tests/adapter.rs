@@ -33,6 +33,32 @@ async fn test_adapter_remove() {
     .await
 }
 
+#[tokio::test]
+async fn test_adapter_checkpoint() {
+    let checkpoint_path = "rocksdb/test_adapter_checkpoint_copy";
+    let _ = std::fs::remove_dir_all(checkpoint_path);
+
+    let db =
+        RocksAdapter::new("rocksdb/test_adapter_checkpoint".to_string(), 64).unwrap();
+    let tx_hash = Hash::digest(get_random_bytes(10));
+    let tx_key = CommonHashKey::new(1, tx_hash.clone());
+    db.insert::<TransactionSchema>(tx_key.clone(), mock_signed_tx(tx_hash.clone()))
+        .await
+        .unwrap();
+
+    let column_families = db.create_checkpoint(checkpoint_path).unwrap();
+    assert!(column_families.len() > 1);
+    assert!(db.create_checkpoint(checkpoint_path).is_err());
+
+    let checkpoint = RocksAdapter::new(checkpoint_path.to_string(), 64).unwrap();
+    let stx = checkpoint
+        .get::<TransactionSchema>(tx_key)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tx_hash, stx.tx_hash);
+}
+
 async fn adapter_insert_test(db: impl StorageAdapter) {
     let tx_hash = Hash::digest(get_random_bytes(10));
     let tx_key = CommonHashKey::new(1, tx_hash.clone());
@@ -7,8 +7,8 @@ use rand::random;
 
 use protocol::traits::ServiceResponse;
 use protocol::types::{
-    Block, BlockHeader, Hash, Proof, RawTransaction, Receipt, ReceiptResponse,
-    SignedTransaction, TransactionRequest,
+    Block, BlockHeader, Hash, PayloadEncoding, Proof, RawTransaction, Receipt,
+    ReceiptResponse, SignedTransaction, TransactionRequest,
 };
 use protocol::Bytes;
 
@@ -21,6 +21,7 @@ fn mock_signed_tx(tx_hash: Hash) -> SignedTransaction {
         service_name: "test".to_owned(),
         method: "test".to_owned(),
         payload: "test".to_owned(),
+        encoding: PayloadEncoding::Json,
     };
 
     let raw = RawTransaction {
@@ -60,6 +61,7 @@ fn mock_receipt(tx_hash: Hash) -> Receipt {
         cycles_used: 10,
         events: vec![],
         response,
+        fee: None,
     }
 }
 
@@ -5,7 +5,7 @@ use std::sync::Arc;
 use test::Bencher;
 
 use protocol::traits::{CommonStorage, Context, Storage};
-use protocol::types::Hash;
+use protocol::types::{Event, EventFilter, Hash};
 use tokio::runtime::Runtime;
 
 use crate::adapter::memory::MemoryAdapter;
@@ -97,6 +97,83 @@ async fn test_storage_receipts_get_batch_decode() {
     }
 }
 
+#[tokio::test]
+async fn test_storage_events_index() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+    let event = |service: &str, name: &str, data: String| Event {
+        service: service.to_owned(),
+        name: name.to_owned(),
+        data,
+    };
+
+    for height in 10..13 {
+        let mut receipt = mock_receipt(Hash::digest(get_random_bytes(10)));
+        receipt.events = vec![
+            event("asset", "transfer", format!("transfer {}", height)),
+            event("asset", "approve", format!("approve {}", height)),
+            event("other", "transfer", format!("other {}", height)),
+        ];
+
+        storage
+            .insert_receipts(Context::new(), height, vec![receipt])
+            .await
+            .unwrap();
+    }
+
+    let filter = EventFilter {
+        service: "asset".to_owned(),
+        name: Some("transfer".to_owned()),
+    };
+    let events = storage
+        .get_events(Context::new(), filter, 11, 20, 0, 10)
+        .await
+        .unwrap();
+    let data = events
+        .iter()
+        .map(|e| e.event.data.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(data, vec!["transfer 11", "transfer 12"]);
+    assert_eq!(events[0].height, 11);
+    assert_eq!(events[0].index, 0);
+
+    let filter = EventFilter {
+        service: "asset".to_owned(),
+        name: None,
+    };
+    let events = storage
+        .get_events(Context::new(), filter.clone(), 10, 11, 0, 10)
+        .await
+        .unwrap();
+    let data = events
+        .iter()
+        .map(|e| e.event.data.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        data,
+        vec!["transfer 10", "approve 10", "transfer 11", "approve 11"]
+    );
+
+    let events = storage
+        .get_events(Context::new(), filter, 10, 12, 1, 3)
+        .await
+        .unwrap();
+    let data = events
+        .iter()
+        .map(|e| e.event.data.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(data, vec!["approve 10", "transfer 11", "approve 11"]);
+
+    let filter = EventFilter {
+        service: "missing".to_owned(),
+        name: None,
+    };
+    let events = storage
+        .get_events(Context::new(), filter, 0, 20, 0, 10)
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+}
+
 #[tokio::test]
 async fn test_storage_transactions_insert() {
     let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
@@ -178,6 +255,81 @@ async fn test_storage_latest_proof_insert() {
     assert_eq!(proof.block_hash, proof_2.block_hash);
 }
 
+#[tokio::test]
+async fn test_storage_prune_history() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+    let mut hashes = Vec::new();
+
+    for height in 1..=3 {
+        let tx_hashes = (0..2)
+            .map(|_| Hash::digest(get_random_bytes(10)))
+            .collect::<Vec<_>>();
+        let mut block = mock_block(height, Hash::digest(get_random_bytes(10)));
+        block.ordered_tx_hashes = tx_hashes.clone();
+
+        storage.insert_block(Context::new(), block).await.unwrap();
+        storage
+            .insert_transactions(
+                Context::new(),
+                height,
+                tx_hashes.iter().cloned().map(mock_signed_tx).collect(),
+            )
+            .await
+            .unwrap();
+        storage
+            .insert_receipts(
+                Context::new(),
+                height,
+                tx_hashes.iter().cloned().map(mock_receipt).collect(),
+            )
+            .await
+            .unwrap();
+        hashes.push(tx_hashes);
+    }
+    assert_eq!(
+        storage.get_pruned_height(Context::new()).await.unwrap(),
+        None
+    );
+
+    storage.prune_history(Context::new(), 3).await.unwrap();
+    assert_eq!(
+        storage.get_pruned_height(Context::new()).await.unwrap(),
+        Some(3)
+    );
+
+    for (height, tx_hashes) in (1..=3).zip(hashes.iter()) {
+        let kept = height >= 3;
+        for tx_hash in tx_hashes {
+            let stx = storage
+                .get_transaction_by_hash(Context::new(), tx_hash)
+                .await
+                .unwrap();
+            assert_eq!(stx.is_some(), kept);
+            let receipt = storage
+                .get_receipt_by_hash(Context::new(), tx_hash.clone())
+                .await
+                .unwrap();
+            assert_eq!(receipt.is_some(), kept);
+
+            // the hash index is kept for the replay protection
+            assert!(storage
+                .contains_transaction(Context::new(), tx_hash)
+                .await
+                .unwrap());
+        }
+
+        // blocks are kept
+        let block = storage.get_block(Context::new(), height).await.unwrap();
+        assert!(block.is_some());
+    }
+
+    let unknown_hash = Hash::digest(get_random_bytes(10));
+    assert!(!storage
+        .contains_transaction(Context::new(), &unknown_hash)
+        .await
+        .unwrap());
+}
+
 #[rustfmt::skip]
 /// Bench in Intel(R) Core(TM) i7-4770HQ CPU @ 2.20GHz (8 x 2200)
 /// test tests::storage::bench_insert_10000_receipts ... bench:  33,954,916 ns/iter (+/- 3,818,780)
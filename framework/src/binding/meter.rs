@@ -0,0 +1,99 @@
+use std::cell::{Cell, RefCell};
+use std::panic;
+
+use bytes::Bytes;
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::ServiceState;
+use protocol::types::{ServiceContext, ServiceContextError, StorageCycles};
+use protocol::ProtocolResult;
+
+// `CyclesMeter` charges the storage cycles of the service state accessed
+// through the stores and the sdk to the context of the running call.
+//
+// Nothing is charged when no call is running, such as in genesis, the block
+// hooks and the tests using the stores standalone.
+#[derive(Default)]
+pub struct CyclesMeter {
+    cycles: Cell<StorageCycles>,
+    context: RefCell<Option<ServiceContext>>,
+}
+
+impl CyclesMeter {
+    pub fn new(cycles: StorageCycles) -> Self {
+        CyclesMeter {
+            cycles: Cell::new(cycles),
+            context: RefCell::new(None),
+        }
+    }
+
+    // Change the storage cycles charged from the next access on
+    pub fn set_cycles(&self, cycles: StorageCycles) {
+        self.cycles.set(cycles);
+    }
+
+    // Charge the cycles to `context` until the returned guard is dropped
+    pub fn enter(&self, context: ServiceContext) -> MeterGuard<'_> {
+        let prev = self.context.borrow_mut().replace(context);
+        MeterGuard { meter: self, prev }
+    }
+
+    // Get a value from the state by key, and charge the read
+    pub fn get<S: ServiceState, Key: FixedCodec, Ret: FixedCodec>(
+        &self,
+        state: &RefCell<S>,
+        key: &Key,
+    ) -> ProtocolResult<Option<Ret>> {
+        let key_bytes = key.encode_fixed()?;
+        let value: Option<Bytes> = state.borrow().get(&key_bytes)?;
+
+        let len = key_bytes.len() + value.as_ref().map_or(0, Bytes::len);
+        self.charge(self.cycles.get().read_cycles(len));
+
+        value.map(Ret::decode_fixed).transpose()
+    }
+
+    // Insert a pair of key / value to the state, and charge the write
+    pub fn insert<S: ServiceState, Key: FixedCodec, Val: FixedCodec>(
+        &self,
+        state: &RefCell<S>,
+        key: Key,
+        val: Val,
+    ) -> ProtocolResult<()> {
+        let key_bytes = key.encode_fixed()?;
+        let val_bytes = val.encode_fixed()?;
+
+        let len = key_bytes.len() + val_bytes.len();
+        self.charge(self.cycles.get().write_cycles(len));
+
+        state.borrow_mut().insert(key_bytes, val_bytes)
+    }
+
+    // Abort the running call by unwinding to the executor if it runs out of
+    // cycles, the stores have no way to return an error to the service.
+    fn charge(&self, cycles: u64) {
+        if cycles == 0 {
+            return;
+        }
+
+        let out_of_cycles = match self.context.borrow().as_ref() {
+            Some(context) => !context.sub_cycles(cycles),
+            None => false,
+        };
+
+        if out_of_cycles {
+            panic::resume_unwind(Box::new(ServiceContextError::OutOfCycles));
+        }
+    }
+}
+
+pub struct MeterGuard<'a> {
+    meter: &'a CyclesMeter,
+    prev: Option<ServiceContext>,
+}
+
+impl<'a> Drop for MeterGuard<'a> {
+    fn drop(&mut self) {
+        *self.meter.context.borrow_mut() = self.prev.take();
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests;
 
+pub mod meter;
 pub mod sdk;
 pub mod state;
 pub mod store;
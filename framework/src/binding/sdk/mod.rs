@@ -10,41 +10,55 @@ use derive_more::Display;
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
-    ChainQuerier, SDKFactory, ServiceSDK, ServiceState, StoreArray, StoreBool, StoreMap,
-    StoreString, StoreUint64,
+    ChainQuerier, Dispatcher, SDKFactory, ServiceResponse, ServiceSDK, ServiceState,
+    StoreArray, StoreBool, StoreMap, StoreString, StoreUint64,
+};
+use protocol::types::{
+    Address, Block, Hash, Receipt, ServiceContext, SignedTransaction,
 };
-use protocol::types::{Address, Block, Hash, Receipt, SignedTransaction};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
-use crate::binding::state::GeneralServiceState;
+use crate::binding::meter::CyclesMeter;
+use crate::binding::state::{get_address_key, GeneralServiceState};
 use crate::binding::store::{
     DefaultStoreArray, DefaultStoreBool, DefaultStoreMap, DefaultStoreString,
     DefaultStoreUint64,
 };
 use crate::executor::ServiceStateMap;
 
-pub struct DefaultSDKFactory<C: ChainQuerier, DB: TrieDB> {
+const SDK_DISPATCH_ERROR_CODE: u64 = 62078;
+
+pub struct DefaultSDKFactory<C: ChainQuerier, DB: TrieDB, D: Dispatcher> {
     states: Rc<ServiceStateMap<DB>>,
     chain_querier: Rc<C>,
+    dispatcher: Rc<D>,
+    meter: Rc<CyclesMeter>,
 }
 
-impl<C: ChainQuerier, DB: TrieDB> DefaultSDKFactory<C, DB> {
-    pub fn new(states: Rc<ServiceStateMap<DB>>, chain_querier: Rc<C>) -> Self {
+impl<C: ChainQuerier, DB: TrieDB, D: Dispatcher> DefaultSDKFactory<C, DB, D> {
+    pub fn new(
+        states: Rc<ServiceStateMap<DB>>,
+        chain_querier: Rc<C>,
+        dispatcher: Rc<D>,
+        meter: Rc<CyclesMeter>,
+    ) -> Self {
         DefaultSDKFactory {
             states,
             chain_querier,
+            dispatcher,
+            meter,
         }
     }
 }
 
-impl<C: ChainQuerier, DB: 'static + TrieDB>
-    SDKFactory<DefaultServiceSDK<GeneralServiceState<DB>, C>>
-    for DefaultSDKFactory<C, DB>
+impl<C: ChainQuerier, DB: 'static + TrieDB, D: Dispatcher>
+    SDKFactory<DefaultServiceSDK<GeneralServiceState<DB>, C, D>>
+    for DefaultSDKFactory<C, DB, D>
 {
     fn get_sdk(
         &self,
         name: &str,
-    ) -> ProtocolResult<DefaultServiceSDK<GeneralServiceState<DB>, C>> {
+    ) -> ProtocolResult<DefaultServiceSDK<GeneralServiceState<DB>, C, D>> {
         let state = self.states.get(name).ok_or(SDKError::NotFoundService {
             service: name.to_owned(),
         })?;
@@ -52,25 +66,38 @@ impl<C: ChainQuerier, DB: 'static + TrieDB>
         Ok(DefaultServiceSDK::new(
             Rc::clone(state),
             Rc::clone(&self.chain_querier),
+            Rc::clone(&self.dispatcher),
+            Rc::clone(&self.meter),
         ))
     }
 }
 
-pub struct DefaultServiceSDK<S: ServiceState, C: ChainQuerier> {
+pub struct DefaultServiceSDK<S: ServiceState, C: ChainQuerier, D: Dispatcher> {
     state: Rc<RefCell<S>>,
     chain_querier: Rc<C>,
+    dispatcher: Rc<D>,
+    meter: Rc<CyclesMeter>,
 }
 
-impl<S: ServiceState, C: ChainQuerier> DefaultServiceSDK<S, C> {
-    pub fn new(state: Rc<RefCell<S>>, chain_querier: Rc<C>) -> Self {
+impl<S: ServiceState, C: ChainQuerier, D: Dispatcher> DefaultServiceSDK<S, C, D> {
+    pub fn new(
+        state: Rc<RefCell<S>>,
+        chain_querier: Rc<C>,
+        dispatcher: Rc<D>,
+        meter: Rc<CyclesMeter>,
+    ) -> Self {
         Self {
             state,
             chain_querier,
+            dispatcher,
+            meter,
         }
     }
 }
 
-impl<S: 'static + ServiceState, C: ChainQuerier> ServiceSDK for DefaultServiceSDK<S, C> {
+impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
+    for DefaultServiceSDK<S, C, D>
+{
     // Alloc or recover a `Map` by` var_name`
     fn alloc_or_recover_map<
         K: 'static + Send + FixedCodec + Clone + PartialEq,
@@ -82,6 +109,7 @@ impl<S: 'static + ServiceState, C: ChainQuerier> ServiceSDK for DefaultServiceSD
         Box::new(DefaultStoreMap::<S, K, V>::new(
             Rc::clone(&self.state),
             var_name,
+            Rc::clone(&self.meter),
         ))
     }
 
@@ -93,37 +121,48 @@ impl<S: 'static + ServiceState, C: ChainQuerier> ServiceSDK for DefaultServiceSD
         Box::new(DefaultStoreArray::<S, E>::new(
             Rc::clone(&self.state),
             var_name,
+            Rc::clone(&self.meter),
         ))
     }
 
     // Alloc or recover a `Uint64` by` var_name`
     fn alloc_or_recover_uint64(&mut self, var_name: &str) -> Box<dyn StoreUint64> {
-        Box::new(DefaultStoreUint64::new(Rc::clone(&self.state), var_name))
+        Box::new(DefaultStoreUint64::new(
+            Rc::clone(&self.state),
+            var_name,
+            Rc::clone(&self.meter),
+        ))
     }
 
     // Alloc or recover a `String` by` var_name`
     fn alloc_or_recover_string(&mut self, var_name: &str) -> Box<dyn StoreString> {
-        Box::new(DefaultStoreString::new(Rc::clone(&self.state), var_name))
+        Box::new(DefaultStoreString::new(
+            Rc::clone(&self.state),
+            var_name,
+            Rc::clone(&self.meter),
+        ))
     }
 
     // Alloc or recover a `Bool` by` var_name`
     fn alloc_or_recover_bool(&mut self, var_name: &str) -> Box<dyn StoreBool> {
-        Box::new(DefaultStoreBool::new(Rc::clone(&self.state), var_name))
+        Box::new(DefaultStoreBool::new(
+            Rc::clone(&self.state),
+            var_name,
+            Rc::clone(&self.meter),
+        ))
     }
 
     // Get a value from the service state by key
     fn get_value<Key: FixedCodec, Ret: FixedCodec>(&self, key: &Key) -> Option<Ret> {
-        self.state
-            .borrow()
-            .get(key)
+        self.meter
+            .get(&self.state, key)
             .unwrap_or_else(|e| panic!("service sdk get value failed: {}", e))
     }
 
     // Set a value to the service state by key
     fn set_value<Key: FixedCodec, Val: FixedCodec>(&mut self, key: Key, val: Val) {
-        self.state
-            .borrow_mut()
-            .insert(key, val)
+        self.meter
+            .insert(&self.state, key, val)
             .unwrap_or_else(|e| panic!("service sdk set value failed: {}", e));
     }
 
@@ -133,9 +172,8 @@ impl<S: 'static + ServiceState, C: ChainQuerier> ServiceSDK for DefaultServiceSD
         address: &Address,
         key: &Key,
     ) -> Option<Ret> {
-        self.state
-            .borrow()
-            .get_account_value(address, key)
+        get_address_key(address, key)
+            .and_then(|key| self.meter.get(&self.state, &key))
             .unwrap_or_else(|e| panic!("service sdk get account value failed: {}", e))
     }
 
@@ -146,9 +184,8 @@ impl<S: 'static + ServiceState, C: ChainQuerier> ServiceSDK for DefaultServiceSD
         key: Key,
         val: Val,
     ) {
-        self.state
-            .borrow_mut()
-            .set_account_value(address, key, val)
+        get_address_key(address, &key)
+            .and_then(|key| self.meter.insert(&self.state, key, val))
             .unwrap_or_else(|e| panic!("service sdk set account value failed: {}", e));
     }
 
@@ -178,6 +215,66 @@ impl<S: 'static + ServiceState, C: ChainQuerier> ServiceSDK for DefaultServiceSD
             .get_receipt_by_hash(tx_hash)
             .unwrap_or_else(|e| panic!("service sdk get receipt by hash failed: {}", e))
     }
+
+    // Call a `#[read]` method of another service by name, the call shares
+    // cycles and events with `ctx`
+    fn read(
+        &self,
+        ctx: &ServiceContext,
+        service: &str,
+        method: &str,
+        payload: &str,
+    ) -> ServiceResponse<String> {
+        let context = ServiceContext::with_context(
+            ctx,
+            None,
+            service.to_owned(),
+            method.to_owned(),
+            payload.to_owned(),
+        );
+
+        self.dispatcher.read(context)
+    }
+
+    // Call a `#[write]` method of another service by name, the call shares
+    // cycles and events with `ctx`
+    fn write(
+        &mut self,
+        ctx: &ServiceContext,
+        service: &str,
+        method: &str,
+        payload: &str,
+    ) -> ServiceResponse<String> {
+        let context = ServiceContext::with_context(
+            ctx,
+            None,
+            service.to_owned(),
+            method.to_owned(),
+            payload.to_owned(),
+        );
+
+        self.dispatcher.write(context)
+    }
+}
+
+// `NoopDispatcher` is for services that are used standalone, such as in
+// tests, every call to another service fails
+pub struct NoopDispatcher;
+
+impl Dispatcher for NoopDispatcher {
+    fn read(&self, context: ServiceContext) -> ServiceResponse<String> {
+        ServiceResponse::from_error(
+            SDK_DISPATCH_ERROR_CODE,
+            format!("can not dispatch to service {}", context.get_service_name()),
+        )
+    }
+
+    fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
+        ServiceResponse::from_error(
+            SDK_DISPATCH_ERROR_CODE,
+            format!("can not dispatch to service {}", context.get_service_name()),
+        )
+    }
 }
 
 #[derive(Debug, Display)]
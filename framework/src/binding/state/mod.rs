@@ -1,16 +1,17 @@
 mod trie;
 pub mod trie_db;
 
-pub use trie::{MPTTrie, MPTTrieError};
+pub use trie::{verify_proof, MPTTrie, MPTTrieError};
 pub use trie_db::{RocksTrieDB, RocksTrieDBError};
 
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
 
 use bytes::Bytes;
 use cita_trie::DB as TrieDB;
 
 use protocol::fixed_codec::FixedCodec;
-use protocol::traits::ServiceState;
+use protocol::traits::{ServiceState, StateProof, StateProofEntry};
 use protocol::types::{Address, Hash, MerkleRoot};
 use protocol::ProtocolResult;
 
@@ -21,6 +22,13 @@ pub struct GeneralServiceState<DB: TrieDB> {
     // serializations.
     cache_map: HashMap<Bytes, Bytes>,
     stash_map: HashMap<Bytes, Bytes>,
+
+    // One journal per open checkpoint, holding the cache value of each key
+    // before its first insert since the checkpoint.
+    journals: Vec<HashMap<Bytes, Option<Bytes>>>,
+
+    // Keys looked up while recording, to prove what a read has touched.
+    read_keys: RefCell<Option<BTreeSet<Bytes>>>,
 }
 
 impl<DB: TrieDB> GeneralServiceState<DB> {
@@ -30,10 +38,78 @@ impl<DB: TrieDB> GeneralServiceState<DB> {
 
             cache_map: HashMap::new(),
             stash_map: HashMap::new(),
+            journals: Vec::new(),
+
+            read_keys: RefCell::new(None),
+        }
+    }
+
+    /// Start recording the keys looked up, until `take_read_keys` is called.
+    pub fn record_reads(&self) {
+        *self.read_keys.borrow_mut() = Some(BTreeSet::new());
+    }
+
+    /// Stop recording and return the keys looked up since `record_reads`.
+    pub fn take_read_keys(&self) -> Vec<Bytes> {
+        self.read_keys
+            .borrow_mut()
+            .take()
+            .map(|keys| keys.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Prove the committed value of an encoded key, uncommitted changes in the
+    /// cache and stash are not taken into account.
+    pub fn get_proof(&self, key: &Bytes) -> ProtocolResult<StateProofEntry> {
+        Ok(StateProofEntry {
+            key: key.clone(),
+            value: self.trie.get(key)?,
+            proof: self.trie.get_proof(key)?,
+        })
+    }
+
+    /// Open a checkpoint in the cache, to roll back to with
+    /// `revert_checkpoint`. Only the keys inserted after it are recorded.
+    pub fn checkpoint(&mut self) {
+        self.journals.push(HashMap::new());
+    }
+
+    /// Roll back the cache to the latest checkpoint and close it.
+    pub fn revert_checkpoint(&mut self) {
+        if let Some(journal) = self.journals.pop() {
+            for (key, value) in journal.into_iter() {
+                match value {
+                    Some(value) => self.cache_map.insert(key, value),
+                    None => self.cache_map.remove(&key),
+                };
+            }
         }
     }
 
+    /// Keep the changes since the latest checkpoint and close it, the
+    /// enclosing checkpoint can still roll them back.
+    pub fn release_checkpoint(&mut self) {
+        if let Some(journal) = self.journals.pop() {
+            if let Some(parent) = self.journals.last_mut() {
+                for (key, value) in journal.into_iter() {
+                    parent.entry(key).or_insert(value);
+                }
+            }
+        }
+    }
+
+    /// Roll back all data in the cache and stash, which is never committed.
+    pub fn discard(&mut self) {
+        self.cache_map.clear();
+        self.stash_map.clear();
+        self.journals.clear();
+    }
+
     fn get_bytes_value(&self, key: Bytes) -> ProtocolResult<Option<Bytes>> {
+        if let Some(keys) = self.read_keys.borrow_mut().as_mut() {
+            keys.insert(key.clone());
+        }
+
         if let Some(value_bytes) = self.cache_map.get(&key) {
             if value_bytes.is_empty() {
                 return Ok(None);
@@ -88,8 +164,15 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
         key: Key,
         value: Value,
     ) -> ProtocolResult<()> {
-        self.cache_map
-            .insert(key.encode_fixed()?, value.encode_fixed()?);
+        let key = key.encode_fixed()?;
+
+        if let Some(journal) = self.journals.last_mut() {
+            if !journal.contains_key(&key) {
+                journal.insert(key.clone(), self.cache_map.get(&key).cloned());
+            }
+        }
+
+        self.cache_map.insert(key, value.encode_fixed()?);
         Ok(())
     }
 
@@ -115,6 +198,7 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
     // Roll back all data in the cache
     fn revert_cache(&mut self) -> ProtocolResult<()> {
         self.cache_map.clear();
+        self.journals.clear();
         Ok(())
     }
 
@@ -138,7 +222,36 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
     }
 }
 
-fn get_address_key<Key: FixedCodec>(
+/// Verify the state proof of a service read: each service root must be proven
+/// in the root state trie, and each entry in the trie of its service.
+pub fn verify_state_proof(proof: &StateProof) -> ProtocolResult<bool> {
+    for service in proof.services.iter() {
+        let service_key = service.service_name.encode_fixed()?;
+        let service_root = match verify_proof(
+            &proof.state_root,
+            &service_key,
+            service.service_root_proof.clone(),
+        ) {
+            Ok(service_root) => service_root,
+            Err(_) => return Ok(false),
+        };
+
+        if service_root != Some(service.service_root.encode_fixed()?) {
+            return Ok(false);
+        }
+
+        for entry in service.entries.iter() {
+            match verify_proof(&service.service_root, &entry.key, entry.proof.clone()) {
+                Ok(value) if value == entry.value => continue,
+                _ => return Ok(false),
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+pub(crate) fn get_address_key<Key: FixedCodec>(
     address: &Address,
     key: &Key,
 ) -> ProtocolResult<Hash> {
@@ -155,7 +268,7 @@ mod tests {
 
     use cita_trie::MemoryDB;
 
-    use protocol::traits::ServiceState;
+    use protocol::traits::{ServiceState, ServiceStateProof, StateProof};
 
     use super::*;
     use crate::binding::state::MPTTrie;
@@ -176,4 +289,47 @@ mod tests {
         assert_eq!(state.get::<Bytes, Bytes>(&key).unwrap().is_some(), false);
         assert_eq!(state.contains(&key).unwrap(), false);
     }
+
+    #[test]
+    fn test_state_proof() {
+        let db = Arc::new(MemoryDB::new(false));
+        let mut state = GeneralServiceState::new(MPTTrie::new(Arc::clone(&db)));
+
+        let key = Bytes::from("balance");
+        state.insert(key.clone(), Bytes::from("100")).unwrap();
+        state.stash().unwrap();
+        let service_root = state.commit().unwrap();
+
+        let mut root_state = GeneralServiceState::new(MPTTrie::new(Arc::clone(&db)));
+        root_state
+            .insert("asset".to_owned(), service_root.clone())
+            .unwrap();
+        root_state.stash().unwrap();
+        let state_root = root_state.commit().unwrap();
+
+        state.record_reads();
+        state.get::<Bytes, Bytes>(&key).unwrap();
+        state.get::<Bytes, Bytes>(&Bytes::from("missing")).unwrap();
+        let entries = state
+            .take_read_keys()
+            .iter()
+            .map(|key| state.get_proof(key).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 2);
+
+        let service_key = "asset".to_owned().encode_fixed().unwrap();
+        let mut proof = StateProof {
+            state_root,
+            services: vec![ServiceStateProof {
+                service_name: "asset".to_owned(),
+                service_root,
+                service_root_proof: root_state.get_proof(&service_key).unwrap().proof,
+                entries,
+            }],
+        };
+        assert!(verify_state_proof(&proof).unwrap());
+
+        proof.services[0].entries[0].value = Some(Bytes::from("1000000"));
+        assert!(!verify_state_proof(&proof).unwrap());
+    }
 }
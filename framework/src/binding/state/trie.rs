@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use bytes::Bytes;
-use cita_trie::{PatriciaTrie, Trie, TrieError, DB as TrieDB};
+use cita_trie::{MemoryDB, PatriciaTrie, Trie, TrieError, DB as TrieDB};
 use derive_more::{Display, From};
 use hasher::HasherKeccak;
 use lazy_static::lazy_static;
@@ -54,6 +54,18 @@ impl<DB: TrieDB> MPTTrie<DB> {
         Ok(())
     }
 
+    /// Get the merkle patricia proof of a key, which also proves absence when
+    /// the key is not in the trie.
+    pub fn get_proof(&self, key: &Bytes) -> ProtocolResult<Vec<Bytes>> {
+        Ok(self
+            .trie
+            .get_proof(key)
+            .map_err(MPTTrieError::from)?
+            .into_iter()
+            .map(Bytes::from)
+            .collect())
+    }
+
     pub fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
         let root_bytes = self.trie.root().map_err(MPTTrieError::from)?;
         let root = MerkleRoot::from_bytes(Bytes::from(root_bytes))?;
@@ -62,6 +74,23 @@ impl<DB: TrieDB> MPTTrie<DB> {
     }
 }
 
+/// Verify a merkle patricia proof against the root, returning the proven value,
+/// or `None` if the proof shows the key is absent.
+pub fn verify_proof(
+    root: &MerkleRoot,
+    key: &Bytes,
+    proof: Vec<Bytes>,
+) -> ProtocolResult<Option<Bytes>> {
+    let trie =
+        PatriciaTrie::new(Arc::new(MemoryDB::new(true)), Arc::clone(&HASHER_INST));
+    let proof = proof.into_iter().map(|node| node.to_vec()).collect();
+
+    Ok(trie
+        .verify_proof(&root.as_bytes(), key, proof)
+        .map_err(MPTTrieError::from)?
+        .map(Bytes::from))
+}
+
 #[derive(Debug, Display, From)]
 pub enum MPTTrieError {
     #[display(fmt = "{:?}", _0)]
@@ -1,25 +1,37 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Instant;
 
 use bytes::Bytes;
 use derive_more::{Display, From};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
-use rocksdb::{Options, WriteBatch, DB};
+use rlp::Rlp;
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{IteratorMode, Options, WriteBatch, DB};
 
 use common_apm::metrics::storage::{on_storage_get_state, on_storage_put_state};
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::MerkleRoot;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 // 49999 is the largest prime number within 50000.
 const RAND_SEED: u64 = 49999;
+// Trie nodes shorter than a hash are embedded in their parents, the others
+// are stored by their hashes.
+const NODE_HASH_LEN: usize = 32;
 
 pub struct RocksTrieDB {
     light: bool,
     db: Arc<DB>,
     cache_size: usize,
     cache: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    // Keys written since the last prune started, `None` unless pruning is
+    // enabled.
+    written_keys: Option<Mutex<HashSet<Vec<u8>>>>,
+    prune_lock: Mutex<()>,
 }
 
 impl RocksTrieDB {
@@ -28,6 +40,25 @@ impl RocksTrieDB {
         light: bool,
         max_open_files: i32,
         cache_size: usize,
+    ) -> ProtocolResult<Self> {
+        Self::open(path, light, false, max_open_files, cache_size)
+    }
+
+    /// Open a trie db whose unreachable nodes can be deleted by `prune`.
+    pub fn new_with_pruning<P: AsRef<Path>>(
+        path: P,
+        max_open_files: i32,
+        cache_size: usize,
+    ) -> ProtocolResult<Self> {
+        Self::open(path, false, true, max_open_files, cache_size)
+    }
+
+    fn open<P: AsRef<Path>>(
+        path: P,
+        light: bool,
+        pruning: bool,
+        max_open_files: i32,
+        cache_size: usize,
     ) -> ProtocolResult<Self> {
         let mut opts = Options::default();
         opts.create_if_missing(true);
@@ -42,9 +73,156 @@ impl RocksTrieDB {
             db: Arc::new(db),
             cache: RwLock::new(HashMap::with_capacity(cache_size + cache_size)),
             cache_size,
+            written_keys: if pruning {
+                Some(Mutex::new(HashSet::new()))
+            } else {
+                None
+            },
+            prune_lock: Mutex::new(()),
         })
     }
 
+    /// Delete the trie nodes unreachable from all of `roots`, and return the
+    /// number of deleted nodes. The values of the trie at each root which are
+    /// roots themselves, namely the service roots in the root state, are
+    /// followed as well.
+    ///
+    /// Nodes written since the last prune started are always kept, for they
+    /// may belong to a state committed after `roots` were picked. Thus only
+    /// nodes written before the last prune are deleted, and `roots` must
+    /// include the latest committed state root picked after the last prune
+    /// started.
+    pub fn prune(&self, roots: &[MerkleRoot]) -> ProtocolResult<usize> {
+        let written_keys = match &self.written_keys {
+            Some(written_keys) => written_keys,
+            None => return Err(RocksTrieDBError::PruningDisabled.into()),
+        };
+        let _lock = self.prune_lock.lock();
+        let inst = Instant::now();
+
+        let recent_keys = mem::take(&mut *written_keys.lock());
+        let mut marked_keys = HashMap::new();
+        for root in roots.iter() {
+            self.mark(root, &mut marked_keys, &mut |_, _| Ok(()))?;
+        }
+
+        let unreachable_keys = self
+            .db
+            .iterator(IteratorMode::Start)
+            .map(|(key, _)| key)
+            .filter(|key| {
+                !marked_keys.contains_key(&**key) && !recent_keys.contains(&**key)
+            })
+            .collect::<Vec<_>>();
+
+        // Hold the lock so that no key is written between the check and the
+        // deletion.
+        let written_keys = written_keys.lock();
+        let mut batch = WriteBatch::default();
+        let mut count = 0;
+        {
+            let mut cache = self.cache.write();
+            for key in unreachable_keys.iter() {
+                if !written_keys.contains(&**key) {
+                    batch.delete(key);
+                    cache.remove(&**key);
+                    count += 1;
+                }
+            }
+        }
+        self.db.write(batch).map_err(to_store_err)?;
+
+        log::info!(
+            "[framework] prune {} trie nodes of {} roots, cost {:?}",
+            count,
+            roots.len(),
+            inst.elapsed()
+        );
+        Ok(count)
+    }
+
+    /// Create a consistent copy of the trie db at `path`, which must not exist,
+    /// without blocking writes.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> ProtocolResult<()> {
+        let checkpoint = Checkpoint::new(&self.db).map_err(to_store_err)?;
+        checkpoint.create_checkpoint(path).map_err(to_store_err)?;
+        Ok(())
+    }
+
+    /// Visit each trie node reachable from the root once, following the
+    /// service roots in the root state the same way as `prune`.
+    pub fn visit_state<F>(&self, root: &MerkleRoot, mut f: F) -> ProtocolResult<()>
+    where
+        F: FnMut(&[u8], &[u8]) -> ProtocolResult<()>,
+    {
+        self.mark(root, &mut HashMap::new(), &mut f)?;
+        Ok(())
+    }
+
+    /// Count the trie nodes reachable from the root that are not in the db,
+    /// there is none in a complete state.
+    pub fn count_missing(&self, root: &MerkleRoot) -> ProtocolResult<usize> {
+        self.mark(root, &mut HashMap::new(), &mut |_, _| Ok(()))
+    }
+
+    // Mark all nodes reachable from the root, and pass each newly marked node
+    // to `on_marked`. The value of `marked_keys` tells if the node is marked
+    // as a node of the root state, whose values are followed as service roots.
+    // Return the number of nodes not found.
+    fn mark<F>(
+        &self,
+        root: &MerkleRoot,
+        marked_keys: &mut HashMap<Vec<u8>, bool>,
+        on_marked: &mut F,
+    ) -> ProtocolResult<usize>
+    where
+        F: FnMut(&[u8], &[u8]) -> ProtocolResult<()>,
+    {
+        let mut stack = vec![(root.as_bytes().to_vec(), true)];
+        let mut missing = 0;
+
+        while let Some((key, is_root_state)) = stack.pop() {
+            // Revisit a node only if it turns out to be of the root state.
+            let revisit = match marked_keys.get(&key) {
+                Some(marked_root_state) if *marked_root_state || !is_root_state => {
+                    continue
+                }
+                Some(_) => true,
+                None => false,
+            };
+            let node = match self.db.get(&key).map_err(to_store_err)? {
+                Some(node) => node,
+                None => {
+                    missing += 1;
+                    continue;
+                }
+            };
+            if !revisit {
+                on_marked(&key, &node)?;
+            }
+            marked_keys.insert(key, is_root_state);
+
+            visit_node(Rlp::new(&node), &mut |child| match child {
+                NodeRef::Hash(key) => stack.push((key.to_vec(), is_root_state)),
+                NodeRef::Value(value) if is_root_state => {
+                    if let Ok(service_root) =
+                        MerkleRoot::decode_fixed(Bytes::copy_from_slice(value))
+                    {
+                        stack.push((service_root.as_bytes().to_vec(), false));
+                    }
+                }
+                NodeRef::Value(_) => {}
+            })?;
+        }
+        Ok(missing)
+    }
+
+    fn record_written<'a, I: Iterator<Item = &'a Vec<u8>>>(&self, keys: I) {
+        if let Some(written_keys) = &self.written_keys {
+            written_keys.lock().extend(keys.cloned());
+        }
+    }
+
     fn inner_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, RocksTrieDBError> {
         let res = {
             let cache = self.cache.read();
@@ -127,6 +305,7 @@ impl cita_trie::DB for RocksTrieDB {
     fn insert(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), Self::Error> {
         let inst = Instant::now();
         let size = key.len() + value.len();
+        self.record_written(std::iter::once(&key));
 
         {
             let mut cache = self.cache.write();
@@ -152,6 +331,7 @@ impl cita_trie::DB for RocksTrieDB {
 
         let mut total_size = 0;
         let mut batch = WriteBatch::default();
+        self.record_written(keys.iter());
 
         {
             let mut cache = self.cache.write();
@@ -214,6 +394,58 @@ impl cita_trie::DB for RocksTrieDB {
     }
 }
 
+enum NodeRef<'a> {
+    Hash(&'a [u8]),
+    Value(&'a [u8]),
+}
+
+// Visit the children stored by hashes and the values of an encoded trie node.
+fn visit_node<F: FnMut(NodeRef)>(node: Rlp, f: &mut F) -> Result<(), RocksTrieDBError> {
+    if !node.is_list() {
+        // The empty node.
+        return Ok(());
+    }
+
+    match node.item_count()? {
+        // Leaf or extension node, told apart by the flag of the compact key.
+        2 => {
+            let is_leaf = node.at(0)?.data()?.first().map_or(false, |b| b >> 4 >= 2);
+            if is_leaf {
+                f(NodeRef::Value(node.at(1)?.data()?));
+            } else {
+                visit_child(node.at(1)?, f)?;
+            }
+        }
+        // Branch node.
+        17 => {
+            for i in 0..16 {
+                visit_child(node.at(i)?, f)?;
+            }
+            let value = node.at(16)?.data()?;
+            if !value.is_empty() {
+                f(NodeRef::Value(value));
+            }
+        }
+        _ => return Err(RocksTrieDBError::InvalidNode),
+    }
+    Ok(())
+}
+
+fn visit_child<F: FnMut(NodeRef)>(
+    child: Rlp,
+    f: &mut F,
+) -> Result<(), RocksTrieDBError> {
+    if child.is_list() {
+        visit_node(child, f)
+    } else {
+        let data = child.data()?;
+        if data.len() == NODE_HASH_LEN {
+            f(NodeRef::Hash(data));
+        }
+        Ok(())
+    }
+}
+
 fn rand_remove_list<T: Clone>(keys: Vec<&T>, num: usize) -> Vec<T> {
     let mut len = keys.len() - 1;
     let mut idx_list = (0..len).collect::<Vec<_>>();
@@ -242,6 +474,15 @@ pub enum RocksTrieDBError {
 
     #[display(fmt = "batch length dont match")]
     BatchLengthMismatch,
+
+    #[display(fmt = "pruning is disabled")]
+    PruningDisabled,
+
+    #[display(fmt = "decode trie node {:?}", _0)]
+    DecodeNode(rlp::DecoderError),
+
+    #[display(fmt = "invalid trie node")]
+    InvalidNode,
 }
 
 impl std::error::Error for RocksTrieDBError {}
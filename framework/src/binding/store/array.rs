@@ -9,17 +9,19 @@ use protocol::traits::{ServiceState, StoreArray};
 use protocol::types::Hash;
 use protocol::ProtocolResult;
 
+use crate::binding::meter::CyclesMeter;
 use crate::binding::store::FixedKeys;
 
 pub struct DefaultStoreArray<S: ServiceState, E: FixedCodec> {
     state: Rc<RefCell<S>>,
     var_name: Hash,
     keys: FixedKeys<Hash>,
+    meter: Rc<CyclesMeter>,
     phantom: PhantomData<E>,
 }
 
 impl<S: ServiceState, E: FixedCodec> DefaultStoreArray<S, E> {
-    pub fn new(state: Rc<RefCell<S>>, name: &str) -> Self {
+    pub fn new(state: Rc<RefCell<S>>, name: &str, meter: Rc<CyclesMeter>) -> Self {
         let var_name = Hash::digest(Bytes::from(name.to_owned() + "array"));
 
         let opt_bs: Option<Bytes> = state
@@ -37,16 +39,14 @@ impl<S: ServiceState, E: FixedCodec> DefaultStoreArray<S, E> {
             state,
             var_name,
             keys,
+            meter,
             phantom: PhantomData,
         }
     }
 
     fn inner_get(&self, index: u64) -> ProtocolResult<Option<E>> {
         if let Some(k) = self.keys.inner.get(index as usize) {
-            self.state
-                .borrow()
-                .get(k)?
-                .map_or_else(|| Ok(None), |v| Ok(Some(v)))
+            self.meter.get(&self.state, k)
         } else {
             Ok(None)
         }
@@ -58,22 +58,26 @@ impl<S: ServiceState, E: FixedCodec> DefaultStoreArray<S, E> {
         let key = Hash::digest(elm.encode_fixed()?);
 
         self.keys.inner.push(key.clone());
-        self.state
-            .borrow_mut()
-            .insert(self.var_name.clone(), self.keys.encode_fixed()?)?;
+        self.meter.insert(
+            &self.state,
+            self.var_name.clone(),
+            self.keys.encode_fixed()?,
+        )?;
 
-        self.state.borrow_mut().insert(key, elm)
+        self.meter.insert(&self.state, key, elm)
     }
 
     // TODO(@zhounan): Atomicity of insert(k, v) and insert self.keys to
     // ServiceState is not guaranteed for now That must be settled soon after.
     fn inner_remove(&mut self, index: u64) -> ProtocolResult<()> {
         let key = self.keys.inner.remove(index as usize);
-        self.state
-            .borrow_mut()
-            .insert(self.var_name.clone(), self.keys.encode_fixed()?)?;
+        self.meter.insert(
+            &self.state,
+            self.var_name.clone(),
+            self.keys.encode_fixed()?,
+        )?;
 
-        self.state.borrow_mut().insert(key, Bytes::new())
+        self.meter.insert(&self.state, key, Bytes::new())
     }
 }
 
@@ -11,6 +11,7 @@ use protocol::traits::{ServiceState, StoreMap};
 use protocol::types::Hash;
 use protocol::ProtocolResult;
 
+use crate::binding::meter::CyclesMeter;
 use crate::binding::store::{get_bucket_index, Bucket, FixedBuckets};
 
 pub struct DefaultStoreMap<S: ServiceState, K: FixedCodec + PartialEq, V: FixedCodec> {
@@ -19,6 +20,7 @@ pub struct DefaultStoreMap<S: ServiceState, K: FixedCodec + PartialEq, V: FixedC
     keys: RefCell<FixedBuckets<K>>,
     len_key: Bytes,
     len: u64,
+    meter: Rc<CyclesMeter>,
     phantom: PhantomData<V>,
 }
 
@@ -28,7 +30,7 @@ where
     K: 'static + Send + FixedCodec + PartialEq,
     V: 'static + FixedCodec,
 {
-    pub fn new(state: Rc<RefCell<S>>, name: &str) -> Self {
+    pub fn new(state: Rc<RefCell<S>>, name: &str, meter: Rc<CyclesMeter>) -> Self {
         let len_key = Bytes::from(name.to_string() + "_map_len");
         let len = state
             .borrow()
@@ -42,6 +44,7 @@ where
             len,
             var_name: name.to_string(),
             keys: RefCell::new(FixedBuckets::new()),
+            meter,
             phantom: PhantomData,
         }
     }
@@ -54,13 +57,14 @@ where
         if !self.inner_contains(bkt_idx, &key)? {
             self.keys.borrow_mut().insert(bkt_idx, key);
 
-            self.state.borrow_mut().insert(
+            self.meter.insert(
+                &self.state,
                 self.get_bucket_name(bkt_idx),
                 self.keys.borrow().get_bucket(bkt_idx).encode_fixed()?,
             )?;
             self.len_add_one()?;
         }
-        self.state.borrow_mut().insert(mk, value)
+        self.meter.insert(&self.state, mk, value)
     }
 
     fn inner_get(&self, key: &K) -> ProtocolResult<Option<V>> {
@@ -68,10 +72,7 @@ where
         let bkt_idx = get_bucket_index(&key_bytes);
 
         if self.inner_contains(bkt_idx, &key)? {
-            self.state
-                .borrow()
-                .get(&self.get_map_key(&key_bytes))?
-                .map_or_else(|| Ok(None), |v| Ok(Some(v)))
+            self.meter.get(&self.state, &self.get_map_key(&key_bytes))
         } else {
             Ok(None)
         }
@@ -87,13 +88,16 @@ where
             let bkt_name = self.get_bucket_name(bkt_idx);
 
             let _ = self.keys.borrow_mut().remove_item(bkt_idx, key)?;
-            self.state.borrow_mut().insert(
+            self.meter.insert(
+                &self.state,
                 bkt_name,
                 self.keys.borrow().get_bucket(bkt_idx).encode_fixed()?,
             )?;
-            self.state
-                .borrow_mut()
-                .insert(self.get_map_key(&key_bytes), Bytes::new())?;
+            self.meter.insert(
+                &self.state,
+                self.get_map_key(&key_bytes),
+                Bytes::new(),
+            )?;
             self.len_sub_one()?;
             Ok(Some(value))
         } else {
@@ -107,8 +111,9 @@ where
             return Ok(self.keys.borrow().contains(bkt_idx, key));
         }
 
-        let bkt = if let Some(bytes) =
-            self.state.borrow().get(&self.get_bucket_name(bkt_idx))?
+        let bkt = if let Some(bytes) = self
+            .meter
+            .get(&self.state, &self.get_bucket_name(bkt_idx))?
         {
             <_>::decode_fixed(bytes)?
         } else {
@@ -139,16 +144,14 @@ where
 
     fn len_add_one(&mut self) -> ProtocolResult<()> {
         self.len += 1;
-        self.state
-            .borrow_mut()
-            .insert(self.len_key.clone(), self.len.encode_fixed()?)
+        self.meter
+            .insert(&self.state, self.len_key.clone(), self.len.encode_fixed()?)
     }
 
     fn len_sub_one(&mut self) -> ProtocolResult<()> {
         self.len -= 1;
-        self.state
-            .borrow_mut()
-            .insert(self.len_key.clone(), self.len.encode_fixed()?)
+        self.meter
+            .insert(&self.state, self.len_key.clone(), self.len.encode_fixed()?)
     }
 
     fn recover_all_buckets(&self) {
@@ -165,7 +168,7 @@ where
             .iter()
             .map(|idx| {
                 let name = self.get_bucket_name(*idx);
-                self.state.borrow().get(&name).unwrap()
+                self.meter.get(&self.state, &name).unwrap()
             })
             .collect::<Vec<_>>();
 
@@ -312,7 +315,11 @@ mod tests {
         let state = Rc::new(RefCell::new(GeneralServiceState::new(MPTTrie::new(
             Arc::new(MemoryDB::new(false)),
         ))));
-        let mut map = DefaultStoreMap::<_, Bytes, Bytes>::new(Rc::clone(&state), "test");
+        let mut map = DefaultStoreMap::<_, Bytes, Bytes>::new(
+            Rc::clone(&state),
+            "test",
+            Rc::new(CyclesMeter::default()),
+        );
         let key_1 = gen_bytes();
         let val_1 = gen_bytes();
         let key_2 = gen_bytes();
@@ -7,33 +7,37 @@ use protocol::traits::{ServiceState, StoreBool, StoreString, StoreUint64};
 use protocol::types::Hash;
 use protocol::ProtocolResult;
 
+use crate::binding::meter::CyclesMeter;
+
 pub struct DefaultStoreBool<S: ServiceState> {
     state: Rc<RefCell<S>>,
     key: Hash,
+    meter: Rc<CyclesMeter>,
 }
 
 impl<S: ServiceState> DefaultStoreBool<S> {
-    pub fn new(state: Rc<RefCell<S>>, var_name: &str) -> Self {
+    pub fn new(state: Rc<RefCell<S>>, var_name: &str, meter: Rc<CyclesMeter>) -> Self {
         Self {
             state,
             key: Hash::digest(Bytes::from(var_name.to_owned() + "bool")),
+            meter,
         }
     }
 
     fn inner_get(&self) -> ProtocolResult<bool> {
-        let b: Option<bool> = self.state.borrow().get(&self.key)?;
+        let b: Option<bool> = self.meter.get(&self.state, &self.key)?;
 
         match b {
             Some(v) => Ok(v),
             None => {
-                self.state.borrow_mut().insert(self.key.clone(), false)?;
+                self.meter.insert(&self.state, self.key.clone(), false)?;
                 Ok(false)
             }
         }
     }
 
     fn inner_set(&mut self, b: bool) -> ProtocolResult<()> {
-        self.state.borrow_mut().insert(self.key.clone(), b)?;
+        self.meter.insert(&self.state, self.key.clone(), b)?;
         Ok(())
     }
 }
@@ -53,29 +57,29 @@ impl<S: ServiceState> StoreBool for DefaultStoreBool<S> {
 pub struct DefaultStoreUint64<S: ServiceState> {
     state: Rc<RefCell<S>>,
     key: Hash,
+    meter: Rc<CyclesMeter>,
 }
 
 impl<S: ServiceState> DefaultStoreUint64<S> {
-    pub fn new(state: Rc<RefCell<S>>, var_name: &str) -> Self {
+    pub fn new(state: Rc<RefCell<S>>, var_name: &str, meter: Rc<CyclesMeter>) -> Self {
         Self {
             state,
             key: Hash::digest(Bytes::from(var_name.to_owned() + "uint64")),
+            meter,
         }
     }
 
     fn inner_get(&self) -> u64 {
         let u: Option<u64> = self
-            .state
-            .borrow()
-            .get(&self.key)
+            .meter
+            .get(&self.state, &self.key)
             .unwrap_or_else(|e| panic!("StoreUint64 get failed: {}", e));
 
         match u {
             Some(v) => v,
             None => {
-                self.state
-                    .borrow_mut()
-                    .insert(self.key.clone(), 0u64)
+                self.meter
+                    .insert(&self.state, self.key.clone(), 0u64)
                     .unwrap_or_else(|e| panic!("StoreUint64 get failed: {}", e));
                 0
             }
@@ -83,9 +87,8 @@ impl<S: ServiceState> DefaultStoreUint64<S> {
     }
 
     fn inner_set(&mut self, val: u64) {
-        self.state
-            .borrow_mut()
-            .insert(self.key.clone(), val)
+        self.meter
+            .insert(&self.state, self.key.clone(), val)
             .unwrap_or_else(|e| panic!("StoreUint64 set failed: {}", e));
     }
 
@@ -220,32 +223,32 @@ impl<S: ServiceState> StoreUint64 for DefaultStoreUint64<S> {
 pub struct DefaultStoreString<S: ServiceState> {
     state: Rc<RefCell<S>>,
     key: Hash,
+    meter: Rc<CyclesMeter>,
 }
 
 impl<S: ServiceState> DefaultStoreString<S> {
-    pub fn new(state: Rc<RefCell<S>>, var_name: &str) -> Self {
+    pub fn new(state: Rc<RefCell<S>>, var_name: &str, meter: Rc<CyclesMeter>) -> Self {
         Self {
             state,
             key: Hash::digest(Bytes::from(var_name.to_owned() + "string")),
+            meter,
         }
     }
 
     fn inner_set(&mut self, val: &str) -> ProtocolResult<()> {
-        self.state
-            .borrow_mut()
-            .insert(self.key.clone(), val.to_string())?;
+        self.meter
+            .insert(&self.state, self.key.clone(), val.to_string())?;
         Ok(())
     }
 
     fn inner_get(&self) -> ProtocolResult<String> {
-        let s: Option<String> = self.state.borrow().get(&self.key)?;
+        let s: Option<String> = self.meter.get(&self.state, &self.key)?;
 
         match s {
             Some(v) => Ok(v),
             None => {
-                self.state
-                    .borrow_mut()
-                    .insert(self.key.clone(), "".to_string())?;
+                self.meter
+                    .insert(&self.state, self.key.clone(), "".to_string())?;
                 Ok("".to_string())
             }
         }
@@ -8,12 +8,14 @@ use cita_trie::MemoryDB;
 
 use protocol::traits::{CommonStorage, Context, ServiceResponse, ServiceSDK, Storage};
 use protocol::types::{
-    Address, Block, BlockHeader, Event, Hash, MerkleRoot, Proof, RawTransaction,
-    Receipt, ReceiptResponse, SignedTransaction, TransactionRequest, Validator,
+    Address, Block, BlockHeader, Event, EventFilter, Hash, IndexedEvent, MerkleRoot,
+    PayloadEncoding, Proof, RawTransaction, Receipt, ReceiptResponse, SignedTransaction,
+    TransactionRequest, Validator,
 };
 use protocol::ProtocolResult;
 
-use crate::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK};
+use crate::binding::meter::CyclesMeter;
+use crate::binding::sdk::{DefaultChainQuerier, DefaultServiceSDK, NoopDispatcher};
 use crate::binding::store::StoreError;
 use crate::binding::tests::state::new_state;
 
@@ -26,7 +28,12 @@ fn test_service_sdk() {
     let arcs = Arc::new(MockStorage {});
     let cq = DefaultChainQuerier::new(Arc::clone(&arcs));
 
-    let mut sdk = DefaultServiceSDK::new(Rc::clone(&rs), Rc::new(cq));
+    let mut sdk = DefaultServiceSDK::new(
+        Rc::clone(&rs),
+        Rc::new(cq),
+        Rc::new(NoopDispatcher),
+        Rc::new(CyclesMeter::default()),
+    );
 
     // test sdk store bool
     let mut sdk_bool = sdk.alloc_or_recover_bool("test_bool");
@@ -216,6 +223,18 @@ impl Storage for MockStorage {
     async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
         Err(StoreError::GetNone.into())
     }
+
+    async fn get_events(
+        &self,
+        _ctx: Context,
+        _: EventFilter,
+        _: u64,
+        _: u64,
+        _: u64,
+        _: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        Err(StoreError::GetNone.into())
+    }
 }
 
 // #####################
@@ -248,6 +267,7 @@ pub fn mock_transaction_request() -> TransactionRequest {
         service_name: "mock-service".to_owned(),
         method: "mock-method".to_owned(),
         payload: "mock-payload".to_owned(),
+        encoding: PayloadEncoding::Json,
     }
 }
 
@@ -284,6 +304,7 @@ pub fn mock_receipt() -> Receipt {
         cycles_used: 100,
         events: vec![mock_event()],
         response: mock_receipt_response(),
+        fee: None,
     }
 }
 
@@ -196,6 +196,48 @@ fn test_state_account() {
     assert_eq!(val, value);
 }
 
+#[test]
+fn test_prune_state() {
+    let triedb = Arc::new(new_pruning_triedb("test_prune_state"));
+    let keys = (0..100)
+        .map(|_| Bytes::from(rand_bytes()))
+        .collect::<Vec<_>>();
+
+    let mut state = GeneralServiceState::new(MPTTrie::new(Arc::clone(&triedb)));
+    for key in keys.iter() {
+        state.insert(key.clone(), Bytes::from("old")).unwrap();
+    }
+    state.stash().unwrap();
+    let old_service_root = state.commit().unwrap();
+    for key in keys.iter() {
+        state.insert(key.clone(), Bytes::from("new")).unwrap();
+    }
+    state.stash().unwrap();
+    let service_root = state.commit().unwrap();
+
+    let mut root_state = GeneralServiceState::new(MPTTrie::new(Arc::clone(&triedb)));
+    root_state.insert("test".to_owned(), service_root).unwrap();
+    root_state.stash().unwrap();
+    let state_root = root_state.commit().unwrap();
+
+    // Nodes written since the last prune started are kept.
+    assert_eq!(triedb.prune(&[state_root.clone()]).unwrap(), 0);
+    assert!(triedb.prune(&[state_root.clone()]).unwrap() > 0);
+    assert!(MPTTrie::from(old_service_root, Arc::clone(&triedb)).is_err());
+
+    let root_state = GeneralServiceState::new(
+        MPTTrie::from(state_root, Arc::clone(&triedb)).unwrap(),
+    );
+    let service_root: MerkleRoot = root_state.get(&"test".to_owned()).unwrap().unwrap();
+    let state = GeneralServiceState::new(MPTTrie::from(service_root, triedb).unwrap());
+    for key in keys.iter() {
+        assert_eq!(
+            state.get::<Bytes, Bytes>(key).unwrap(),
+            Some(Bytes::from("new"))
+        );
+    }
+}
+
 pub fn new_state(
     memdb: Arc<MemoryDB>,
     root: Option<MerkleRoot>,
@@ -214,6 +256,14 @@ fn new_triedb(name: &str) -> RocksTrieDB {
     RocksTrieDB::new(path, false, 1024, 2000).unwrap()
 }
 
+fn new_pruning_triedb(name: &str) -> RocksTrieDB {
+    let mut path = PathBuf::from("./free-space/");
+    path.push(name);
+    // Nodes left by previous runs would be pruned.
+    let _ = std::fs::remove_dir_all(&path);
+    RocksTrieDB::new_with_pruning(path, 1024, 2000).unwrap()
+}
+
 fn rand_bytes() -> Vec<u8> {
     (0..32).map(|_| rand::random::<u8>()).collect::<Vec<u8>>()
 }
@@ -1,13 +1,19 @@
 use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
 use std::sync::Arc;
 
 use bytes::Bytes;
 use cita_trie::MemoryDB;
 
+use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{StoreArray, StoreBool, StoreMap, StoreString, StoreUint64};
-use protocol::types::Hash;
+use protocol::types::{
+    Address, Hash, PayloadEncoding, ServiceContext, ServiceContextError,
+    ServiceContextParams, StorageCycles,
+};
 
+use crate::binding::meter::CyclesMeter;
 use crate::binding::store::{
     DefaultStoreArray, DefaultStoreBool, DefaultStoreMap, DefaultStoreString,
     DefaultStoreUint64,
@@ -19,7 +25,11 @@ fn test_default_store_bool() {
     let memdb = Arc::new(MemoryDB::new(false));
     let state = new_state(Arc::clone(&memdb), None);
 
-    let mut sb = DefaultStoreBool::new(Rc::new(RefCell::new(state)), "test");
+    let mut sb = DefaultStoreBool::new(
+        Rc::new(RefCell::new(state)),
+        "test",
+        Rc::new(CyclesMeter::default()),
+    );
 
     assert_eq!(sb.get(), false);
     sb.set(true);
@@ -33,7 +43,11 @@ fn test_default_store_uint64() {
     let memdb = Arc::new(MemoryDB::new(false));
     let state = new_state(Arc::clone(&memdb), None);
 
-    let mut su = DefaultStoreUint64::new(Rc::new(RefCell::new(state)), "test");
+    let mut su = DefaultStoreUint64::new(
+        Rc::new(RefCell::new(state)),
+        "test",
+        Rc::new(CyclesMeter::default()),
+    );
 
     assert_eq!(su.get(), 0u64);
     su.set(8u64);
@@ -64,7 +78,8 @@ fn test_default_store_string() {
     let state = new_state(Arc::clone(&memdb), None);
 
     let rs = Rc::new(RefCell::new(state));
-    let mut ss = DefaultStoreString::new(Rc::clone(&rs), "test");
+    let mut ss =
+        DefaultStoreString::new(Rc::clone(&rs), "test", Rc::new(CyclesMeter::default()));
 
     assert_eq!(ss.get(), "");
 
@@ -83,7 +98,11 @@ fn test_default_store_map() {
     let state = new_state(Arc::clone(&memdb), None);
     let rs = Rc::new(RefCell::new(state));
 
-    let mut sm = DefaultStoreMap::<_, Hash, Bytes>::new(Rc::clone(&rs), "test");
+    let mut sm = DefaultStoreMap::<_, Hash, Bytes>::new(
+        Rc::clone(&rs),
+        "test",
+        Rc::new(CyclesMeter::default()),
+    );
 
     assert_eq!(sm.get(&Hash::digest(Bytes::from("key_1"))).is_none(), true);
     sm.insert(Hash::digest(Bytes::from("key_1")), Bytes::from("val_1"));
@@ -116,7 +135,11 @@ fn test_default_store_map() {
     assert_eq!(sm.contains(&Hash::digest(Bytes::from("key_1"))), false);
     assert_eq!(sm.len(), 1u64);
 
-    let sm = DefaultStoreMap::<_, Hash, Bytes>::new(Rc::clone(&rs), "test");
+    let sm = DefaultStoreMap::<_, Hash, Bytes>::new(
+        Rc::clone(&rs),
+        "test",
+        Rc::new(CyclesMeter::default()),
+    );
     assert_eq!(
         sm.get(&Hash::digest(Bytes::from("key_2"))).unwrap(),
         Bytes::from("val_2")
@@ -129,7 +152,11 @@ fn test_default_store_array() {
     let state = new_state(Arc::clone(&memdb), None);
     let rs = Rc::new(RefCell::new(state));
 
-    let mut sa = DefaultStoreArray::<_, Bytes>::new(Rc::clone(&rs), "test");
+    let mut sa = DefaultStoreArray::<_, Bytes>::new(
+        Rc::clone(&rs),
+        "test",
+        Rc::new(CyclesMeter::default()),
+    );
 
     assert_eq!(sa.len(), 0u64);
     assert_eq!(sa.get(0u64).is_none(), true);
@@ -154,3 +181,66 @@ fn test_default_store_array() {
     assert_eq!(sa.len(), 1u64);
     assert_eq!(sa.get(0u64).unwrap(), Bytes::from("222"));
 }
+
+#[test]
+fn test_store_cycles_meter() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let state = new_state(Arc::clone(&memdb), None);
+    let rs = Rc::new(RefCell::new(state));
+
+    let meter = Rc::new(CyclesMeter::new(StorageCycles {
+        read: 100,
+        write: 1000,
+        byte: 1,
+    }));
+    let mut su = DefaultStoreUint64::new(Rc::clone(&rs), "test", Rc::clone(&meter));
+
+    // Nothing is charged outside a call
+    su.set(8u64);
+
+    let key_len = Hash::digest(Bytes::from("testuint64"))
+        .encode_fixed()
+        .unwrap()
+        .len() as u64;
+    let write_cycles = 1_000 + key_len + 8;
+    let read_cycles = 100 + key_len + 8;
+
+    let context = mock_context(2_000);
+    {
+        let _guard = meter.enter(context.clone());
+
+        su.set(9u64);
+        assert_eq!(context.get_cycles_used(), write_cycles);
+        assert_eq!(su.get(), 9u64);
+        assert_eq!(context.get_cycles_used(), write_cycles + read_cycles);
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| su.set(10u64)));
+        assert!(res.unwrap_err().is::<ServiceContextError>());
+        assert_eq!(context.get_cycles_used(), write_cycles + read_cycles);
+    }
+
+    // The meter stops charging once the guard is dropped
+    su.set(11u64);
+    assert_eq!(context.get_cycles_used(), write_cycles + read_cycles);
+}
+
+fn mock_context(cycles_limit: u64) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller: Address::from_hash(Hash::from_empty()).unwrap(),
+        height: 1,
+        timestamp: 0,
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        service_payload_encoding: PayloadEncoding::Json,
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+use cita_trie::DB as TrieDB;
+
+use protocol::traits::{Dispatcher, Service, ServiceResponse};
+use protocol::types::ServiceContext;
+
+use crate::executor::{ServiceStateMap, SERVICE_NOT_FOUND_CODE};
+
+pub const SERVICE_REENTRANT_CODE: u64 = 62079;
+
+// Services hold their sdk, and the sdk holds the dispatcher, so the services
+// are held weakly to avoid a reference cycle.
+pub struct ServiceDispatcher<DB: TrieDB> {
+    states: Rc<ServiceStateMap<DB>>,
+    services: RefCell<HashMap<String, Weak<RefCell<Box<dyn Service>>>>>,
+}
+
+impl<DB: TrieDB> ServiceDispatcher<DB> {
+    pub fn new(states: Rc<ServiceStateMap<DB>>) -> Self {
+        Self {
+            states,
+            services: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(&self, name: String, service: &Rc<RefCell<Box<dyn Service>>>) {
+        self.services
+            .borrow_mut()
+            .insert(name, Rc::downgrade(service));
+    }
+
+    fn get_service(&self, name: &str) -> Option<Rc<RefCell<Box<dyn Service>>>> {
+        self.services.borrow().get(name).and_then(Weak::upgrade)
+    }
+}
+
+impl<DB: TrieDB> Dispatcher for ServiceDispatcher<DB> {
+    fn read(&self, context: ServiceContext) -> ServiceResponse<String> {
+        let service = match self.get_service(context.get_service_name()) {
+            Some(service) => service,
+            None => return service_not_found(&context),
+        };
+
+        // A service calling back into a service that is in the middle of a
+        // write would see its state half changed
+        let ret = match service.try_borrow() {
+            Ok(service) => service.read_(context),
+            Err(_) => return service_reentrant(&context),
+        };
+        ret
+    }
+
+    fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
+        let service = match self.get_service(context.get_service_name()) {
+            Some(service) => service,
+            None => return service_not_found(&context),
+        };
+
+        let mut service = match service.try_borrow_mut() {
+            Ok(service) => service,
+            Err(_) => return service_reentrant(&context),
+        };
+
+        // Roll back what the callee has done if it fails, as a failed tx is
+        // rolled back, but leave the caller free to go on
+        let event_index = context.get_events_len();
+        for state in self.states.values() {
+            state.borrow_mut().checkpoint();
+        }
+
+        let ret = service.write_(context.clone());
+
+        for state in self.states.values() {
+            if ret.is_error() {
+                state.borrow_mut().revert_checkpoint();
+            } else {
+                state.borrow_mut().release_checkpoint();
+            }
+        }
+        if ret.is_error() {
+            context.truncate_events(event_index);
+        }
+
+        ret
+    }
+}
+
+fn service_not_found(context: &ServiceContext) -> ServiceResponse<String> {
+    ServiceResponse::from_error(
+        SERVICE_NOT_FOUND_CODE,
+        format!("can not found service {}", context.get_service_name()),
+    )
+}
+
+fn service_reentrant(context: &ServiceContext) -> ServiceResponse<String> {
+    ServiceResponse::from_error(
+        SERVICE_REENTRANT_CODE,
+        format!(
+            "service {} is already in a call",
+            context.get_service_name()
+        ),
+    )
+}
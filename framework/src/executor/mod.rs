@@ -1,8 +1,10 @@
+mod dispatcher;
 mod error;
 mod factory;
 #[cfg(test)]
 mod tests;
 
+pub use dispatcher::{ServiceDispatcher, SERVICE_REENTRANT_CODE};
 pub use factory::ServiceExecutorFactory;
 
 use std::{
@@ -19,21 +21,54 @@ use std::{
 use cita_trie::DB as TrieDB;
 
 use common_apm::muta_apm;
+use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
-    Context, Executor, ExecutorParams, ExecutorResp, Service, ServiceMapping,
-    ServiceResponse, ServiceState, Storage,
+    Context, DryRunResp, Executor, ExecutorParams, ExecutorResp, Service,
+    ServiceMapping, ServiceResponse, ServiceState, ServiceStateProof, StateProof,
+    Storage,
 };
 use protocol::types::{
-    Address, Event, Hash, MerkleRoot, Receipt, ReceiptResponse, ServiceContext,
-    ServiceContextParams, ServiceParam, SignedTransaction, TransactionRequest,
+    fee_policy, sequential_nonce_enabled, storage_cycles, Address, Event, FeePledge,
+    FeePolicy, Hash, MerkleRoot, PayloadEncoding, Receipt, ReceiptFee, ReceiptResponse,
+    ServiceContext, ServiceContextError, ServiceContextParams, ServiceParam,
+    ServiceSchema, SignedTransaction, TransactionRequest, FEE_ESCROW,
+    OUT_OF_CYCLES_CODE,
 };
 use protocol::{ProtocolError, ProtocolResult};
 
+use crate::binding::meter::CyclesMeter;
 use crate::binding::sdk::{DefaultChainQuerier, DefaultSDKFactory};
 use crate::binding::state::{GeneralServiceState, MPTTrie};
 use crate::executor::error::ExecutorError;
 
 const SERVICE_NOT_FOUND_CODE: u64 = 62077;
+pub const INVALID_NONCE_CODE: u64 = 62080;
+pub const INSUFFICIENT_FEE_CODE: u64 = 62081;
+
+// Sequential nonces are account values of the root state
+const NONCE_KEY: &str = "nonce";
+
+// The receipt of a tx rejected without being executed
+fn rejected_receipt(
+    params: &ExecutorParams,
+    stx: &SignedTransaction,
+    code: u64,
+    message: String,
+) -> Receipt {
+    Receipt {
+        state_root: MerkleRoot::from_empty(),
+        height: params.height,
+        tx_hash: stx.tx_hash.clone(),
+        cycles_used: 0,
+        events: vec![],
+        response: ReceiptResponse {
+            service_name: stx.raw.request.service_name.to_owned(),
+            method: stx.raw.request.method.to_owned(),
+            response: ServiceResponse::from_error(code, message),
+        },
+        fee: None,
+    }
+}
 
 trait TxHooks {
     fn before(
@@ -117,6 +152,12 @@ impl<DB: TrieDB> ServiceStateMap<DB> {
 
         Ok(())
     }
+
+    fn discard(&self) {
+        for state in self.0.values() {
+            state.borrow_mut().discard();
+        }
+    }
 }
 
 struct CommitHooks<DB: TrieDB> {
@@ -198,6 +239,9 @@ pub struct ServiceExecutor<S: Storage, DB: TrieDB, Mapping: ServiceMapping> {
     states: Rc<ServiceStateMap<DB>>,
     root_state: GeneralServiceState<DB>,
     services: HashMap<String, Rc<RefCell<Box<dyn Service>>>>,
+    meter: Rc<CyclesMeter>,
+    sequential_nonce: bool,
+    fee: Option<FeePolicy>,
 
     phantom: PhantomData<S>,
 }
@@ -221,9 +265,17 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         }
 
         let states = Rc::new(states);
-        let sdk_factory =
-            DefaultSDKFactory::new(Rc::clone(&states), Rc::clone(&querier));
-
+        let dispatcher = Rc::new(ServiceDispatcher::new(Rc::clone(&states)));
+        let sdk_factory = DefaultSDKFactory::new(
+            Rc::clone(&states),
+            Rc::clone(&querier),
+            Rc::clone(&dispatcher),
+            Rc::new(CyclesMeter::default()),
+        );
+
+        // Keep the services alive, so that the later ones can call the earlier
+        // ones in genesis
+        let mut genesis_services = Vec::new();
         for params in services.into_iter() {
             let state =
                 states
@@ -232,15 +284,19 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
                         service: params.name.to_owned(),
                     })?;
 
-            let mut service = mapping.get_service(&params.name, &sdk_factory)?;
+            let service = mapping.get_service(&params.name, &sdk_factory)?;
+            let service = Rc::new(RefCell::new(service));
+            dispatcher.register(params.name.to_owned(), &service);
+
             panic::catch_unwind(AssertUnwindSafe(|| {
-                service.genesis_(params.payload.clone())
+                service.borrow_mut().genesis_(params.payload.clone())
             }))
             .map_err(|e| {
                 ProtocolError::from(ExecutorError::InitService(format!("{:?}", e)))
             })?;
 
             state.borrow_mut().stash()?;
+            genesis_services.push(service);
         }
 
         let trie = MPTTrie::new(Arc::clone(&trie_db));
@@ -276,13 +332,21 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         }
 
         let states = Rc::new(states);
-        let sdk_factory =
-            DefaultSDKFactory::new(Rc::clone(&states), Rc::clone(&querier));
+        let dispatcher = Rc::new(ServiceDispatcher::new(Rc::clone(&states)));
+        let meter = Rc::new(CyclesMeter::new(storage_cycles()));
+        let sdk_factory = DefaultSDKFactory::new(
+            Rc::clone(&states),
+            Rc::clone(&querier),
+            Rc::clone(&dispatcher),
+            Rc::clone(&meter),
+        );
 
         let mut services = HashMap::new();
         for name in list_service_name.iter() {
             let service = service_mapping.get_service(name, &sdk_factory)?;
-            services.insert(name.clone(), Rc::new(RefCell::new(service)));
+            let service = Rc::new(RefCell::new(service));
+            dispatcher.register(name.clone(), &service);
+            services.insert(name.clone(), service);
         }
 
         Ok(Self {
@@ -290,6 +354,9 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
             states,
             root_state,
             services,
+            meter,
+            sequential_nonce: sequential_nonce_enabled(),
+            fee: fee_policy(),
             phantom: PhantomData,
         })
     }
@@ -343,6 +410,110 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         Ok(())
     }
 
+    // Return the receipt of a rejected tx if its nonce is not the next one of
+    // the sender
+    fn check_nonce(
+        &self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+    ) -> ProtocolResult<Option<Receipt>> {
+        let nonce = self.get_account_nonce(&stx.raw.sender)?;
+
+        if stx.raw.sequential_nonce() != Some(nonce) {
+            return Ok(Some(rejected_receipt(
+                params,
+                stx,
+                INVALID_NONCE_CODE,
+                format!("invalid nonce, expect {}", nonce),
+            )));
+        }
+
+        Ok(None)
+    }
+
+    fn bump_nonce(&mut self, sender: &Address) -> ProtocolResult<()> {
+        let nonce = self.get_account_nonce(sender)?;
+        self.root_state
+            .set_account_value(sender, NONCE_KEY.to_owned(), nonce + 1)
+    }
+
+    // Move `value` of the fee asset from `from` to `to` by calling the asset
+    // service as `from`, return false if the transfer fails
+    fn transfer_fee(
+        &mut self,
+        params: &ExecutorParams,
+        fee: &FeePolicy,
+        from: &Address,
+        to: &Address,
+        value: u64,
+    ) -> ProtocolResult<bool> {
+        // The asset service rejects transferring to oneself
+        if from == to || value == 0 {
+            return Ok(true);
+        }
+
+        let payload = serde_json::json!({
+            "asset_id": fee.asset_id,
+            "to": to,
+            "value": value,
+        });
+        let request = TransactionRequest {
+            service_name: fee.asset_service.to_owned(),
+            method: "transfer".to_owned(),
+            payload: payload.to_string(),
+            encoding: PayloadEncoding::Json,
+        };
+        let context = self.get_context(
+            None,
+            None,
+            from,
+            0,
+            std::u64::MAX,
+            params,
+            &request,
+            Rc::new(RefCell::new(vec![])),
+        )?;
+
+        let ret = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.call(context, ExecType::Write)
+        }));
+
+        match ret {
+            Ok(resp) if !resp.is_error() => {
+                self.stash()?;
+                Ok(true)
+            }
+            _ => {
+                self.revert_cache()?;
+                Ok(false)
+            }
+        }
+    }
+
+    // Pay the fee to the proposer and refund the rest of the pledge to the
+    // sender. Nothing but the executor moves the escrow, so the transfers only
+    // fail if the asset service does, and then the asset is left in the escrow
+    // rather than failing the block.
+    fn settle_fee(
+        &mut self,
+        params: &ExecutorParams,
+        fee: &FeePolicy,
+        stx: &SignedTransaction,
+        max_fee: u64,
+        amount: u64,
+    ) -> ProtocolResult<()> {
+        if !self.transfer_fee(params, fee, &FEE_ESCROW, &params.proposer, amount)? {
+            log::error!("pay fee {} of tx {:?} failed", amount, stx.tx_hash);
+        }
+
+        let refund = max_fee - amount;
+        if !self.transfer_fee(params, fee, &FEE_ESCROW, &stx.raw.sender, refund)? {
+            log::error!("refund fee {} of tx {:?} failed", refund, stx.tx_hash);
+        }
+
+        Ok(())
+    }
+
     fn get_service(
         &self,
         service: &str,
@@ -381,6 +552,7 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
             service_name: request.service_name.to_owned(),
             service_method: request.method.to_owned(),
             service_payload: request.payload.to_owned(),
+            service_payload_encoding: request.encoding,
             extra: None,
             events: event,
         };
@@ -428,6 +600,14 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
                 self.call(service_context.clone(), exec_type)
             })) {
                 Ok(r) => Ok(r),
+                // The stores abort the call by unwinding if it runs out of cycles
+                Err(e) if e.is::<ServiceContextError>() => {
+                    self.revert_cache()?;
+                    Ok(ServiceResponse::from_error(
+                        OUT_OF_CYCLES_CODE,
+                        ServiceContextError::OutOfCycles.to_string(),
+                    ))
+                }
                 Err(e) => {
                     self.revert_cache()?;
                     log::error!(
@@ -476,6 +656,7 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         }
 
         let service = service.unwrap();
+        let _meter = self.meter.enter(context.clone());
         match exec_type {
             ExecType::Read => service.borrow().read_(context),
             ExecType::Write => service.borrow_mut().write_(context),
@@ -501,6 +682,43 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         let mut receipts = txs
             .iter()
             .map(|stx| {
+                if self.sequential_nonce {
+                    if let Some(receipt) = self.check_nonce(params, stx)? {
+                        return Ok(receipt);
+                    }
+                }
+
+                // Pledge the max fee to the escrow, and settle it after the tx
+                // is executed. It is pledged before the nonce is used, so a tx
+                // which can not pay leaves the nonce to another one.
+                let pledge = match self.fee.clone() {
+                    Some(fee) => {
+                        let max_fee =
+                            fee.max_fee(stx.raw.cycles_limit, stx.raw.cycles_price);
+
+                        if !self.transfer_fee(
+                            params,
+                            &fee,
+                            &stx.raw.sender,
+                            &FEE_ESCROW,
+                            max_fee,
+                        )? {
+                            return Ok(rejected_receipt(
+                                params,
+                                stx,
+                                INSUFFICIENT_FEE_CODE,
+                                format!("insufficient fee, expect {}", max_fee),
+                            ));
+                        }
+                        Some((fee, max_fee))
+                    }
+                    None => None,
+                };
+
+                if self.sequential_nonce {
+                    self.bump_nonce(&stx.raw.sender)?;
+                }
+
                 let event = Rc::new(RefCell::new(vec![]));
                 let service_context = self.get_context(
                     Some(stx.tx_hash.clone()),
@@ -519,6 +737,29 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
                     ExecType::Write,
                     Rc::clone(&event),
                 )?;
+
+                let receipt_fee = match pledge {
+                    Some((fee, max_fee)) => {
+                        let amount = fee
+                            .tx_fee(
+                                service_context.get_cycles_used(),
+                                stx.raw.cycles_price,
+                                exec_resp.is_error(),
+                            )
+                            .min(max_fee);
+
+                        self.settle_fee(params, &fee, stx, max_fee, amount)?;
+
+                        Some(ReceiptFee {
+                            asset_id: fee.asset_id,
+                            payer: stx.raw.sender.clone(),
+                            payee: params.proposer.clone(),
+                            amount,
+                        })
+                    }
+                    None => None,
+                };
+
                 Ok(Receipt {
                     state_root: MerkleRoot::from_empty(),
                     height: service_context.get_current_height(),
@@ -530,6 +771,7 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
                         method: service_context.get_service_method().to_owned(),
                         response: exec_resp,
                     },
+                    fee: receipt_fee,
                 })
             })
             .collect::<Result<Vec<Receipt>, ProtocolError>>()?;
@@ -573,4 +815,147 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
                 ProtocolError::from(ExecutorError::QueryService(format!("{:?}", e)))
             })
     }
+
+    fn get_account_nonce(&self, address: &Address) -> ProtocolResult<u64> {
+        let nonce = self
+            .root_state
+            .get_account_value(address, &NONCE_KEY.to_owned())?;
+
+        Ok(nonce.unwrap_or(0))
+    }
+
+    fn tx_pledges(
+        &self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+    ) -> ProtocolResult<Vec<FeePledge>> {
+        let mut pledges = match &self.fee {
+            Some(fee) => vec![fee.pledge(stx.raw.cycles_limit, stx.raw.cycles_price)],
+            None => vec![],
+        };
+
+        for name in self.service_mapping.list_service_name().iter() {
+            let context = self.get_context(
+                Some(stx.tx_hash.clone()),
+                Some(stx.raw.nonce.clone()),
+                &stx.raw.sender,
+                stx.raw.cycles_price,
+                stx.raw.cycles_limit,
+                params,
+                &stx.raw.request,
+                Rc::new(RefCell::new(vec![])),
+            )?;
+
+            if let Some(pledge) = self.get_service(name)?.borrow().tx_pledge_(context) {
+                pledges.push(pledge);
+            }
+        }
+
+        Ok(pledges)
+    }
+
+    fn dry_run(
+        &mut self,
+        ctx: Context,
+        params: &ExecutorParams,
+        caller: &Address,
+        cycles_price: u64,
+        request: &TransactionRequest,
+    ) -> ProtocolResult<DryRunResp> {
+        let event = Rc::new(RefCell::new(vec![]));
+        let service_context = self.get_context(
+            None,
+            None,
+            caller,
+            cycles_price,
+            params.cycles_limit,
+            params,
+            request,
+            Rc::clone(&event),
+        )?;
+
+        let resp = self.catch_call(
+            ctx,
+            service_context.clone(),
+            ExecType::Write,
+            Rc::clone(&event),
+        );
+        // Nothing is committed, so drop every change the call has stashed
+        self.states.discard();
+
+        Ok(DryRunResp {
+            response: resp?,
+            cycles_used: service_context.get_cycles_used(),
+            events: service_context.get_events(),
+        })
+    }
+
+    fn list_service_name(&self) -> Vec<String> {
+        self.service_mapping.list_service_name()
+    }
+
+    fn get_service_schema(&self, service: &str) -> ProtocolResult<ServiceSchema> {
+        let service = self.get_service(service)?;
+        let schema = service.borrow().schema_();
+        Ok(schema)
+    }
+
+    fn read_with_proof(
+        &self,
+        params: &ExecutorParams,
+        caller: &Address,
+        cycles_price: u64,
+        request: &TransactionRequest,
+    ) -> ProtocolResult<(ServiceResponse<String>, StateProof)> {
+        // A read may call other services, so record reads of all states
+        for state in self.states.values() {
+            state.borrow().record_reads();
+        }
+
+        let resp = self.read(params, caller, cycles_price, request);
+
+        let mut read_keys = self
+            .states
+            .iter()
+            .map(|(name, state)| (name.to_owned(), state.borrow().take_read_keys()))
+            .filter(|(_, keys)| !keys.is_empty())
+            .collect::<Vec<_>>();
+        read_keys.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let resp = resp?;
+
+        let mut services = Vec::with_capacity(read_keys.len());
+        for (name, keys) in read_keys.into_iter() {
+            let state = self
+                .states
+                .get(&name)
+                .ok_or_else(|| ExecutorError::NotFoundService {
+                    service: name.to_owned(),
+                })?
+                .borrow();
+
+            let service_root = self.root_state.get_proof(&name.encode_fixed()?)?;
+            let entries = keys
+                .iter()
+                .map(|key| state.get_proof(key))
+                .collect::<ProtocolResult<Vec<_>>>()?;
+
+            services.push(ServiceStateProof {
+                service_root: match service_root.value {
+                    Some(root) => MerkleRoot::decode_fixed(root)?,
+                    None => MerkleRoot::from_empty(),
+                },
+                service_root_proof: service_root.proof,
+                service_name: name,
+                entries,
+            });
+        }
+
+        let proof = StateProof {
+            state_root: params.state_root.clone(),
+            services,
+        };
+
+        Ok((resp, proof))
+    }
 }
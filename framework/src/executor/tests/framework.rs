@@ -9,8 +9,9 @@ use protocol::traits::{
     ServiceMapping, ServiceResponse, ServiceSDK, Storage,
 };
 use protocol::types::{
-    Address, Block, BlockHeader, Genesis, Hash, Proof, RawTransaction, Receipt,
-    ServiceContext, SignedTransaction, TransactionRequest,
+    Address, Block, BlockHeader, EventFilter, Genesis, Hash, IndexedEvent,
+    PayloadEncoding, Proof, RawTransaction, Receipt, ServiceContext, SignedTransaction,
+    TransactionRequest,
 };
 use protocol::ProtocolResult;
 use std::sync::Arc;
@@ -84,6 +85,7 @@ pub fn construct_stx(
             service_name,
             method,
             payload,
+            encoding: PayloadEncoding::Json,
         },
         sender: ADMIN_ACCOUNT.clone(),
     };
@@ -214,6 +216,18 @@ impl Storage for MockStorage {
     async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
         unimplemented!()
     }
+
+    async fn get_events(
+        &self,
+        _ctx: Context,
+        _: EventFilter,
+        _: u64,
+        _: u64,
+        _: u64,
+        _: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        unimplemented!()
+    }
 }
 
 pub struct MockServiceMapping;
@@ -20,13 +20,18 @@ use protocol::traits::{
     ServiceMapping, ServiceSDK, Storage,
 };
 use protocol::types::{
-    Address, Block, BlockHeader, Genesis, Hash, Proof, RawTransaction, Receipt,
-    SignedTransaction, TransactionRequest,
+    sequential_nonce_to_hash, Address, Block, BlockHeader, EventFilter, FeePolicy,
+    Genesis, Hash, IndexedEvent, PayloadEncoding, Proof, RawTransaction, Receipt,
+    ReceiptFee, SignedTransaction, StorageCycles, TransactionRequest, FEE_ESCROW,
+    OUT_OF_CYCLES_CODE,
 };
 use protocol::ProtocolResult;
 
-use crate::executor::{ServiceExecutor, SERVICE_NOT_FOUND_CODE};
-use test_service::TestService;
+use crate::executor::{
+    ServiceExecutor, INSUFFICIENT_FEE_CODE, INVALID_NONCE_CODE, SERVICE_NOT_FOUND_CODE,
+    SERVICE_REENTRANT_CODE,
+};
+use test_service::{TestCallPayload, TestService};
 
 macro_rules! read {
     ($executor:expr, $params:expr, $caller:expr, $payload:expr) => {{
@@ -34,6 +39,7 @@ macro_rules! read {
             service_name: "test".to_owned(),
             method: "test_read".to_owned(),
             payload: $payload.to_owned(),
+            encoding: PayloadEncoding::Json,
         };
 
         $executor
@@ -82,6 +88,7 @@ fn test_create_genesis() {
        payload:
            r#"{"asset_id": "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c", "user": "muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705"}"#
                .to_owned(),
+       encoding:     PayloadEncoding::Json,
    };
     let res = executor.read(&params, &caller, 1, &request).unwrap();
     let resp: GetBalanceResponse = serde_json::from_str(&res.succeed_data).unwrap();
@@ -133,6 +140,369 @@ fn test_exec() {
     assert_eq!(asset.supply, 320_000_011);
 }
 
+#[test]
+fn test_sequential_nonce() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    executor.sequential_nonce = true;
+
+    let params = ExecutorParams {
+        state_root: root,
+        height: 1,
+        timestamp: 0,
+        cycles_limit: std::u64::MAX,
+        proposer: Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_write".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": ""
+    }"#
+    .to_owned();
+
+    let mut stx_0 = stx.clone();
+    stx_0.raw.nonce = sequential_nonce_to_hash(0);
+    let mut stx_1 = stx.clone();
+    stx_1.raw.nonce = sequential_nonce_to_hash(1);
+    let mut stx_3 = stx;
+    stx_3.raw.nonce = sequential_nonce_to_hash(3);
+
+    let txs = vec![stx_0.clone(), stx_1, stx_3, stx_0];
+    let executor_resp = executor.exec(Context::new(), &params, &txs).unwrap();
+    let codes = executor_resp
+        .receipts
+        .iter()
+        .map(|receipt| receipt.response.response.code)
+        .collect::<Vec<_>>();
+
+    assert_eq!(codes, vec![0, 0, INVALID_NONCE_CODE, INVALID_NONCE_CODE]);
+
+    let sender = &txs[0].raw.sender;
+    assert_eq!(executor.get_account_nonce(sender).unwrap(), 2);
+
+    let executor = ServiceExecutor::with_root(
+        executor_resp.state_root,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    assert_eq!(executor.get_account_nonce(sender).unwrap(), 2);
+}
+
+#[test]
+fn test_storage_out_of_cycles() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    executor.meter.set_cycles(StorageCycles {
+        write: 10_000,
+        ..Default::default()
+    });
+
+    let params = ExecutorParams {
+        state_root: root,
+        height: 1,
+        timestamp: 0,
+        cycles_limit: std::u64::MAX,
+        proposer: Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_write_twice".to_owned();
+    stx.raw.request.payload =
+        r#"{ "key": "foo", "value": "bar", "extra": "baz" }"#.to_owned();
+    // Enough for the first write only
+    stx.raw.cycles_limit = 36_000;
+
+    let executor_resp = executor
+        .exec(Context::new(), &params, &[stx.clone()])
+        .unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, OUT_OF_CYCLES_CODE);
+
+    // Overflowing the cycles used runs out of cycles as well
+    executor.meter.set_cycles(StorageCycles {
+        write: std::u64::MAX,
+        ..Default::default()
+    });
+    stx.raw.cycles_limit = std::u64::MAX;
+    let executor_resp = executor.exec(Context::new(), &params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, OUT_OF_CYCLES_CODE);
+
+    // The writes of the calls are reverted
+    let caller =
+        Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705").unwrap();
+    let foo = read!(executor, &params, &caller, r#""foo""#);
+    assert_eq!(foo.succeed_data, r#""""#);
+}
+
+#[test]
+fn test_fee() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    let asset_id = Hash::from_hex(
+        "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c",
+    )
+    .unwrap();
+    executor.fee = Some(FeePolicy {
+        asset_service: "asset".to_owned(),
+        asset_id: asset_id.clone(),
+        failure_fee: 10,
+    });
+    executor.sequential_nonce = true;
+
+    let proposer = Address::from_hash(Hash::from_empty()).unwrap();
+    let params = ExecutorParams {
+        state_root: root,
+        height: 1,
+        timestamp: 0,
+        cycles_limit: std::u64::MAX,
+        proposer: proposer.clone(),
+    };
+
+    let issuer =
+        Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705").unwrap();
+    let mut stx = mock_signed_tx();
+    stx.raw.sender = issuer.clone();
+    stx.raw.nonce = sequential_nonce_to_hash(0);
+    stx.raw.cycles_limit = 100_000;
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_write".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": ""
+    }"#
+    .to_owned();
+
+    // Runs out of cycles, so charged the failure fee too
+    let mut stx_failed = stx.clone();
+    stx_failed.raw.nonce = sequential_nonce_to_hash(1);
+    stx_failed.raw.cycles_limit = 10_000;
+
+    // Holds none of the asset
+    let mut stx_poor = stx.clone();
+    stx_poor.raw.sender = mock_signed_tx().raw.sender;
+
+    // Only the max fee is pledged, as no service has a tx pledge
+    let pledges = executor.tx_pledges(&params, &stx).unwrap();
+    let max_fee = 100_000 * stx.raw.cycles_price + 10;
+    assert_eq!(pledges.len(), 1);
+    assert_eq!(pledges[0].amount, max_fee);
+
+    let txs = vec![stx, stx_failed, stx_poor];
+    let executor_resp = executor.exec(Context::new(), &params, &txs).unwrap();
+    let receipts = &executor_resp.receipts;
+
+    assert_eq!(receipts[0].response.response.code, 0);
+    assert_eq!(
+        receipts[0].fee,
+        Some(ReceiptFee {
+            asset_id: asset_id.clone(),
+            payer: issuer.clone(),
+            payee: proposer.clone(),
+            amount: receipts[0].cycles_used,
+        })
+    );
+
+    assert!(receipts[1].response.response.is_error());
+    let failed_fee = (receipts[1].cycles_used + 10).min(10_010);
+    assert_eq!(receipts[1].fee.as_ref().unwrap().amount, failed_fee);
+
+    assert_eq!(receipts[2].response.response.code, INSUFFICIENT_FEE_CODE);
+    assert_eq!(receipts[2].fee, None);
+
+    // The fee is pledged before the nonce is used, so the nonce of the poor
+    // sender is left for a tx which can pay
+    assert_eq!(executor.get_account_nonce(&issuer).unwrap(), 2);
+    assert_eq!(executor.get_account_nonce(&txs[2].raw.sender).unwrap(), 0);
+
+    let balance = |user: &Address| {
+        let request = TransactionRequest {
+            service_name: "asset".to_owned(),
+            method: "get_balance".to_owned(),
+            payload: serde_json::json!({ "asset_id": asset_id, "user": user })
+                .to_string(),
+            encoding: PayloadEncoding::Json,
+        };
+        let res = executor.read(&params, &issuer, 1, &request).unwrap();
+        let resp: GetBalanceResponse = serde_json::from_str(&res.succeed_data).unwrap();
+        resp.balance
+    };
+
+    let charged = receipts[0].cycles_used + failed_fee;
+    assert_eq!(balance(&issuer), 320_000_011 - charged);
+    assert_eq!(balance(&proposer), charged);
+    assert_eq!(balance(&*FEE_ESCROW), 0);
+}
+
+#[test]
+fn test_fee_escrow() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    let asset_id = Hash::from_hex(
+        "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c",
+    )
+    .unwrap();
+    executor.fee = Some(FeePolicy {
+        asset_service: "asset".to_owned(),
+        asset_id: asset_id.clone(),
+        failure_fee: 10,
+    });
+
+    let proposer = Address::from_hash(Hash::from_empty()).unwrap();
+    let params = ExecutorParams {
+        state_root: root,
+        height: 1,
+        timestamp: 0,
+        cycles_limit: std::u64::MAX,
+        proposer: proposer.clone(),
+    };
+
+    let issuer =
+        Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705").unwrap();
+    let asset_tx = |sender: &Address, cycles_price: u64, method: &str, payload| {
+        let mut stx = mock_signed_tx();
+        stx.raw.sender = sender.clone();
+        stx.raw.cycles_price = cycles_price;
+        stx.raw.cycles_limit = 100_000;
+        stx.raw.request.method = method.to_owned();
+        stx.raw.request.payload = serde_json::Value::to_string(&payload);
+        stx
+    };
+
+    // The proposer approves the sender, who then tries to take the pledge of
+    // its own tx from the proposer
+    let max_fee = 100_000 + 10;
+    let txs = vec![
+        asset_tx(
+            &issuer,
+            0,
+            "transfer",
+            serde_json::json!({ "asset_id": asset_id, "to": proposer, "value": 1000 }),
+        ),
+        asset_tx(
+            &proposer,
+            0,
+            "approve",
+            serde_json::json!({ "asset_id": asset_id, "to": issuer, "value": 1_000_000 }),
+        ),
+        asset_tx(
+            &issuer,
+            1,
+            "transfer_from",
+            serde_json::json!({
+                "asset_id": asset_id,
+                "sender": proposer,
+                "recipient": issuer,
+                "value": 1000 + max_fee,
+            }),
+        ),
+    ];
+    let executor_resp = executor.exec(Context::new(), &params, &txs).unwrap();
+    let receipts = &executor_resp.receipts;
+
+    assert_eq!(receipts[0].response.response.code, 0);
+    assert_eq!(receipts[1].response.response.code, 0);
+    assert!(receipts[2].response.response.is_error());
+    let fee = receipts[2].fee.as_ref().unwrap().amount;
+
+    let balance = |user: &Address| {
+        let request = TransactionRequest {
+            service_name: "asset".to_owned(),
+            method: "get_balance".to_owned(),
+            payload: serde_json::json!({ "asset_id": asset_id, "user": user })
+                .to_string(),
+            encoding: PayloadEncoding::Json,
+        };
+        let res = executor.read(&params, &issuer, 1, &request).unwrap();
+        let resp: GetBalanceResponse = serde_json::from_str(&res.succeed_data).unwrap();
+        resp.balance
+    };
+
+    assert_eq!(balance(&issuer), 320_000_011 - 1000 - fee);
+    assert_eq!(balance(&proposer), 1000 + fee);
+    assert_eq!(balance(&*FEE_ESCROW), 0);
+}
+
 #[test]
 fn test_emit_event() {
     let toml_str = include_str!("./genesis_services.toml");
@@ -185,6 +555,61 @@ fn test_emit_event() {
     assert_eq!(&receipt.events[0].service, "wow");
 }
 
+#[test]
+fn test_dry_run() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root: root.clone(),
+        height: 1,
+        timestamp: 0,
+        cycles_limit: std::u64::MAX,
+        proposer: Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    let stx = mock_signed_tx();
+    let request = TransactionRequest {
+        service_name: "test".to_owned(),
+        method: "test_write".to_owned(),
+        payload: r#"{
+            "key": "dry",
+            "value": "run",
+            "extra": ""
+        }"#
+        .to_owned(),
+        encoding: PayloadEncoding::Json,
+    };
+
+    let dry_run_resp = executor
+        .dry_run(Context::new(), &params, &stx.raw.sender, 1, &request)
+        .unwrap();
+
+    assert_eq!(dry_run_resp.response.code, 0);
+    assert!(dry_run_resp.cycles_used > 0);
+
+    let state_root = executor.commit(Context::new()).unwrap();
+    assert_eq!(state_root, root);
+}
+
 #[test]
 fn test_revert_event_on_exec_error() {
     let toml_str = include_str!("./genesis_services.toml");
@@ -356,6 +781,149 @@ fn test_tx_hook() {
     assert_eq!(&receipt.events[1].data, "test_tx_hook_after invoked");
 }
 
+#[test]
+fn test_cross_service_call() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root: root,
+        height: 1,
+        timestamp: 0,
+        cycles_limit: std::u64::MAX,
+        proposer: Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_call_service".to_owned();
+    stx.raw.request.payload = serde_json::to_string(&TestCallPayload {
+        service: "asset".to_owned(),
+        method: "create_asset".to_owned(),
+        payload: r#"{ "name": "MutaToken2", "symbol": "MT2", "supply": 320000011 }"#
+            .to_owned(),
+    })
+    .unwrap();
+    let txs = vec![stx.clone()];
+    let executor_resp = executor.exec(Context::new(), &params, &txs).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+    // Cycles of the caller and the callee
+    assert_eq!(receipt.cycles_used, 42_000);
+
+    let resp: String =
+        serde_json::from_str(&receipt.response.response.succeed_data).unwrap();
+    let asset: Asset = serde_json::from_str(&resp).unwrap();
+    assert_eq!(asset.symbol, "MT2");
+
+    // A service can not be called back while it is calling out
+    stx.raw.request.payload = serde_json::to_string(&TestCallPayload {
+        service: "test".to_owned(),
+        method: "test_write".to_owned(),
+        payload: r#"{ "key": "foo", "value": "bar", "extra": "" }"#.to_owned(),
+    })
+    .unwrap();
+    let txs = vec![stx];
+    let executor_resp = executor.exec(Context::new(), &params, &txs).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, SERVICE_REENTRANT_CODE);
+}
+
+#[test]
+fn test_revert_failed_service_call() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root: root,
+        height: 1,
+        timestamp: 0,
+        cycles_limit: std::u64::MAX,
+        proposer: Address::from_hash(Hash::from_empty()).unwrap(),
+    };
+
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_call_service_and_go_on".to_owned();
+    stx.raw.request.payload = serde_json::to_string(&TestCallPayload {
+        service: "test_callee".to_owned(),
+        method: "test_write_and_fail".to_owned(),
+        payload: r#"{ "key": "foo", "value": "bar", "extra": "" }"#.to_owned(),
+    })
+    .unwrap();
+    let mut stx_succeed = stx.clone();
+    stx_succeed.raw.request.payload = serde_json::to_string(&TestCallPayload {
+        service: "test_callee".to_owned(),
+        method: "test_write".to_owned(),
+        payload: r#"{ "key": "bar", "value": "foo", "extra": "" }"#.to_owned(),
+    })
+    .unwrap();
+
+    let txs = vec![stx, stx_succeed];
+    let executor_resp = executor.exec(Context::new(), &params, &txs).unwrap();
+    let receipts = &executor_resp.receipts;
+
+    // The caller goes on after the callee fails, but what the callee has
+    // written and emitted is rolled back
+    assert_eq!(receipts[0].response.response.code, 0);
+    assert_eq!(
+        receipts[0].response.response.succeed_data,
+        r#""write and fail""#
+    );
+    assert!(receipts[0].events.is_empty());
+    assert_eq!(receipts[1].response.response.code, 0);
+
+    let caller =
+        Address::from_str("muta14e0lmgck835vm2dfm0w3ckv6svmez8fdgdl705").unwrap();
+    let read_callee = |key: &str| {
+        let request = TransactionRequest {
+            service_name: "test_callee".to_owned(),
+            method: "test_read".to_owned(),
+            payload: serde_json::to_string(key).unwrap(),
+            encoding: PayloadEncoding::Json,
+        };
+        executor.read(&params, &caller, 1, &request).unwrap()
+    };
+    assert_eq!(read_callee("foo").succeed_data, r#""""#);
+    assert_eq!(read_callee("bar").succeed_data, r#""foo""#);
+}
+
 #[test]
 fn test_commit_tx_hook_use_panic_tx() {
     let toml_str = include_str!("./genesis_services.toml");
@@ -564,6 +1132,7 @@ fn mock_signed_tx() -> SignedTransaction {
             method: "create_asset".to_owned(),
             payload: r#"{ "name": "MutaToken2", "symbol": "MT2", "supply": 320000011 }"#
                 .to_owned(),
+            encoding: PayloadEncoding::Json,
         },
         sender: Address::from_pubkey_bytes(Bytes::from(
             hex::decode(PUB_KEY_STR).unwrap(),
@@ -593,6 +1162,7 @@ impl ServiceMapping for MockServiceMapping {
             "asset" => Box::new(AssetService::new(sdk)) as Box<dyn Service>,
             "metadata" => Box::new(MetadataService::new(sdk)) as Box<dyn Service>,
             "test" => Box::new(TestService::new(sdk)) as Box<dyn Service>,
+            "test_callee" => Box::new(TestService::new(sdk)) as Box<dyn Service>,
             _ => panic!("not found service"),
         };
 
@@ -600,7 +1170,12 @@ impl ServiceMapping for MockServiceMapping {
     }
 
     fn list_service_name(&self) -> Vec<String> {
-        vec!["asset".to_owned(), "metadata".to_owned(), "test".to_owned()]
+        vec![
+            "asset".to_owned(),
+            "metadata".to_owned(),
+            "test".to_owned(),
+            "test_callee".to_owned(),
+        ]
     }
 }
 
@@ -717,4 +1292,16 @@ impl Storage for MockStorage {
     async fn get_latest_proof(&self, _ctx: Context) -> ProtocolResult<Proof> {
         unimplemented!()
     }
+
+    async fn get_events(
+        &self,
+        _ctx: Context,
+        _: EventFilter,
+        _: u64,
+        _: u64,
+        _: u64,
+        _: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>> {
+        unimplemented!()
+    }
 }
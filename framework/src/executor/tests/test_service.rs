@@ -18,6 +18,13 @@ pub struct TestWritePayload {
 #[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub struct TestWriteResponse {}
 
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TestCallPayload {
+    pub service: String,
+    pub method: String,
+    pub payload: String,
+}
+
 #[service]
 impl<SDK: ServiceSDK> TestService<SDK> {
     pub fn new(sdk: SDK) -> Self {
@@ -46,6 +53,18 @@ impl<SDK: ServiceSDK> TestService<SDK> {
         ServiceResponse::<TestWriteResponse>::from_succeed(TestWriteResponse {})
     }
 
+    #[cycles(21_000)]
+    #[write]
+    fn test_write_twice(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TestWritePayload,
+    ) -> ServiceResponse<TestWriteResponse> {
+        self.sdk.set_value(payload.key, payload.value.clone());
+        self.sdk.set_value(payload.extra, payload.value);
+        ServiceResponse::<TestWriteResponse>::from_succeed(TestWriteResponse {})
+    }
+
     #[cycles(21_000)]
     #[write]
     fn test_revert_event(
@@ -78,6 +97,43 @@ impl<SDK: ServiceSDK> TestService<SDK> {
         ServiceResponse::<TestWriteResponse>::from_succeed(TestWriteResponse {})
     }
 
+    #[cycles(21_000)]
+    #[write]
+    fn test_call_service(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TestCallPayload,
+    ) -> ServiceResponse<String> {
+        self.sdk
+            .write(&ctx, &payload.service, &payload.method, &payload.payload)
+    }
+
+    #[cycles(21_000)]
+    #[write]
+    fn test_write_and_fail(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TestWritePayload,
+    ) -> ServiceResponse<TestWriteResponse> {
+        self.sdk.set_value(payload.key, payload.value);
+        ctx.emit_event("wow".to_owned(), "test-name".to_owned(), "test".to_owned());
+        ServiceResponse::from_error(112, "write and fail".to_owned())
+    }
+
+    // Go on whether the callee fails or not, and return its error message
+    #[cycles(21_000)]
+    #[write]
+    fn test_call_service_and_go_on(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TestCallPayload,
+    ) -> ServiceResponse<String> {
+        let ret =
+            self.sdk
+                .write(&ctx, &payload.service, &payload.method, &payload.payload);
+        ServiceResponse::from_succeed(ret.error_message)
+    }
+
     #[cycles(21_000)]
     #[write]
     fn test_panic(
@@ -83,6 +83,9 @@ pub enum CodecError {
 
     #[display(fmt = "from string {}", _0)]
     FromStringUtf8(std::string::FromUtf8Error),
+
+    #[display(fmt = "invalid payload encoding {}", _0)]
+    InvalidPayloadEncoding(u32),
 }
 
 impl Error for CodecError {}
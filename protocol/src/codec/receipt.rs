@@ -4,7 +4,10 @@ use bytes::Bytes;
 use prost::Message;
 
 use crate::{
-    codec::{primitive::Hash, CodecError, ProtocolCodecSync},
+    codec::{
+        primitive::{Address, Hash},
+        CodecError, ProtocolCodecSync,
+    },
     field, impl_default_bytes_codec_for,
     traits::ServiceResponse,
     types::primitive as protocol_primitive,
@@ -35,6 +38,24 @@ pub struct Receipt {
 
     #[prost(message, tag = "6")]
     pub response: Option<ReceiptResponse>,
+
+    #[prost(message, tag = "7")]
+    pub fee: Option<ReceiptFee>,
+}
+
+#[derive(Clone, Message)]
+pub struct ReceiptFee {
+    #[prost(message, tag = "1")]
+    pub asset_id: Option<Hash>,
+
+    #[prost(message, tag = "2")]
+    pub payer: Option<Address>,
+
+    #[prost(message, tag = "3")]
+    pub payee: Option<Address>,
+
+    #[prost(uint64, tag = "4")]
+    pub amount: u64,
 }
 
 #[derive(Clone, Message)]
@@ -67,6 +88,21 @@ pub struct Event {
     pub data: Vec<u8>,
 }
 
+#[derive(Clone, Message)]
+pub struct IndexedEvent {
+    #[prost(uint64, tag = "1")]
+    pub height: u64,
+
+    #[prost(message, tag = "2")]
+    pub tx_hash: Option<Hash>,
+
+    #[prost(uint64, tag = "3")]
+    pub index: u64,
+
+    #[prost(message, tag = "4")]
+    pub event: Option<Event>,
+}
+
 // #################
 // Conversion
 // #################
@@ -115,6 +151,7 @@ impl From<receipt::Receipt> for Receipt {
         let tx_hash = Some(Hash::from(receipt.tx_hash));
         let events = receipt.events.into_iter().map(Event::from).collect();
         let response = Some(ReceiptResponse::from(receipt.response));
+        let fee = receipt.fee.map(ReceiptFee::from);
 
         Receipt {
             state_root,
@@ -123,6 +160,7 @@ impl From<receipt::Receipt> for Receipt {
             cycles_used: receipt.cycles_used,
             events,
             response,
+            fee,
         }
     }
 }
@@ -139,6 +177,10 @@ impl TryFrom<Receipt> for receipt::Receipt {
             .into_iter()
             .map(protocol_receipt::Event::try_from)
             .collect::<Result<Vec<protocol_receipt::Event>, ProtocolError>>()?;
+        let fee = receipt
+            .fee
+            .map(protocol_receipt::ReceiptFee::try_from)
+            .transpose()?;
 
         let receipt = receipt::Receipt {
             state_root: protocol_primitive::Hash::try_from(state_root)?,
@@ -147,12 +189,43 @@ impl TryFrom<Receipt> for receipt::Receipt {
             cycles_used: receipt.cycles_used,
             events,
             response: receipt::ReceiptResponse::try_from(response)?,
+            fee,
         };
 
         Ok(receipt)
     }
 }
 
+// ReceiptFee
+
+impl From<receipt::ReceiptFee> for ReceiptFee {
+    fn from(fee: receipt::ReceiptFee) -> ReceiptFee {
+        ReceiptFee {
+            asset_id: Some(Hash::from(fee.asset_id)),
+            payer: Some(Address::from(fee.payer)),
+            payee: Some(Address::from(fee.payee)),
+            amount: fee.amount,
+        }
+    }
+}
+
+impl TryFrom<ReceiptFee> for receipt::ReceiptFee {
+    type Error = ProtocolError;
+
+    fn try_from(fee: ReceiptFee) -> Result<receipt::ReceiptFee, Self::Error> {
+        let asset_id = field!(fee.asset_id, "ReceiptFee", "asset_id")?;
+        let payer = field!(fee.payer, "ReceiptFee", "payer")?;
+        let payee = field!(fee.payee, "ReceiptFee", "payee")?;
+
+        Ok(receipt::ReceiptFee {
+            asset_id: protocol_primitive::Hash::try_from(asset_id)?,
+            payer: protocol_primitive::Address::try_from(payer)?,
+            payee: protocol_primitive::Address::try_from(payee)?,
+            amount: fee.amount,
+        })
+    }
+}
+
 // Event
 impl From<receipt::Event> for Event {
     fn from(event: receipt::Event) -> Event {
@@ -177,8 +250,37 @@ impl TryFrom<Event> for receipt::Event {
     }
 }
 
+// IndexedEvent
+
+impl From<receipt::IndexedEvent> for IndexedEvent {
+    fn from(indexed: receipt::IndexedEvent) -> IndexedEvent {
+        IndexedEvent {
+            height: indexed.height,
+            tx_hash: Some(Hash::from(indexed.tx_hash)),
+            index: indexed.index,
+            event: Some(Event::from(indexed.event)),
+        }
+    }
+}
+
+impl TryFrom<IndexedEvent> for receipt::IndexedEvent {
+    type Error = ProtocolError;
+
+    fn try_from(indexed: IndexedEvent) -> Result<receipt::IndexedEvent, Self::Error> {
+        let tx_hash = field!(indexed.tx_hash, "IndexedEvent", "tx_hash")?;
+        let event = field!(indexed.event, "IndexedEvent", "event")?;
+
+        Ok(receipt::IndexedEvent {
+            height: indexed.height,
+            tx_hash: protocol_primitive::Hash::try_from(tx_hash)?,
+            index: indexed.index,
+            event: receipt::Event::try_from(event)?,
+        })
+    }
+}
+
 // #################
 // Codec
 // #################
 
-impl_default_bytes_codec_for!(receipt, [Receipt]);
+impl_default_bytes_codec_for!(receipt, [Receipt, IndexedEvent]);
@@ -29,6 +29,7 @@ fn test_codec() {
     test!(primitive, MerkleRoot, mock_merkle_root);
 
     test!(receipt, Receipt, mock_receipt);
+    test!(receipt, IndexedEvent, mock_indexed_event);
 
     test!(transaction, TransactionRequest, mock_transaction_request);
     test!(transaction, RawTransaction, mock_raw_tx);
@@ -21,6 +21,9 @@ pub struct TransactionRequest {
 
     #[prost(bytes, tag = "3")]
     pub payload: Vec<u8>,
+
+    #[prost(uint32, tag = "4")]
+    pub encoding: u32,
 }
 
 #[derive(Clone, Message)]
@@ -74,6 +77,10 @@ impl From<transaction::TransactionRequest> for TransactionRequest {
             service_name: request.service_name.as_bytes().to_vec(),
             method: request.method.as_bytes().to_vec(),
             payload: request.payload.as_bytes().to_vec(),
+            encoding: match request.encoding {
+                transaction::PayloadEncoding::Json => 0,
+                transaction::PayloadEncoding::Fixed => 1,
+            },
         }
     }
 }
@@ -91,6 +98,11 @@ impl TryFrom<TransactionRequest> for transaction::TransactionRequest {
                 .map_err(CodecError::FromStringUtf8)?,
             payload: String::from_utf8(request.payload)
                 .map_err(CodecError::FromStringUtf8)?,
+            encoding: match request.encoding {
+                0 => transaction::PayloadEncoding::Json,
+                1 => transaction::PayloadEncoding::Fixed,
+                n => return Err(CodecError::InvalidPayloadEncoding(n).into()),
+            },
         })
     }
 }
@@ -1,8 +1,103 @@
+use bytes::Bytes;
+use muta_codec_derive::RlpFixedCodec;
+
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
 use crate::traits::ServiceResponse;
-use crate::types::receipt::ReceiptResponse;
+use crate::types::receipt::{Event, Receipt, ReceiptFee, ReceiptResponse};
+use crate::types::{Hash, MerkleRoot};
 use crate::ProtocolResult;
 
+// The receipts without a fee are encoded as before the fee is added, so the
+// receipt roots of the existing blocks stay the same.
+#[derive(RlpFixedCodec)]
+struct LegacyReceipt {
+    state_root: MerkleRoot,
+    height: u64,
+    tx_hash: Hash,
+    cycles_used: u64,
+    events: Vec<Event>,
+    response: ReceiptResponse,
+}
+
+#[derive(RlpFixedCodec)]
+struct FeeReceipt {
+    state_root: MerkleRoot,
+    height: u64,
+    tx_hash: Hash,
+    cycles_used: u64,
+    events: Vec<Event>,
+    response: ReceiptResponse,
+    fee: ReceiptFee,
+}
+
+const LEGACY_RECEIPT_FIELDS: usize = 6;
+
+impl rlp::Encodable for Receipt {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let receipt = self.clone();
+
+        match receipt.fee {
+            None => s.append(&LegacyReceipt {
+                state_root: receipt.state_root,
+                height: receipt.height,
+                tx_hash: receipt.tx_hash,
+                cycles_used: receipt.cycles_used,
+                events: receipt.events,
+                response: receipt.response,
+            }),
+            Some(fee) => s.append(&FeeReceipt {
+                state_root: receipt.state_root,
+                height: receipt.height,
+                tx_hash: receipt.tx_hash,
+                cycles_used: receipt.cycles_used,
+                events: receipt.events,
+                response: receipt.response,
+                fee,
+            }),
+        };
+    }
+}
+
+impl rlp::Decodable for Receipt {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if r.item_count()? == LEGACY_RECEIPT_FIELDS {
+            let receipt: LegacyReceipt = r.as_val()?;
+
+            Ok(Receipt {
+                state_root: receipt.state_root,
+                height: receipt.height,
+                tx_hash: receipt.tx_hash,
+                cycles_used: receipt.cycles_used,
+                events: receipt.events,
+                response: receipt.response,
+                fee: None,
+            })
+        } else {
+            let receipt: FeeReceipt = r.as_val()?;
+
+            Ok(Receipt {
+                state_root: receipt.state_root,
+                height: receipt.height,
+                tx_hash: receipt.tx_hash,
+                cycles_used: receipt.cycles_used,
+                events: receipt.events,
+                response: receipt.response,
+                fee: Some(receipt.fee),
+            })
+        }
+    }
+}
+
+impl FixedCodec for Receipt {
+    fn encode_fixed(&self) -> ProtocolResult<Bytes> {
+        Ok(Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
 impl rlp::Encodable for ReceiptResponse {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
         s.begin_list(5)
@@ -65,6 +65,51 @@ fn test_fixed_codec() {
     test_eq!(receipt, Receipt, mock_receipt);
 }
 
+#[test]
+fn test_fixed_codec_payload_encoding() {
+    let json_tx = mock_raw_tx();
+    let bytes = json_tx.encode_fixed().unwrap();
+    // The json encoded transactions keep the same encoding as before
+    assert_eq!(rlp::Rlp::new(&bytes).item_count().unwrap(), 9);
+
+    let mut fixed_tx = mock_raw_tx();
+    fixed_tx.request.encoding = PayloadEncoding::Fixed;
+    let bytes = fixed_tx.encode_fixed().unwrap();
+    assert_eq!(rlp::Rlp::new(&bytes).item_count().unwrap(), 10);
+    assert_eq!(RawTransaction::decode_fixed(bytes).unwrap(), fixed_tx);
+
+    // An explicit json encoding, or any item after the encoding, is rejected
+    let append = |bytes: Bytes, encoding: PayloadEncoding| {
+        let rlp = rlp::Rlp::new(&bytes);
+        let mut s = rlp::RlpStream::new_list(rlp.item_count().unwrap() + 1);
+        for item in rlp.iter() {
+            s.append_raw(item.as_raw(), 1);
+        }
+        s.append(&encoding);
+        Bytes::from(s.out())
+    };
+    for tx in [json_tx, fixed_tx].iter() {
+        let bytes = append(tx.encode_fixed().unwrap(), PayloadEncoding::Json);
+        assert!(RawTransaction::decode_fixed(bytes).is_err());
+        let bytes = append(tx.request.encode_fixed().unwrap(), PayloadEncoding::Json);
+        assert!(TransactionRequest::decode_fixed(bytes).is_err());
+    }
+}
+
+#[test]
+fn test_fixed_codec_receipt_fee() {
+    let receipt = mock_receipt();
+    let bytes = receipt.encode_fixed().unwrap();
+    // The receipts without a fee keep the same encoding as before
+    assert_eq!(rlp::Rlp::new(&bytes).item_count().unwrap(), 6);
+
+    let mut receipt = mock_receipt();
+    receipt.fee = Some(mock_receipt_fee());
+    let bytes = receipt.encode_fixed().unwrap();
+    assert_eq!(rlp::Rlp::new(&bytes).item_count().unwrap(), 7);
+    assert_eq!(Receipt::decode_fixed(bytes).unwrap(), receipt);
+}
+
 #[test]
 fn test_signed_tx_serialize_size() {
     let txs: Vec<Bytes> = (0..50_000)
@@ -6,8 +6,10 @@ use rand::random;
 use crate::traits::ServiceResponse;
 use crate::types::block::{Block, BlockHeader, Pill, Proof, Validator};
 use crate::types::primitive::{Address, Hash, MerkleRoot};
-use crate::types::receipt::{Event, Receipt, ReceiptResponse};
-use crate::types::transaction::{RawTransaction, SignedTransaction, TransactionRequest};
+use crate::types::receipt::{Event, IndexedEvent, Receipt, ReceiptFee, ReceiptResponse};
+use crate::types::transaction::{
+    PayloadEncoding, RawTransaction, SignedTransaction, TransactionRequest,
+};
 
 // #####################
 // Mock Primitive
@@ -50,6 +52,16 @@ pub fn mock_receipt() -> Receipt {
         cycles_used: 100,
         events: vec![mock_event()],
         response: mock_receipt_response(),
+        fee: None,
+    }
+}
+
+pub fn mock_receipt_fee() -> ReceiptFee {
+    ReceiptFee {
+        asset_id: mock_hash(),
+        payer: mock_address(),
+        payee: mock_address(),
+        amount: 100,
     }
 }
 
@@ -61,6 +73,15 @@ pub fn mock_event() -> Event {
     }
 }
 
+pub fn mock_indexed_event() -> IndexedEvent {
+    IndexedEvent {
+        height: 13,
+        tx_hash: mock_hash(),
+        index: 0,
+        event: mock_event(),
+    }
+}
+
 // #####################
 // Mock Transaction
 // #####################
@@ -70,6 +91,7 @@ pub fn mock_transaction_request() -> TransactionRequest {
         service_name: "mock-service".to_owned(),
         method: "mock-method".to_owned(),
         payload: "mock-payload".to_owned(),
+        encoding: PayloadEncoding::Json,
     }
 }
 
@@ -1,12 +1,96 @@
 use bytes::BytesMut;
 
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
-use crate::types::{Hash, RawTransaction, TransactionRequest};
+use crate::types::{Hash, PayloadEncoding, RawTransaction, TransactionRequest};
 use crate::ProtocolResult;
 
+// The encoding of a JSON payload is omitted, so the transactions with JSON
+// payloads keep the same hashes as before the payload encoding is introduced.
+const RAW_TX_FIELDS: usize = 9;
+const TX_REQUEST_FIELDS: usize = 3;
+
+impl rlp::Encodable for PayloadEncoding {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let encoding: u8 = match self {
+            PayloadEncoding::Json => 0,
+            PayloadEncoding::Fixed => 1,
+        };
+        s.append(&encoding);
+    }
+}
+
+impl rlp::Decodable for PayloadEncoding {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        match r.as_val::<u8>()? {
+            0 => Ok(PayloadEncoding::Json),
+            1 => Ok(PayloadEncoding::Fixed),
+            _ => Err(rlp::DecoderError::Custom("invalid payload encoding")),
+        }
+    }
+}
+
+// Decode the encoding after the `fields` items. A JSON encoding is only ever
+// omitted, so that a transaction has a single encoding and so a single hash.
+fn decode_encoding(
+    r: &rlp::Rlp,
+    fields: usize,
+) -> Result<PayloadEncoding, rlp::DecoderError> {
+    match r.item_count()? {
+        count if count == fields => Ok(PayloadEncoding::Json),
+        count if count == fields + 1 => match r.at(fields)?.as_val()? {
+            PayloadEncoding::Json => {
+                Err(rlp::DecoderError::Custom("explicit json payload encoding"))
+            }
+            encoding => Ok(encoding),
+        },
+        _ => Err(rlp::DecoderError::RlpIncorrectListLen),
+    }
+}
+
+impl rlp::Encodable for TransactionRequest {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        match self.encoding {
+            PayloadEncoding::Json => s.begin_list(TX_REQUEST_FIELDS),
+            PayloadEncoding::Fixed => s.begin_list(TX_REQUEST_FIELDS + 1),
+        };
+        s.append(&self.method);
+        s.append(&self.service_name);
+        s.append(&self.payload);
+        if self.encoding != PayloadEncoding::Json {
+            s.append(&self.encoding);
+        }
+    }
+}
+
+impl rlp::Decodable for TransactionRequest {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let encoding = decode_encoding(r, TX_REQUEST_FIELDS)?;
+
+        Ok(TransactionRequest {
+            method: r.at(0)?.as_val()?,
+            service_name: r.at(1)?.as_val()?,
+            payload: r.at(2)?.as_val()?,
+            encoding,
+        })
+    }
+}
+
+impl FixedCodec for TransactionRequest {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        Ok(bytes::Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
 impl rlp::Encodable for RawTransaction {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
-        s.begin_list(9);
+        match self.request.encoding {
+            PayloadEncoding::Json => s.begin_list(RAW_TX_FIELDS),
+            PayloadEncoding::Fixed => s.begin_list(RAW_TX_FIELDS + 1),
+        };
         s.append(&self.chain_id.as_bytes().to_vec());
         s.append(&self.cycles_limit);
         s.append(&self.cycles_price);
@@ -16,6 +100,9 @@ impl rlp::Encodable for RawTransaction {
         s.append(&self.request.payload);
         s.append(&self.timeout);
         s.append(&self.sender);
+        if self.request.encoding != PayloadEncoding::Json {
+            s.append(&self.request.encoding);
+        }
     }
 }
 
@@ -30,10 +117,12 @@ impl rlp::Decodable for RawTransaction {
         let nonce = Hash::from_bytes(BytesMut::from(r.at(3)?.data()?).freeze())
             .map_err(|_| rlp::DecoderError::RlpInvalidLength)?;
 
+        let encoding = decode_encoding(r, RAW_TX_FIELDS)?;
         let request = TransactionRequest {
             method: r.at(4)?.as_val()?,
             service_name: r.at(5)?.as_val()?,
             payload: r.at(6)?.as_val()?,
+            encoding,
         };
         let timeout = r.at(7)?.as_val()?;
         let sender = r.at(8)?.as_val()?;
@@ -56,6 +56,13 @@ impl From<ProtocolError> for Box<dyn Error + Send> {
     }
 }
 
+impl ProtocolError {
+    /// The inner error, if it is of type `E`.
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.error.downcast_ref::<E>()
+    }
+}
+
 impl Error for ProtocolError {}
 
 pub type ProtocolResult<T> = Result<T, ProtocolError>;
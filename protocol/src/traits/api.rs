@@ -1,7 +1,10 @@
 use async_trait::async_trait;
 
-use crate::traits::{Context, ServiceResponse};
-use crate::types::{Address, Block, BlockHeader, Hash, Receipt, SignedTransaction};
+use crate::traits::{Context, DryRunResp, ServiceResponse, StateProof};
+use crate::types::{
+    Address, Block, BlockHeader, EventFilter, Hash, IndexedEvent, PayloadEncoding,
+    Receipt, ServiceSchema, SignedTransaction,
+};
 use crate::ProtocolResult;
 
 #[async_trait]
@@ -12,6 +15,13 @@ pub trait APIAdapter: Send + Sync {
         signed_tx: SignedTransaction,
     ) -> ProtocolResult<()>;
 
+    /// Insert a batch of transactions, see `MemPool::insert_batch`.
+    async fn insert_signed_txs_batch(
+        &self,
+        ctx: Context,
+        signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<Vec<ProtocolResult<()>>>;
+
     async fn get_block_by_height(
         &self,
         ctx: Context,
@@ -36,6 +46,33 @@ pub trait APIAdapter: Send + Sync {
         tx_hash: Hash,
     ) -> ProtocolResult<Option<SignedTransaction>>;
 
+    /// Get all receipts of an executed block, in the order of its transactions.
+    async fn get_receipts_by_height(
+        &self,
+        ctx: Context,
+        height: u64,
+    ) -> ProtocolResult<Option<Vec<Receipt>>>;
+
+    /// Get events matching the filter within `[from_height, to_height]`, see
+    /// `Storage::get_events`. `to_height` defaults to the latest executed
+    /// height, and `from_height` to the oldest height allowed below it.
+    async fn get_events(
+        &self,
+        ctx: Context,
+        filter: EventFilter,
+        from_height: Option<u64>,
+        to_height: Option<u64>,
+        offset: u64,
+        limit: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>>;
+
+    async fn get_account_nonce(
+        &self,
+        ctx: Context,
+        height: Option<u64>,
+        address: Address,
+    ) -> ProtocolResult<u64>;
+
     async fn query_service(
         &self,
         ctx: Context,
@@ -46,5 +83,42 @@ pub trait APIAdapter: Send + Sync {
         service_name: String,
         method: String,
         payload: String,
+        payload_encoding: PayloadEncoding,
     ) -> ProtocolResult<ServiceResponse<String>>;
+
+    async fn query_service_with_proof(
+        &self,
+        ctx: Context,
+        height: u64,
+        cycles_limit: u64,
+        cycles_price: u64,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+        payload_encoding: PayloadEncoding,
+    ) -> ProtocolResult<(ServiceResponse<String>, StateProof)>;
+
+    async fn dry_run_transaction(
+        &self,
+        ctx: Context,
+        height: u64,
+        cycles_limit: u64,
+        cycles_price: u64,
+        caller: Address,
+        service_name: String,
+        method: String,
+        payload: String,
+        payload_encoding: PayloadEncoding,
+    ) -> ProtocolResult<DryRunResp>;
+
+    /// Get the names of all the services.
+    async fn list_services(&self, ctx: Context) -> ProtocolResult<Vec<String>>;
+
+    /// Get the schema of the read and write methods of a service.
+    async fn get_service_schema(
+        &self,
+        ctx: Context,
+        service_name: String,
+    ) -> ProtocolResult<ServiceSchema>;
 }
@@ -3,7 +3,8 @@ use std::iter::Iterator;
 use crate::fixed_codec::FixedCodec;
 use crate::traits::{ExecutorParams, ServiceResponse};
 use crate::types::{
-    Address, Block, Hash, MerkleRoot, Receipt, ServiceContext, SignedTransaction,
+    Address, Block, FeePledge, Hash, MerkleRoot, Receipt, ServiceContext,
+    ServiceSchema, SignedTransaction,
 };
 use crate::ProtocolResult;
 
@@ -34,6 +35,14 @@ pub trait ServiceMapping: Send + Sync {
     fn list_service_name(&self) -> Vec<String>;
 }
 
+// `Dispatcher` routes a call to the `#[read]` or `#[write]` method of the
+// service named in the context, it is how services call each other by name.
+pub trait Dispatcher {
+    fn read(&self, context: ServiceContext) -> ServiceResponse<String>;
+
+    fn write(&self, context: ServiceContext) -> ServiceResponse<String>;
+}
+
 // `ServiceState` provides access to` world state` and `account` for` service`.
 // The bottom layer is an MPT tree.
 //
@@ -105,6 +114,7 @@ pub trait AdmissionControl {
 //   after the block is executed.
 // - read: Provide some read-only functions for users or other services to call
 // - write: provide some writable functions for users or other services to call
+// - schema: Describe the read and write functions for users to call
 pub trait Service {
     // Executed to create genesis states when starting chain
     fn genesis_(&mut self, _payload: String) {}
@@ -121,9 +131,18 @@ pub trait Service {
     // Called after tx execution
     fn tx_hook_after_(&mut self, _ctx: ServiceContext) -> ServiceResponse<String>;
 
+    // The fee pledged from the sender by the tx hooks before tx execution,
+    // which the mempool requires the sender to hold
+    fn tx_pledge_(&self, _ctx: ServiceContext) -> Option<FeePledge> {
+        None
+    }
+
     fn write_(&mut self, ctx: ServiceContext) -> ServiceResponse<String>;
 
     fn read_(&self, ctx: ServiceContext) -> ServiceResponse<String>;
+
+    // The schema of the read and write functions
+    fn schema_(&self) -> ServiceSchema;
 }
 
 // `ServiceSDK` provides multiple rich interfaces for `service` developers
@@ -200,6 +219,28 @@ pub trait ServiceSDK {
     // Get a receipt by `tx_hash`
     // if not found on the chain, return None
     fn get_receipt_by_hash(&self, tx_hash: &Hash) -> Option<Receipt>;
+
+    // Call a `#[read]` method of another service by name, the call shares
+    // cycles and events with `ctx`
+    fn read(
+        &self,
+        ctx: &ServiceContext,
+        service: &str,
+        method: &str,
+        payload: &str,
+    ) -> ServiceResponse<String>;
+
+    // Call a `#[write]` method of another service by name, the call shares
+    // cycles and events with `ctx`
+    // NOTE: All state changes made by the call are reverted if it returns an
+    // error, and a service can not be called back while it is calling out
+    fn write(
+        &mut self,
+        ctx: &ServiceContext,
+        service: &str,
+        method: &str,
+        payload: &str,
+    ) -> ServiceResponse<String>;
 }
 
 pub trait StoreMap<K: FixedCodec + PartialEq, V: FixedCodec> {
@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
+use bytes::Bytes;
 use creep::Context;
 
 use crate::traits::{ServiceMapping, Storage};
 use crate::types::{
-    Address, MerkleRoot, Receipt, SignedTransaction, TransactionRequest,
+    Address, Event, FeePledge, MerkleRoot, Receipt, ServiceSchema, SignedTransaction,
+    TransactionRequest,
 };
 use crate::ProtocolResult;
 
@@ -24,6 +26,39 @@ pub struct ExecutorParams {
     pub proposer: Address,
 }
 
+/// The outcome of a write request executed without committing it.
+#[derive(Debug, Clone)]
+pub struct DryRunResp {
+    pub response: ServiceResponse<String>,
+    pub cycles_used: u64,
+    pub events: Vec<Event>,
+}
+
+/// Merkle patricia proofs of the state a service read touched, anchored to
+/// the state root of a block.
+#[derive(Debug, Clone)]
+pub struct StateProof {
+    pub state_root: MerkleRoot,
+    pub services: Vec<ServiceStateProof>,
+}
+
+/// The root of a service state proven in the root state trie, and the keys
+/// read from the service state proven against it.
+#[derive(Debug, Clone)]
+pub struct ServiceStateProof {
+    pub service_name: String,
+    pub service_root: MerkleRoot,
+    pub service_root_proof: Vec<Bytes>,
+    pub entries: Vec<StateProofEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StateProofEntry {
+    pub key: Bytes,
+    pub value: Option<Bytes>,
+    pub proof: Vec<Bytes>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ServiceResponse<T: Default> {
     pub code: u64,
@@ -89,4 +124,43 @@ pub trait Executor {
         cycles_price: u64,
         request: &TransactionRequest,
     ) -> ProtocolResult<ServiceResponse<String>>;
+
+    /// Like `read`, but also prove every state key the read touched.
+    fn read_with_proof(
+        &self,
+        params: &ExecutorParams,
+        caller: &Address,
+        cycles_price: u64,
+        request: &TransactionRequest,
+    ) -> ProtocolResult<(ServiceResponse<String>, StateProof)>;
+
+    /// The next sequential nonce of an account, the sequential nonce mode
+    /// must be enabled for the nonce to be tracked.
+    fn get_account_nonce(&self, address: &Address) -> ProtocolResult<u64>;
+
+    /// The fees pledged from the sender before the tx is executed, by the fee
+    /// policy and the `tx_pledge_` of the services.
+    fn tx_pledges(
+        &self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+    ) -> ProtocolResult<Vec<FeePledge>>;
+
+    /// Execute a write request along with the tx hooks, using
+    /// `params.cycles_limit` as its cycles limit, then discard all the state
+    /// changes.
+    fn dry_run(
+        &mut self,
+        ctx: Context,
+        params: &ExecutorParams,
+        caller: &Address,
+        cycles_price: u64,
+        request: &TransactionRequest,
+    ) -> ProtocolResult<DryRunResp>;
+
+    /// The names of all the services, in the order of the service mapping.
+    fn list_service_name(&self) -> Vec<String>;
+
+    /// The schema of the read and write methods of a service.
+    fn get_service_schema(&self, service: &str) -> ProtocolResult<ServiceSchema>;
 }
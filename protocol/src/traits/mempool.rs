@@ -20,6 +20,15 @@ impl MixedTxHashes {
 pub trait MemPool: Send + Sync {
     async fn insert(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()>;
 
+    /// Verify a batch of transactions in parallel at once, the whole batch is
+    /// rejected if any of them is invalid. Otherwise they are inserted in order
+    /// and the insertion result of each one is returned.
+    async fn insert_batch(
+        &self,
+        ctx: Context,
+        txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<Vec<ProtocolResult<()>>>;
+
     async fn package(
         &self,
         ctx: Context,
@@ -73,10 +82,13 @@ pub trait MemPoolAdapter: Send + Sync {
         tx: Box<SignedTransaction>,
     ) -> ProtocolResult<()>;
 
+    /// The fees pledged by the `pending_txs` of the sender are counted along
+    /// with the ones of the tx against the balance of the sender.
     async fn check_transaction(
         &self,
         ctx: Context,
         tx: &SignedTransaction,
+        pending_txs: &[SignedTransaction],
     ) -> ProtocolResult<()>;
 
     async fn check_storage_exist(
@@ -8,15 +8,16 @@ mod storage;
 
 pub use api::APIAdapter;
 pub use binding::{
-    AdmissionControl, ChainQuerier, SDKFactory, Service, ServiceMapping, ServiceSDK,
-    ServiceState, StoreArray, StoreBool, StoreMap, StoreString, StoreUint64,
+    AdmissionControl, ChainQuerier, Dispatcher, SDKFactory, Service, ServiceMapping,
+    ServiceSDK, ServiceState, StoreArray, StoreBool, StoreMap, StoreString, StoreUint64,
 };
 pub use consensus::{
     CommonConsensusAdapter, Consensus, ConsensusAdapter, MessageTarget, NodeInfo,
     Synchronization, SynchronizationAdapter,
 };
 pub use executor::{
-    Executor, ExecutorFactory, ExecutorParams, ExecutorResp, ServiceResponse,
+    DryRunResp, Executor, ExecutorFactory, ExecutorParams, ExecutorResp,
+    ServiceResponse, ServiceStateProof, StateProof, StateProofEntry,
 };
 pub use mempool::{MemPool, MemPoolAdapter, MixedTxHashes};
 pub use network::{
@@ -24,8 +25,9 @@ pub use network::{
     TrustFeedback,
 };
 pub use storage::{
-    CommonStorage, IntoIteratorByRef, MaintenanceStorage, Storage, StorageAdapter,
-    StorageBatchModify, StorageCategory, StorageIterator, StorageSchema,
+    Backup, BackupManifest, CommonStorage, IntoIteratorByRef, MaintenanceStorage,
+    Storage, StorageAdapter, StorageBatchModify, StorageCategory, StorageIterator,
+    StorageSchema,
 };
 
 pub use creep::{Cloneable, Context};
@@ -1,11 +1,14 @@
+use std::path::PathBuf;
+
 use async_trait::async_trait;
 use derive_more::Display;
+use serde::{Deserialize, Serialize};
 
 use crate::codec::ProtocolCodec;
 use crate::traits::Context;
 use crate::types::block::{Block, BlockHeader, Proof};
-use crate::types::receipt::Receipt;
-use crate::types::{Hash, SignedTransaction};
+use crate::types::receipt::{EventFilter, IndexedEvent, Receipt};
+use crate::types::{Hash, MerkleRoot, SignedTransaction};
 use crate::ProtocolResult;
 
 #[derive(Debug, Copy, Clone, Display)]
@@ -16,6 +19,7 @@ pub enum StorageCategory {
     SignedTransaction,
     Wal,
     HashHeight,
+    Event,
 }
 
 pub type StorageIterator<'a, S> = Box<
@@ -108,6 +112,19 @@ pub trait Storage: CommonStorage {
         hashes: Vec<Hash>,
     ) -> ProtocolResult<Vec<Option<Receipt>>>;
 
+    /// Get events matching the filter within `[from_height, to_height]`,
+    /// ordered by height and then by their position in the block. The first
+    /// `offset` events are skipped and at most `limit` events are returned.
+    async fn get_events(
+        &self,
+        ctx: Context,
+        filter: EventFilter,
+        from_height: u64,
+        to_height: u64,
+        offset: u64,
+        limit: u64,
+    ) -> ProtocolResult<Vec<IndexedEvent>>;
+
     async fn update_latest_proof(
         &self,
         ctx: Context,
@@ -115,11 +132,46 @@ pub trait Storage: CommonStorage {
     ) -> ProtocolResult<()>;
 
     async fn get_latest_proof(&self, ctx: Context) -> ProtocolResult<Proof>;
+
+    /// The height below which the transactions and receipts are pruned, `None`
+    /// if they are never pruned.
+    async fn get_pruned_height(&self, _ctx: Context) -> ProtocolResult<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Whether the transaction is committed, including the pruned ones.
+    async fn contains_transaction(
+        &self,
+        ctx: Context,
+        hash: &Hash,
+    ) -> ProtocolResult<bool> {
+        Ok(self.get_transaction_by_hash(ctx, hash).await?.is_some())
+    }
 }
 
 #[async_trait]
 pub trait MaintenanceStorage: CommonStorage {}
 
+/// The description of a backup, the backup restores the node to the latest
+/// block of `height`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub height: u64,
+    pub state_root: MerkleRoot,
+    pub column_families: Vec<String>,
+}
+
+#[async_trait]
+pub trait Backup: Send + Sync {
+    /// Back up the node data to the directory `path`, which must not exist,
+    /// while the node keeps running.
+    async fn backup(
+        &self,
+        ctx: Context,
+        path: PathBuf,
+    ) -> ProtocolResult<BackupManifest>;
+}
+
 pub enum StorageBatchModify<S: StorageSchema> {
     Remove,
     Insert(<S as StorageSchema>::Value),
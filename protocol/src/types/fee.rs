@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Address, Hash};
+
+lazy_static! {
+    static ref FEE_POLICY: ArcSwap<Option<FeePolicy>> = ArcSwap::from(Arc::new(None));
+
+    /// Holds the max fee pledged by the sender while its tx is executed. No
+    /// one has its key, so only the executor moves the asset out of it.
+    pub static ref FEE_ESCROW: Address = Address::from_hash(Hash::digest(b"muta_fee_escrow"))
+        .expect("fee escrow address");
+}
+
+/// The fee charged by the executor from the senders of the transactions in a
+/// native asset, and paid to the proposers of the blocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FeePolicy {
+    /// The service holding the native asset, its `transfer` method is called
+    /// with the payload `{"asset_id", "to", "value"}` to move the fee.
+    #[serde(default = "default_asset_service")]
+    pub asset_service: String,
+    pub asset_id: Hash,
+    /// Charged in addition to the cycles fee if the transaction fails.
+    #[serde(default)]
+    pub failure_fee: u64,
+}
+
+/// An amount of an asset pledged from the sender before a transaction is
+/// executed, which the sender must hold for the transaction to be admitted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeePledge {
+    /// The service holding the asset, its `get_balance` method is called with
+    /// the payload `{"asset_id", "user"}` to read the balance of the sender.
+    pub asset_service: String,
+    pub asset_id: Hash,
+    pub amount: u64,
+}
+
+fn default_asset_service() -> String {
+    "asset".to_owned()
+}
+
+impl FeePolicy {
+    /// The most a transaction can be charged, which the sender must be able to
+    /// pay before it is executed.
+    pub fn max_fee(&self, cycles_limit: u64, cycles_price: u64) -> u64 {
+        cycles_limit
+            .saturating_mul(cycles_price)
+            .saturating_add(self.failure_fee)
+    }
+
+    /// The max fee pledged to the escrow before a transaction is executed.
+    pub fn pledge(&self, cycles_limit: u64, cycles_price: u64) -> FeePledge {
+        FeePledge {
+            asset_service: self.asset_service.clone(),
+            asset_id: self.asset_id.clone(),
+            amount: self.max_fee(cycles_limit, cycles_price),
+        }
+    }
+
+    pub fn tx_fee(&self, cycles_used: u64, cycles_price: u64, failed: bool) -> u64 {
+        let fee = cycles_used.saturating_mul(cycles_price);
+
+        if failed {
+            fee.saturating_add(self.failure_fee)
+        } else {
+            fee
+        }
+    }
+}
+
+/// Set the fee policy, `None` if no fee is charged. It changes how
+/// transactions are executed, so it must be set the same on every node of a
+/// chain.
+pub fn init_fee_policy(policy: Option<FeePolicy>) {
+    FEE_POLICY.store(Arc::new(policy));
+}
+
+pub fn fee_policy() -> Option<FeePolicy> {
+    FEE_POLICY.load().as_ref().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FeePolicy;
+    use crate::types::Hash;
+
+    #[test]
+    fn test_fee_policy() {
+        let policy: FeePolicy = serde_json::from_str(
+            r#"{"asset_id": "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c", "failure_fee": 10}"#,
+        )
+        .unwrap();
+        assert_eq!(policy.asset_service, "asset");
+        assert_eq!(policy.failure_fee, 10);
+
+        assert_eq!(policy.max_fee(100, 2), 210);
+        assert_eq!(policy.max_fee(std::u64::MAX, 2), std::u64::MAX);
+        assert_eq!(policy.pledge(100, 2).amount, 210);
+        assert_eq!(policy.tx_fee(50, 2, false), 100);
+        assert_eq!(policy.tx_fee(50, 2, true), 110);
+
+        let policy = FeePolicy {
+            asset_service: "asset".to_owned(),
+            asset_id: Hash::from_empty(),
+            failure_fee: 0,
+        };
+        assert_eq!(policy.tx_fee(50, 2, true), 100);
+    }
+}
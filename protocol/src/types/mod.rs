@@ -1,7 +1,9 @@
 pub(crate) mod block;
+pub(crate) mod fee;
 pub(crate) mod genesis;
 pub(crate) mod primitive;
 pub(crate) mod receipt;
+pub(crate) mod schema;
 pub(crate) mod service_context;
 pub(crate) mod transaction;
 
@@ -13,14 +15,28 @@ use crate::{ProtocolError, ProtocolErrorKind};
 
 pub use block::{Block, BlockHeader, Pill, Proof, Validator};
 pub use bytes::{Bytes, BytesMut};
+pub use fee::{fee_policy, init_fee_policy, FeePledge, FeePolicy, FEE_ESCROW};
 pub use genesis::{Genesis, ServiceParam};
 pub use primitive::{
     address_hrp, address_hrp_inited, init_address_hrp, Address, Hash, Hex, JsonString,
     MerkleRoot, Metadata, ValidatorExtend, GENESIS_HEIGHT, METADATA_KEY,
 };
-pub use receipt::{Event, Receipt, ReceiptResponse};
-pub use service_context::{ServiceContext, ServiceContextError, ServiceContextParams};
-pub use transaction::{RawTransaction, SignedTransaction, TransactionRequest};
+pub use receipt::{
+    Event, EventFilter, IndexedEvent, Receipt, ReceiptFee, ReceiptResponse,
+};
+pub use schema::{
+    AnySchemaProbe, JsonSchemaProbe, MethodKind, MethodSchema, SchemaProbe,
+    ServiceSchema,
+};
+pub use service_context::{
+    init_storage_cycles, storage_cycles, ServiceContext, ServiceContextError,
+    ServiceContextParams, StorageCycles, OUT_OF_CYCLES_CODE,
+};
+pub use transaction::{
+    decode_fixed_payload, encode_fixed_payload, init_sequential_nonce,
+    sequential_nonce_enabled, sequential_nonce_to_hash, PayloadEncoding, RawTransaction,
+    SignedTransaction, TransactionRequest,
+};
 
 #[derive(Debug, Display, From)]
 pub enum TypesError {
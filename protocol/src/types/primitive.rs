@@ -12,13 +12,14 @@ use lazy_static::lazy_static;
 use muta_codec_derive::RlpFixedCodec;
 use ophelia::{PublicKey, UncompressedPublicKey};
 use ophelia_secp256k1::Secp256k1PublicKey;
+use schemars::JsonSchema;
 use serde::de;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use smol_str::SmolStr;
 
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
-use crate::types::TypesError;
+use crate::types::{FeePolicy, StorageCycles, TypesError};
 use crate::ProtocolResult;
 
 pub const METADATA_KEY: &str = "metadata";
@@ -63,7 +64,7 @@ pub fn address_hrp_inited() -> bool {
 pub const GENESIS_HEIGHT: u64 = 0;
 
 /// Hash length
-const HASH_LEN: usize = 32;
+pub(crate) const HASH_LEN: usize = 32;
 
 // Should started with 0x
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -400,7 +401,15 @@ impl fmt::Display for Address {
 }
 
 #[derive(
-    RlpFixedCodec, Deserialize, Default, Serialize, Clone, Debug, PartialEq, Eq,
+    RlpFixedCodec,
+    Deserialize,
+    Default,
+    Serialize,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    JsonSchema,
 )]
 pub struct Metadata {
     pub chain_id: Hash,
@@ -428,9 +437,38 @@ impl Metadata {
             .expect("bech32_address_hrp in genesis payload is not string?")
             .to_string()
     }
+
+    pub fn get_sequential_nonce_from_json(payload: String) -> bool {
+        let nodes: Value = serde_json::from_str(payload.as_str())
+            .expect("metadata's genesis payload is invalid JSON");
+        nodes["sequential_nonce"].as_bool().unwrap_or(false)
+    }
+
+    pub fn get_storage_cycles_from_json(payload: String) -> StorageCycles {
+        let nodes: Value = serde_json::from_str(payload.as_str())
+            .expect("metadata's genesis payload is invalid JSON");
+
+        match nodes.get("storage_cycles") {
+            Some(cycles) => serde_json::from_value(cycles.clone())
+                .expect("storage_cycles in genesis payload is invalid"),
+            None => StorageCycles::default(),
+        }
+    }
+
+    pub fn get_fee_policy_from_json(payload: String) -> Option<FeePolicy> {
+        let nodes: Value = serde_json::from_str(payload.as_str())
+            .expect("metadata's genesis payload is invalid JSON");
+
+        nodes.get("fee").map(|fee| {
+            serde_json::from_value(fee.clone())
+                .expect("fee in genesis payload is invalid")
+        })
+    }
 }
 
-#[derive(RlpFixedCodec, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[derive(
+    RlpFixedCodec, Serialize, Deserialize, Clone, PartialEq, Eq, Default, JsonSchema,
+)]
 pub struct ValidatorExtend {
     pub bls_pub_key: Hex,
     pub pub_key: Hex,
@@ -2,7 +2,7 @@ use bytes::Bytes;
 use muta_codec_derive::RlpFixedCodec;
 
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
-use crate::types::{Hash, MerkleRoot};
+use crate::types::{Address, Hash, MerkleRoot};
 use crate::{traits::ServiceResponse, ProtocolResult};
 
 #[derive(RlpFixedCodec, Debug, Clone, PartialEq, Eq)]
@@ -12,7 +12,23 @@ pub struct Event {
     pub data: String,
 }
 
-#[derive(RlpFixedCodec, Clone, Debug, PartialEq, Eq)]
+/// An event with the position it was emitted at, as kept by the event index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedEvent {
+    pub height: u64,
+    pub tx_hash: Hash,
+    pub index: u64,
+    pub event: Event,
+}
+
+/// Select indexed events by service, and optionally by event name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventFilter {
+    pub service: String,
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Receipt {
     pub state_root: MerkleRoot,
     pub height: u64,
@@ -20,6 +36,17 @@ pub struct Receipt {
     pub cycles_used: u64,
     pub events: Vec<Event>,
     pub response: ReceiptResponse,
+    /// `None` if no fee policy is set.
+    pub fee: Option<ReceiptFee>,
+}
+
+/// The fee charged from the sender of the transaction.
+#[derive(RlpFixedCodec, Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptFee {
+    pub asset_id: Hash,
+    pub payer: Address,
+    pub payee: Address,
+    pub amount: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
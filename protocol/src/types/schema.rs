@@ -0,0 +1,166 @@
+use std::marker::PhantomData;
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{Address, Hash, Hex};
+
+/// The machine-readable description of the `#[read]` and `#[write]` methods
+/// of a service, generated by the `#[service]` macro.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ServiceSchema {
+    pub methods: Vec<MethodSchema>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MethodSchema {
+    pub name: String,
+    pub kind: MethodKind,
+    /// The cycles charged by the `#[cycles]` attribute, `None` if the method
+    /// has no such attribute.
+    pub cycles: Option<u64>,
+    /// The JSON Schema of the payload, `None` if the method takes no payload.
+    pub payload: Option<Value>,
+    /// The JSON Schema of the `succeed_data` of the response.
+    pub response: Value,
+    /// `false` if the payload or the response does not implement `JsonSchema`,
+    /// in which case its schema is empty and says nothing about the value.
+    pub typed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MethodKind {
+    Read,
+    Write,
+}
+
+/// Generates the JSON Schema of `T` if it implements `JsonSchema`, otherwise
+/// an empty schema which accepts any value, so the payloads and responses of
+/// a service do not have to implement `JsonSchema`. `is_typed` tells the two
+/// apart, since an empty schema is also valid for a type implementing it.
+///
+/// ```ignore
+/// use protocol::types::{AnySchemaProbe, JsonSchemaProbe, SchemaProbe};
+///
+/// let probe = &SchemaProbe::<Payload>::new();
+/// let (schema, typed) = (probe.json_schema(), probe.is_typed());
+/// ```
+pub struct SchemaProbe<T>(PhantomData<T>);
+
+impl<T> SchemaProbe<T> {
+    pub fn new() -> Self {
+        SchemaProbe(PhantomData)
+    }
+}
+
+impl<T> Default for SchemaProbe<T> {
+    fn default() -> Self {
+        SchemaProbe::new()
+    }
+}
+
+pub trait JsonSchemaProbe {
+    fn json_schema(&self) -> Value;
+
+    fn is_typed(&self) -> bool;
+}
+
+impl<T: JsonSchema> JsonSchemaProbe for SchemaProbe<T> {
+    fn json_schema(&self) -> Value {
+        serde_json::to_value(schemars::schema_for!(T))
+            .expect("serialize json schema should never fail")
+    }
+
+    fn is_typed(&self) -> bool {
+        true
+    }
+}
+
+pub trait AnySchemaProbe {
+    fn json_schema(&self) -> Value;
+
+    fn is_typed(&self) -> bool;
+}
+
+impl<T> AnySchemaProbe for &SchemaProbe<T> {
+    fn json_schema(&self) -> Value {
+        Value::Object(Default::default())
+    }
+
+    fn is_typed(&self) -> bool {
+        false
+    }
+}
+
+fn string_schema(format: &str) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        format: Some(format.to_owned()),
+        ..Default::default()
+    }
+    .into()
+}
+
+impl JsonSchema for Hash {
+    fn schema_name() -> String {
+        "Hash".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema("hash")
+    }
+}
+
+impl JsonSchema for Address {
+    fn schema_name() -> String {
+        "Address".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema("address")
+    }
+}
+
+impl JsonSchema for Hex {
+    fn schema_name() -> String {
+        "Hex".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema("hex")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::{AnySchemaProbe, JsonSchemaProbe, SchemaProbe};
+    use crate::types::{Address, Metadata};
+
+    #[test]
+    fn test_schema_probe() {
+        #[derive(Serialize, Deserialize)]
+        struct NoSchema {
+            value: u64,
+        }
+
+        assert_eq!((&SchemaProbe::<NoSchema>::new()).json_schema(), json!({}));
+        assert!(!(&SchemaProbe::<NoSchema>::new()).is_typed());
+
+        assert!((&SchemaProbe::<Address>::new()).is_typed());
+        let schema = (&SchemaProbe::<Address>::new()).json_schema();
+        assert_eq!(schema["type"], json!("string"));
+        assert_eq!(schema["format"], json!("address"));
+
+        let schema = (&SchemaProbe::<Metadata>::new()).json_schema();
+        assert_eq!(schema["title"], json!("Metadata"));
+        assert!(schema["properties"]["verifier_list"].is_object());
+        assert!(schema["definitions"]["ValidatorExtend"].is_object());
+    }
+}
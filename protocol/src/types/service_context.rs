@@ -1,12 +1,60 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
+use arc_swap::ArcSwap;
 use bytes::Bytes;
 use derive_more::{Display, From};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 
-use crate::types::{Address, Event, Hash};
+use crate::types::{Address, Event, Hash, PayloadEncoding};
 use crate::{ProtocolError, ProtocolErrorKind};
 
+/// The code of the response of a call aborted for running out of cycles, the
+/// same as the one returned by the `#[cycles]` attribute.
+pub const OUT_OF_CYCLES_CODE: u64 = 3;
+
+lazy_static! {
+    static ref STORAGE_CYCLES: ArcSwap<StorageCycles> =
+        ArcSwap::from(Arc::new(StorageCycles::default()));
+}
+
+/// The cycles charged for accessing the service state through the stores and
+/// the sdk, nothing is charged by default.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct StorageCycles {
+    /// Charged for every value read.
+    pub read: u64,
+    /// Charged for every value written.
+    pub write: u64,
+    /// Charged for every byte of the keys and values read or written.
+    pub byte: u64,
+}
+
+impl StorageCycles {
+    pub fn read_cycles(&self, bytes: usize) -> u64 {
+        self.read
+            .saturating_add(self.byte.saturating_mul(bytes as u64))
+    }
+
+    pub fn write_cycles(&self, bytes: usize) -> u64 {
+        self.write
+            .saturating_add(self.byte.saturating_mul(bytes as u64))
+    }
+}
+
+/// Set the storage cycles. It changes the cycles used by transactions, so it
+/// must be set the same on every node of a chain.
+pub fn init_storage_cycles(cycles: StorageCycles) {
+    STORAGE_CYCLES.store(Arc::new(cycles));
+}
+
+pub fn storage_cycles() -> StorageCycles {
+    **STORAGE_CYCLES.load()
+}
+
 #[derive(Debug, Clone)]
 pub struct ServiceContextParams {
     pub tx_hash: Option<Hash>,
@@ -19,6 +67,7 @@ pub struct ServiceContextParams {
     pub service_name: String,
     pub service_method: String,
     pub service_payload: String,
+    pub service_payload_encoding: PayloadEncoding,
     pub extra: Option<Bytes>,
     pub timestamp: u64,
     pub events: Rc<RefCell<Vec<Event>>>,
@@ -38,6 +87,7 @@ pub struct ServiceContext {
     service_name: String,
     service_method: String,
     service_payload: String,
+    service_payload_encoding: PayloadEncoding,
     extra: Option<Bytes>,
     timestamp: u64,
     events: Rc<RefCell<Vec<Event>>>,
@@ -57,6 +107,7 @@ impl ServiceContext {
             service_name: params.service_name,
             service_method: params.service_method,
             service_payload: params.service_payload,
+            service_payload_encoding: params.service_payload_encoding,
             extra: params.extra,
             timestamp: params.timestamp,
             events: params.events,
@@ -64,6 +115,7 @@ impl ServiceContext {
         }
     }
 
+    // The services call each other with JSON payloads
     pub fn with_context(
         context: &ServiceContext,
         extra: Option<Bytes>,
@@ -82,6 +134,7 @@ impl ServiceContext {
             service_name,
             service_method,
             service_payload,
+            service_payload_encoding: PayloadEncoding::Json,
             extra,
             timestamp: context.get_timestamp(),
             events: Rc::clone(&context.events),
@@ -101,12 +154,23 @@ impl ServiceContext {
         self.events.borrow().clone()
     }
 
+    pub fn get_events_len(&self) -> usize {
+        self.events.borrow().len()
+    }
+
+    /// Drop the events emitted after the first `len` ones.
+    pub fn truncate_events(&self, len: usize) {
+        self.events.borrow_mut().truncate(len);
+    }
+
+    // Overflowing the cycles used counts as running out of cycles
     pub fn sub_cycles(&self, cycles: u64) -> bool {
-        if self.get_cycles_used() + cycles <= self.cycles_limit {
-            *self.cycles_used.borrow_mut() = self.get_cycles_used() + cycles;
-            true
-        } else {
-            false
+        match self.get_cycles_used().checked_add(cycles) {
+            Some(cycles_used) if cycles_used <= self.cycles_limit => {
+                *self.cycles_used.borrow_mut() = cycles_used;
+                true
+            }
+            _ => false,
         }
     }
 
@@ -142,6 +206,10 @@ impl ServiceContext {
         &self.service_payload
     }
 
+    pub fn get_payload_encoding(&self) -> PayloadEncoding {
+        self.service_payload_encoding
+    }
+
     pub fn get_extra(&self) -> Option<Bytes> {
         self.extra.clone()
     }
@@ -190,8 +258,8 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    use super::{ServiceContext, ServiceContextParams};
-    use crate::types::{Address, Hash};
+    use super::{ServiceContext, ServiceContextParams, StorageCycles};
+    use crate::types::{Address, Hash, PayloadEncoding};
 
     #[test]
     fn test_request_context() {
@@ -207,6 +275,7 @@ mod tests {
             service_name: "service_name".to_owned(),
             service_method: "service_method".to_owned(),
             service_payload: "service_payload".to_owned(),
+            service_payload_encoding: PayloadEncoding::Json,
             extra: None,
             events: Rc::new(RefCell::new(vec![])),
         };
@@ -214,6 +283,9 @@ mod tests {
 
         ctx.sub_cycles(8);
         assert_eq!(ctx.get_cycles_used(), 18);
+        assert!(!ctx.sub_cycles(83));
+        assert!(!ctx.sub_cycles(std::u64::MAX));
+        assert_eq!(ctx.get_cycles_used(), 18);
 
         assert_eq!(ctx.get_cycles_limit(), 100);
         assert_eq!(ctx.get_cycles_price(), 8);
@@ -235,4 +307,21 @@ mod tests {
         assert!(bro.canceled());
         assert_eq!(bro.cancel_reason(), Some(reason));
     }
+
+    #[test]
+    fn test_storage_cycles() {
+        let cycles = StorageCycles {
+            read: 100,
+            write: 1000,
+            byte: 10,
+        };
+        assert_eq!(cycles.read_cycles(8), 180);
+        assert_eq!(cycles.write_cycles(8), 1080);
+
+        let cycles = StorageCycles {
+            byte: std::u64::MAX,
+            ..Default::default()
+        };
+        assert_eq!(cycles.write_cycles(2), std::u64::MAX);
+    }
 }
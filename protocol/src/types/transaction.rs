@@ -1,11 +1,34 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use bytes::Bytes;
 use muta_codec_derive::RlpFixedCodec;
 use serde::{Deserialize, Serialize};
 
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
-use crate::types::primitive::{Address, Hash, JsonString};
+use crate::types::primitive::{Address, Hash, Hex, JsonString, HASH_LEN};
 use crate::ProtocolResult;
 
+static SEQUENTIAL_NONCE: AtomicBool = AtomicBool::new(false);
+
+/// Enable the sequential nonce mode, in which each sender must use nonces
+/// 0, 1, 2... in order. It changes how transactions are executed, so it must
+/// be set the same on every node of a chain.
+pub fn init_sequential_nonce(enabled: bool) {
+    SEQUENTIAL_NONCE.store(enabled, Ordering::SeqCst);
+}
+
+pub fn sequential_nonce_enabled() -> bool {
+    SEQUENTIAL_NONCE.load(Ordering::SeqCst)
+}
+
+/// Encode a sequential nonce as a big endian `u64`, left padded with zeros.
+pub fn sequential_nonce_to_hash(nonce: u64) -> Hash {
+    let mut bytes = vec![0u8; HASH_LEN - 8];
+    bytes.extend_from_slice(&nonce.to_be_bytes());
+
+    Hash::from_bytes(Bytes::from(bytes)).expect("sequential nonce is 32 bytes")
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 pub struct RawTransaction {
     pub chain_id: Hash,
@@ -17,11 +40,58 @@ pub struct RawTransaction {
     pub sender: Address,
 }
 
-#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+impl RawTransaction {
+    /// Decode the nonce as a sequential nonce, `None` if it isn't one.
+    pub fn sequential_nonce(&self) -> Option<u64> {
+        let bytes = self.nonce.as_bytes();
+        if bytes.len() != HASH_LEN || bytes[..HASH_LEN - 8].iter().any(|b| *b != 0) {
+            return None;
+        }
+
+        let mut nonce = [0u8; 8];
+        nonce.copy_from_slice(&bytes[HASH_LEN - 8..]);
+        Some(u64::from_be_bytes(nonce))
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 pub struct TransactionRequest {
     pub method: String,
     pub service_name: String,
     pub payload: JsonString,
+    /// How `payload` is encoded, a JSON string by default.
+    #[serde(default)]
+    pub encoding: PayloadEncoding,
+}
+
+/// The encoding of the payload of a transaction request.
+///
+/// A `Fixed` payload is the `FixedCodec` bytes of the payload type, as a 0x
+/// prefixed hex string. It can only be sent to the methods declared with
+/// `#[read(codec = "fixed")]` or `#[write(codec = "fixed")]`.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadEncoding {
+    Json,
+    Fixed,
+}
+
+impl Default for PayloadEncoding {
+    fn default() -> Self {
+        PayloadEncoding::Json
+    }
+}
+
+/// Decode a `PayloadEncoding::Fixed` payload.
+pub fn decode_fixed_payload<T: FixedCodec>(payload: &str) -> ProtocolResult<T> {
+    let bytes = Hex::from_string(payload.to_owned())?.decode();
+    T::decode_fixed(bytes)
+}
+
+/// Encode a payload as a `PayloadEncoding::Fixed` payload.
+pub fn encode_fixed_payload<T: FixedCodec>(payload: &T) -> ProtocolResult<String> {
+    let bytes = payload.encode_fixed()?;
+    Ok("0x".to_owned() + &hex::encode(bytes))
 }
 
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
@@ -31,3 +101,37 @@ pub struct SignedTransaction {
     pub pubkey: Bytes,
     pub signature: Bytes,
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{sequential_nonce_to_hash, PayloadEncoding, RawTransaction};
+    use crate::types::{Address, Hash, TransactionRequest};
+
+    fn mock_raw_tx(nonce: Hash) -> RawTransaction {
+        RawTransaction {
+            chain_id: Hash::from_empty(),
+            cycles_price: 1,
+            cycles_limit: 100,
+            nonce,
+            request: TransactionRequest {
+                method: "test".to_owned(),
+                service_name: "test".to_owned(),
+                payload: "".to_owned(),
+                encoding: PayloadEncoding::Json,
+            },
+            timeout: 0,
+            sender: Address::from_hash(Hash::from_empty()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_sequential_nonce() {
+        let raw = mock_raw_tx(sequential_nonce_to_hash(42));
+        assert_eq!(raw.sequential_nonce(), Some(42));
+
+        let raw = mock_raw_tx(Hash::digest(Bytes::from("not sequential")));
+        assert_eq!(raw.sequential_nonce(), None);
+    }
+}
@@ -38,6 +38,7 @@ pub struct ConfigNetworkBootstrap {
 pub struct ConfigConsensus {
     pub overlord_gap: usize,
     pub sync_txs_chunk_size: usize,
+    pub wal_retained_files: usize,
 }
 
 impl Default for ConfigConsensus {
@@ -45,6 +46,7 @@ impl Default for ConfigConsensus {
         Self {
             overlord_gap: 5,
             sync_txs_chunk_size: 5000,
+            wal_retained_files: 3,
         }
     }
 }
@@ -16,6 +16,7 @@ use common_crypto::{
     BlsCommonReference, BlsPrivateKey, BlsPublicKey, PublicKey, Secp256k1,
     Secp256k1PrivateKey, ToPublicKey, UncompressedPublicKey,
 };
+use common_pubsub::ChainPubSub;
 use core_api::adapter::DefaultAPIAdapter;
 use core_consensus::fixed_types::{FixedBlock, FixedProof, FixedSignedTxs};
 use core_consensus::message::{
@@ -44,7 +45,8 @@ use protocol::traits::{
     Storage,
 };
 use protocol::types::{
-    Address, Block, BlockHeader, Genesis, Hash, Metadata, Proof, Validator,
+    Address, Block, BlockHeader, Genesis, Hash, Metadata, PayloadEncoding, Proof,
+    Validator,
 };
 use protocol::{fixed_codec::FixedCodec, ProtocolResult};
 
@@ -208,7 +210,8 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             config.mempool.broadcast_txs_interval,
         );
     let mempool = Arc::new(
-        HashMemPool::new(consts::MEMPOOL_POOL_SIZE, mempool_adapter, vec![]).await,
+        HashMemPool::new(consts::MEMPOOL_POOL_SIZE, false, mempool_adapter, vec![])
+            .await,
     );
 
     // self private key
@@ -225,6 +228,8 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
         Arc::clone(&storage),
         Arc::new(db.clone()),
         Arc::clone(&service_mapping),
+        None,
+        true,
     );
 
     // Create full transactions wal
@@ -239,7 +244,10 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
         .to_str()
         .expect("wal path string")
         .to_string();
-    let consensus_wal = Arc::new(ConsensusWal::new(wal_path));
+    let consensus_wal = Arc::new(ConsensusWal::new(
+        wal_path,
+        config.consensus.wal_retained_files,
+    ));
 
     let exec_resp = api_adapter
         .query_service(
@@ -251,6 +259,7 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             "metadata".to_string(),
             "get_metadata".to_string(),
             "".to_string(),
+            PayloadEncoding::Json,
         )
         .await?;
 
@@ -361,6 +370,7 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             status_agent.clone(),
             Arc::clone(&crypto),
             config.consensus.overlord_gap,
+            ChainPubSub::default(),
         )?;
 
     let exec_demon = consensus_adapter.take_exec_demon();
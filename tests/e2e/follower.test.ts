@@ -0,0 +1,114 @@
+import { parse } from 'toml';
+import { readFileSync } from 'fs';
+import * as sdk from '@mutadev/muta-sdk';
+import { Muta } from "@mutadev/muta-sdk";
+import { CHAIN_ID, delay } from './utils';
+
+const { Account, retry } = sdk;
+const { toHex } = sdk.utils;
+
+// Run by `make follower-test`: three of the four verifiers, a follower with the
+// key of the fourth verifier which does not forward txs, and a follower which
+// does.
+const validator = get_client('../../examples/config-1.toml');
+const follower = get_client('../../examples/follower-1.toml');
+const forwarding_follower = get_client('../../examples/follower-2.toml');
+
+const from_pk =
+  "0x5ec982173d54d830b6789cbbbe43eaa2853a5ff752d1ebc1b266cf9790314f8a";
+const to_addr = "muta15a8a9ksxe3hhjpw3l7wz7ry778qg8h9wz8y35p";
+const asset_id =
+  "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c";
+
+describe("follower test via @mutadev/muta-sdk-js", () => {
+  test("syncs the blocks", async () => {
+    const height = await validator.getLatestBlockHeight();
+    await wait_for_height(follower, height);
+    await wait_for_height(forwarding_follower, height);
+
+    const block = await validator.getBlock(hex_height(height));
+    expect(await follower.getBlock(hex_height(height))).toEqual(block);
+    expect(await forwarding_follower.getBlock(hex_height(height))).toEqual(block);
+  }, 120000);
+
+  test("stays out of consensus", async () => {
+    const follower_addr = Account.fromPrivateKey(
+      get_privkey('../../examples/follower-1.toml'),
+    ).address;
+
+    // The fourth verifier would propose about a quarter of the blocks if it
+    // joined the consensus
+    const from = await validator.getLatestBlockHeight() + 1;
+    const to = from + 11;
+    await wait_for_height(follower, to);
+    for (let height = from; height <= to; height++) {
+      const block = await follower.getBlock(hex_height(height));
+      expect(block!.header.proposer).not.toBe(follower_addr);
+    }
+  }, 120000);
+
+  test("rejects txs without forwarding them", async () => {
+    const tx = await sign_transfer(follower);
+    try {
+      await follower.sendTransaction(tx);
+      throw 'should failed';
+    } catch (e) {
+      expect(String(e)).toContain('not accepted');
+    }
+  });
+
+  test("forwards txs to the validators", async () => {
+    const tx = await sign_transfer(forwarding_follower);
+    const txHash = await forwarding_follower.sendTransaction(tx);
+
+    const receipt = await retry(() => validator.getReceipt(toHex(txHash)));
+    expect(Number(receipt.response.response.code)).toBe(0);
+
+    // and the follower syncs the block of the tx
+    const synced = await retry(() => forwarding_follower.getReceipt(toHex(txHash)));
+    expect(synced).toEqual(receipt);
+  }, 120000);
+});
+
+async function sign_transfer(client: any) {
+  const account = Account.fromPrivateKey(from_pk);
+  const tx = await client.composeTransaction({
+    method: 'transfer',
+    payload: {
+      asset_id: asset_id,
+      to: to_addr,
+      value: 0x01,
+    },
+    serviceName: 'asset',
+    sender: account.address,
+  });
+  return account.signTransaction(tx);
+}
+
+async function wait_for_height(client: any, height: number) {
+  for (let i = 0; i < 100; i++) {
+    if (await client.getLatestBlockHeight() >= height) {
+      return;
+    }
+    await delay(1000);
+  }
+  throw new Error('not synced to height ' + height);
+}
+
+function hex_height(height: number) {
+  return '0x' + height.toString(16);
+}
+
+function get_privkey(file_path: string) {
+  return parse(readFileSync(file_path, 'utf-8')).privkey;
+}
+
+function get_client(file_path: string) {
+  const config = parse(readFileSync(file_path, 'utf-8'));
+  const graphql_port = config.graphql.listening_address.split(':')[1];
+  const muta = new Muta({
+    endpoint: 'http://localhost:' + graphql_port + '/graphql',
+    chainId: CHAIN_ID
+  });
+  return muta.client();
+}
@@ -4,7 +4,8 @@ use common_crypto::{
 };
 use protocol::fixed_codec::FixedCodec;
 use protocol::types::{
-    Address, Hash, JsonString, RawTransaction, SignedTransaction, TransactionRequest,
+    Address, Hash, JsonString, PayloadEncoding, RawTransaction, SignedTransaction,
+    TransactionRequest,
 };
 use protocol::{Bytes, BytesMut};
 use rand::{rngs::OsRng, RngCore};
@@ -61,6 +62,7 @@ impl SignedTransactionBuilder {
             service_name: "metadata".to_owned(),
             method: "get_metadata".to_owned(),
             payload: self.payload,
+            encoding: PayloadEncoding::Json,
         };
 
         let raw = RawTransaction {